@@ -18,13 +18,14 @@
 //! 4. Anywhere you see query(&deps, ...) you must replace it with query(&mut deps, ...)
 
 use cosmwasm_std::{
-    coin, coins, from_binary, BankMsg, Binary, Coin, ContractResult, Event, Reply, Response,
-    StakingMsg, SubMsg, SubMsgExecutionResponse, SubMsgResult, SystemResult,
+    coin, coins, from_binary, to_binary, to_vec, BankMsg, Binary, Coin, ContractResult, CosmosMsg,
+    Event, Reply, Response, StakingMsg, SubMsg, SubMsgExecutionResponse, SubMsgResult,
+    SystemResult, WasmMsg,
 };
 use cosmwasm_vm::{
     testing::{
         execute, instantiate, mock_env, mock_info, mock_instance, mock_instance_options, query,
-        reply, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+        reply, ChainError, MockApi, MockChain, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
     },
     Backend, Instance,
 };
@@ -246,3 +247,120 @@ fn reply_and_query() {
     assert_eq!(result.data, Some(data));
     assert_eq!(result.events, events);
 }
+
+/// Sets up a chain with two reflect contracts. The first one is owned by "creator" and
+/// holds 100 earth, the second one is owned by the first one.
+fn setup_chain() -> (MockChain, String, String) {
+    let chain = MockChain::new();
+    chain.set_balance("creator", &coins(100, "earth"));
+    let code_id = chain.store_code(WASM).unwrap();
+    let init_msg = to_vec(&InstantiateMsg {}).unwrap();
+    let (outer, _) = chain
+        .instantiate(code_id, "creator", &init_msg, &coins(100, "earth"), None)
+        .unwrap();
+    let (inner, _) = chain
+        .instantiate(code_id, "creator", &init_msg, &[], None)
+        .unwrap();
+    let change_owner = to_vec(&ExecuteMsg::ChangeOwner {
+        owner: outer.to_string(),
+    })
+    .unwrap();
+    chain
+        .execute("creator", inner.as_str(), &change_owner, &[])
+        .unwrap();
+    (chain, outer.to_string(), inner.to_string())
+}
+
+/// A message that makes `inner` send `amount` to "friend", with `funds` attached
+fn inner_send(inner: &str, amount: u128, funds: u128) -> CosmosMsg<CustomMsg> {
+    let msgs = vec![BankMsg::Send {
+        to_address: String::from("friend"),
+        amount: coins(amount, "earth"),
+    }
+    .into()];
+    WasmMsg::Execute {
+        contract_addr: inner.to_string(),
+        msg: to_binary(&ExecuteMsg::ReflectMsg { msgs }).unwrap(),
+        funds: coins(funds, "earth"),
+    }
+    .into()
+}
+
+fn query_reply(chain: &MockChain, contract: &str, id: u64) -> Reply {
+    let msg = to_vec(&QueryMsg::SubMsgResult { id }).unwrap();
+    from_binary(&chain.query(contract, &msg).unwrap()).unwrap()
+}
+
+#[test]
+fn chain_reply_on_success() {
+    let (chain, outer, inner) = setup_chain();
+
+    let msg = to_vec(&ExecuteMsg::ReflectSubMsg {
+        msgs: vec![SubMsg::reply_on_success(inner_send(&inner, 10, 10), 1)],
+    })
+    .unwrap();
+    chain.execute("creator", &outer, &msg, &[]).unwrap();
+
+    assert_eq!(chain.balance(&outer), coins(90, "earth"));
+    assert_eq!(chain.balance(&inner), vec![]);
+    assert_eq!(chain.balance("friend"), coins(10, "earth"));
+
+    let reply = query_reply(&chain, &outer, 1);
+    assert_eq!(reply.id, 1);
+    let types: Vec<String> = reply
+        .result
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|event| event.ty)
+        .collect();
+    assert_eq!(types, ["execute", "wasm", "transfer"]);
+}
+
+#[test]
+fn chain_reply_on_error_rolls_back_submessage() {
+    let (chain, outer, inner) = setup_chain();
+
+    // inner cannot send more than the 10 earth it receives
+    let msg = to_vec(&ExecuteMsg::ReflectSubMsg {
+        msgs: vec![SubMsg::reply_on_error(inner_send(&inner, 50, 10), 2)],
+    })
+    .unwrap();
+    chain.execute("creator", &outer, &msg, &[]).unwrap();
+
+    // the funds sent to inner are back and nothing reached friend
+    assert_eq!(chain.balance(&outer), coins(100, "earth"));
+    assert_eq!(chain.balance(&inner), vec![]);
+    assert_eq!(chain.balance("friend"), vec![]);
+
+    let reply = query_reply(&chain, &outer, 2);
+    assert_eq!(reply.id, 2);
+    let error = reply.result.unwrap_err();
+    assert!(error.contains("Insufficient funds"), "{}", error);
+}
+
+#[test]
+fn chain_failed_submessage_rolls_back_transaction() {
+    let (chain, outer, inner) = setup_chain();
+
+    // the first submessage succeeds, the second one fails without a reply
+    let msg = to_vec(&ExecuteMsg::ReflectSubMsg {
+        msgs: vec![
+            SubMsg::reply_on_success(inner_send(&inner, 10, 10), 3),
+            SubMsg::new(inner_send(&inner, 50, 10)),
+        ],
+    })
+    .unwrap();
+    let result = chain.execute("creator", &outer, &msg, &[]);
+    match result.unwrap_err() {
+        ChainError::InsufficientFunds { .. } => {}
+        err => panic!("Unexpected error: {:?}", err),
+    }
+
+    // all transfers and the reply of the first submessage are rolled back
+    assert_eq!(chain.balance(&outer), coins(100, "earth"));
+    assert_eq!(chain.balance(&inner), vec![]);
+    assert_eq!(chain.balance("friend"), vec![]);
+    let msg = to_vec(&QueryMsg::SubMsgResult { id: 3 }).unwrap();
+    chain.query(&outer, &msg).unwrap_err();
+}
@@ -11,15 +11,64 @@ use elliptic_curve::sec1::ToEncodedPoint;
 use k256::ecdsa::SigningKey; // type alias
 use sha2::Sha256;
 
+#[cfg(feature = "sr25519")]
+use cosmwasm_crypto::sr25519_verify;
+#[cfg(feature = "hashes")]
+use cosmwasm_crypto::{blake2b, keccak256, ripemd160, sha256};
+#[cfg(feature = "bls12_381")]
 use cosmwasm_crypto::{
-    ed25519_batch_verify, ed25519_verify, secp256k1_recover_pubkey, secp256k1_verify,
+    bls12_381_aggregate_g1, bls12_381_aggregate_g2, bls12_381_aggregate_verify,
+    bls12_381_hash_to_g1, bls12_381_hash_to_g2, bls12_381_pairing_equality,
 };
+use cosmwasm_crypto::{
+    ed25519_batch_verify, ed25519_verify, secp256k1_batch_verify, secp256k1_recover_pubkey,
+    secp256k1_schnorr_verify, secp256k1_verify,
+};
+#[cfg(feature = "secp256r1")]
+use cosmwasm_crypto::{secp256r1_recover_pubkey, secp256r1_verify};
 use std::cmp::min;
 
 const COSMOS_SECP256K1_MSG_HEX: &str = "0a93010a90010a1c2f636f736d6f732e62616e6b2e763162657461312e4d736753656e6412700a2d636f736d6f7331706b707472653766646b6c366766727a6c65736a6a766878686c63337234676d6d6b38727336122d636f736d6f7331717970717870713971637273737a673270767871367273307a716733797963356c7a763778751a100a0575636f736d12073132333435363712650a4e0a460a1f2f636f736d6f732e63727970746f2e736563703235366b312e5075624b657912230a21034f04181eeba35391b858633a765c4a0c189697b40d216354d50890d350c7029012040a02080112130a0d0a0575636f736d12043230303010c09a0c1a0c73696d642d74657374696e672001";
 const COSMOS_SECP256K1_SIGNATURE_HEX: &str = "c9dd20e07464d3a688ff4b710b1fbc027e495e797cfa0b4804da2ed117959227772de059808f765aa29b8f92edf30f4c2c5a438e30d3fe6897daa7141e3ce6f9";
 const COSMOS_SECP256K1_PUBKEY_BASE64: &str = "A08EGB7ro1ORuFhjOnZcSgwYlpe0DSFjVNUIkNNQxwKQ";
 
+// Test data from https://github.com/cosmos/cosmjs/blob/v0.24.0-alpha.22/packages/crypto/src/secp256k1.spec.ts#L195-L394
+const COSMOS_SECP256K1_TESTS_JSON: &str = "./testdata/secp256k1_tests.json";
+
+// Test vector from RFC 6979, A.2.5 (ECDSA, 256 Bits (Prime Field)), with SHA-256
+#[cfg(feature = "secp256r1")]
+const SECP256R1_MSG: &str = "sample";
+#[cfg(feature = "secp256r1")]
+const SECP256R1_SIGNATURE_HEX: &str = "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8";
+#[cfg(feature = "secp256r1")]
+const SECP256R1_PUBKEY_HEX: &str = "0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299";
+
+// Generated with blst from the key material [1u8; 32] and [2u8; 32].
+// The signature is the aggregate of the signatures of "block 1" by key 1 and "block 2" by key 2.
+#[cfg(feature = "bls12_381")]
+const BLS12_381_DST_G2: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+#[cfg(feature = "bls12_381")]
+const BLS12_381_PUBKEY1_HEX: &str = "95a254501b7733239ed3cec4d56737977bd09ede881d8a234560e83e5525017add3b1dcc3eabfb85e12a4131b19c253b";
+#[cfg(feature = "bls12_381")]
+const BLS12_381_PUBKEY2_HEX: &str = "ac80a5e08c712d5f08f0306ad743f7d8c215d982489b84a1d6ba805733d94c006e8938f9089a75db3ffa135af33bc69a";
+#[cfg(feature = "bls12_381")]
+const BLS12_381_AGGREGATE_SIG_HEX: &str = "b680170e37ab63624d0b166031c13d55a807b38277cfa10262b9c82fe1e4e758eb63527a5df49adf54ce584ba00eb45b1631b7980bdcaa9eddc6db0019333084dfa94cedce9405cc53611217a25c7b2d5717af77424b820563c1293496fda35d";
+
+// Test vector 1 from https://github.com/bitcoin/bips/blob/master/bip-0340/test-vectors.csv
+const SECP256K1_SCHNORR_MSG_HEX: &str =
+    "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89";
+const SECP256K1_SCHNORR_SIGNATURE_HEX: &str = "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a";
+const SECP256K1_SCHNORR_PUBKEY_HEX: &str =
+    "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659";
+
+// Generated with schnorrkel from the mini secret key [7u8; 32] and the "substrate" signing context
+#[cfg(feature = "sr25519")]
+const SR25519_MSG: &str = "cosmwasm";
+#[cfg(feature = "sr25519")]
+const SR25519_SIGNATURE_HEX: &str = "fce73cea3fcc441e702a09d189a022e258eeaf49961e74b9b9e1642db618420df2187c1edf8ef0e5adcf50875cb1aa33e9421b71a97baf268a0cdb81a97f0f86";
+#[cfg(feature = "sr25519")]
+const SR25519_PUBKEY_HEX: &str = "7c0f469d3bd340bae718203fa30ca071a5e37c751e891dbded837b213d45d91d";
+
 // TEST 3 test vector from https://tools.ietf.org/html/rfc8032#section-7.1
 const COSMOS_ED25519_MSG_HEX: &str = "af82";
 const COSMOS_ED25519_SIGNATURE_HEX: &str = "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a";
@@ -73,6 +122,36 @@ fn read_decode_cosmos_sigs() -> (Vec<Vec<u8>>, Vec<Vec<u8>>, Vec<Vec<u8>>) {
     (messages, signatures, public_keys)
 }
 
+#[derive(Deserialize, Debug)]
+struct EncodedSecp256k1 {
+    message_hash: String,
+    signature: String,
+    #[serde(rename = "pubkey")]
+    public_key: String,
+}
+
+#[allow(clippy::type_complexity)]
+fn read_decode_secp256k1_sigs() -> (Vec<Vec<u8>>, Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    use std::fs::File;
+    use std::io::BufReader;
+
+    let file = File::open(COSMOS_SECP256K1_TESTS_JSON).unwrap();
+    let reader = BufReader::new(file);
+    let codes: Vec<EncodedSecp256k1> = serde_json::from_reader(reader).unwrap();
+
+    let mut message_hashes: Vec<Vec<u8>> = vec![];
+    let mut signatures: Vec<Vec<u8>> = vec![];
+    let mut public_keys: Vec<Vec<u8>> = vec![];
+
+    for encoded in codes {
+        message_hashes.push(hex::decode(&encoded.message_hash).unwrap());
+        signatures.push(hex::decode(&encoded.signature).unwrap());
+        public_keys.push(hex::decode(&encoded.public_key).unwrap());
+    }
+
+    (message_hashes, signatures, public_keys)
+}
+
 fn bench_crypto(c: &mut Criterion) {
     let mut group = c.benchmark_group("Crypto");
 
@@ -86,6 +165,59 @@ fn bench_crypto(c: &mut Criterion) {
         });
     });
 
+    // Secp256k1 batch verification of different batch lengths
+    {
+        let (message_hashes, signatures, public_keys) = read_decode_secp256k1_sigs();
+        let message_hashes: Vec<&[u8]> = message_hashes.iter().map(|m| m.as_slice()).collect();
+        let signatures: Vec<&[u8]> = signatures.iter().map(|m| m.as_slice()).collect();
+        let public_keys: Vec<&[u8]> = public_keys.iter().map(|m| m.as_slice()).collect();
+
+        for n in (1..=min(message_hashes.len(), 10)).step_by(2) {
+            group.bench_function(
+                format!("secp256k1_batch_verify_{}", convert_no_fmt(n as i64)),
+                |b| {
+                    b.iter(|| {
+                        assert!(secp256k1_batch_verify(
+                            &message_hashes[..n],
+                            &signatures[..n],
+                            &public_keys[..n]
+                        )
+                        .unwrap());
+                    });
+                },
+            );
+        }
+    }
+
+    // Secp256k1 batch verification of different batch lengths, with the same pubkey
+    {
+        let message_hash = Sha256::digest(&hex::decode(COSMOS_SECP256K1_MSG_HEX).unwrap());
+        let message_hashes = [message_hash.as_slice()];
+        let signature = hex::decode(COSMOS_SECP256K1_SIGNATURE_HEX).unwrap();
+        let signatures = [signature.as_slice()];
+        let public_key = base64::decode(COSMOS_SECP256K1_PUBKEY_BASE64).unwrap();
+        let public_keys = [public_key.as_slice()];
+
+        for n in (1..10).step_by(2) {
+            group.bench_function(
+                format!(
+                    "secp256k1_batch_verify_one_pubkey_{}",
+                    convert_no_fmt(n as i64)
+                ),
+                |b| {
+                    b.iter(|| {
+                        assert!(secp256k1_batch_verify(
+                            &message_hashes.repeat(n),
+                            &signatures.repeat(n),
+                            &public_keys
+                        )
+                        .unwrap());
+                    });
+                },
+            );
+        }
+    }
+
     group.bench_function("secp256k1_recover_pubkey", |b| {
         let message_hash =
             hex!("82ff40c0a986c6a5cfad4ddf4c3aa6996f1a7837f9c398e17e5de5cbd5a12b28");
@@ -107,6 +239,38 @@ fn bench_crypto(c: &mut Criterion) {
         });
     });
 
+    #[cfg(feature = "secp256r1")]
+    group.bench_function("secp256r1_verify", |b| {
+        let message_hash = Sha256::digest(SECP256R1_MSG.as_bytes());
+        let signature = hex::decode(SECP256R1_SIGNATURE_HEX).unwrap();
+        let public_key = hex::decode(SECP256R1_PUBKEY_HEX).unwrap();
+        b.iter(|| {
+            assert!(secp256r1_verify(&message_hash, &signature, &public_key).unwrap());
+        });
+    });
+
+    #[cfg(feature = "secp256r1")]
+    group.bench_function("secp256r1_recover_pubkey", |b| {
+        let message_hash = Sha256::digest(SECP256R1_MSG.as_bytes());
+        let signature = hex::decode(SECP256R1_SIGNATURE_HEX).unwrap();
+        let recovery_param: u8 = 0;
+        let expected = hex::decode(SECP256R1_PUBKEY_HEX).unwrap();
+        b.iter(|| {
+            let pubkey =
+                secp256r1_recover_pubkey(&message_hash, &signature, recovery_param).unwrap();
+            assert_eq!(pubkey, expected);
+        });
+    });
+
+    group.bench_function("secp256k1_schnorr_verify", |b| {
+        let message = hex::decode(SECP256K1_SCHNORR_MSG_HEX).unwrap();
+        let signature = hex::decode(SECP256K1_SCHNORR_SIGNATURE_HEX).unwrap();
+        let public_key = hex::decode(SECP256K1_SCHNORR_PUBKEY_HEX).unwrap();
+        b.iter(|| {
+            assert!(secp256k1_schnorr_verify(&message, &signature, &public_key).unwrap());
+        });
+    });
+
     group.bench_function("ed25519_verify", |b| {
         let message = hex::decode(COSMOS_ED25519_MSG_HEX).unwrap();
         let signature = hex::decode(COSMOS_ED25519_SIGNATURE_HEX).unwrap();
@@ -116,6 +280,16 @@ fn bench_crypto(c: &mut Criterion) {
         });
     });
 
+    #[cfg(feature = "sr25519")]
+    group.bench_function("sr25519_verify", |b| {
+        let message = SR25519_MSG.as_bytes();
+        let signature = hex::decode(SR25519_SIGNATURE_HEX).unwrap();
+        let public_key = hex::decode(SR25519_PUBKEY_HEX).unwrap();
+        b.iter(|| {
+            assert!(sr25519_verify(message, &signature, &public_key).unwrap());
+        });
+    });
+
     // Ed25519 batch verification of different batch lengths
     {
         let (messages, signatures, public_keys) = read_decode_cosmos_sigs();
@@ -171,6 +345,96 @@ fn bench_crypto(c: &mut Criterion) {
         }
     }
 
+    #[cfg(feature = "bls12_381")]
+    group.bench_function("bls12_381_hash_to_g1", |b| {
+        b.iter(|| bls12_381_hash_to_g1(b"block 1", BLS12_381_DST_G2));
+    });
+
+    #[cfg(feature = "bls12_381")]
+    group.bench_function("bls12_381_hash_to_g2", |b| {
+        b.iter(|| bls12_381_hash_to_g2(b"block 1", BLS12_381_DST_G2));
+    });
+
+    #[cfg(feature = "bls12_381")]
+    // BLS12-381 aggregation and pairings of different lengths
+    {
+        let g1_points: Vec<_> = (0..16u8)
+            .map(|i| bls12_381_hash_to_g1(&[i], BLS12_381_DST_G2))
+            .collect();
+        let g2_points: Vec<_> = (0..16u8)
+            .map(|i| bls12_381_hash_to_g2(&[i], BLS12_381_DST_G2))
+            .collect();
+        let g1_points: Vec<&[u8]> = g1_points.iter().map(|p| p.as_slice()).collect();
+        let g2_points: Vec<&[u8]> = g2_points.iter().map(|p| p.as_slice()).collect();
+
+        for n in [1usize, 4, 16] {
+            group.bench_function(
+                format!("bls12_381_aggregate_g1_{}", convert_no_fmt(n as i64)),
+                |b| {
+                    b.iter(|| bls12_381_aggregate_g1(&g1_points[..n]).unwrap());
+                },
+            );
+            group.bench_function(
+                format!("bls12_381_aggregate_g2_{}", convert_no_fmt(n as i64)),
+                |b| {
+                    b.iter(|| bls12_381_aggregate_g2(&g2_points[..n]).unwrap());
+                },
+            );
+            group.bench_function(
+                format!("bls12_381_pairing_equality_{}", convert_no_fmt(n as i64)),
+                |b| {
+                    b.iter(|| {
+                        bls12_381_pairing_equality(
+                            &g1_points[..n],
+                            &g2_points[..n],
+                            g1_points[0],
+                            g2_points[0],
+                        )
+                        .unwrap()
+                    });
+                },
+            );
+        }
+    }
+
+    #[cfg(feature = "bls12_381")]
+    group.bench_function("bls12_381_aggregate_verify", |b| {
+        let public_keys = [
+            hex::decode(BLS12_381_PUBKEY1_HEX).unwrap(),
+            hex::decode(BLS12_381_PUBKEY2_HEX).unwrap(),
+        ];
+        let public_keys: Vec<&[u8]> = public_keys.iter().map(|pk| pk.as_slice()).collect();
+        let messages: [&[u8]; 2] = [b"block 1", b"block 2"];
+        let signature = hex::decode(BLS12_381_AGGREGATE_SIG_HEX).unwrap();
+        b.iter(|| {
+            assert!(bls12_381_aggregate_verify(
+                &public_keys,
+                &messages,
+                &signature,
+                BLS12_381_DST_G2
+            )
+            .unwrap());
+        });
+    });
+
+    // Hashing of different input lengths
+    #[cfg(feature = "hashes")]
+    for len in [32usize, 1024, 16 * 1024] {
+        let data = vec![0x9D; len];
+        group.bench_function(format!("sha256_{}_bytes", len), |b| {
+            b.iter(|| sha256(&data));
+        });
+        group.bench_function(format!("keccak256_{}_bytes", len), |b| {
+            b.iter(|| keccak256(&data));
+        });
+        group.bench_function(format!("ripemd160_{}_bytes", len), |b| {
+            b.iter(|| ripemd160(&data));
+        });
+        group.bench_function(format!("blake2b_{}_bytes", len), |b| {
+            b.iter(|| blake2b(&data));
+        });
+    }
+
     group.finish();
 }
 
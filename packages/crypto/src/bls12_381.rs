@@ -0,0 +1,549 @@
+use blst::{
+    blst_fp12, blst_hash_to_g1, blst_hash_to_g2, blst_p1, blst_p1_add_or_double_affine,
+    blst_p1_affine, blst_p1_affine_generator, blst_p1_affine_in_g1, blst_p1_affine_is_inf,
+    blst_p1_cneg, blst_p1_compress, blst_p1_from_affine, blst_p1_to_affine, blst_p1_uncompress,
+    blst_p2, blst_p2_add_or_double_affine, blst_p2_affine, blst_p2_affine_in_g2,
+    blst_p2_affine_is_inf, blst_p2_compress, blst_p2_to_affine, blst_p2_uncompress, BLST_ERROR,
+};
+use std::convert::TryInto;
+
+use crate::errors::{CryptoError, CryptoResult};
+
+/// Length of a serialized compressed G1 point
+pub const BLS12_381_G1_POINT_LEN: usize = 48;
+/// Length of a serialized compressed G2 point
+pub const BLS12_381_G2_POINT_LEN: usize = 96;
+
+/// Aggregates (adds up) a list of G1 points.
+///
+/// The points are expected in compressed form (48 bytes each) as specified by
+/// the [Zcash serialization format](https://github.com/zkcrypto/pairing/tree/34aa52b0f7bef705917252ea63e5a13fa01af551/src/bls12_381#serialization).
+/// Every point is checked for being on the curve and in the G1 subgroup.
+/// The sum is returned in compressed form.
+pub fn bls12_381_aggregate_g1(points: &[&[u8]]) -> CryptoResult<[u8; BLS12_381_G1_POINT_LEN]> {
+    if points.is_empty() {
+        return Err(CryptoError::batch_err("Empty list of points"));
+    }
+
+    let mut sum = blst_p1::default();
+    let sum_ptr: *mut blst_p1 = &mut sum;
+    for point in points {
+        let point = g1_from_bytes(point)?;
+        unsafe { blst_p1_add_or_double_affine(sum_ptr, sum_ptr, &point) };
+    }
+    Ok(g1_to_bytes(&sum))
+}
+
+/// Aggregates (adds up) a list of G2 points.
+///
+/// The points are expected in compressed form (96 bytes each).
+/// Every point is checked for being on the curve and in the G2 subgroup.
+/// The sum is returned in compressed form.
+pub fn bls12_381_aggregate_g2(points: &[&[u8]]) -> CryptoResult<[u8; BLS12_381_G2_POINT_LEN]> {
+    if points.is_empty() {
+        return Err(CryptoError::batch_err("Empty list of points"));
+    }
+
+    let mut sum = blst_p2::default();
+    let sum_ptr: *mut blst_p2 = &mut sum;
+    for point in points {
+        let point = g2_from_bytes(point)?;
+        unsafe { blst_p2_add_or_double_affine(sum_ptr, sum_ptr, &point) };
+    }
+    Ok(g2_to_bytes(&sum))
+}
+
+/// Checks the pairing equation
+///
+/// e(p_1, q_1) * e(p_2, q_2) * ... * e(p_n, q_n) = e(r, s)
+///
+/// with G1 points `ps` and `r` and G2 points `qs` and `s`, all in compressed form.
+/// This is the building block for verifying all kinds of BLS signatures, e.g. drand
+/// beacons which use signatures in G1 and public keys in G2.
+///
+/// The cost of this operation is dominated by the n + 1 pairings.
+pub fn bls12_381_pairing_equality(
+    ps: &[&[u8]],
+    qs: &[&[u8]],
+    r: &[u8],
+    s: &[u8],
+) -> CryptoResult<bool> {
+    if ps.len() != qs.len() {
+        return Err(CryptoError::batch_err(
+            "Mismatched number of G1 and G2 points",
+        ));
+    }
+
+    let mut terms = Vec::with_capacity(ps.len() + 1);
+    for (p, q) in ps.iter().zip(qs.iter()) {
+        terms.push((g1_from_bytes(p)?, g2_from_bytes(q)?));
+    }
+    terms.push((g1_neg(&g1_from_bytes(r)?), g2_from_bytes(s)?));
+
+    Ok(pairing_product_is_one(&terms))
+}
+
+/// Verifies an aggregate BLS signature over distinct messages.
+///
+/// This uses the "minimal-pubkey-size" variant of BLS, i.e. public keys are G1 points
+/// (48 bytes) and the signature is a G2 point (96 bytes), as used by Ethereum 2.0 light clients.
+/// Each message is hashed to G2 using `dst` as the domain separation tag.
+///
+/// The caller is responsible for ensuring that the messages are distinct or that the
+/// public keys come with a proof of possession. If all messages are the same, aggregate
+/// the public keys using [`bls12_381_aggregate_g1`] and pass a single public key and message,
+/// which is a lot cheaper.
+pub fn bls12_381_aggregate_verify(
+    public_keys: &[&[u8]],
+    messages: &[&[u8]],
+    signature: &[u8],
+    dst: &[u8],
+) -> CryptoResult<bool> {
+    if public_keys.is_empty() || public_keys.len() != messages.len() {
+        return Err(CryptoError::batch_err(
+            "Mismatched / erroneous number of public keys / messages",
+        ));
+    }
+
+    let signature = read_signature(signature)?;
+    let signature = g2_from_bytes(&signature)?;
+
+    let mut terms = Vec::with_capacity(public_keys.len() + 1);
+    for (public_key, message) in public_keys.iter().zip(messages.iter()) {
+        let public_key = read_pubkey(public_key)?;
+        let public_key = g1_from_bytes(&public_key)?;
+        if unsafe { blst_p1_affine_is_inf(&public_key) } {
+            return Err(CryptoError::invalid_pubkey_format());
+        }
+        terms.push((public_key, g2_to_affine(&hash_to_g2(message, dst))));
+    }
+    // e(-g1, signature)
+    let generator_neg = g1_neg(unsafe { &*blst_p1_affine_generator() });
+    terms.push((generator_neg, signature));
+
+    Ok(pairing_product_is_one(&terms))
+}
+
+/// Hashes a message to a G1 point as specified in
+/// [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380.html) using the
+/// BLS12381G1_XMD:SHA-256_SSWU_RO_ suite and the domain separation tag `dst`.
+///
+/// The point is returned in compressed form.
+pub fn bls12_381_hash_to_g1(msg: &[u8], dst: &[u8]) -> [u8; BLS12_381_G1_POINT_LEN] {
+    let mut out = blst_p1::default();
+    unsafe {
+        blst_hash_to_g1(
+            &mut out,
+            msg.as_ptr(),
+            msg.len(),
+            dst.as_ptr(),
+            dst.len(),
+            std::ptr::null(),
+            0,
+        )
+    };
+    g1_to_bytes(&out)
+}
+
+/// Hashes a message to a G2 point as specified in
+/// [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380.html) using the
+/// BLS12381G2_XMD:SHA-256_SSWU_RO_ suite and the domain separation tag `dst`.
+///
+/// The point is returned in compressed form.
+pub fn bls12_381_hash_to_g2(msg: &[u8], dst: &[u8]) -> [u8; BLS12_381_G2_POINT_LEN] {
+    g2_to_bytes(&hash_to_g2(msg, dst))
+}
+
+fn hash_to_g2(msg: &[u8], dst: &[u8]) -> blst_p2 {
+    let mut out = blst_p2::default();
+    unsafe {
+        blst_hash_to_g2(
+            &mut out,
+            msg.as_ptr(),
+            msg.len(),
+            dst.as_ptr(),
+            dst.len(),
+            std::ptr::null(),
+            0,
+        )
+    };
+    out
+}
+
+/// Decodes a compressed G1 point and checks that it is in the G1 subgroup
+fn g1_from_bytes(data: &[u8]) -> CryptoResult<blst_p1_affine> {
+    let data: [u8; BLS12_381_G1_POINT_LEN] =
+        data.try_into().map_err(|_| CryptoError::invalid_point())?;
+    let mut point = blst_p1_affine::default();
+    if unsafe { blst_p1_uncompress(&mut point, data.as_ptr()) } != BLST_ERROR::BLST_SUCCESS {
+        return Err(CryptoError::invalid_point());
+    }
+    if !unsafe { blst_p1_affine_in_g1(&point) } {
+        return Err(CryptoError::invalid_point());
+    }
+    Ok(point)
+}
+
+/// Decodes a compressed G2 point and checks that it is in the G2 subgroup
+fn g2_from_bytes(data: &[u8]) -> CryptoResult<blst_p2_affine> {
+    let data: [u8; BLS12_381_G2_POINT_LEN] =
+        data.try_into().map_err(|_| CryptoError::invalid_point())?;
+    let mut point = blst_p2_affine::default();
+    if unsafe { blst_p2_uncompress(&mut point, data.as_ptr()) } != BLST_ERROR::BLST_SUCCESS {
+        return Err(CryptoError::invalid_point());
+    }
+    if !unsafe { blst_p2_affine_in_g2(&point) } {
+        return Err(CryptoError::invalid_point());
+    }
+    Ok(point)
+}
+
+fn g1_to_bytes(point: &blst_p1) -> [u8; BLS12_381_G1_POINT_LEN] {
+    let mut out = [0u8; BLS12_381_G1_POINT_LEN];
+    unsafe { blst_p1_compress(out.as_mut_ptr(), point) };
+    out
+}
+
+fn g2_to_bytes(point: &blst_p2) -> [u8; BLS12_381_G2_POINT_LEN] {
+    let mut out = [0u8; BLS12_381_G2_POINT_LEN];
+    unsafe { blst_p2_compress(out.as_mut_ptr(), point) };
+    out
+}
+
+fn g1_neg(point: &blst_p1_affine) -> blst_p1_affine {
+    let mut projective = blst_p1::default();
+    let mut out = blst_p1_affine::default();
+    unsafe {
+        blst_p1_from_affine(&mut projective, point);
+        blst_p1_cneg(&mut projective, true);
+        blst_p1_to_affine(&mut out, &projective);
+    }
+    out
+}
+
+fn g2_to_affine(point: &blst_p2) -> blst_p2_affine {
+    let mut out = blst_p2_affine::default();
+    unsafe { blst_p2_to_affine(&mut out, point) };
+    out
+}
+
+/// Checks that the product of the pairings of all terms is one.
+/// Terms containing the point at infinity contribute a factor of one and are skipped.
+fn pairing_product_is_one(terms: &[(blst_p1_affine, blst_p2_affine)]) -> bool {
+    let mut product = blst_fp12::default(); // one
+    for (p, q) in terms {
+        if unsafe { blst_p1_affine_is_inf(p) || blst_p2_affine_is_inf(q) } {
+            continue;
+        }
+        product *= blst_fp12::miller_loop(q, p);
+    }
+    product.final_exp() == blst_fp12::default()
+}
+
+fn read_pubkey(data: &[u8]) -> CryptoResult<[u8; BLS12_381_G1_POINT_LEN]> {
+    data.try_into()
+        .map_err(|_| CryptoError::invalid_pubkey_format())
+}
+
+fn read_signature(data: &[u8]) -> CryptoResult<[u8; BLS12_381_G2_POINT_LEN]> {
+    data.try_into()
+        .map_err(|_| CryptoError::invalid_signature_format())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use blst::{min_pk, min_sig};
+    use hex_literal::hex;
+
+    // Domain separation tag for signatures in G2, as used by Ethereum 2.0
+    const DST_G2: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+    // Domain separation tag for signatures in G1, as used by drand's quicknet
+    const DST_G1: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+    const CHECKPOINT: &[u8] = b"checkpoint";
+    const FOO: &[u8] = b"foo";
+
+    const G1_IDENTITY: [u8; 48] = {
+        let mut out = [0u8; 48];
+        out[0] = 0xc0;
+        out
+    };
+
+    fn min_pk_key(seed: u8) -> min_pk::SecretKey {
+        min_pk::SecretKey::key_gen(&[seed; 32], &[]).unwrap()
+    }
+
+    fn min_sig_key(seed: u8) -> min_sig::SecretKey {
+        min_sig::SecretKey::key_gen(&[seed; 32], &[]).unwrap()
+    }
+
+    #[test]
+    fn bls12_381_hash_to_g1_works() {
+        // Test vectors from RFC 9380, J.9.1 (BLS12381G1_XMD:SHA-256_SSWU_RO_)
+        const DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        assert_eq!(
+            bls12_381_hash_to_g1(b"", DST),
+            hex!("852926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1")
+        );
+        assert_eq!(
+            bls12_381_hash_to_g1(b"abc", DST),
+            hex!("83567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903")
+        );
+    }
+
+    #[test]
+    fn bls12_381_hash_to_g2_works() {
+        // Test vectors from RFC 9380, J.10.1 (BLS12381G2_XMD:SHA-256_SSWU_RO_)
+        const DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+        assert_eq!(
+            bls12_381_hash_to_g2(b"", DST),
+            hex!("a5cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a")
+        );
+        assert_eq!(
+            bls12_381_hash_to_g2(b"abc", DST),
+            hex!("939cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd802c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6")
+        );
+    }
+
+    #[test]
+    fn bls12_381_aggregate_g1_works() {
+        let pk1 = min_pk_key(1).sk_to_pk().compress();
+        let pk2 = min_pk_key(2).sk_to_pk().compress();
+        let pk3 = min_pk_key(3).sk_to_pk().compress();
+
+        let expected = min_pk::AggregatePublicKey::aggregate(
+            &[
+                &min_pk_key(1).sk_to_pk(),
+                &min_pk_key(2).sk_to_pk(),
+                &min_pk_key(3).sk_to_pk(),
+            ],
+            true,
+        )
+        .unwrap()
+        .to_public_key()
+        .compress();
+        let sum = bls12_381_aggregate_g1(&[&pk1, &pk2, &pk3]).unwrap();
+        assert_eq!(sum, expected);
+
+        // Order does not matter
+        let sum = bls12_381_aggregate_g1(&[&pk3, &pk1, &pk2]).unwrap();
+        assert_eq!(sum, expected);
+
+        // Single point
+        assert_eq!(bls12_381_aggregate_g1(&[&pk1]).unwrap(), pk1);
+
+        // Identity is the neutral element
+        assert_eq!(bls12_381_aggregate_g1(&[&pk1, &G1_IDENTITY]).unwrap(), pk1);
+        assert_eq!(
+            bls12_381_aggregate_g1(&[&G1_IDENTITY]).unwrap(),
+            G1_IDENTITY
+        );
+    }
+
+    #[test]
+    fn bls12_381_aggregate_g1_fails_for_invalid_input() {
+        let pk1 = min_pk_key(1).sk_to_pk().compress();
+
+        match bls12_381_aggregate_g1(&[]).unwrap_err() {
+            CryptoError::BatchErr { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        match bls12_381_aggregate_g1(&[&pk1[..47]]).unwrap_err() {
+            CryptoError::InvalidPoint { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        // Not on the curve
+        let mut not_on_curve = pk1;
+        not_on_curve[47] ^= 0x01;
+        match bls12_381_aggregate_g1(&[&not_on_curve]).unwrap_err() {
+            CryptoError::InvalidPoint { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn bls12_381_aggregate_g2_works() {
+        let sk1 = min_pk_key(1);
+        let sk2 = min_pk_key(2);
+        let sig1 = sk1.sign(FOO, DST_G2, &[]);
+        let sig2 = sk2.sign(b"bar", DST_G2, &[]);
+
+        let expected = min_pk::AggregateSignature::aggregate(&[&sig1, &sig2], true)
+            .unwrap()
+            .to_signature()
+            .compress();
+        let sum = bls12_381_aggregate_g2(&[&sig1.compress(), &sig2.compress()]).unwrap();
+        assert_eq!(sum, expected);
+
+        match bls12_381_aggregate_g2(&[]).unwrap_err() {
+            CryptoError::BatchErr { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        match bls12_381_aggregate_g2(&[&sig1.compress()[..95]]).unwrap_err() {
+            CryptoError::InvalidPoint { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn bls12_381_aggregate_verify_works() {
+        let messages: [&[u8]; 3] = [b"block 1", b"block 2", b"block 3"];
+        let keys = [min_pk_key(1), min_pk_key(2), min_pk_key(3)];
+        let public_keys: Vec<_> = keys.iter().map(|k| k.sk_to_pk().compress()).collect();
+        let public_keys: Vec<&[u8]> = public_keys.iter().map(|pk| &pk[..]).collect();
+        let signatures: Vec<_> = keys
+            .iter()
+            .zip(messages.iter())
+            .map(|(k, m)| k.sign(m, DST_G2, &[]).compress())
+            .collect();
+        let signatures: Vec<&[u8]> = signatures.iter().map(|s| &s[..]).collect();
+        let signature = bls12_381_aggregate_g2(&signatures).unwrap();
+
+        assert!(bls12_381_aggregate_verify(&public_keys, &messages, &signature, DST_G2).unwrap());
+
+        // Single signature
+        assert!(bls12_381_aggregate_verify(
+            &public_keys[..1],
+            &messages[..1],
+            signatures[0],
+            DST_G2
+        )
+        .unwrap());
+
+        // Same message signed by all, verified against the aggregated public key
+        let signatures: Vec<_> = keys
+            .iter()
+            .map(|k| k.sign(CHECKPOINT, DST_G2, &[]).compress())
+            .collect();
+        let signatures: Vec<&[u8]> = signatures.iter().map(|s| &s[..]).collect();
+        let signature = bls12_381_aggregate_g2(&signatures).unwrap();
+        let public_key = bls12_381_aggregate_g1(&public_keys).unwrap();
+        assert!(
+            bls12_381_aggregate_verify(&[&public_key], &[CHECKPOINT], &signature, DST_G2).unwrap()
+        );
+    }
+
+    #[test]
+    fn bls12_381_aggregate_verify_fails_for_wrong_data() {
+        let messages: [&[u8]; 2] = [b"block 1", b"block 2"];
+        let keys = [min_pk_key(1), min_pk_key(2)];
+        let public_keys: Vec<_> = keys.iter().map(|k| k.sk_to_pk().compress()).collect();
+        let public_keys: Vec<&[u8]> = public_keys.iter().map(|pk| &pk[..]).collect();
+        let signatures: Vec<_> = keys
+            .iter()
+            .zip(messages.iter())
+            .map(|(k, m)| k.sign(m, DST_G2, &[]).compress())
+            .collect();
+        let signatures: Vec<&[u8]> = signatures.iter().map(|s| &s[..]).collect();
+        let signature = bls12_381_aggregate_g2(&signatures).unwrap();
+
+        // Swapped messages
+        assert!(!bls12_381_aggregate_verify(
+            &public_keys,
+            &[messages[1], messages[0]],
+            &signature,
+            DST_G2
+        )
+        .unwrap());
+        // Wrong DST
+        assert!(!bls12_381_aggregate_verify(&public_keys, &messages, &signature, DST_G1).unwrap());
+        // Missing signature part
+        assert!(
+            !bls12_381_aggregate_verify(&public_keys, &messages, signatures[0], DST_G2).unwrap()
+        );
+    }
+
+    #[test]
+    fn bls12_381_aggregate_verify_fails_for_invalid_input() {
+        let pk = min_pk_key(1).sk_to_pk().compress();
+        let sig = min_pk_key(1).sign(FOO, DST_G2, &[]).compress();
+
+        match bls12_381_aggregate_verify(&[], &[], &sig, DST_G2).unwrap_err() {
+            CryptoError::BatchErr { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        match bls12_381_aggregate_verify(&[&pk, &pk], &[FOO], &sig, DST_G2).unwrap_err() {
+            CryptoError::BatchErr { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        match bls12_381_aggregate_verify(&[&pk[..47]], &[FOO], &sig, DST_G2).unwrap_err() {
+            CryptoError::InvalidPubkeyFormat { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        match bls12_381_aggregate_verify(&[&G1_IDENTITY], &[FOO], &sig, DST_G2).unwrap_err() {
+            CryptoError::InvalidPubkeyFormat { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        match bls12_381_aggregate_verify(&[&pk], &[FOO], &sig[..95], DST_G2).unwrap_err() {
+            CryptoError::InvalidSignatureFormat { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        let mut not_on_curve = sig;
+        not_on_curve[95] ^= 0x01;
+        match bls12_381_aggregate_verify(&[&pk], &[FOO], &not_on_curve, DST_G2).unwrap_err() {
+            CryptoError::InvalidPoint { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn bls12_381_pairing_equality_works() {
+        // drand style: signature in G1, public key in G2.
+        // e(signature, g2) = e(H(m), public_key)
+        let sk = min_sig_key(7);
+        let public_key = sk.sk_to_pk().compress();
+        let message = b"round 1234";
+        let signature = sk.sign(message, DST_G1, &[]).compress();
+        let g2_generator = {
+            let mut out = [0u8; BLS12_381_G2_POINT_LEN];
+            unsafe {
+                blst::blst_p2_affine_compress(out.as_mut_ptr(), blst::blst_p2_affine_generator())
+            };
+            out
+        };
+        let message_point = bls12_381_hash_to_g1(message, DST_G1);
+
+        assert!(bls12_381_pairing_equality(
+            &[&signature],
+            &[&g2_generator],
+            &message_point,
+            &public_key
+        )
+        .unwrap());
+
+        // Wrong message
+        let other_point = bls12_381_hash_to_g1(b"round 1235", DST_G1);
+        assert!(!bls12_381_pairing_equality(
+            &[&signature],
+            &[&g2_generator],
+            &other_point,
+            &public_key
+        )
+        .unwrap());
+
+        // Empty product equals e(identity, s)
+        assert!(bls12_381_pairing_equality(&[], &[], &G1_IDENTITY, &public_key).unwrap());
+        assert!(!bls12_381_pairing_equality(&[], &[], &message_point, &public_key).unwrap());
+    }
+
+    #[test]
+    fn bls12_381_pairing_equality_fails_for_invalid_input() {
+        let p = bls12_381_hash_to_g1(b"p", DST_G1);
+        let q = bls12_381_hash_to_g2(b"q", DST_G2);
+
+        match bls12_381_pairing_equality(&[&p, &p], &[&q], &p, &q).unwrap_err() {
+            CryptoError::BatchErr { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        match bls12_381_pairing_equality(&[&q], &[&q], &p, &q).unwrap_err() {
+            CryptoError::InvalidPoint { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        match bls12_381_pairing_equality(&[&p], &[&p], &p, &q).unwrap_err() {
+            CryptoError::InvalidPoint { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+    }
+}
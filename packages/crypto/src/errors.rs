@@ -34,6 +34,11 @@ pub enum CryptoError {
         #[cfg(feature = "backtraces")]
         backtrace: Backtrace,
     },
+    #[error("Invalid point")]
+    InvalidPoint {
+        #[cfg(feature = "backtraces")]
+        backtrace: Backtrace,
+    },
     #[error("Invalid recovery parameter. Supported values: 0 and 1.")]
     InvalidRecoveryParam {
         #[cfg(feature = "backtraces")]
@@ -79,6 +84,13 @@ impl CryptoError {
         }
     }
 
+    pub fn invalid_point() -> Self {
+        CryptoError::InvalidPoint {
+            #[cfg(feature = "backtraces")]
+            backtrace: Backtrace::capture(),
+        }
+    }
+
     pub fn invalid_recovery_param() -> Self {
         CryptoError::InvalidRecoveryParam {
             #[cfg(feature = "backtraces")]
@@ -95,6 +107,7 @@ impl CryptoError {
             CryptoError::InvalidPubkeyFormat { .. } => 5,
             CryptoError::InvalidRecoveryParam { .. } => 6,
             CryptoError::BatchErr { .. } => 7,
+            CryptoError::InvalidPoint { .. } => 8,
             CryptoError::GenericErr { .. } => 10,
         }
     }
@@ -153,4 +166,13 @@ mod tests {
             _ => panic!("wrong error type!"),
         }
     }
+
+    #[test]
+    fn invalid_point_works() {
+        let error = CryptoError::invalid_point();
+        match error {
+            CryptoError::InvalidPoint { .. } => {}
+            _ => panic!("wrong error type!"),
+        }
+    }
 }
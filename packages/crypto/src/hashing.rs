@@ -0,0 +1,95 @@
+use blake2::Blake2b;
+use digest::Digest;
+use ripemd160::Ripemd160;
+use sha2::Sha256;
+use sha3::Keccak256;
+
+/// Length of a SHA-256 hash
+pub const SHA256_HASH_LEN: usize = 32;
+/// Length of a Keccak-256 hash
+pub const KECCAK256_HASH_LEN: usize = 32;
+/// Length of a RIPEMD-160 hash
+pub const RIPEMD160_HASH_LEN: usize = 20;
+/// Length of a BLAKE2b hash
+pub const BLAKE2B_HASH_LEN: usize = 64;
+
+/// Hashes the data with SHA-256 (SHA-2 with 256 bit output)
+pub fn sha256(data: &[u8]) -> [u8; SHA256_HASH_LEN] {
+    Sha256::digest(data).into()
+}
+
+/// Hashes the data with Keccak-256, as used in Ethereum.
+///
+/// This is not the same as the standardized SHA3-256, which uses a different padding.
+pub fn keccak256(data: &[u8]) -> [u8; KECCAK256_HASH_LEN] {
+    Keccak256::digest(data).into()
+}
+
+/// Hashes the data with RIPEMD-160, as used in Bitcoin addresses
+pub fn ripemd160(data: &[u8]) -> [u8; RIPEMD160_HASH_LEN] {
+    Ripemd160::digest(data).into()
+}
+
+/// Hashes the data with BLAKE2b without a key, using the full output length of 512 bits
+pub fn blake2b(data: &[u8]) -> [u8; BLAKE2B_HASH_LEN] {
+    // GenericArray only converts into arrays of up to 32 elements
+    let mut out = [0u8; BLAKE2B_HASH_LEN];
+    out.copy_from_slice(&Blake2b::digest(data));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn sha256_works() {
+        assert_eq!(
+            sha256(b""),
+            hex!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_eq!(
+            sha256(b"abc"),
+            hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+    }
+
+    #[test]
+    fn keccak256_works() {
+        assert_eq!(
+            keccak256(b""),
+            hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+        );
+        assert_eq!(
+            keccak256(b"abc"),
+            hex!("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45")
+        );
+    }
+
+    #[test]
+    fn ripemd160_works() {
+        assert_eq!(
+            ripemd160(b""),
+            hex!("9c1185a5c5e9fc54612808977ee8f548b2258d31")
+        );
+        assert_eq!(
+            ripemd160(b"abc"),
+            hex!("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc")
+        );
+    }
+
+    #[test]
+    fn blake2b_works() {
+        // Test vectors from https://datatracker.ietf.org/doc/html/rfc7693#appendix-A
+        // and https://github.com/BLAKE2/BLAKE2/blob/master/testvectors/blake2b-kat.txt
+        assert_eq!(
+            blake2b(b""),
+            hex!("786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce")
+        );
+        assert_eq!(
+            blake2b(b"abc"),
+            hex!("ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923")
+        );
+    }
+}
@@ -4,18 +4,57 @@
 //! This crate does not adhere to semantic versioning.
 #![cfg_attr(feature = "backtraces", feature(backtrace))]
 
+#[cfg(feature = "bls12_381")]
+mod bls12_381;
 mod ed25519;
 mod errors;
+#[cfg(feature = "hashes")]
+mod hashing;
 mod identity_digest;
 mod secp256k1;
+mod secp256k1_schnorr;
+#[cfg(feature = "secp256r1")]
+mod secp256r1;
+#[cfg(feature = "sr25519")]
+mod sr25519;
 
+#[cfg(feature = "bls12_381")]
+#[doc(hidden)]
+pub use crate::bls12_381::{
+    bls12_381_aggregate_g1, bls12_381_aggregate_g2, bls12_381_aggregate_verify,
+    bls12_381_hash_to_g1, bls12_381_hash_to_g2, bls12_381_pairing_equality,
+};
+#[cfg(feature = "bls12_381")]
+#[doc(hidden)]
+pub use crate::bls12_381::{BLS12_381_G1_POINT_LEN, BLS12_381_G2_POINT_LEN};
 #[doc(hidden)]
 pub use crate::ed25519::EDDSA_PUBKEY_LEN;
 #[doc(hidden)]
 pub use crate::ed25519::{ed25519_batch_verify, ed25519_verify};
 #[doc(hidden)]
 pub use crate::errors::{CryptoError, CryptoResult};
+#[cfg(feature = "hashes")]
+#[doc(hidden)]
+pub use crate::hashing::{blake2b, keccak256, ripemd160, sha256};
+#[cfg(feature = "hashes")]
 #[doc(hidden)]
-pub use crate::secp256k1::{secp256k1_recover_pubkey, secp256k1_verify};
+pub use crate::hashing::{
+    BLAKE2B_HASH_LEN, KECCAK256_HASH_LEN, RIPEMD160_HASH_LEN, SHA256_HASH_LEN,
+};
+#[doc(hidden)]
+pub use crate::secp256k1::{secp256k1_batch_verify, secp256k1_recover_pubkey, secp256k1_verify};
 #[doc(hidden)]
 pub use crate::secp256k1::{ECDSA_PUBKEY_MAX_LEN, ECDSA_SIGNATURE_LEN, MESSAGE_HASH_MAX_LEN};
+#[doc(hidden)]
+pub use crate::secp256k1_schnorr::secp256k1_schnorr_verify;
+#[doc(hidden)]
+pub use crate::secp256k1_schnorr::{SCHNORR_PUBKEY_LEN, SCHNORR_SIGNATURE_LEN};
+#[cfg(feature = "secp256r1")]
+#[doc(hidden)]
+pub use crate::secp256r1::{secp256r1_recover_pubkey, secp256r1_verify};
+#[cfg(feature = "sr25519")]
+#[doc(hidden)]
+pub use crate::sr25519::sr25519_verify;
+#[cfg(feature = "sr25519")]
+#[doc(hidden)]
+pub use crate::sr25519::SR25519_PUBKEY_LEN;
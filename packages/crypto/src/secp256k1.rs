@@ -3,7 +3,13 @@ use k256::{
     ecdsa::recoverable,
     ecdsa::signature::{DigestVerifier, Signature as _}, // traits
     ecdsa::{Signature, VerifyingKey},                   // type aliases
+    elliptic_curve::group::Group,                       // trait
     elliptic_curve::sec1::ToEncodedPoint,
+    lincomb,
+    FieldBytes,
+    ProjectivePoint,
+    PublicKey,
+    Scalar,
 };
 use std::convert::TryInto;
 
@@ -64,6 +70,135 @@ pub fn secp256k1_verify(
     }
 }
 
+/// Verifies a batch of secp256k1 ECDSA signatures.
+///
+/// The batch is valid if every signature is valid for its message hash and public key. The result
+/// does not tell which signature is invalid. Unlike Ed25519 signatures, ECDSA signatures cannot be
+/// combined into a single verification equation, so every signature is still verified on its own.
+/// The speedup compared to calling [`secp256k1_verify`] for each signature comes from inverting the
+/// `s` values of all signatures with a single scalar inversion and from parsing a public key that
+/// is shared by the whole batch only once.
+///
+/// Message hashes, signatures and public keys use the same encodings as for [`secp256k1_verify`].
+/// Like there, high-S signatures are normalized before verification.
+///
+/// The following input shapes are supported:
+///  - `n` message hashes, `n` signatures and `n` public keys: signature `i` is verified against
+///    message hash `i` and public key `i`.
+///  - One message hash, `n` signatures and `n` public keys: all signers signed the same message,
+///    e.g. in a multisig.
+///  - One public key, `n` message hashes and `n` signatures: a single signer signed many messages.
+///
+/// All other shapes result in a [`CryptoError::BatchErr`]. A batch without signatures is valid.
+pub fn secp256k1_batch_verify(
+    message_hashes: &[&[u8]],
+    signatures: &[&[u8]],
+    public_keys: &[&[u8]],
+) -> CryptoResult<bool> {
+    // Structural checks
+    let messages_len = message_hashes.len();
+    let signatures_len = signatures.len();
+    let public_keys_len = public_keys.len();
+
+    if (messages_len == signatures_len && messages_len == public_keys_len)
+        || (messages_len == 1 && signatures_len == public_keys_len)
+        || (public_keys_len == 1 && messages_len == signatures_len)
+    { // We're good to go
+    } else {
+        return Err(CryptoError::batch_err(
+            "Mismatched / erroneous number of message hashes / signatures / public keys",
+        ));
+    }
+
+    // Validation
+    let message_hashes = message_hashes
+        .iter()
+        .map(|&message_hash| read_hash(message_hash))
+        .collect::<Result<Vec<_>, _>>()?;
+    let signatures = signatures
+        .iter()
+        .map(|&signature| {
+            let signature = read_signature(signature)?;
+            let mut signature = Signature::from_bytes(&signature)
+                .map_err(|e| CryptoError::generic_err(e.to_string()))?;
+            // Non low-S signatures require normalization
+            signature
+                .normalize_s()
+                .map_err(|e| CryptoError::generic_err(e.to_string()))?;
+            Ok(signature)
+        })
+        .collect::<CryptoResult<Vec<_>>>()?;
+    let public_keys = public_keys
+        .iter()
+        .map(|&public_key| {
+            check_pubkey(public_key)?;
+            let public_key = PublicKey::from_sec1_bytes(public_key)
+                .map_err(|e| CryptoError::generic_err(e.to_string()))?;
+            Ok(public_key.to_projective())
+        })
+        .collect::<CryptoResult<Vec<_>>>()?;
+
+    // Verification
+    let s_inverses = batch_invert(
+        &signatures
+            .iter()
+            .map(|signature| *signature.s())
+            .collect::<Vec<_>>(),
+    );
+    for (i, (signature, s_inv)) in signatures.iter().zip(s_inverses).enumerate() {
+        let message_hash = if messages_len == 1 {
+            &message_hashes[0]
+        } else {
+            &message_hashes[i]
+        };
+        let public_key = if public_keys_len == 1 {
+            &public_keys[0]
+        } else {
+            &public_keys[i]
+        };
+
+        // Same as k256's verification, but with the inverse of s computed upfront
+        let z = Scalar::from_bytes_reduced(FieldBytes::from_slice(message_hash));
+        let r = *signature.r();
+        let big_r = lincomb(
+            &ProjectivePoint::generator(),
+            &(z * s_inv),
+            public_key,
+            &(r * s_inv),
+        );
+        if bool::from(big_r.is_identity()) {
+            return Ok(false);
+        }
+        let encoded = big_r.to_affine().to_encoded_point(true);
+        let x = FieldBytes::from_slice(&encoded.as_bytes()[1..]);
+        if Scalar::from_bytes_reduced(x) != r {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Inverts a list of non-zero scalars with a single scalar inversion (Montgomery's trick).
+///
+/// This replaces one (expensive) inversion per scalar by three multiplications.
+fn batch_invert(scalars: &[Scalar]) -> Vec<Scalar> {
+    // products[i] is the product of all scalars before index i
+    let mut products = Vec::with_capacity(scalars.len());
+    let mut product = Scalar::one();
+    for scalar in scalars {
+        products.push(product);
+        product *= scalar;
+    }
+
+    let mut product_inv = product.invert().unwrap();
+    let mut inverses = vec![Scalar::zero(); scalars.len()];
+    for i in (0..scalars.len()).rev() {
+        inverses[i] = product_inv * products[i];
+        product_inv *= scalars[i];
+    }
+    inverses
+}
+
 /// Recovers a public key from a message hash and a signature.
 ///
 /// This is required when working with Ethereum where public keys
@@ -308,6 +443,157 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_secp256k1_batch_verify() {
+        // Explicit / external hashing
+        let message_hash = Sha256::digest(MSG.as_bytes());
+        let other_message_hash = Sha256::digest(b"Hello Batch!");
+
+        // Signing
+        let secret_key1 = SigningKey::random(&mut OsRng);
+        let secret_key2 = SigningKey::random(&mut OsRng);
+        let signature1: Signature = secret_key1.sign_digest(Sha256::new().chain(MSG));
+        let signature2: Signature = secret_key2.sign_digest(Sha256::new().chain(MSG));
+        let signature3: Signature = secret_key1.sign_digest(Sha256::new().chain("Hello Batch!"));
+        let public_key1 = secret_key1.verifying_key().to_encoded_point(true);
+        let public_key2 = secret_key2.verifying_key().to_encoded_point(false);
+
+        // One message, multiple signatures and public keys (multisig)
+        assert!(secp256k1_batch_verify(
+            &[&message_hash],
+            &[signature1.as_ref(), signature2.as_ref()],
+            &[public_key1.as_bytes(), public_key2.as_bytes()],
+        )
+        .unwrap());
+
+        // One public key, multiple messages and signatures
+        assert!(secp256k1_batch_verify(
+            &[&message_hash, &other_message_hash],
+            &[signature1.as_ref(), signature3.as_ref()],
+            &[public_key1.as_bytes()],
+        )
+        .unwrap());
+
+        // Equal number of messages, signatures and public keys
+        assert!(secp256k1_batch_verify(
+            &[&message_hash, &message_hash, &other_message_hash],
+            &[
+                signature1.as_ref(),
+                signature2.as_ref(),
+                signature3.as_ref()
+            ],
+            &[
+                public_key1.as_bytes(),
+                public_key2.as_bytes(),
+                public_key1.as_bytes()
+            ],
+        )
+        .unwrap());
+
+        // One wrong signature fails the whole batch
+        assert!(!secp256k1_batch_verify(
+            &[&message_hash],
+            &[signature1.as_ref(), signature2.as_ref()],
+            &[public_key2.as_bytes(), public_key2.as_bytes()],
+        )
+        .unwrap());
+        assert!(!secp256k1_batch_verify(
+            &[&message_hash, &message_hash],
+            &[signature1.as_ref(), signature3.as_ref()],
+            &[public_key1.as_bytes()],
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_cosmos_secp256k1_batch_verify() {
+        let public_key = base64::decode(COSMOS_SECP256K1_PUBKEY_BASE64).unwrap();
+        let message_hashes: Vec<_> = [
+            COSMOS_SECP256K1_MSG_HEX1,
+            COSMOS_SECP256K1_MSG_HEX2,
+            COSMOS_SECP256K1_MSG_HEX3,
+        ]
+        .iter()
+        .map(|msg| Sha256::digest(&hex::decode(msg).unwrap()))
+        .collect();
+        let signatures: Vec<_> = [
+            COSMOS_SECP256K1_SIGNATURE_HEX1,
+            COSMOS_SECP256K1_SIGNATURE_HEX2,
+            COSMOS_SECP256K1_SIGNATURE_HEX3,
+        ]
+        .iter()
+        .map(|sig| hex::decode(sig).unwrap())
+        .collect();
+
+        let message_hashes: Vec<&[u8]> = message_hashes.iter().map(|m| m.as_slice()).collect();
+        let signatures: Vec<&[u8]> = signatures.iter().map(|s| s.as_slice()).collect();
+
+        assert!(secp256k1_batch_verify(&message_hashes, &signatures, &[&public_key]).unwrap());
+        assert!(secp256k1_batch_verify(
+            &message_hashes,
+            &signatures,
+            &[&public_key, &public_key, &public_key]
+        )
+        .unwrap());
+
+        // Swapped signatures fail
+        assert!(!secp256k1_batch_verify(
+            &message_hashes,
+            &[signatures[1], signatures[0], signatures[2]],
+            &[&public_key]
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_secp256k1_batch_verify_empty() {
+        let message_hash = Sha256::digest(MSG.as_bytes());
+        let public_key = base64::decode(COSMOS_SECP256K1_PUBKEY_BASE64).unwrap();
+
+        assert!(secp256k1_batch_verify(&[], &[], &[]).unwrap());
+        assert!(secp256k1_batch_verify(&[&message_hash], &[], &[]).unwrap());
+        assert!(secp256k1_batch_verify(&[], &[], &[&public_key]).unwrap());
+    }
+
+    #[test]
+    fn test_secp256k1_batch_verify_errors() {
+        let message_hash = Sha256::digest(&hex::decode(COSMOS_SECP256K1_MSG_HEX1).unwrap());
+        let signature = hex::decode(COSMOS_SECP256K1_SIGNATURE_HEX1).unwrap();
+        let public_key = base64::decode(COSMOS_SECP256K1_PUBKEY_BASE64).unwrap();
+
+        // Mismatched number of message hashes / signatures / public keys
+        let result = secp256k1_batch_verify(
+            &[&message_hash, &message_hash],
+            &[&signature, &signature, &signature],
+            &[&public_key],
+        );
+        match result.unwrap_err() {
+            CryptoError::BatchErr { .. } => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // Wrong hash length
+        let result = secp256k1_batch_verify(&[&message_hash[1..]], &[&signature], &[&public_key]);
+        match result.unwrap_err() {
+            CryptoError::InvalidHashFormat { .. } => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // Wrong signature length
+        let result = secp256k1_batch_verify(&[&message_hash], &[&signature[1..]], &[&public_key]);
+        match result.unwrap_err() {
+            CryptoError::InvalidSignatureFormat { .. } => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // Wrong public key format
+        let result = secp256k1_batch_verify(&[&message_hash], &[&signature], &[&public_key[1..]]);
+        match result.unwrap_err() {
+            CryptoError::InvalidPubkeyFormat { .. } => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
     #[test]
     fn secp256k1_recover_pubkey_works() {
         // Test data from https://github.com/ethereumjs/ethereumjs-util/blob/v6.1.0/test/index.js#L496
@@ -0,0 +1,242 @@
+use digest::Digest; // trait
+use k256::{
+    elliptic_curve::group::ff::PrimeField, // trait
+    elliptic_curve::group::Group,          // trait
+    elliptic_curve::sec1::ToEncodedPoint,  // trait
+    elliptic_curve::subtle::Choice,
+    elliptic_curve::weierstrass::DecompressPoint, // trait
+    lincomb,
+    AffinePoint,
+    FieldBytes,
+    ProjectivePoint,
+    Scalar,
+};
+use sha2::Sha256;
+use std::convert::TryInto;
+
+use crate::errors::{CryptoError, CryptoResult};
+
+/// Length of a serialized BIP-340 Schnorr signature (32 bytes r, 32 bytes s)
+pub const SCHNORR_SIGNATURE_LEN: usize = 64;
+
+/// Length of a serialized x-only public key
+pub const SCHNORR_PUBKEY_LEN: usize = 32;
+
+/// BIP-340 Schnorr secp256k1 implementation.
+///
+/// This function verifies messages against a signature, with the public key of the signer,
+/// using Schnorr signatures over the secp256k1 elliptic curve as specified in
+/// [BIP-340](https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki), which is
+/// what Bitcoin uses for Taproot.
+///
+/// The message is not hashed by this function. In Bitcoin it is typically a 32 byte
+/// transaction hash, but BIP-340 allows messages of any length.
+/// The signature and public key are in BIP-340 format:
+/// - signature: 32 bytes x coordinate of R followed by 32 bytes s (64 bytes).
+/// - public key: x-only public key (32 bytes).
+pub fn secp256k1_schnorr_verify(
+    message: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+) -> CryptoResult<bool> {
+    // Validation
+    let signature = read_signature(signature)?;
+    let pubkey = read_pubkey(public_key)?;
+
+    // The public key is the point with the given x coordinate and an even y coordinate.
+    // Coordinates not on the curve (including x >= p) fail verification.
+    let pubkey_point = AffinePoint::decompress(FieldBytes::from_slice(&pubkey), Choice::from(0));
+    let pubkey_point = match Option::<AffinePoint>::from(pubkey_point) {
+        Some(point) => ProjectivePoint::from(point),
+        None => return Ok(false),
+    };
+
+    let (r, s) = signature.split_at(32);
+    let s = match Scalar::from_repr(*FieldBytes::from_slice(s)) {
+        Some(s) => s,
+        None => return Ok(false), // s >= n
+    };
+
+    // e = int(hash_BIP0340/challenge(bytes(r) || bytes(P) || m)) mod n
+    let challenge = tagged_hash(b"BIP0340/challenge")
+        .chain(r)
+        .chain(pubkey)
+        .chain(message)
+        .finalize();
+    let e = Scalar::from_bytes_reduced(&challenge);
+
+    // R = s⋅G - e⋅P
+    let big_r = lincomb(&ProjectivePoint::generator(), &s, &pubkey_point, &-e);
+    if bool::from(big_r.is_identity()) {
+        return Ok(false);
+    }
+
+    // Verification succeeds if R has an even y coordinate and its x coordinate equals r.
+    // An r >= p never matches a reduced x coordinate.
+    let encoded = big_r.to_affine().to_encoded_point(true);
+    let has_even_y = encoded.as_bytes()[0] == 0x02;
+    Ok(has_even_y && &encoded.as_bytes()[1..] == r)
+}
+
+/// Returns a SHA-256 hasher prefixed with the BIP-340 tag, i.e.
+/// `SHA256(SHA256(tag) || SHA256(tag))`.
+fn tagged_hash(tag: &[u8]) -> Sha256 {
+    let tag_hash = Sha256::digest(tag);
+    Sha256::new().chain(tag_hash).chain(tag_hash)
+}
+
+/// Error raised when signature is not 64 bytes long (32 bytes r, 32 bytes s)
+struct InvalidSchnorrSignatureFormat;
+
+impl From<InvalidSchnorrSignatureFormat> for CryptoError {
+    fn from(_original: InvalidSchnorrSignatureFormat) -> Self {
+        CryptoError::invalid_signature_format()
+    }
+}
+
+fn read_signature(data: &[u8]) -> Result<[u8; 64], InvalidSchnorrSignatureFormat> {
+    data.try_into().map_err(|_| InvalidSchnorrSignatureFormat)
+}
+
+/// Error raised when pubkey is not a 32 bytes long x-only public key
+struct InvalidSchnorrPubkeyFormat;
+
+impl From<InvalidSchnorrPubkeyFormat> for CryptoError {
+    fn from(_original: InvalidSchnorrPubkeyFormat) -> Self {
+        CryptoError::invalid_pubkey_format()
+    }
+}
+
+fn read_pubkey(data: &[u8]) -> Result<[u8; 32], InvalidSchnorrPubkeyFormat> {
+    data.try_into().map_err(|_| InvalidSchnorrPubkeyFormat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    struct TestVector {
+        public_key: &'static [u8],
+        message: &'static [u8],
+        signature: &'static [u8],
+        valid: bool,
+    }
+
+    // Test vectors 0-6 from https://github.com/bitcoin/bips/blob/master/bip-0340/test-vectors.csv
+    const BIP340_VECTORS: [TestVector; 7] = [
+        TestVector {
+            public_key: &hex!("F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9"),
+            message: &hex!("0000000000000000000000000000000000000000000000000000000000000000"),
+            signature: &hex!("E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0"),
+            valid: true,
+        },
+        TestVector {
+            public_key: &hex!("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659"),
+            message: &hex!("243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89"),
+            signature: &hex!("6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A"),
+            valid: true,
+        },
+        TestVector {
+            public_key: &hex!("DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8"),
+            message: &hex!("7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C"),
+            signature: &hex!("5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7"),
+            valid: true,
+        },
+        TestVector {
+            public_key: &hex!("25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517"),
+            message: &hex!("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF"),
+            signature: &hex!("7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3"),
+            valid: true,
+        },
+        TestVector {
+            public_key: &hex!("D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9"),
+            message: &hex!("4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703"),
+            signature: &hex!("00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C6376AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4"),
+            valid: true,
+        },
+        // public key not on the curve
+        TestVector {
+            public_key: &hex!("EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34"),
+            message: &hex!("243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89"),
+            signature: &hex!("6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B"),
+            valid: false,
+        },
+        // has_even_y(R) is false
+        TestVector {
+            public_key: &hex!("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659"),
+            message: &hex!("243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89"),
+            signature: &hex!("FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A14602975563CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2"),
+            valid: false,
+        },
+    ];
+
+    #[test]
+    fn test_secp256k1_schnorr_verify_bip340_vectors() {
+        for (i, vector) in BIP340_VECTORS.iter().enumerate() {
+            let result =
+                secp256k1_schnorr_verify(vector.message, vector.signature, vector.public_key)
+                    .unwrap();
+            assert_eq!(result, vector.valid, "Test vector {} failed", i);
+        }
+    }
+
+    #[test]
+    fn test_secp256k1_schnorr_verify_arbitrary_message_length() {
+        // Signed with the secret key of BIP-340 test vector 1 and zero auxiliary randomness
+        let public_key = hex!("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659");
+        let signature = hex!("ab4433e40a07f5caf39b30385dd478e83360a0906d379f8d6f6d69b7c6ba0919d6205dbc6000f3152883e7bc52a2a23aa7a082c9f5811ea4fdfaf6875b736ae4");
+
+        assert!(secp256k1_schnorr_verify(b"cosmwasm", &signature, &public_key).unwrap());
+
+        // Wrong message fails
+        assert!(!secp256k1_schnorr_verify(b"cosmwasm\0", &signature, &public_key).unwrap());
+    }
+
+    #[test]
+    fn test_secp256k1_schnorr_verify_rejects_out_of_range_values() {
+        let vector = &BIP340_VECTORS[1];
+
+        // s equal to the curve order
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&vector.signature[..32]);
+        signature[32..].copy_from_slice(&hex!(
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141"
+        ));
+        assert!(!secp256k1_schnorr_verify(vector.message, &signature, vector.public_key).unwrap());
+
+        // r equal to the field size
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&hex!(
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F"
+        ));
+        signature[32..].copy_from_slice(&vector.signature[32..]);
+        assert!(!secp256k1_schnorr_verify(vector.message, &signature, vector.public_key).unwrap());
+
+        // public key equal to the field size
+        let public_key = hex!("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F");
+        assert!(!secp256k1_schnorr_verify(vector.message, vector.signature, &public_key).unwrap());
+    }
+
+    #[test]
+    fn test_secp256k1_schnorr_verify_errors() {
+        let vector = &BIP340_VECTORS[0];
+
+        // Wrong signature length
+        let result =
+            secp256k1_schnorr_verify(vector.message, &vector.signature[..63], vector.public_key);
+        match result.unwrap_err() {
+            CryptoError::InvalidSignatureFormat { .. } => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // Compressed SEC1 public keys are not x-only
+        let mut public_key = vec![0x02];
+        public_key.extend_from_slice(vector.public_key);
+        let result = secp256k1_schnorr_verify(vector.message, vector.signature, &public_key);
+        match result.unwrap_err() {
+            CryptoError::InvalidPubkeyFormat { .. } => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use digest::Digest; // trait
+use p256::{
+    ecdsa::signature::{DigestVerifier, Signature as _}, // traits
+    ecdsa::{Signature, VerifyingKey},                   // type aliases
+    elliptic_curve::ops::Reduce,
+    elliptic_curve::sec1::ToEncodedPoint,
+    elliptic_curve::subtle::Choice,
+    elliptic_curve::DecompressPoint,
+    AffinePoint,
+    FieldBytes,
+    ProjectivePoint,
+    PublicKey,
+    Scalar,
+    U256,
+};
+use std::convert::TryInto;
+
+use crate::errors::{CryptoError, CryptoResult};
+use crate::identity_digest::Identity256;
+
+/// Length of a serialized compressed public key
+const ECDSA_COMPRESSED_PUBKEY_LEN: usize = 33;
+/// Length of a serialized uncompressed public key
+const ECDSA_UNCOMPRESSED_PUBKEY_LEN: usize = 65;
+
+/// ECDSA secp256r1 (also known as NIST P-256 or prime256v1) implementation.
+///
+/// This function verifies message hashes (typically, hashed using SHA-256) against a signature,
+/// with the public key of the signer, using the secp256r1 elliptic curve digital signature
+/// parametrization / algorithm. This is the curve used by WebAuthn / passkeys.
+///
+/// The signature and public key are in the same format as for secp256k1:
+/// - signature:  Serialized "compact" signature (64 bytes).
+/// - public key: Serialized according to SEC 1 (33 or 65 bytes).
+///
+/// In contrast to [`secp256k1_verify`](crate::secp256k1_verify), high-S signatures are
+/// accepted as they are. WebAuthn authenticators do not normalize their signatures.
+pub fn secp256r1_verify(
+    message_hash: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+) -> CryptoResult<bool> {
+    let message_hash = read_hash(message_hash)?;
+    let signature = read_signature(signature)?;
+    check_pubkey(public_key)?;
+
+    // Already hashed, just build Digest container
+    let message_digest = Identity256::new().chain(message_hash);
+
+    let signature =
+        Signature::from_bytes(&signature).map_err(|e| CryptoError::generic_err(e.to_string()))?;
+
+    let public_key = VerifyingKey::from_sec1_bytes(public_key)
+        .map_err(|e| CryptoError::generic_err(e.to_string()))?;
+
+    match public_key.verify_digest(message_digest, &signature) {
+        Ok(()) => Ok(true),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Recovers a secp256r1 public key from a message hash and a signature.
+///
+/// `recovery_param` is the parity of the y coordinate of the signature's R point and
+/// must be 0 or 1. Like for [`secp256k1_recover_pubkey`](crate::secp256k1_recover_pubkey),
+/// the values 2 and 3 are unsupported and all other values are invalid.
+///
+/// Returns the recovered pubkey in uncompressed form, which can be used
+/// in secp256r1_verify directly.
+pub fn secp256r1_recover_pubkey(
+    message_hash: &[u8],
+    signature: &[u8],
+    recovery_param: u8,
+) -> CryptoResult<Vec<u8>> {
+    let message_hash = read_hash(message_hash)?;
+    let signature = read_signature(signature)?;
+
+    if recovery_param > 1 {
+        return Err(CryptoError::invalid_recovery_param());
+    }
+
+    let signature =
+        Signature::from_bytes(&signature).map_err(|e| CryptoError::generic_err(e.to_string()))?;
+    let (r, s) = signature.split_scalars();
+
+    // R is the point with x coordinate r and the y parity given by the recovery param
+    let big_r = Option::<AffinePoint>::from(AffinePoint::decompress(
+        &r.to_bytes(),
+        Choice::from(recovery_param),
+    ))
+    .ok_or_else(|| CryptoError::generic_err("Signature R is not a valid curve point"))?;
+
+    // pubkey = r^-1 * (s * R - z * G) = (s * r^-1) * R + (-z * r^-1) * G
+    let z = <Scalar as Reduce<U256>>::from_be_bytes_reduced(FieldBytes::from(message_hash));
+    let r_inv = Option::<Scalar>::from(r.invert())
+        .ok_or_else(|| CryptoError::generic_err("Signature r is not invertible"))?;
+    let point =
+        ProjectivePoint::from(big_r) * (*s * r_inv) + ProjectivePoint::GENERATOR * (-z * r_inv);
+
+    let pubkey = PublicKey::from_affine(point.to_affine())
+        .map_err(|e| CryptoError::generic_err(e.to_string()))?;
+    let encoded: Vec<u8> = pubkey.to_encoded_point(false).as_bytes().into();
+    Ok(encoded)
+}
+
+fn read_hash(data: &[u8]) -> CryptoResult<[u8; 32]> {
+    data.try_into()
+        .map_err(|_| CryptoError::invalid_hash_format())
+}
+
+fn read_signature(data: &[u8]) -> CryptoResult<[u8; 64]> {
+    data.try_into()
+        .map_err(|_| CryptoError::invalid_signature_format())
+}
+
+fn check_pubkey(data: &[u8]) -> CryptoResult<()> {
+    let ok = match data.first() {
+        Some(0x02) | Some(0x03) => data.len() == ECDSA_COMPRESSED_PUBKEY_LEN,
+        Some(0x04) => data.len() == ECDSA_UNCOMPRESSED_PUBKEY_LEN,
+        _ => false,
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(CryptoError::invalid_pubkey_format())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hex_literal::hex;
+    use p256::{
+        ecdsa::signature::DigestSigner, // trait
+        ecdsa::SigningKey,              // type alias
+        elliptic_curve::rand_core::OsRng,
+    };
+    use sha2::Sha256;
+
+    // For generic signature verification
+    const MSG: &str = "Hello World!";
+
+    #[test]
+    fn test_secp256r1_verify() {
+        // Explicit / external hashing
+        let message_digest = Sha256::new().chain(MSG);
+        let message_hash = message_digest.clone().finalize();
+
+        // Signing
+        let secret_key = SigningKey::random(&mut OsRng);
+        let signature: Signature = secret_key.sign_digest(message_digest);
+
+        let public_key = VerifyingKey::from(&secret_key);
+
+        // Verification (uncompressed public key)
+        assert!(secp256r1_verify(
+            &message_hash,
+            signature.as_bytes(),
+            public_key.to_encoded_point(false).as_bytes()
+        )
+        .unwrap());
+
+        // Verification (compressed public key)
+        assert!(secp256r1_verify(
+            &message_hash,
+            signature.as_bytes(),
+            public_key.to_encoded_point(true).as_bytes()
+        )
+        .unwrap());
+
+        // Wrong message fails
+        let bad_message_hash = Sha256::new().chain(MSG).chain("\0").finalize();
+        assert!(!secp256r1_verify(
+            &bad_message_hash,
+            signature.as_bytes(),
+            public_key.to_encoded_point(false).as_bytes()
+        )
+        .unwrap());
+
+        // Other pubkey fails
+        let other_secret_key = SigningKey::random(&mut OsRng);
+        let other_public_key = VerifyingKey::from(&other_secret_key);
+        assert!(!secp256r1_verify(
+            &message_hash,
+            signature.as_bytes(),
+            other_public_key.to_encoded_point(false).as_bytes()
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn secp256r1_verify_works_for_test_vector() {
+        // Test vector from RFC 6979, A.2.5 (ECDSA, 256 Bits (Prime Field)), with SHA-256 and message "sample"
+        let public_key = hex!("0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299");
+        let message_hash = Sha256::digest(b"sample");
+        let signature = hex!("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8");
+        assert!(secp256r1_verify(&message_hash, &signature, &public_key).unwrap());
+
+        // This is a high-S signature. Its low-S counterpart (s' = n - s) is valid as well.
+        let signature_low_s = hex!("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf37160834e36ad29a83bf2bc9385e491d6099c8fdf9d1ed67aa7ea5f51f93782857a9");
+        assert!(secp256r1_verify(&message_hash, &signature_low_s, &public_key).unwrap());
+
+        // Wrong message fails
+        let other_hash = Sha256::digest(b"test");
+        assert!(!secp256r1_verify(&other_hash, &signature, &public_key).unwrap());
+    }
+
+    #[test]
+    fn secp256r1_verify_fails_for_invalid_input() {
+        let public_key = hex!("0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299");
+        let message_hash = Sha256::digest(b"sample");
+        let signature = hex!("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8");
+
+        match secp256r1_verify(&message_hash[..31], &signature, &public_key).unwrap_err() {
+            CryptoError::InvalidHashFormat { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        match secp256r1_verify(&message_hash, &signature[..63], &public_key).unwrap_err() {
+            CryptoError::InvalidSignatureFormat { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        match secp256r1_verify(&message_hash, &signature, &public_key[1..]).unwrap_err() {
+            CryptoError::InvalidPubkeyFormat { .. } => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn secp256r1_recover_pubkey_works() {
+        // Round trip with random keys
+        for _ in 0..8 {
+            let secret_key = SigningKey::random(&mut OsRng);
+            let expected = VerifyingKey::from(&secret_key)
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec();
+            let message_digest = Sha256::new().chain(MSG);
+            let message_hash = message_digest.clone().finalize();
+            let signature: Signature = secret_key.sign_digest(message_digest);
+
+            let recovered: Vec<_> = [0, 1]
+                .iter()
+                .map(|param| {
+                    secp256r1_recover_pubkey(&message_hash, signature.as_bytes(), *param).unwrap()
+                })
+                .collect();
+            assert!(recovered.contains(&expected));
+            assert_ne!(recovered[0], recovered[1]);
+        }
+
+        // Test vector from RFC 6979, A.2.5 (see above)
+        let expected = hex!("0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299");
+        let message_hash = Sha256::digest(b"sample");
+        let signature = hex!("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8");
+        let pubkey = secp256r1_recover_pubkey(&message_hash, &signature, 0).unwrap();
+        assert_eq!(pubkey, expected);
+    }
+
+    #[test]
+    fn secp256r1_recover_pubkey_fails_for_invalid_recovery_param() {
+        let message_hash = Sha256::digest(b"sample");
+        let signature = hex!("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8");
+
+        for recovery_param in [2u8, 3, 4, 255] {
+            match secp256r1_recover_pubkey(&message_hash, &signature, recovery_param).unwrap_err() {
+                CryptoError::InvalidRecoveryParam { .. } => {}
+                err => panic!("Unexpected error: {}", err),
+            }
+        }
+    }
+}
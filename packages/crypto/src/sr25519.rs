@@ -0,0 +1,155 @@
+use schnorrkel::{PublicKey, Signature};
+use std::convert::TryInto;
+
+use crate::errors::{CryptoError, CryptoResult};
+
+/// Length of a serialized public key
+pub const SR25519_PUBKEY_LEN: usize = 32;
+
+/// Signing context used by Substrate for sr25519 signatures
+const SUBSTRATE_SIGNING_CONTEXT: &[u8] = b"substrate";
+
+/// Schnorr sr25519 implementation.
+///
+/// This function verifies messages against a signature, with the public key of the signer,
+/// using Schnorr signatures over the Ristretto group of Curve25519 as used by
+/// [Substrate](https://docs.substrate.io/v3/advanced/cryptography/#public-key-cryptography).
+///
+/// Messages are signed with the "substrate" signing context, which is what Substrate
+/// accounts use.
+/// The signature and public key are in Substrate format:
+/// - signature: raw sr25519 signature (64 bytes).
+/// - public key: raw sr25519 public key (32 bytes).
+pub fn sr25519_verify(message: &[u8], signature: &[u8], public_key: &[u8]) -> CryptoResult<bool> {
+    // Validation
+    let signature = read_signature(signature)?;
+    let pubkey = read_pubkey(public_key)?;
+
+    // Verification
+    let signature = match Signature::from_bytes(&signature) {
+        Ok(signature) => signature,
+        Err(_) => return Ok(false),
+    };
+    let pubkey = match PublicKey::from_bytes(&pubkey) {
+        Ok(pubkey) => pubkey,
+        Err(_) => return Ok(false),
+    };
+    match pubkey.verify_simple(SUBSTRATE_SIGNING_CONTEXT, message, &signature) {
+        Ok(()) => Ok(true),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Error raised when signature is not 64 bytes long
+struct InvalidSr25519SignatureFormat;
+
+impl From<InvalidSr25519SignatureFormat> for CryptoError {
+    fn from(_original: InvalidSr25519SignatureFormat) -> Self {
+        CryptoError::invalid_signature_format()
+    }
+}
+
+fn read_signature(data: &[u8]) -> Result<[u8; 64], InvalidSr25519SignatureFormat> {
+    data.try_into().map_err(|_| InvalidSr25519SignatureFormat)
+}
+
+/// Error raised when pubkey is not 32 bytes long
+struct InvalidSr25519PubkeyFormat;
+
+impl From<InvalidSr25519PubkeyFormat> for CryptoError {
+    fn from(_original: InvalidSr25519PubkeyFormat) -> Self {
+        CryptoError::invalid_pubkey_format()
+    }
+}
+
+fn read_pubkey(data: &[u8]) -> Result<[u8; 32], InvalidSr25519PubkeyFormat> {
+    data.try_into().map_err(|_| InvalidSr25519PubkeyFormat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use schnorrkel::{ExpansionMode, MiniSecretKey};
+
+    // For generic signature verification
+    const MSG: &str = "Hello World!";
+
+    // Signature generated with the "substrate" signing context from the mini secret key [7u8; 32]
+    // (expanded in ed25519 mode, which is what Substrate uses)
+    const SUBSTRATE_MSG: &[u8] = b"cosmwasm";
+    const SUBSTRATE_PUBLIC_KEY: [u8; 32] =
+        hex!("7c0f469d3bd340bae718203fa30ca071a5e37c751e891dbded837b213d45d91d");
+    const SUBSTRATE_SIGNATURE: [u8; 64] = hex!("fce73cea3fcc441e702a09d189a022e258eeaf49961e74b9b9e1642db618420df2187c1edf8ef0e5adcf50875cb1aa33e9421b71a97baf268a0cdb81a97f0f86");
+
+    #[test]
+    fn test_sr25519_verify() {
+        let message = MSG.as_bytes();
+        // Signing
+        let keypair = MiniSecretKey::generate().expand_to_keypair(ExpansionMode::Ed25519);
+        let context = schnorrkel::signing_context(SUBSTRATE_SIGNING_CONTEXT);
+        let signature = keypair.sign(context.bytes(message));
+
+        let signature_bytes = signature.to_bytes();
+        let public_key_bytes = keypair.public.to_bytes();
+
+        // Verification
+        assert!(sr25519_verify(message, &signature_bytes, &public_key_bytes).unwrap());
+
+        // Wrong message fails
+        let bad_message = [message, b"\0"].concat();
+        assert!(!sr25519_verify(&bad_message, &signature_bytes, &public_key_bytes).unwrap());
+
+        // Other pubkey fails
+        let other_keypair = MiniSecretKey::generate().expand_to_keypair(ExpansionMode::Ed25519);
+        let other_public_key_bytes = other_keypair.public.to_bytes();
+        assert!(!sr25519_verify(message, &signature_bytes, &other_public_key_bytes).unwrap());
+
+        // Other signing context fails
+        let other_context = schnorrkel::signing_context(b"polkadot");
+        let other_signature = keypair.sign(other_context.bytes(message)).to_bytes();
+        assert!(!sr25519_verify(message, &other_signature, &public_key_bytes).unwrap());
+    }
+
+    #[test]
+    fn test_substrate_sr25519_verify() {
+        let keypair = MiniSecretKey::from_bytes(&[7u8; 32])
+            .unwrap()
+            .expand_to_keypair(ExpansionMode::Ed25519);
+        assert_eq!(keypair.public.to_bytes(), SUBSTRATE_PUBLIC_KEY);
+
+        assert!(
+            sr25519_verify(SUBSTRATE_MSG, &SUBSTRATE_SIGNATURE, &SUBSTRATE_PUBLIC_KEY).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sr25519_verify_errors() {
+        // Wrong signature length
+        let result = sr25519_verify(
+            SUBSTRATE_MSG,
+            &SUBSTRATE_SIGNATURE[..63],
+            &SUBSTRATE_PUBLIC_KEY,
+        );
+        match result.unwrap_err() {
+            CryptoError::InvalidSignatureFormat { .. } => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // Wrong pubkey length
+        let result = sr25519_verify(
+            SUBSTRATE_MSG,
+            &SUBSTRATE_SIGNATURE,
+            &SUBSTRATE_PUBLIC_KEY[..31],
+        );
+        match result.unwrap_err() {
+            CryptoError::InvalidPubkeyFormat { .. } => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // Signature without the schnorrkel marker bit is rejected
+        let mut unmarked = SUBSTRATE_SIGNATURE;
+        unmarked[63] &= 0x7f;
+        assert!(!sr25519_verify(SUBSTRATE_MSG, &unmarked, &SUBSTRATE_PUBLIC_KEY).unwrap());
+    }
+}
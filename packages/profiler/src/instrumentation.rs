@@ -59,8 +59,13 @@ impl<'d> Module<'d> {
         let wasm = walrus_module.emit_wasm();
         //let wasmer_module = wasmer::Module::new(&store, wasm).unwrap();
 
-        let wasmer_module =
-            cosmwasm_vm::internals::compile(&wasm, None, &[profiling.clone()]).unwrap();
+        let wasmer_module = cosmwasm_vm::internals::compile(
+            &wasm,
+            None,
+            &cosmwasm_vm::OperatorCostTable::default(),
+            &[profiling.clone()],
+        )
+        .unwrap();
         let store = wasmer_module.store();
 
         // Mock imports that do nothing.
@@ -84,6 +89,7 @@ impl<'d> Module<'d> {
             backend,
             999999999,
             false,
+            cosmwasm_vm::GasConfig::default(),
             Some(vec![("profiling", fns_to_import)].into_iter().collect()),
         )
         .unwrap();
@@ -18,8 +18,12 @@ pub enum VerificationError {
     InvalidSignatureFormat,
     #[error("Invalid public key format")]
     InvalidPubkeyFormat,
+    #[error("Invalid point")]
+    InvalidPoint,
     #[error("Invalid recovery parameter. Supported values: 0 and 1.")]
     InvalidRecoveryParam,
+    #[error("{kind} is not supported by this Api")]
+    Unsupported { kind: String },
     #[error("Unknown error: {error_code}")]
     UnknownErr {
         error_code: u32,
@@ -29,6 +33,10 @@ pub enum VerificationError {
 }
 
 impl VerificationError {
+    pub fn unsupported(kind: impl Into<String>) -> Self {
+        VerificationError::Unsupported { kind: kind.into() }
+    }
+
     pub fn unknown_err(error_code: u32) -> Self {
         VerificationError::UnknownErr {
             error_code,
@@ -52,9 +60,17 @@ impl PartialEq<VerificationError> for VerificationError {
             VerificationError::InvalidSignatureFormat => {
                 matches!(rhs, VerificationError::InvalidSignatureFormat)
             }
+            VerificationError::InvalidPoint => matches!(rhs, VerificationError::InvalidPoint),
             VerificationError::InvalidRecoveryParam => {
                 matches!(rhs, VerificationError::InvalidRecoveryParam)
             }
+            VerificationError::Unsupported { kind } => {
+                if let VerificationError::Unsupported { kind: rhs_kind } = rhs {
+                    kind == rhs_kind
+                } else {
+                    false
+                }
+            }
             VerificationError::UnknownErr { error_code, .. } => {
                 if let VerificationError::UnknownErr {
                     error_code: rhs_error_code,
@@ -78,6 +94,7 @@ impl From<CryptoError> for VerificationError {
             CryptoError::InvalidPubkeyFormat { .. } => VerificationError::InvalidPubkeyFormat,
             CryptoError::InvalidSignatureFormat { .. } => VerificationError::InvalidSignatureFormat,
             CryptoError::GenericErr { .. } => VerificationError::GenericErr,
+            CryptoError::InvalidPoint { .. } => VerificationError::InvalidPoint,
             CryptoError::InvalidRecoveryParam { .. } => VerificationError::InvalidRecoveryParam,
             CryptoError::BatchErr { .. } => VerificationError::BatchErr,
         }
@@ -89,6 +106,15 @@ mod tests {
     use super::*;
 
     // constructors
+    #[test]
+    fn unsupported_works() {
+        let error = VerificationError::unsupported("sha256");
+        match error {
+            VerificationError::Unsupported { kind } => assert_eq!(kind, "sha256"),
+            _ => panic!("wrong error type!"),
+        }
+    }
+
     #[test]
     fn unknown_err_works() {
         let error = VerificationError::unknown_err(123);
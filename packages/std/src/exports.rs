@@ -8,6 +8,8 @@
 //! the contract-specific function pointer. This is done via the `#[entry_point]`
 //! macro attribute from cosmwasm-derive.
 use std::marker::PhantomData;
+#[cfg(feature = "abort")]
+use std::sync::Once;
 use std::vec::Vec;
 
 use serde::de::DeserializeOwned;
@@ -18,6 +20,8 @@ use crate::ibc::{
     IbcBasicResponse, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcPacketAckMsg,
     IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse,
 };
+#[cfg(feature = "abort")]
+use crate::imports::handle_panic;
 use crate::imports::{ExternalApi, ExternalQuerier, ExternalStorage};
 use crate::memory::{alloc, consume_region, release_buffer, Region};
 use crate::query::CustomQuery;
@@ -38,6 +42,38 @@ extern "C" fn requires_staking() -> () {}
 #[no_mangle]
 extern "C" fn requires_stargate() -> () {}
 
+#[cfg(feature = "extended_storage")]
+#[no_mangle]
+extern "C" fn requires_extended_storage() -> () {}
+
+#[cfg(feature = "secp256r1")]
+#[no_mangle]
+extern "C" fn requires_secp256r1() -> () {}
+
+#[cfg(feature = "hashes")]
+#[no_mangle]
+extern "C" fn requires_hashes() -> () {}
+
+#[cfg(feature = "bls12_381")]
+#[no_mangle]
+extern "C" fn requires_bls12_381() -> () {}
+
+#[cfg(feature = "sr25519")]
+#[no_mangle]
+extern "C" fn requires_sr25519() -> () {}
+
+#[cfg(feature = "secp256k1_schnorr")]
+#[no_mangle]
+extern "C" fn requires_secp256k1_schnorr() -> () {}
+
+#[cfg(feature = "secp256k1_batch")]
+#[no_mangle]
+extern "C" fn requires_secp256k1_batch() -> () {}
+
+#[cfg(feature = "abort")]
+#[no_mangle]
+extern "C" fn requires_abort() -> () {}
+
 /// interface_version_* exports mark which Wasm VM interface level this contract is compiled for.
 /// They can be checked by cosmwasm_vm.
 /// Update this whenever the Wasm VM interface breaks.
@@ -60,6 +96,24 @@ extern "C" fn deallocate(pointer: u32) {
     let _ = unsafe { consume_region(pointer as *mut Region) };
 }
 
+/// Installs a panic hook that passes the panic message and location to the host
+/// using the abort import. Without it, a panic only shows up as an `unreachable` trap.
+/// The hook is only set on the first call, later entry point calls on the same instance are no-ops.
+#[cfg(feature = "abort")]
+fn install_panic_handler() {
+    static SET_HOOK: Once = Once::new();
+    SET_HOOK.call_once(|| {
+        std::panic::set_hook(Box::new(|info| {
+            // E.g. "panicked at 'attempt to subtract with overflow', src/contract.rs:42:5"
+            let full_message = info.to_string();
+            handle_panic(&full_message);
+        }));
+    });
+}
+
+#[cfg(not(feature = "abort"))]
+fn install_panic_handler() {}
+
 // TODO: replace with https://doc.rust-lang.org/std/ops/trait.Try.html once stabilized
 macro_rules! r#try_into_contract_result {
     ($expr:expr) => {
@@ -93,6 +147,7 @@ where
     C: CustomMsg,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_instantiate(
         instantiate_fn,
         env_ptr as *mut Region,
@@ -121,6 +176,7 @@ where
     C: CustomMsg,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_execute(
         execute_fn,
         env_ptr as *mut Region,
@@ -148,6 +204,7 @@ where
     C: CustomMsg,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_migrate(migrate_fn, env_ptr as *mut Region, msg_ptr as *mut Region);
     let v = to_vec(&res).unwrap();
     release_buffer(v) as u32
@@ -170,6 +227,7 @@ where
     C: CustomMsg,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_sudo(sudo_fn, env_ptr as *mut Region, msg_ptr as *mut Region);
     let v = to_vec(&res).unwrap();
     release_buffer(v) as u32
@@ -191,6 +249,7 @@ where
     C: CustomMsg,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_reply(reply_fn, env_ptr as *mut Region, msg_ptr as *mut Region);
     let v = to_vec(&res).unwrap();
     release_buffer(v) as u32
@@ -211,6 +270,7 @@ where
     M: DeserializeOwned,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_query(query_fn, env_ptr as *mut Region, msg_ptr as *mut Region);
     let v = to_vec(&res).unwrap();
     release_buffer(v) as u32
@@ -232,6 +292,7 @@ where
     Q: CustomQuery,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_ibc_channel_open(contract_fn, env_ptr as *mut Region, msg_ptr as *mut Region);
     let v = to_vec(&res).unwrap();
     release_buffer(v) as u32
@@ -255,6 +316,7 @@ where
     C: CustomMsg,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_ibc_channel_connect(contract_fn, env_ptr as *mut Region, msg_ptr as *mut Region);
     let v = to_vec(&res).unwrap();
     release_buffer(v) as u32
@@ -278,6 +340,7 @@ where
     C: CustomMsg,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_ibc_channel_close(contract_fn, env_ptr as *mut Region, msg_ptr as *mut Region);
     let v = to_vec(&res).unwrap();
     release_buffer(v) as u32
@@ -302,6 +365,7 @@ where
     C: CustomMsg,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_ibc_packet_receive(contract_fn, env_ptr as *mut Region, msg_ptr as *mut Region);
     let v = to_vec(&res).unwrap();
     release_buffer(v) as u32
@@ -326,6 +390,7 @@ where
     C: CustomMsg,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_ibc_packet_ack(contract_fn, env_ptr as *mut Region, msg_ptr as *mut Region);
     let v = to_vec(&res).unwrap();
     release_buffer(v) as u32
@@ -351,6 +416,7 @@ where
     C: CustomMsg,
     E: ToString,
 {
+    install_panic_handler();
     let res = _do_ibc_packet_timeout(contract_fn, env_ptr as *mut Region, msg_ptr as *mut Region);
     let v = to_vec(&res).unwrap();
     release_buffer(v) as u32
@@ -1,3 +1,5 @@
+#[cfg(any(feature = "hashes", feature = "bls12_381"))]
+use std::convert::TryInto;
 use std::vec::Vec;
 
 use crate::addresses::{Addr, CanonicalAddr};
@@ -6,6 +8,8 @@ use crate::errors::{RecoverPubkeyError, StdError, StdResult, SystemError, Verifi
 use crate::import_helpers::{from_high_half, from_low_half};
 use crate::memory::{alloc, build_region, consume_region, Region};
 use crate::results::SystemResult;
+#[cfg(feature = "extended_storage")]
+use crate::sections::decode_sections;
 #[cfg(feature = "iterator")]
 use crate::sections::decode_sections2;
 use crate::sections::encode_sections;
@@ -21,6 +25,19 @@ use crate::{
 const CANONICAL_ADDRESS_BUFFER_LENGTH: usize = 64;
 /// An upper bound for typical human readable address formats (e.g. 42 for Ethereum hex addresses or 90 for bech32)
 const HUMAN_ADDRESS_BUFFER_LENGTH: usize = 90;
+/// The maximum input length of the hash functions (see MAX_LENGTH_HASH_INPUT in the VM)
+#[cfg(feature = "hashes")]
+const HASH_INPUT_MAX_LENGTH: usize = 128 * 1024;
+/// The maximum message length of the BLS12-381 hash-to-curve functions (see MAX_LENGTH_BLS12_381_MESSAGE in the VM)
+#[cfg(feature = "bls12_381")]
+const BLS12_381_MESSAGE_MAX_LENGTH: usize = 8 * 1024;
+/// The maximum length of a domain separation tag (see MAX_LENGTH_BLS12_381_DST in the VM)
+#[cfg(feature = "bls12_381")]
+const BLS12_381_DST_MAX_LENGTH: usize = 255;
+/// The maximum number of keys the VM reads in one db_read_many call (see MAX_COUNT_DB_READ_MANY in the VM).
+/// Longer lists of keys are split into multiple calls.
+#[cfg(feature = "extended_storage")]
+const DB_READ_MANY_MAX_KEYS: usize = 256;
 
 // This interface will compile into required Wasm imports.
 // A complete documentation those functions is available in the VM that provides them:
@@ -36,6 +53,16 @@ extern "C" {
     #[cfg(feature = "iterator")]
     fn db_next(iterator_id: u32) -> u32;
 
+    // Reads multiple keys at once. Keys and values are encoded as sections.
+    // An empty value section means the key does not exist.
+    #[cfg(feature = "extended_storage")]
+    fn db_read_many(keys_ptr: u32) -> u32;
+    // Like db_next but only returns the key or value. Returns 0 if there are no more elements.
+    #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+    fn db_next_key(iterator_id: u32) -> u32;
+    #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+    fn db_next_value(iterator_id: u32) -> u32;
+
     fn addr_validate(source_ptr: u32) -> u32;
     fn addr_canonicalize(source_ptr: u32, destination_ptr: u32) -> u32;
     fn addr_humanize(source_ptr: u32, destination_ptr: u32) -> u32;
@@ -46,12 +73,37 @@ extern "C" {
     /// greater than 1 in case of error.
     fn secp256k1_verify(message_hash_ptr: u32, signature_ptr: u32, public_key_ptr: u32) -> u32;
 
+    /// Verifies a batch of message hashes against a batch of signatures with a batch of public keys,
+    /// using the secp256k1 ECDSA parametrization.
+    /// Returns 0 on verification success (all batches verify correctly), 1 on verification failure, and values
+    /// greater than 1 in case of error.
+    #[cfg(feature = "secp256k1_batch")]
+    fn secp256k1_batch_verify(
+        message_hashes_ptr: u32,
+        signatures_ptr: u32,
+        public_keys_ptr: u32,
+    ) -> u32;
+
     fn secp256k1_recover_pubkey(
         message_hash_ptr: u32,
         signature_ptr: u32,
         recovery_param: u32,
     ) -> u64;
 
+    /// Verifies message hashes against a signature with a public key, using the
+    /// secp256r1 (NIST P-256) ECDSA parametrization.
+    /// Returns 0 on verification success, 1 on verification failure, and values
+    /// greater than 1 in case of error.
+    #[cfg(feature = "secp256r1")]
+    fn secp256r1_verify(message_hash_ptr: u32, signature_ptr: u32, public_key_ptr: u32) -> u32;
+
+    #[cfg(feature = "secp256r1")]
+    fn secp256r1_recover_pubkey(
+        message_hash_ptr: u32,
+        signature_ptr: u32,
+        recovery_param: u32,
+    ) -> u64;
+
     /// Verifies a message against a signature with a public key, using the
     /// ed25519 EdDSA scheme.
     /// Returns 0 on verification success, 1 on verification failure, and values
@@ -64,11 +116,71 @@ extern "C" {
     /// greater than 1 in case of error.
     fn ed25519_batch_verify(messages_ptr: u32, signatures_ptr: u32, public_keys_ptr: u32) -> u32;
 
+    /// Verifies a message against a signature with a public key, using the
+    /// sr25519 Schnorr scheme with the "substrate" signing context.
+    /// Returns 0 on verification success, 1 on verification failure, and values
+    /// greater than 1 in case of error.
+    #[cfg(feature = "sr25519")]
+    fn sr25519_verify(message_ptr: u32, signature_ptr: u32, public_key_ptr: u32) -> u32;
+
+    /// Verifies a message against a BIP-340 Schnorr signature with an x-only public key,
+    /// over the secp256k1 elliptic curve.
+    /// Returns 0 on verification success, 1 on verification failure, and values
+    /// greater than 1 in case of error.
+    #[cfg(feature = "secp256k1_schnorr")]
+    fn secp256k1_schnorr_verify(message_ptr: u32, signature_ptr: u32, public_key_ptr: u32) -> u32;
+
+    /// Aggregates a list of BLS12-381 points, encoded with `sections`.
+    /// Returns the error code in the high half and a region containing the
+    /// compressed sum in the low half.
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_aggregate_g1(points_ptr: u32) -> u64;
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_aggregate_g2(points_ptr: u32) -> u64;
+
+    /// Checks the BLS12-381 pairing equation e(p_1, q_1) * ... * e(p_n, q_n) = e(r, s).
+    /// Returns 0 if the equation holds, 1 if it does not, and values
+    /// greater than 1 in case of error.
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_pairing_equality(ps_ptr: u32, qs_ptr: u32, r_ptr: u32, s_ptr: u32) -> u32;
+
+    /// Verifies an aggregate BLS12-381 signature of distinct messages.
+    /// Returns 0 on verification success, 1 on verification failure, and values
+    /// greater than 1 in case of error.
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_aggregate_verify(
+        public_keys_ptr: u32,
+        messages_ptr: u32,
+        signature_ptr: u32,
+        dst_ptr: u32,
+    ) -> u32;
+
+    /// Hash-to-curve functions. They return a region containing the compressed point.
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_hash_to_g1(msg_ptr: u32, dst_ptr: u32) -> u32;
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_hash_to_g2(msg_ptr: u32, dst_ptr: u32) -> u32;
+
+    /// Hash functions. They return a region containing the hash.
+    #[cfg(feature = "hashes")]
+    fn sha256(data_ptr: u32) -> u32;
+    #[cfg(feature = "hashes")]
+    fn keccak256(data_ptr: u32) -> u32;
+    #[cfg(feature = "hashes")]
+    fn ripemd160(data_ptr: u32) -> u32;
+    #[cfg(feature = "hashes")]
+    fn blake2b(data_ptr: u32) -> u32;
+
     /// Writes a debug message (UFT-8 encoded) to the host for debugging purposes.
     /// The host is free to log or process this in any way it considers appropriate.
     /// In production environments it is expected that those messages are discarded.
     fn debug(source_ptr: u32);
 
+    /// Aborts the contract execution with a message (UTF-8 encoded), which the host
+    /// returns as an error. This is called by the panic handler (see `exports.rs`).
+    #[cfg(feature = "abort")]
+    fn abort(source_ptr: u32);
+
     /// Executes a query on the chain (import). Not to be confused with the
     /// query export, which queries the state of the contract.
     fn query_chain(request: u32) -> u32;
@@ -100,6 +212,27 @@ impl Storage for ExternalStorage {
         Some(data)
     }
 
+    #[cfg(feature = "extended_storage")]
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        let mut out = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(DB_READ_MANY_MAX_KEYS) {
+            let keys = build_region(&encode_sections(chunk));
+            let keys_ptr = &*keys as *const Region as u32;
+
+            let read = unsafe { db_read_many(keys_ptr) };
+            let values = unsafe { consume_region(read as *mut Region) };
+            // Empty values cannot be stored, so an empty section means the key does not exist
+            out.extend(decode_sections(values).into_iter().map(|value| {
+                if value.is_empty() {
+                    None
+                } else {
+                    Some(value)
+                }
+            }));
+        }
+        out
+    }
+
     fn set(&mut self, key: &[u8], value: &[u8]) {
         if value.is_empty() {
             panic!("TL;DR: Value must not be empty in Storage::set but in most cases you can use Storage::remove instead. Long story: Getting empty values from storage is not well supported at the moment. Some of our internal interfaces cannot differentiate between a non-existent key and an empty value. Right now, you cannot rely on the behaviour of empty values. To protect you from trouble later on, we stop here. Sorry for the inconvenience! We highly welcome you to contribute to CosmWasm, making this more solid one way or the other.");
@@ -127,16 +260,52 @@ impl Storage for ExternalStorage {
         end: Option<&[u8]>,
         order: Order,
     ) -> Box<dyn Iterator<Item = Record>> {
-        // There is lots of gotchas on turning options into regions for FFI, thus this design
-        // See: https://github.com/CosmWasm/cosmwasm/pull/509
-        let start_region = start.map(build_region);
-        let end_region = end.map(build_region);
-        let start_region_addr = get_optional_region_address(&start_region.as_ref());
-        let end_region_addr = get_optional_region_address(&end_region.as_ref());
-        let iterator_id = unsafe { db_scan(start_region_addr, end_region_addr, order as i32) };
+        let iterator_id = scan(start, end, order);
         let iter = ExternalIterator { iterator_id };
         Box::new(iter)
     }
+
+    #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+    fn range_keys(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Vec<u8>>> {
+        let iterator_id = scan(start, end, order);
+        let iter = ExternalPartIterator {
+            iterator_id,
+            next_part: db_next_key,
+        };
+        Box::new(iter)
+    }
+
+    #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+    fn range_values(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Vec<u8>>> {
+        let iterator_id = scan(start, end, order);
+        let iter = ExternalPartIterator {
+            iterator_id,
+            next_part: db_next_value,
+        };
+        Box::new(iter)
+    }
+}
+
+#[cfg(feature = "iterator")]
+/// Creates an iterator in the VM and returns its ID
+fn scan(start: Option<&[u8]>, end: Option<&[u8]>, order: Order) -> u32 {
+    // There is lots of gotchas on turning options into regions for FFI, thus this design
+    // See: https://github.com/CosmWasm/cosmwasm/pull/509
+    let start_region = start.map(build_region);
+    let end_region = end.map(build_region);
+    let start_region_addr = get_optional_region_address(&start_region.as_ref());
+    let end_region_addr = get_optional_region_address(&end_region.as_ref());
+    unsafe { db_scan(start_region_addr, end_region_addr, order as i32) }
 }
 
 #[cfg(feature = "iterator")]
@@ -163,6 +332,29 @@ impl Iterator for ExternalIterator {
     }
 }
 
+#[cfg(all(feature = "iterator", feature = "extended_storage"))]
+/// ExternalPartIterator is like ExternalIterator but only gets the keys or the values
+/// of the elements, using db_next_key or db_next_value.
+struct ExternalPartIterator {
+    iterator_id: u32,
+    next_part: unsafe extern "C" fn(iterator_id: u32) -> u32,
+}
+
+#[cfg(all(feature = "iterator", feature = "extended_storage"))]
+impl Iterator for ExternalPartIterator {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next_result = unsafe { (self.next_part)(self.iterator_id) };
+        if next_result == 0 {
+            // no more elements
+            return None;
+        }
+        let data = unsafe { consume_region(next_result as *mut Region) };
+        Some(data)
+    }
+}
+
 /// A stateless convenience wrapper around imports provided by the VM
 #[derive(Copy, Clone)]
 pub struct ExternalApi {}
@@ -268,6 +460,39 @@ impl Api for ExternalApi {
         }
     }
 
+    #[cfg(feature = "secp256k1_batch")]
+    fn secp256k1_batch_verify(
+        &self,
+        message_hashes: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        let hashes_encoded = encode_sections(message_hashes);
+        let hashes_send = build_region(&hashes_encoded);
+        let hashes_send_ptr = &*hashes_send as *const Region as u32;
+
+        let sigs_encoded = encode_sections(signatures);
+        let sigs_send = build_region(&sigs_encoded);
+        let sigs_send_ptr = &*sigs_send as *const Region as u32;
+
+        let pubkeys_encoded = encode_sections(public_keys);
+        let pubkeys_send = build_region(&pubkeys_encoded);
+        let pubkeys_send_ptr = &*pubkeys_send as *const Region as u32;
+
+        let result =
+            unsafe { secp256k1_batch_verify(hashes_send_ptr, sigs_send_ptr, pubkeys_send_ptr) };
+        match result {
+            0 => Ok(true),
+            1 => Ok(false),
+            3 => Err(VerificationError::InvalidHashFormat),
+            4 => Err(VerificationError::InvalidSignatureFormat),
+            5 => Err(VerificationError::InvalidPubkeyFormat),
+            7 => Err(VerificationError::BatchErr),
+            10 => Err(VerificationError::GenericErr),
+            error_code => Err(VerificationError::unknown_err(error_code)),
+        }
+    }
+
     fn secp256k1_recover_pubkey(
         &self,
         message_hash: &[u8],
@@ -296,6 +521,62 @@ impl Api for ExternalApi {
         }
     }
 
+    #[cfg(feature = "secp256r1")]
+    fn secp256r1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        let hash_send = build_region(message_hash);
+        let hash_send_ptr = &*hash_send as *const Region as u32;
+        let sig_send = build_region(signature);
+        let sig_send_ptr = &*sig_send as *const Region as u32;
+        let pubkey_send = build_region(public_key);
+        let pubkey_send_ptr = &*pubkey_send as *const Region as u32;
+
+        let result = unsafe { secp256r1_verify(hash_send_ptr, sig_send_ptr, pubkey_send_ptr) };
+        match result {
+            0 => Ok(true),
+            1 => Ok(false),
+            2 => panic!("MessageTooLong must not happen. This is a bug in the VM."),
+            3 => Err(VerificationError::InvalidHashFormat),
+            4 => Err(VerificationError::InvalidSignatureFormat),
+            5 => Err(VerificationError::InvalidPubkeyFormat),
+            10 => Err(VerificationError::GenericErr),
+            error_code => Err(VerificationError::unknown_err(error_code)),
+        }
+    }
+
+    #[cfg(feature = "secp256r1")]
+    fn secp256r1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recover_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        let hash_send = build_region(message_hash);
+        let hash_send_ptr = &*hash_send as *const Region as u32;
+        let sig_send = build_region(signature);
+        let sig_send_ptr = &*sig_send as *const Region as u32;
+
+        let result =
+            unsafe { secp256r1_recover_pubkey(hash_send_ptr, sig_send_ptr, recover_param.into()) };
+        let error_code = from_high_half(result);
+        let pubkey_ptr = from_low_half(result);
+        match error_code {
+            0 => {
+                let pubkey = unsafe { consume_region(pubkey_ptr as *mut Region) };
+                Ok(pubkey)
+            }
+            2 => panic!("MessageTooLong must not happen. This is a bug in the VM."),
+            3 => Err(RecoverPubkeyError::InvalidHashFormat),
+            4 => Err(RecoverPubkeyError::InvalidSignatureFormat),
+            6 => Err(RecoverPubkeyError::InvalidRecoveryParam),
+            error_code => Err(RecoverPubkeyError::unknown_err(error_code)),
+        }
+    }
+
     fn ed25519_verify(
         &self,
         message: &[u8],
@@ -354,6 +635,170 @@ impl Api for ExternalApi {
         }
     }
 
+    #[cfg(feature = "sr25519")]
+    fn sr25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        let msg_send = build_region(message);
+        let msg_send_ptr = &*msg_send as *const Region as u32;
+        let sig_send = build_region(signature);
+        let sig_send_ptr = &*sig_send as *const Region as u32;
+        let pubkey_send = build_region(public_key);
+        let pubkey_send_ptr = &*pubkey_send as *const Region as u32;
+
+        let result = unsafe { sr25519_verify(msg_send_ptr, sig_send_ptr, pubkey_send_ptr) };
+        match result {
+            0 => Ok(true),
+            1 => Ok(false),
+            2 => panic!("Error code 2 unused since CosmWasm 0.15. This is a bug in the VM."),
+            3 => panic!("InvalidHashFormat must not happen. This is a bug in the VM."),
+            4 => Err(VerificationError::InvalidSignatureFormat),
+            5 => Err(VerificationError::InvalidPubkeyFormat),
+            10 => Err(VerificationError::GenericErr),
+            error_code => Err(VerificationError::unknown_err(error_code)),
+        }
+    }
+
+    #[cfg(feature = "secp256k1_schnorr")]
+    fn secp256k1_schnorr_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        let msg_send = build_region(message);
+        let msg_send_ptr = &*msg_send as *const Region as u32;
+        let sig_send = build_region(signature);
+        let sig_send_ptr = &*sig_send as *const Region as u32;
+        let pubkey_send = build_region(public_key);
+        let pubkey_send_ptr = &*pubkey_send as *const Region as u32;
+
+        let result =
+            unsafe { secp256k1_schnorr_verify(msg_send_ptr, sig_send_ptr, pubkey_send_ptr) };
+        match result {
+            0 => Ok(true),
+            1 => Ok(false),
+            2 => panic!("Error code 2 unused since CosmWasm 0.15. This is a bug in the VM."),
+            3 => panic!("InvalidHashFormat must not happen. This is a bug in the VM."),
+            4 => Err(VerificationError::InvalidSignatureFormat),
+            5 => Err(VerificationError::InvalidPubkeyFormat),
+            10 => Err(VerificationError::GenericErr),
+            error_code => Err(VerificationError::unknown_err(error_code)),
+        }
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_aggregate_g1(&self, points: &[&[u8]]) -> Result<[u8; 48], VerificationError> {
+        aggregate_with_import(bls12_381_aggregate_g1, points)
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_aggregate_g2(&self, points: &[&[u8]]) -> Result<[u8; 96], VerificationError> {
+        aggregate_with_import(bls12_381_aggregate_g2, points)
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_pairing_equality(
+        &self,
+        ps: &[&[u8]],
+        qs: &[&[u8]],
+        r: &[u8],
+        s: &[u8],
+    ) -> Result<bool, VerificationError> {
+        let ps_encoded = encode_sections(ps);
+        let ps_send = build_region(&ps_encoded);
+        let ps_send_ptr = &*ps_send as *const Region as u32;
+
+        let qs_encoded = encode_sections(qs);
+        let qs_send = build_region(&qs_encoded);
+        let qs_send_ptr = &*qs_send as *const Region as u32;
+
+        let r_send = build_region(r);
+        let r_send_ptr = &*r_send as *const Region as u32;
+        let s_send = build_region(s);
+        let s_send_ptr = &*s_send as *const Region as u32;
+
+        let result =
+            unsafe { bls12_381_pairing_equality(ps_send_ptr, qs_send_ptr, r_send_ptr, s_send_ptr) };
+        match result {
+            0 => Ok(true),
+            1 => Ok(false),
+            7 => Err(VerificationError::BatchErr),
+            8 => Err(VerificationError::InvalidPoint),
+            10 => Err(VerificationError::GenericErr),
+            error_code => Err(VerificationError::unknown_err(error_code)),
+        }
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_aggregate_verify(
+        &self,
+        public_keys: &[&[u8]],
+        messages: &[&[u8]],
+        signature: &[u8],
+        dst: &[u8],
+    ) -> Result<bool, VerificationError> {
+        let pubkeys_encoded = encode_sections(public_keys);
+        let pubkeys_send = build_region(&pubkeys_encoded);
+        let pubkeys_send_ptr = &*pubkeys_send as *const Region as u32;
+
+        let msgs_encoded = encode_sections(messages);
+        let msgs_send = build_region(&msgs_encoded);
+        let msgs_send_ptr = &*msgs_send as *const Region as u32;
+
+        let sig_send = build_region(signature);
+        let sig_send_ptr = &*sig_send as *const Region as u32;
+        let dst_send = build_region(dst);
+        let dst_send_ptr = &*dst_send as *const Region as u32;
+
+        let result = unsafe {
+            bls12_381_aggregate_verify(pubkeys_send_ptr, msgs_send_ptr, sig_send_ptr, dst_send_ptr)
+        };
+        match result {
+            0 => Ok(true),
+            1 => Ok(false),
+            4 => Err(VerificationError::InvalidSignatureFormat),
+            5 => Err(VerificationError::InvalidPubkeyFormat),
+            7 => Err(VerificationError::BatchErr),
+            8 => Err(VerificationError::InvalidPoint),
+            10 => Err(VerificationError::GenericErr),
+            error_code => Err(VerificationError::unknown_err(error_code)),
+        }
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_hash_to_g1(&self, msg: &[u8], dst: &[u8]) -> StdResult<[u8; 48]> {
+        hash_to_curve_with_import("bls12_381_hash_to_g1", bls12_381_hash_to_g1, msg, dst)
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_hash_to_g2(&self, msg: &[u8], dst: &[u8]) -> StdResult<[u8; 96]> {
+        hash_to_curve_with_import("bls12_381_hash_to_g2", bls12_381_hash_to_g2, msg, dst)
+    }
+
+    #[cfg(feature = "hashes")]
+    fn sha256(&self, data: &[u8]) -> StdResult<[u8; 32]> {
+        hash_with_import("sha256", sha256, data)
+    }
+
+    #[cfg(feature = "hashes")]
+    fn keccak256(&self, data: &[u8]) -> StdResult<[u8; 32]> {
+        hash_with_import("keccak256", keccak256, data)
+    }
+
+    #[cfg(feature = "hashes")]
+    fn ripemd160(&self, data: &[u8]) -> StdResult<[u8; 20]> {
+        hash_with_import("ripemd160", ripemd160, data)
+    }
+
+    #[cfg(feature = "hashes")]
+    fn blake2b(&self, data: &[u8]) -> StdResult<[u8; 64]> {
+        hash_with_import("blake2b", blake2b, data)
+    }
+
     fn debug(&self, message: &str) {
         // keep the boxes in scope, so we free it at the end (don't cast to pointers same line as build_region)
         let region = build_region(message.as_bytes());
@@ -362,6 +807,97 @@ impl Api for ExternalApi {
     }
 }
 
+/// Calls one of the hash imports and returns the hash written by the VM
+#[cfg(feature = "hashes")]
+fn hash_with_import<const N: usize>(
+    name: &str,
+    import: unsafe extern "C" fn(data_ptr: u32) -> u32,
+    data: &[u8],
+) -> StdResult<[u8; N]> {
+    if data.len() > HASH_INPUT_MAX_LENGTH {
+        // In this case, the VM will refuse to read the input from the contract.
+        // Stop here to allow handling the error in the contract.
+        return Err(StdError::generic_err(format!(
+            "input too long for {}",
+            name
+        )));
+    }
+    let data = build_region(data);
+    let data_ptr = &*data as *const Region as u32;
+
+    let result = unsafe { import(data_ptr) };
+    let hash = unsafe { consume_region(result as *mut Region) };
+    Ok(hash.try_into().unwrap_or_else(|hash: Vec<u8>| {
+        panic!(
+            "Got a {} hash of {} bytes. This is a bug in the VM.",
+            name,
+            hash.len()
+        )
+    }))
+}
+
+/// Calls one of the BLS12-381 aggregation imports and returns the compressed sum written by the VM
+#[cfg(feature = "bls12_381")]
+fn aggregate_with_import<const N: usize>(
+    import: unsafe extern "C" fn(points_ptr: u32) -> u64,
+    points: &[&[u8]],
+) -> Result<[u8; N], VerificationError> {
+    let points_encoded = encode_sections(points);
+    let points_send = build_region(&points_encoded);
+    let points_send_ptr = &*points_send as *const Region as u32;
+
+    let result = unsafe { import(points_send_ptr) };
+    let error_code = from_high_half(result);
+    let sum_ptr = from_low_half(result);
+    match error_code {
+        0 => {
+            let sum = unsafe { consume_region(sum_ptr as *mut Region) };
+            Ok(sum.try_into().unwrap_or_else(|sum: Vec<u8>| {
+                panic!(
+                    "Got an aggregated point of {} bytes. This is a bug in the VM.",
+                    sum.len()
+                )
+            }))
+        }
+        7 => Err(VerificationError::BatchErr),
+        8 => Err(VerificationError::InvalidPoint),
+        10 => Err(VerificationError::GenericErr),
+        error_code => Err(VerificationError::unknown_err(error_code)),
+    }
+}
+
+/// Calls one of the hash-to-curve imports and returns the compressed point written by the VM
+#[cfg(feature = "bls12_381")]
+fn hash_to_curve_with_import<const N: usize>(
+    name: &str,
+    import: unsafe extern "C" fn(msg_ptr: u32, dst_ptr: u32) -> u32,
+    msg: &[u8],
+    dst: &[u8],
+) -> StdResult<[u8; N]> {
+    if msg.len() > BLS12_381_MESSAGE_MAX_LENGTH || dst.len() > BLS12_381_DST_MAX_LENGTH {
+        // In this case, the VM will refuse to read the input from the contract.
+        // Stop here to allow handling the error in the contract.
+        return Err(StdError::generic_err(format!(
+            "input too long for {}",
+            name
+        )));
+    }
+    let msg = build_region(msg);
+    let msg_ptr = &*msg as *const Region as u32;
+    let dst = build_region(dst);
+    let dst_ptr = &*dst as *const Region as u32;
+
+    let result = unsafe { import(msg_ptr, dst_ptr) };
+    let point = unsafe { consume_region(result as *mut Region) };
+    Ok(point.try_into().unwrap_or_else(|point: Vec<u8>| {
+        panic!(
+            "Got a {} point of {} bytes. This is a bug in the VM.",
+            name,
+            point.len()
+        )
+    }))
+}
+
 /// Takes a pointer to a Region and reads the data into a String.
 /// This is for trusted string sources only.
 unsafe fn consume_string_region_written_by_vm(from: *mut Region) -> String {
@@ -370,6 +906,15 @@ unsafe fn consume_string_region_written_by_vm(from: *mut Region) -> String {
     String::from_utf8_unchecked(data)
 }
 
+/// Passes a panic message to the host using the abort import.
+/// The host stops the contract execution, so this does not return.
+#[cfg(feature = "abort")]
+pub fn handle_panic(message: &str) {
+    let region = build_region(message.as_bytes());
+    let region_ptr = &*region as *const Region as u32;
+    unsafe { abort(region_ptr) };
+}
+
 /// A stateless convenience wrapper around imports provided by the VM
 pub struct ExternalQuerier {}
 
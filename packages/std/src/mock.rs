@@ -166,6 +166,20 @@ impl Api for MockApi {
         )?)
     }
 
+    #[cfg(feature = "secp256k1_batch")]
+    fn secp256k1_batch_verify(
+        &self,
+        message_hashes: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        Ok(cosmwasm_crypto::secp256k1_batch_verify(
+            message_hashes,
+            signatures,
+            public_keys,
+        )?)
+    }
+
     fn secp256k1_recover_pubkey(
         &self,
         message_hash: &[u8],
@@ -177,6 +191,32 @@ impl Api for MockApi {
         Ok(pubkey.to_vec())
     }
 
+    #[cfg(feature = "secp256r1")]
+    fn secp256r1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        Ok(cosmwasm_crypto::secp256r1_verify(
+            message_hash,
+            signature,
+            public_key,
+        )?)
+    }
+
+    #[cfg(feature = "secp256r1")]
+    fn secp256r1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        let pubkey =
+            cosmwasm_crypto::secp256r1_recover_pubkey(message_hash, signature, recovery_param)?;
+        Ok(pubkey)
+    }
+
     fn ed25519_verify(
         &self,
         message: &[u8],
@@ -201,6 +241,97 @@ impl Api for MockApi {
         )?)
     }
 
+    #[cfg(feature = "sr25519")]
+    fn sr25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        Ok(cosmwasm_crypto::sr25519_verify(
+            message, signature, public_key,
+        )?)
+    }
+
+    #[cfg(feature = "secp256k1_schnorr")]
+    fn secp256k1_schnorr_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        Ok(cosmwasm_crypto::secp256k1_schnorr_verify(
+            message, signature, public_key,
+        )?)
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_aggregate_g1(&self, points: &[&[u8]]) -> Result<[u8; 48], VerificationError> {
+        Ok(cosmwasm_crypto::bls12_381_aggregate_g1(points)?)
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_aggregate_g2(&self, points: &[&[u8]]) -> Result<[u8; 96], VerificationError> {
+        Ok(cosmwasm_crypto::bls12_381_aggregate_g2(points)?)
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_pairing_equality(
+        &self,
+        ps: &[&[u8]],
+        qs: &[&[u8]],
+        r: &[u8],
+        s: &[u8],
+    ) -> Result<bool, VerificationError> {
+        Ok(cosmwasm_crypto::bls12_381_pairing_equality(ps, qs, r, s)?)
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_aggregate_verify(
+        &self,
+        public_keys: &[&[u8]],
+        messages: &[&[u8]],
+        signature: &[u8],
+        dst: &[u8],
+    ) -> Result<bool, VerificationError> {
+        Ok(cosmwasm_crypto::bls12_381_aggregate_verify(
+            public_keys,
+            messages,
+            signature,
+            dst,
+        )?)
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_hash_to_g1(&self, msg: &[u8], dst: &[u8]) -> StdResult<[u8; 48]> {
+        Ok(cosmwasm_crypto::bls12_381_hash_to_g1(msg, dst))
+    }
+
+    #[cfg(feature = "bls12_381")]
+    fn bls12_381_hash_to_g2(&self, msg: &[u8], dst: &[u8]) -> StdResult<[u8; 96]> {
+        Ok(cosmwasm_crypto::bls12_381_hash_to_g2(msg, dst))
+    }
+
+    #[cfg(feature = "hashes")]
+    fn sha256(&self, data: &[u8]) -> StdResult<[u8; 32]> {
+        Ok(cosmwasm_crypto::sha256(data))
+    }
+
+    #[cfg(feature = "hashes")]
+    fn keccak256(&self, data: &[u8]) -> StdResult<[u8; 32]> {
+        Ok(cosmwasm_crypto::keccak256(data))
+    }
+
+    #[cfg(feature = "hashes")]
+    fn ripemd160(&self, data: &[u8]) -> StdResult<[u8; 20]> {
+        Ok(cosmwasm_crypto::ripemd160(data))
+    }
+
+    #[cfg(feature = "hashes")]
+    fn blake2b(&self, data: &[u8]) -> StdResult<[u8; 64]> {
+        Ok(cosmwasm_crypto::blake2b(data))
+    }
+
     fn debug(&self, message: &str) {
         println!("{}", message);
     }
@@ -717,6 +848,17 @@ mod tests {
     const ED25519_PUBKEY_HEX: &str =
         "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
 
+    const SR25519_MSG: &str = "cosmwasm";
+    const SR25519_SIG_HEX: &str = "fce73cea3fcc441e702a09d189a022e258eeaf49961e74b9b9e1642db618420df2187c1edf8ef0e5adcf50875cb1aa33e9421b71a97baf268a0cdb81a97f0f86";
+    const SR25519_PUBKEY_HEX: &str =
+        "7c0f469d3bd340bae718203fa30ca071a5e37c751e891dbded837b213d45d91d";
+
+    const SCHNORR_MSG_HEX: &str =
+        "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89";
+    const SCHNORR_SIG_HEX: &str = "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a";
+    const SCHNORR_PUBKEY_HEX: &str =
+        "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659";
+
     #[test]
     fn mock_info_works() {
         let info = mock_info("my name", &coins(100, "atom"));
@@ -821,6 +963,65 @@ mod tests {
         assert_eq!(res.unwrap_err(), VerificationError::InvalidPubkeyFormat);
     }
 
+    // Basic "works" test. Exhaustive tests on VM's side (packages/vm/src/imports.rs)
+    #[cfg(feature = "secp256k1_batch")]
+    #[test]
+    fn secp256k1_batch_verify_works() {
+        let api = MockApi::default();
+
+        let hash = hex::decode(SECP256K1_MSG_HASH_HEX).unwrap();
+        let signature = hex::decode(SECP256K1_SIG_HEX).unwrap();
+        let public_key = hex::decode(SECP256K1_PUBKEY_HEX).unwrap();
+
+        let hashes: Vec<&[u8]> = vec![&hash, &hash];
+        let signatures: Vec<&[u8]> = vec![&signature, &signature];
+        let public_keys: Vec<&[u8]> = vec![&public_key];
+
+        assert!(api
+            .secp256k1_batch_verify(&hashes, &signatures, &public_keys)
+            .unwrap());
+    }
+
+    // Basic "fails" test. Exhaustive tests on VM's side (packages/vm/src/imports.rs)
+    #[cfg(feature = "secp256k1_batch")]
+    #[test]
+    fn secp256k1_batch_verify_fails() {
+        let api = MockApi::default();
+
+        let hash = hex::decode(SECP256K1_MSG_HASH_HEX).unwrap();
+        let mut other_hash = hash.clone();
+        // alter hash
+        other_hash[0] ^= 0x01;
+        let signature = hex::decode(SECP256K1_SIG_HEX).unwrap();
+        let public_key = hex::decode(SECP256K1_PUBKEY_HEX).unwrap();
+
+        let hashes: Vec<&[u8]> = vec![&hash, &other_hash];
+        let signatures: Vec<&[u8]> = vec![&signature, &signature];
+        let public_keys: Vec<&[u8]> = vec![&public_key];
+
+        assert!(!api
+            .secp256k1_batch_verify(&hashes, &signatures, &public_keys)
+            .unwrap());
+    }
+
+    // Basic "errors" test. Exhaustive tests on VM's side (packages/vm/src/imports.rs)
+    #[cfg(feature = "secp256k1_batch")]
+    #[test]
+    fn secp256k1_batch_verify_errs() {
+        let api = MockApi::default();
+
+        let hash = hex::decode(SECP256K1_MSG_HASH_HEX).unwrap();
+        let signature = hex::decode(SECP256K1_SIG_HEX).unwrap();
+        let public_key = hex::decode(SECP256K1_PUBKEY_HEX).unwrap();
+
+        let hashes: Vec<&[u8]> = vec![&hash, &hash];
+        let signatures: Vec<&[u8]> = vec![&signature, &signature, &signature];
+        let public_keys: Vec<&[u8]> = vec![&public_key, &public_key];
+
+        let res = api.secp256k1_batch_verify(&hashes, &signatures, &public_keys);
+        assert_eq!(res.unwrap_err(), VerificationError::BatchErr);
+    }
+
     #[test]
     fn secp256k1_recover_pubkey_works() {
         let api = MockApi::default();
@@ -860,6 +1061,38 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "secp256r1")]
+    fn secp256r1_verify_works() {
+        let api = MockApi::default();
+
+        // Test vector from RFC 6979, A.2.5, with SHA-256 and message "sample"
+        let hash = hex!("af2bdbe1aa9b6ec1e2ade1d694f41fc71a831d0268e9891562113d8a62add1bf");
+        let signature = hex!("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8");
+        let public_key = hex!("0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299");
+
+        assert!(api
+            .secp256r1_verify(&hash, &signature, &public_key)
+            .unwrap());
+
+        let mut wrong_hash = hash;
+        wrong_hash[0] ^= 0x01;
+        assert!(!api
+            .secp256r1_verify(&wrong_hash, &signature, &public_key)
+            .unwrap());
+
+        let res = api.secp256r1_verify(&hash, &signature, &[]);
+        assert_eq!(res.unwrap_err(), VerificationError::InvalidPubkeyFormat);
+
+        let pubkey = api.secp256r1_recover_pubkey(&hash, &signature, 0).unwrap();
+        assert_eq!(pubkey, public_key);
+        let result = api.secp256r1_recover_pubkey(&hash, &signature, 42);
+        match result.unwrap_err() {
+            RecoverPubkeyError::InvalidRecoveryParam => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
     #[test]
     fn secp256k1_recover_pubkey_fails_for_wrong_hash() {
         let api = MockApi::default();
@@ -983,6 +1216,235 @@ mod tests {
         assert_eq!(res.unwrap_err(), VerificationError::InvalidPubkeyFormat);
     }
 
+    // Basic "works" test. Exhaustive tests on VM's side (packages/vm/src/imports.rs)
+    #[cfg(feature = "sr25519")]
+    #[test]
+    fn sr25519_verify_works() {
+        let api = MockApi::default();
+
+        let msg = SR25519_MSG.as_bytes().to_vec();
+        let signature = hex::decode(SR25519_SIG_HEX).unwrap();
+        let public_key = hex::decode(SR25519_PUBKEY_HEX).unwrap();
+
+        assert!(api.sr25519_verify(&msg, &signature, &public_key).unwrap());
+    }
+
+    // Basic "fails" test. Exhaustive tests on VM's side (packages/vm/src/imports.rs)
+    #[cfg(feature = "sr25519")]
+    #[test]
+    fn sr25519_verify_fails() {
+        let api = MockApi::default();
+
+        let mut msg = SR25519_MSG.as_bytes().to_vec();
+        // alter msg
+        msg[0] ^= 0x01;
+        let signature = hex::decode(SR25519_SIG_HEX).unwrap();
+        let public_key = hex::decode(SR25519_PUBKEY_HEX).unwrap();
+
+        assert!(!api.sr25519_verify(&msg, &signature, &public_key).unwrap());
+    }
+
+    // Basic "errors" test. Exhaustive tests on VM's side (packages/vm/src/imports.rs)
+    #[cfg(feature = "sr25519")]
+    #[test]
+    fn sr25519_verify_errs() {
+        let api = MockApi::default();
+
+        let msg = SR25519_MSG.as_bytes().to_vec();
+        let signature = hex::decode(SR25519_SIG_HEX).unwrap();
+        let public_key = vec![];
+
+        let res = api.sr25519_verify(&msg, &signature, &public_key);
+        assert_eq!(res.unwrap_err(), VerificationError::InvalidPubkeyFormat);
+    }
+
+    // Basic "works" test. Exhaustive tests on VM's side (packages/vm/src/imports.rs)
+    #[cfg(feature = "secp256k1_schnorr")]
+    #[test]
+    fn secp256k1_schnorr_verify_works() {
+        let api = MockApi::default();
+
+        let msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
+        let signature = hex::decode(SCHNORR_SIG_HEX).unwrap();
+        let public_key = hex::decode(SCHNORR_PUBKEY_HEX).unwrap();
+
+        assert!(api
+            .secp256k1_schnorr_verify(&msg, &signature, &public_key)
+            .unwrap());
+    }
+
+    // Basic "fails" test. Exhaustive tests on VM's side (packages/vm/src/imports.rs)
+    #[cfg(feature = "secp256k1_schnorr")]
+    #[test]
+    fn secp256k1_schnorr_verify_fails() {
+        let api = MockApi::default();
+
+        let mut msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
+        // alter msg
+        msg[0] ^= 0x01;
+        let signature = hex::decode(SCHNORR_SIG_HEX).unwrap();
+        let public_key = hex::decode(SCHNORR_PUBKEY_HEX).unwrap();
+
+        assert!(!api
+            .secp256k1_schnorr_verify(&msg, &signature, &public_key)
+            .unwrap());
+    }
+
+    // Basic "errors" test. Exhaustive tests on VM's side (packages/vm/src/imports.rs)
+    #[cfg(feature = "secp256k1_schnorr")]
+    #[test]
+    fn secp256k1_schnorr_verify_errs() {
+        let api = MockApi::default();
+
+        let msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
+        let signature = hex::decode(SCHNORR_SIG_HEX).unwrap();
+        let public_key = vec![];
+
+        let res = api.secp256k1_schnorr_verify(&msg, &signature, &public_key);
+        assert_eq!(res.unwrap_err(), VerificationError::InvalidPubkeyFormat);
+    }
+
+    #[cfg(feature = "bls12_381")]
+    #[test]
+    fn bls12_381_aggregate_verify_works() {
+        let api = MockApi::default();
+
+        // Generated with blst from the key material [1u8; 32] and [2u8; 32]
+        let dst = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+        let pubkey1 = hex!("95a254501b7733239ed3cec4d56737977bd09ede881d8a234560e83e5525017add3b1dcc3eabfb85e12a4131b19c253b");
+        let pubkey2 = hex!("ac80a5e08c712d5f08f0306ad743f7d8c215d982489b84a1d6ba805733d94c006e8938f9089a75db3ffa135af33bc69a");
+        let signature = hex!("b680170e37ab63624d0b166031c13d55a807b38277cfa10262b9c82fe1e4e758eb63527a5df49adf54ce584ba00eb45b1631b7980bdcaa9eddc6db0019333084dfa94cedce9405cc53611217a25c7b2d5717af77424b820563c1293496fda35d");
+        let public_keys: Vec<&[u8]> = vec![&pubkey1, &pubkey2];
+        let messages: Vec<&[u8]> = vec![b"block 1", b"block 2"];
+
+        assert!(api
+            .bls12_381_aggregate_verify(&public_keys, &messages, &signature, dst)
+            .unwrap());
+
+        let messages: Vec<&[u8]> = vec![b"block 2", b"block 1"];
+        assert!(!api
+            .bls12_381_aggregate_verify(&public_keys, &messages, &signature, dst)
+            .unwrap());
+
+        let res = api.bls12_381_aggregate_verify(&public_keys, &messages[..1], &signature, dst);
+        assert_eq!(res.unwrap_err(), VerificationError::BatchErr);
+
+        let res = api.bls12_381_aggregate_g1(&[&pubkey1, &signature[..48]]);
+        assert_eq!(res.unwrap_err(), VerificationError::InvalidPoint);
+    }
+
+    #[cfg(feature = "bls12_381")]
+    #[test]
+    fn bls12_381_hash_to_curve_works() {
+        let api = MockApi::default();
+
+        // Test vectors from RFC 9380, J.9.1 and J.10.1
+        assert_eq!(
+            api.bls12_381_hash_to_g1(b"abc", b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_")
+                .unwrap(),
+            hex!("83567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903")
+        );
+        assert_eq!(
+            api.bls12_381_hash_to_g2(b"abc", b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_")
+                .unwrap(),
+            hex!("939cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd802c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6")
+        );
+    }
+
+    #[cfg(feature = "hashes")]
+    #[test]
+    fn hash_functions_work() {
+        let api = MockApi::default();
+
+        assert_eq!(
+            api.sha256(b"abc").unwrap(),
+            hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(
+            api.keccak256(b"abc").unwrap(),
+            hex!("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45")
+        );
+        assert_eq!(
+            api.ripemd160(b"abc").unwrap(),
+            hex!("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc")
+        );
+        assert_eq!(
+            api.blake2b(b"abc").unwrap(),
+            hex!("ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923")
+        );
+    }
+
+    #[cfg(not(feature = "secp256k1_batch"))]
+    #[test]
+    fn secp256k1_batch_verify_is_unsupported_without_feature() {
+        let api = MockApi::default();
+
+        let res = api.secp256k1_batch_verify(&[], &[], &[]);
+        assert_eq!(
+            res.unwrap_err(),
+            VerificationError::unsupported("secp256k1_batch_verify")
+        );
+    }
+
+    #[cfg(not(feature = "sr25519"))]
+    #[test]
+    fn sr25519_verify_is_unsupported_without_feature() {
+        let api = MockApi::default();
+
+        let res = api.sr25519_verify(b"msg", &[0u8; 64], &[0u8; 32]);
+        assert_eq!(
+            res.unwrap_err(),
+            VerificationError::unsupported("sr25519_verify")
+        );
+    }
+
+    #[cfg(not(feature = "secp256k1_schnorr"))]
+    #[test]
+    fn secp256k1_schnorr_verify_is_unsupported_without_feature() {
+        let api = MockApi::default();
+
+        let res = api.secp256k1_schnorr_verify(b"msg", &[0u8; 64], &[0u8; 32]);
+        assert_eq!(
+            res.unwrap_err(),
+            VerificationError::unsupported("secp256k1_schnorr_verify")
+        );
+    }
+
+    #[cfg(not(feature = "bls12_381"))]
+    #[test]
+    fn bls12_381_functions_are_unsupported_without_feature() {
+        let api = MockApi::default();
+
+        let res = api.bls12_381_aggregate_g1(&[]);
+        assert_eq!(
+            res.unwrap_err(),
+            VerificationError::unsupported("bls12_381_aggregate_g1")
+        );
+        let res = api.bls12_381_pairing_equality(&[], &[], &[], &[]);
+        assert_eq!(
+            res.unwrap_err(),
+            VerificationError::unsupported("bls12_381_pairing_equality")
+        );
+        assert!(api.bls12_381_hash_to_g1(b"abc", b"dst").is_err());
+    }
+
+    #[cfg(not(feature = "hashes"))]
+    #[test]
+    fn hash_functions_are_unsupported_without_feature() {
+        let api = MockApi::default();
+
+        let err = api.sha256(b"abc").unwrap_err();
+        match err {
+            StdError::VerificationErr { source, .. } => {
+                assert_eq!(source, VerificationError::unsupported("sha256"))
+            }
+            _ => panic!("Unexpected error: {:?}", err),
+        }
+        assert!(api.keccak256(b"abc").is_err());
+        assert!(api.ripemd160(b"abc").is_err());
+        assert!(api.blake2b(b"abc").is_err());
+    }
+
     #[test]
     fn bank_querier_all_balances() {
         let addr = String::from("foobar");
@@ -8,6 +8,22 @@ pub fn decode_sections2(data: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
     (first, second)
 }
 
+/// Decodes an arbitrary number of sections.
+///
+/// The sections are split off from the end, such that the first section
+/// does not need to be re-allocated.
+#[allow(dead_code)] // used in Wasm and tests only
+pub fn decode_sections(mut data: Vec<u8>) -> Vec<Vec<u8>> {
+    let mut sections = Vec::new();
+    while !data.is_empty() {
+        let (rest, tail) = split_tail(data);
+        sections.push(tail);
+        data = rest;
+    }
+    sections.reverse();
+    sections
+}
+
 /// Encodes multiple sections of data into one vector.
 ///
 /// Each section is suffixed by a section length encoded as big endian uint32.
@@ -106,6 +122,30 @@ mod tests {
         assert_ne!(second.as_ptr(), original_ptr);
     }
 
+    #[test]
+    fn decode_sections_works() {
+        assert_eq!(decode_sections(b"".to_vec()), Vec::<Vec<u8>>::new());
+        assert_eq!(
+            decode_sections(b"\0\0\0\0".to_vec()),
+            vec![Vec::<u8>::new()]
+        );
+        assert_eq!(
+            decode_sections(b"\xAA\0\0\0\x01".to_vec()),
+            vec![vec![0xAA]]
+        );
+        assert_eq!(
+            decode_sections(b"\xAA\0\0\0\x01\0\0\0\0\xBB\xCC\0\0\0\x02".to_vec()),
+            vec![vec![0xAA], vec![], vec![0xBB, 0xCC]]
+        );
+    }
+
+    #[test]
+    fn decode_sections_reverses_encode_sections() {
+        let sections: &[&[u8]] = &[b"foo", b"", &[0x9D; 277], b"bar"];
+        let decoded = decode_sections(encode_sections(sections));
+        assert_eq!(decoded, sections);
+    }
+
     #[test]
     fn encode_sections_works_for_empty_sections() {
         let enc = encode_sections(&[]);
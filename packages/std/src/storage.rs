@@ -280,6 +280,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn get_many_works() {
+        let mut store = MemoryStorage::new();
+        store.set(b"foo", b"bar");
+        store.set(b"food", b"bank");
+
+        assert_eq!(store.get_many(&[]), Vec::<Option<Vec<u8>>>::new());
+        assert_eq!(
+            store.get_many(&[b"food", b"nope", b"foo"]),
+            vec![Some(b"bank".to_vec()), None, Some(b"bar".to_vec())]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_keys_and_range_values_work() {
+        let mut store = MemoryStorage::new();
+        store.set(b"ant", b"hill");
+        store.set(b"foo", b"bar");
+        store.set(b"ze", b"bra");
+
+        let keys: Vec<Vec<u8>> = store
+            .range_keys(Some(b"b"), None, Order::Descending)
+            .collect();
+        assert_eq!(keys, vec![b"ze".to_vec(), b"foo".to_vec()]);
+
+        let values: Vec<Vec<u8>> = store
+            .range_values(None, Some(b"g"), Order::Ascending)
+            .collect();
+        assert_eq!(values, vec![b"hill".to_vec(), b"bar".to_vec()]);
+    }
+
     #[test]
     fn memory_storage_implements_debug() {
         let store = MemoryStorage::new();
@@ -29,6 +29,14 @@ pub trait Storage {
     /// is not great yet and might not be possible in all backends. But we're trying to get there.
     fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
 
+    /// Returns the values of multiple keys at once, in the same order as the keys.
+    ///
+    /// The default implementation calls [`Storage::get`] for every key. Implementations
+    /// should override this if they can read multiple keys more efficiently.
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+
     #[cfg(feature = "iterator")]
     /// Allows iteration over a set of key/value pairs, either forwards or backwards.
     ///
@@ -42,6 +50,34 @@ pub trait Storage {
         order: Order,
     ) -> Box<dyn Iterator<Item = Record> + 'a>;
 
+    #[cfg(feature = "iterator")]
+    /// Like [`Storage::range`] but only returns the keys.
+    ///
+    /// The default implementation drops the values returned by `range`. Implementations
+    /// should override this if they can avoid loading the values.
+    fn range_keys<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'a> {
+        Box::new(self.range(start, end, order).map(|(key, _)| key))
+    }
+
+    #[cfg(feature = "iterator")]
+    /// Like [`Storage::range`] but only returns the values.
+    ///
+    /// The default implementation drops the keys returned by `range`. Implementations
+    /// should override this if they can avoid copying the keys.
+    fn range_values<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'a> {
+        Box::new(self.range(start, end, order).map(|(_, value)| value))
+    }
+
     fn set(&mut self, key: &[u8], value: &[u8]);
 
     /// Removes a database entry at `key`.
@@ -98,6 +134,27 @@ pub trait Api {
         public_key: &[u8],
     ) -> Result<bool, VerificationError>;
 
+    /// Verifies a batch of message hashes against a batch of signatures with a batch of public keys,
+    /// using the same encodings as [`secp256k1_verify`].
+    ///
+    /// The batch is valid if all signatures are valid. Three variants are supported: one hash per
+    /// signature and public key, one hash for many signatures and public keys, and many hashes
+    /// and signatures for one public key. The batch size is limited by the host (128 in cosmwasm-vm)
+    /// and gas is charged per signature at a discount compared to single verifications.
+    ///
+    /// This is only provided by implementations built with the `secp256k1_batch` feature,
+    /// other implementations return [`VerificationError::Unsupported`].
+    ///
+    /// [`secp256k1_verify`]: Api::secp256k1_verify
+    fn secp256k1_batch_verify(
+        &self,
+        _message_hashes: &[&[u8]],
+        _signatures: &[&[u8]],
+        _public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        Err(VerificationError::unsupported("secp256k1_batch_verify"))
+    }
+
     fn secp256k1_recover_pubkey(
         &self,
         message_hash: &[u8],
@@ -105,6 +162,31 @@ pub trait Api {
         recovery_param: u8,
     ) -> Result<Vec<u8>, RecoverPubkeyError>;
 
+    /// Verifies a message hash against a signature with a public key, using the
+    /// secp256r1 (NIST P-256) ECDSA parametrization, as used by WebAuthn / passkeys.
+    ///
+    /// Signature and public key use the same encodings as [`secp256k1_verify`].
+    /// High-S signatures are accepted.
+    ///
+    /// [`secp256k1_verify`]: Api::secp256k1_verify
+    #[cfg(feature = "secp256r1")]
+    fn secp256r1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError>;
+
+    /// Recovers a secp256r1 public key from a message hash and a signature.
+    /// The public key is returned in uncompressed form (65 bytes).
+    #[cfg(feature = "secp256r1")]
+    fn secp256r1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError>;
+
     fn ed25519_verify(
         &self,
         message: &[u8],
@@ -119,6 +201,150 @@ pub trait Api {
         public_keys: &[&[u8]],
     ) -> Result<bool, VerificationError>;
 
+    /// Verifies a message against a sr25519 signature (64 bytes) with a public key (32 bytes),
+    /// as used by Substrate based chains. The message is signed with the "substrate" signing context.
+    ///
+    /// This is only provided by implementations built with the `sr25519` feature,
+    /// other implementations return [`VerificationError::Unsupported`].
+    fn sr25519_verify(
+        &self,
+        _message: &[u8],
+        _signature: &[u8],
+        _public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        Err(VerificationError::unsupported("sr25519_verify"))
+    }
+
+    /// Verifies a message against a BIP-340 Schnorr signature (64 bytes) with an x-only
+    /// secp256k1 public key (32 bytes), as used by Bitcoin Taproot.
+    /// The message is not hashed before verification.
+    ///
+    /// This is only provided by implementations built with the `secp256k1_schnorr` feature,
+    /// other implementations return [`VerificationError::Unsupported`].
+    fn secp256k1_schnorr_verify(
+        &self,
+        _message: &[u8],
+        _signature: &[u8],
+        _public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        Err(VerificationError::unsupported("secp256k1_schnorr_verify"))
+    }
+
+    /// Aggregates (adds up) a list of BLS12-381 G1 points in compressed form (48 bytes each).
+    /// Every point is checked for being a valid G1 point. The sum is returned in compressed form.
+    ///
+    /// This can be used to aggregate public keys of signers of the same message.
+    /// The number of points is limited by the host (512 in cosmwasm-vm).
+    ///
+    /// The BLS12-381 functions are only provided by implementations built with the `bls12_381`
+    /// feature, other implementations return [`VerificationError::Unsupported`].
+    fn bls12_381_aggregate_g1(&self, _points: &[&[u8]]) -> Result<[u8; 48], VerificationError> {
+        Err(VerificationError::unsupported("bls12_381_aggregate_g1"))
+    }
+
+    /// Aggregates (adds up) a list of BLS12-381 G2 points in compressed form (96 bytes each).
+    /// Every point is checked for being a valid G2 point. The sum is returned in compressed form.
+    ///
+    /// This can be used to aggregate signatures.
+    /// The number of points is limited by the host (512 in cosmwasm-vm).
+    fn bls12_381_aggregate_g2(&self, _points: &[&[u8]]) -> Result<[u8; 96], VerificationError> {
+        Err(VerificationError::unsupported("bls12_381_aggregate_g2"))
+    }
+
+    /// Checks the pairing equation e(p_1, q_1) * ... * e(p_n, q_n) = e(r, s) on BLS12-381,
+    /// where `ps` and `r` are compressed G1 points and `qs` and `s` are compressed G2 points.
+    ///
+    /// This allows verifying BLS signatures of all kinds, e.g. drand beacons with signatures
+    /// in G1: `bls12_381_pairing_equality(&[signature], &[g2_generator], &message_point, &public_key)`
+    /// where `message_point` is obtained via [`bls12_381_hash_to_g1`].
+    /// The number of pairs is limited by the host (64 in cosmwasm-vm).
+    ///
+    /// [`bls12_381_hash_to_g1`]: Api::bls12_381_hash_to_g1
+    fn bls12_381_pairing_equality(
+        &self,
+        _ps: &[&[u8]],
+        _qs: &[&[u8]],
+        _r: &[u8],
+        _s: &[u8],
+    ) -> Result<bool, VerificationError> {
+        Err(VerificationError::unsupported("bls12_381_pairing_equality"))
+    }
+
+    /// Verifies an aggregate BLS12-381 signature (G2, 96 bytes) of distinct messages by the
+    /// given public keys (G1, 48 bytes). Messages are hashed to G2 using the domain separation tag `dst`.
+    ///
+    /// The caller is responsible for ensuring that the messages are distinct or that the
+    /// public keys come with a proof of possession. If all public keys signed the same message,
+    /// aggregate them with [`bls12_381_aggregate_g1`] first, which is a lot cheaper.
+    /// The number of public keys is limited by the host (64 in cosmwasm-vm).
+    ///
+    /// [`bls12_381_aggregate_g1`]: Api::bls12_381_aggregate_g1
+    fn bls12_381_aggregate_verify(
+        &self,
+        _public_keys: &[&[u8]],
+        _messages: &[&[u8]],
+        _signature: &[u8],
+        _dst: &[u8],
+    ) -> Result<bool, VerificationError> {
+        Err(VerificationError::unsupported("bls12_381_aggregate_verify"))
+    }
+
+    /// Hashes the message to a BLS12-381 G1 point as specified in RFC 9380
+    /// (BLS12381G1_XMD:SHA-256_SSWU_RO_) using the domain separation tag `dst`.
+    /// The point is returned in compressed form.
+    ///
+    /// The message length is limited by the host (8 KiB in cosmwasm-vm), the tag length to 255 bytes.
+    /// Longer inputs result in an error.
+    fn bls12_381_hash_to_g1(&self, _msg: &[u8], _dst: &[u8]) -> StdResult<[u8; 48]> {
+        Err(VerificationError::unsupported("bls12_381_hash_to_g1").into())
+    }
+
+    /// Hashes the message to a BLS12-381 G2 point as specified in RFC 9380
+    /// (BLS12381G2_XMD:SHA-256_SSWU_RO_) using the domain separation tag `dst`.
+    /// The point is returned in compressed form.
+    ///
+    /// The message length is limited by the host (8 KiB in cosmwasm-vm), the tag length to 255 bytes.
+    /// Longer inputs result in an error.
+    fn bls12_381_hash_to_g2(&self, _msg: &[u8], _dst: &[u8]) -> StdResult<[u8; 96]> {
+        Err(VerificationError::unsupported("bls12_381_hash_to_g2").into())
+    }
+
+    /// Hashes the data with SHA-256.
+    ///
+    /// The input length is limited by the host (128 KiB in cosmwasm-vm). Longer inputs
+    /// result in an error.
+    ///
+    /// The hash functions are only provided by implementations built with the `hashes` feature,
+    /// other implementations return [`VerificationError::Unsupported`].
+    fn sha256(&self, _data: &[u8]) -> StdResult<[u8; 32]> {
+        Err(VerificationError::unsupported("sha256").into())
+    }
+
+    /// Hashes the data with Keccak-256, the hash function used in Ethereum.
+    /// This is not the standardized SHA3-256.
+    ///
+    /// The input length is limited by the host (128 KiB in cosmwasm-vm). Longer inputs
+    /// result in an error.
+    fn keccak256(&self, _data: &[u8]) -> StdResult<[u8; 32]> {
+        Err(VerificationError::unsupported("keccak256").into())
+    }
+
+    /// Hashes the data with RIPEMD-160.
+    ///
+    /// The input length is limited by the host (128 KiB in cosmwasm-vm). Longer inputs
+    /// result in an error.
+    fn ripemd160(&self, _data: &[u8]) -> StdResult<[u8; 20]> {
+        Err(VerificationError::unsupported("ripemd160").into())
+    }
+
+    /// Hashes the data with BLAKE2b (unkeyed, 512 bit output).
+    ///
+    /// The input length is limited by the host (128 KiB in cosmwasm-vm). Longer inputs
+    /// result in an error.
+    fn blake2b(&self, _data: &[u8]) -> StdResult<[u8; 64]> {
+        Err(VerificationError::unsupported("blake2b").into())
+    }
+
     /// Emits a debugging message that is handled depending on the environment (typically printed to console or ignored).
     /// Those messages are not persisted to chain.
     fn debug(&self, message: &str);
@@ -11,17 +11,21 @@ use cosmwasm_vm::testing::{
     mock_backend, mock_env, mock_info, mock_instance_options, MockApi, MockQuerier, MockStorage,
 };
 use cosmwasm_vm::{
-    call_execute, call_instantiate, features_from_csv, Cache, CacheOptions, Checksum, Instance,
-    InstanceOptions, Size,
+    call_execute, call_instantiate, features_from_csv, Cache, CacheOptions, Checksum, GasConfig,
+    Instance, InstanceOptions, OperatorCostTable, Size, WasmLimits,
 };
 
 // Instance
 const DEFAULT_MEMORY_LIMIT: Size = Size::mebi(64);
 const DEFAULT_GAS_LIMIT: u64 = 1_000_000_000_000; // ~1ms
-const DEFAULT_INSTANCE_OPTIONS: InstanceOptions = InstanceOptions {
-    gas_limit: DEFAULT_GAS_LIMIT,
-    print_debug: false,
-};
+fn default_instance_options() -> InstanceOptions {
+    InstanceOptions {
+        gas_limit: DEFAULT_GAS_LIMIT,
+        print_debug: false,
+        gas_config: None,
+        max_stack_depth: None,
+    }
+}
 const HIGH_GAS_LIMIT: u64 = 20_000_000_000_000_000; // ~20s, allows many calls on one instance
 
 // Cache
@@ -49,7 +53,7 @@ fn bench_instance(c: &mut Criterion) {
         let backend = mock_backend(&[]);
         let much_gas: InstanceOptions = InstanceOptions {
             gas_limit: HIGH_GAS_LIMIT,
-            ..DEFAULT_INSTANCE_OPTIONS
+            ..default_instance_options()
         };
         let mut instance =
             Instance::from_code(CONTRACT, backend, much_gas, Some(DEFAULT_MEMORY_LIMIT)).unwrap();
@@ -67,7 +71,7 @@ fn bench_instance(c: &mut Criterion) {
         let backend = mock_backend(&[]);
         let much_gas: InstanceOptions = InstanceOptions {
             gas_limit: HIGH_GAS_LIMIT,
-            ..DEFAULT_INSTANCE_OPTIONS
+            ..default_instance_options()
         };
         let mut instance =
             Instance::from_code(CONTRACT, backend, much_gas, Some(DEFAULT_MEMORY_LIMIT)).unwrap();
@@ -91,7 +95,7 @@ fn bench_instance(c: &mut Criterion) {
         let backend = mock_backend(&[]);
         let much_gas: InstanceOptions = InstanceOptions {
             gas_limit: HIGH_GAS_LIMIT,
-            ..DEFAULT_INSTANCE_OPTIONS
+            ..default_instance_options()
         };
         let mut instance =
             Instance::from_code(CONTRACT, backend, much_gas, Some(DEFAULT_MEMORY_LIMIT)).unwrap();
@@ -126,11 +130,14 @@ fn bench_cache(c: &mut Criterion) {
         supported_features: features_from_csv("iterator,staking"),
         memory_cache_size: MEMORY_CACHE_SIZE,
         instance_memory_limit: DEFAULT_MEMORY_LIMIT,
+        operator_cost_table: OperatorCostTable::default(),
+        gas_config: GasConfig::default(),
+        instance_pool_size: 0,
+        wasm_limits: WasmLimits::default(),
     };
 
     group.bench_function("save wasm", |b| {
-        let cache: Cache<MockApi, MockStorage, MockQuerier> =
-            unsafe { Cache::new(options.clone()).unwrap() };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> = Cache::new(options.clone()).unwrap();
 
         b.iter(|| {
             let result = cache.save_wasm(CONTRACT);
@@ -139,8 +146,7 @@ fn bench_cache(c: &mut Criterion) {
     });
 
     group.bench_function("load wasm", |b| {
-        let cache: Cache<MockApi, MockStorage, MockQuerier> =
-            unsafe { Cache::new(options.clone()).unwrap() };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> = Cache::new(options.clone()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         b.iter(|| {
@@ -150,8 +156,7 @@ fn bench_cache(c: &mut Criterion) {
     });
 
     group.bench_function("analyze", |b| {
-        let cache: Cache<MockApi, MockStorage, MockQuerier> =
-            unsafe { Cache::new(options.clone()).unwrap() };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> = Cache::new(options.clone()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         b.iter(|| {
@@ -166,14 +171,17 @@ fn bench_cache(c: &mut Criterion) {
             supported_features: features_from_csv("iterator,staking"),
             memory_cache_size: Size(0),
             instance_memory_limit: DEFAULT_MEMORY_LIMIT,
+            operator_cost_table: OperatorCostTable::default(),
+            gas_config: GasConfig::default(),
+            instance_pool_size: 0,
+            wasm_limits: WasmLimits::default(),
         };
-        let cache: Cache<MockApi, MockStorage, MockQuerier> =
-            unsafe { Cache::new(non_memcache).unwrap() };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> = Cache::new(non_memcache).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         b.iter(|| {
             let _ = cache
-                .get_instance(&checksum, mock_backend(&[]), DEFAULT_INSTANCE_OPTIONS)
+                .get_instance(&checksum, mock_backend(&[]), default_instance_options())
                 .unwrap();
             assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
             assert_eq!(cache.stats().hits_memory_cache, 0);
@@ -184,17 +192,16 @@ fn bench_cache(c: &mut Criterion) {
 
     group.bench_function("instantiate from memory", |b| {
         let checksum = Checksum::generate(CONTRACT);
-        let cache: Cache<MockApi, MockStorage, MockQuerier> =
-            unsafe { Cache::new(options.clone()).unwrap() };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> = Cache::new(options.clone()).unwrap();
         // Load into memory
         cache
-            .get_instance(&checksum, mock_backend(&[]), DEFAULT_INSTANCE_OPTIONS)
+            .get_instance(&checksum, mock_backend(&[]), default_instance_options())
             .unwrap();
 
         b.iter(|| {
             let backend = mock_backend(&[]);
             let _ = cache
-                .get_instance(&checksum, backend, DEFAULT_INSTANCE_OPTIONS)
+                .get_instance(&checksum, backend, default_instance_options())
                 .unwrap();
             assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
             assert!(cache.stats().hits_memory_cache >= 1);
@@ -205,15 +212,14 @@ fn bench_cache(c: &mut Criterion) {
 
     group.bench_function("instantiate from pinned memory", |b| {
         let checksum = Checksum::generate(CONTRACT);
-        let cache: Cache<MockApi, MockStorage, MockQuerier> =
-            unsafe { Cache::new(options.clone()).unwrap() };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> = Cache::new(options.clone()).unwrap();
         // Load into pinned memory
         cache.pin(&checksum).unwrap();
 
         b.iter(|| {
             let backend = mock_backend(&[]);
             let _ = cache
-                .get_instance(&checksum, backend, DEFAULT_INSTANCE_OPTIONS)
+                .get_instance(&checksum, backend, default_instance_options())
                 .unwrap();
             assert_eq!(cache.stats().hits_memory_cache, 0);
             assert!(cache.stats().hits_pinned_memory_cache >= 1);
@@ -232,10 +238,13 @@ pub fn bench_instance_threads(c: &mut Criterion) {
             supported_features: features_from_csv("iterator,staking"),
             memory_cache_size: MEMORY_CACHE_SIZE,
             instance_memory_limit: DEFAULT_MEMORY_LIMIT,
+            operator_cost_table: OperatorCostTable::default(),
+            gas_config: GasConfig::default(),
+            instance_pool_size: 0,
+            wasm_limits: WasmLimits::default(),
         };
 
-        let cache: Cache<MockApi, MockStorage, MockQuerier> =
-            unsafe { Cache::new(options).unwrap() };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> = Cache::new(options).unwrap();
         let cache = Arc::new(cache);
 
         // Find sub-sequence helper
@@ -268,7 +277,7 @@ pub fn bench_instance_threads(c: &mut Criterion) {
             // let checksum = cache.save_wasm(contract.as_slice()).unwrap();
             // Preload into memory
             // cache
-            //     .get_instance(&checksum, mock_backend(&[]), DEFAULT_INSTANCE_OPTIONS)
+            //     .get_instance(&checksum, mock_backend(&[]), default_instance_options())
             //     .unwrap();
             // checksum
         };
@@ -290,7 +299,7 @@ pub fn bench_instance_threads(c: &mut Criterion) {
                                     .get_instance(
                                         &checksum,
                                         mock_backend(&[]),
-                                        DEFAULT_INSTANCE_OPTIONS,
+                                        default_instance_options(),
                                     )
                                     .unwrap(),
                             );
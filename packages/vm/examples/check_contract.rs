@@ -1,10 +1,11 @@
 use std::fs::File;
 use std::io::Read;
+use std::process::exit;
 
 use clap::{App, Arg};
 
-use cosmwasm_vm::features_from_csv;
-use cosmwasm_vm::internals::{check_wasm, compile};
+use cosmwasm_vm::internals::{check_wasm_report, compile};
+use cosmwasm_vm::{features_from_csv, OperatorCostTable, WasmLimits};
 
 const DEFAULT_SUPPORTED_FEATURES: &str = "iterator,staking,stargate";
 
@@ -21,6 +22,11 @@ pub fn main() {
                 .help("Sets the supported features that the desired target chain supports")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("JSON")
+                .long("json")
+                .help("Prints the validation report as JSON")
+        )
         .arg(
             Arg::with_name("WASM")
                 .help("Wasm file to read and compile")
@@ -34,7 +40,10 @@ pub fn main() {
         .value_of("FEATURES")
         .unwrap_or(DEFAULT_SUPPORTED_FEATURES);
     let supported_features = features_from_csv(supported_features_csv);
-    println!("Supported features: {:?}", supported_features);
+    let json = matches.is_present("JSON");
+    if !json {
+        println!("Supported features: {:?}", supported_features);
+    }
 
     // File
     let path = matches.value_of("WASM").expect("Error parsing file name");
@@ -45,9 +54,27 @@ pub fn main() {
     file.read_to_end(&mut wasm).unwrap();
 
     // Check wasm
-    check_wasm(&wasm, &supported_features).unwrap();
+    let report = check_wasm_report(&wasm, &supported_features, &WasmLimits::default());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        for violation in report.violations.iter() {
+            println!("{}", violation);
+            if !violation.names.is_empty() {
+                println!("  names: {}", violation.names.join(", "));
+            }
+        }
+    }
+    if !report.is_valid() {
+        if !json {
+            println!("contract checks failed.");
+        }
+        exit(1);
+    }
 
     // Compile module
-    compile(&wasm, None, &[]).unwrap();
-    println!("contract checks passed.")
+    compile(&wasm, None, &OperatorCostTable::default(), &[]).unwrap();
+    if !json {
+        println!("contract checks passed.")
+    }
 }
@@ -6,7 +6,7 @@ use clap::{App, Arg};
 
 use cosmwasm_vm::internals::compile;
 use cosmwasm_vm::internals::make_runtime_store;
-use cosmwasm_vm::Size;
+use cosmwasm_vm::{OperatorCostTable, Size};
 use wasmer::Module;
 
 pub fn main() {
@@ -69,7 +69,7 @@ pub fn main() {
 
 #[inline(never)]
 fn module_compile(wasm: &[u8], memory_limit: Option<Size>) -> Module {
-    compile(wasm, memory_limit, &[]).unwrap()
+    compile(wasm, memory_limit, &OperatorCostTable::default(), &[]).unwrap()
 }
 
 #[inline(never)]
@@ -5,17 +5,22 @@ use tempfile::TempDir;
 use cosmwasm_std::{coins, Empty};
 use cosmwasm_vm::testing::{mock_backend, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
 use cosmwasm_vm::{
-    call_execute, call_instantiate, features_from_csv, Cache, CacheOptions, InstanceOptions, Size,
+    call_execute, call_instantiate, features_from_csv, Cache, CacheOptions, GasConfig,
+    InstanceOptions, OperatorCostTable, Size, WasmLimits,
 };
 use wasmer::{Exports, Function, ImportObject, Instance as WasmerInstance, Module, Val};
 
 // Instance
 const DEFAULT_MEMORY_LIMIT: Size = Size::mebi(64);
 const DEFAULT_GAS_LIMIT: u64 = 400_000 * 150_000;
-const DEFAULT_INSTANCE_OPTIONS: InstanceOptions = InstanceOptions {
-    gas_limit: DEFAULT_GAS_LIMIT,
-    print_debug: false,
-};
+fn default_instance_options() -> InstanceOptions {
+    InstanceOptions {
+        gas_limit: DEFAULT_GAS_LIMIT,
+        print_debug: false,
+        gas_config: None,
+        max_stack_depth: None,
+    }
+}
 // Cache
 const MEMORY_CACHE_SIZE: Size = Size::mebi(200);
 
@@ -31,10 +36,14 @@ pub fn main() {
         supported_features: features_from_csv("iterator,staking"),
         memory_cache_size: MEMORY_CACHE_SIZE,
         instance_memory_limit: DEFAULT_MEMORY_LIMIT,
+        operator_cost_table: OperatorCostTable::default(),
+        gas_config: GasConfig::default(),
+        instance_pool_size: 0,
+        wasm_limits: WasmLimits::default(),
     };
 
     let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-        unsafe { Cache::new(options).unwrap() };
+        Cache::new(options).unwrap();
     let cache = Arc::new(cache);
 
     let checksum = cache.save_wasm(CONTRACT).unwrap();
@@ -54,7 +63,7 @@ pub fn main() {
         threads.push(thread::spawn(move || {
             let checksum = checksum;
             let mut instance = cache
-                .get_instance(&checksum, mock_backend(&[]), DEFAULT_INSTANCE_OPTIONS)
+                .get_instance(&checksum, mock_backend(&[]), default_instance_options())
                 .unwrap();
             println!("Done instantiating contract");
 
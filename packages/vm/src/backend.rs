@@ -1,6 +1,8 @@
 use std::fmt::Debug;
 use std::ops::AddAssign;
 use std::string::FromUtf8Error;
+
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use cosmwasm_std::{Binary, ContractResult, SystemResult};
@@ -10,7 +12,7 @@ use cosmwasm_std::{Order, Record};
 /// A structure that represents gas cost to be deducted from the remaining gas.
 /// This is always needed when computations are performed outside of
 /// Wasm execution, such as calling crypto APIs or calls into the blockchain.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
 pub struct GasInfo {
     /// The gas cost of a computation that was executed already but not yet charged.
     ///
@@ -112,6 +114,31 @@ pub trait Storage {
     #[cfg(feature = "iterator")]
     fn next(&mut self, iterator_id: u32) -> BackendResult<Option<Record>>;
 
+    /// Returns the key of the next element of the iterator with the given ID.
+    ///
+    /// This is like [`Storage::next`] but the value does not need to be loaded. The default
+    /// implementation calls `next` and drops the value. Implementations should override this
+    /// if they can do better.
+    #[cfg(feature = "iterator")]
+    fn next_key(&mut self, iterator_id: u32) -> BackendResult<Option<Vec<u8>>> {
+        let (result, gas_info) = self.next(iterator_id);
+        (result.map(|record| record.map(|(key, _)| key)), gas_info)
+    }
+
+    /// Returns the value of the next element of the iterator with the given ID.
+    ///
+    /// This is like [`Storage::next`] but the key is not returned. The default
+    /// implementation calls `next` and drops the key. Implementations should override this
+    /// if they can do better.
+    #[cfg(feature = "iterator")]
+    fn next_value(&mut self, iterator_id: u32) -> BackendResult<Option<Vec<u8>>> {
+        let (result, gas_info) = self.next(iterator_id);
+        (
+            result.map(|record| record.map(|(_, value)| value)),
+            gas_info,
+        )
+    }
+
     fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()>;
 
     /// Removes a database entry at `key`.
@@ -130,7 +157,7 @@ pub trait Storage {
 ///
 /// We can use feature flags to opt-in to non-essential methods
 /// for backwards compatibility in systems that don't have them all.
-pub trait BackendApi: Copy + Clone + Send {
+pub trait BackendApi: Clone + Send {
     fn canonical_address(&self, human: &str) -> BackendResult<Vec<u8>>;
     fn human_address(&self, canonical: &[u8]) -> BackendResult<String>;
 }
@@ -157,7 +184,7 @@ pub trait Querier {
 /// attached.
 pub type BackendResult<T> = (core::result::Result<T, BackendError>, GasInfo);
 
-#[derive(Error, Debug)]
+#[derive(Error, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum BackendError {
     #[error("Panic in FFI call")]
     ForeignPanic {},
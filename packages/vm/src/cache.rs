@@ -1,21 +1,30 @@
-use std::collections::HashSet;
-use std::fs::{create_dir_all, File, OpenOptions};
-use std::io::{Read, Write};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fs::{create_dir_all, remove_file, File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
 use wasmer::{Exports, Function, ImportObject, Instance as WasmerInstance, Module, Val};
 
 use crate::backend::{Backend, BackendApi, Querier, Storage};
 use crate::checksum::Checksum;
-use crate::compatibility::check_wasm;
+use crate::compatibility::{check_wasm_with_limits, WasmLimits};
+use crate::environment::GasConfig;
 use crate::errors::{VmError, VmResult};
 use crate::features::required_features_from_module;
-use crate::instance::{Instance, InstanceOptions};
-use crate::modules::{FileSystemCache, InMemoryCache, PinnedMemoryCache};
+use crate::instance::{Instance, InstanceOptions, InstanceState};
+use crate::metrics::MetricsRegistry;
+use crate::modules::{FileSystemCache, InMemoryCache, PinnedMemoryCache, RemovalStats};
 use crate::size::Size;
 use crate::static_analysis::{deserialize_wasm, has_ibc_entry_points};
-use crate::wasm_backend::{compile, make_runtime_store};
+use crate::wasm_backend::{
+    compile, count_mutable_globals, make_runtime_store, OperatorCostTable, DEFAULT_MAX_STACK_DEPTH,
+};
+#[cfg(feature = "interpreter")]
+use crate::wasm_backend::{compile_interpreted, WasmiInstance, WasmiModule};
 use crate::WasmVM;
 
 const STATE_DIR: &str = "state";
@@ -25,6 +34,11 @@ const WASM_DIR: &str = "wasm";
 const CACHE_DIR: &str = "cache";
 // Cacheable things.
 const MODULES_DIR: &str = "modules";
+// The checksums of all pinned codes, one hex encoded checksum per line.
+const PINNED_FILE: &str = "pinned";
+
+/// The maximum number of threads used by [`Cache::warm`]
+const WARM_UP_THREADS: usize = 4;
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Stats {
@@ -41,6 +55,8 @@ pub struct Metrics {
     pub elements_memory_cache: usize,
     pub size_pinned_memory_cache: usize,
     pub size_memory_cache: usize,
+    /// The number of modules pushed out of the memory cache to make room for others
+    pub evictions_memory_cache: u64,
 }
 
 #[derive(Clone, Debug)]
@@ -51,6 +67,19 @@ pub struct CacheOptions {
     /// Memory limit for instances, in bytes. Use a value that is divisible by the Wasm page size 65536,
     /// e.g. full MiBs.
     pub instance_memory_limit: Size,
+    /// Gas prices for Wasm operators, which are compiled into the modules.
+    /// Changing the table causes all modules to be re-compiled from Wasm bytecode.
+    pub operator_cost_table: OperatorCostTable,
+    /// Gas prices for the functionality the VM provides to the contract (crypto, address
+    /// handling, debug, iterators). Used by all instances unless
+    /// [`InstanceOptions::gas_config`] is set.
+    pub gas_config: GasConfig,
+    /// The maximum number of idle instances per checksum that are kept for reuse by
+    /// `get_instance`, see [`Cache::recycle_instance`]. Use 0 to disable instance pooling.
+    /// Pooling is only supported by the Wasmer backend.
+    pub instance_pool_size: usize,
+    /// Limits for the static validation of Wasm codes in `save_wasm`
+    pub wasm_limits: WasmLimits,
 }
 
 pub struct CacheInner {
@@ -61,23 +90,58 @@ pub struct CacheInner {
     pinned_memory_cache: PinnedMemoryCache,
     memory_cache: InMemoryCache,
     fs_cache: FileSystemCache,
+    /// Pinned modules of the interpreter backend
+    #[cfg(feature = "interpreter")]
+    pinned_interpreted_modules: HashMap<Checksum, Arc<WasmiModule>>,
+    /// The checksums of all pinned codes, which are persisted at `pinned_path`
+    pinned_checksums: HashSet<Checksum>,
+    pinned_path: PathBuf,
     stats: Stats,
 }
 
+/// The state of a [`Cache`] that is shared with its background threads,
+/// i.e. the thread restoring pinned modules and the threads of [`Cache::warm`].
+pub struct CacheShared {
+    /// Immutable for the lifetime of the cache,
+    /// i.e. any number of read-only references is allowed to access it concurrently.
+    operator_cost_table: OperatorCostTable,
+    inner: Mutex<CacheInner>,
+    /// The checksums of the modules that are currently loaded from disk or compiled
+    /// without holding the lock of `inner`
+    loading: LoadingModules,
+    metrics: Arc<MetricsRegistry>,
+}
+
 pub struct Cache<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> {
     /// Supported features are immutable for the lifetime of the cache,
     /// i.e. any number of read-only references is allowed to access it concurrently.
     supported_features: HashSet<String>,
-    inner: Mutex<CacheInner>,
+    /// Immutable for the lifetime of the cache
+    wasm_limits: WasmLimits,
+    /// Immutable for the lifetime of the cache
+    gas_config: GasConfig,
+    shared: Arc<CacheShared>,
     // Those two don't store data but only fix type information
     type_api: PhantomData<A>,
     type_storage: PhantomData<S>,
     type_querier: PhantomData<Q>,
     /// To prevent concurrent access to `WasmerInstance::new`
     instantiation_lock: Mutex<()>,
+    instance_pool: Mutex<InstancePool<A, S, Q, W>>,
     type_vm: PhantomData<W>,
 }
 
+/// Idle instances that were returned via `Cache::recycle_instance`
+struct InstancePool<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> {
+    /// The maximum number of idle instances per checksum
+    size: usize,
+    /// The state of the instances of a code right after instantiation
+    initial_states: HashMap<Checksum, Arc<InstanceState>>,
+    /// Codes whose instances cannot be reset since not all of their mutable globals are exported
+    unpoolable: HashSet<Checksum>,
+    idle: HashMap<Checksum, Vec<Instance<A, S, Q, W>>>,
+}
+
 #[derive(PartialEq, Debug)]
 pub struct AnalysisReport {
     pub has_ibc_entry_points: bool,
@@ -89,21 +153,25 @@ where
     A: BackendApi + 'static, // 'static is needed by `impl<…> Instance`
     S: Storage + 'static,    // 'static is needed by `impl<…> Instance`
     Q: Querier + 'static,    // 'static is needed by `impl<…> Instance`
-    W: WasmVM,
+    W: CacheBackend,
 {
     /// Creates a new cache that stores data in `base_dir`.
     ///
-    /// # Safety
+    /// Compiled modules on disk are verified before they are loaded. Corrupted modules
+    /// are re-compiled from the stored Wasm.
     ///
-    /// This function is marked unsafe due to `FileSystemCache::new`, which implicitly
-    /// assumes the disk contents are correct, and there's no way to ensure the artifacts
-    /// stored in the cache haven't been corrupted or tampered with.
-    pub unsafe fn new(options: CacheOptions) -> VmResult<Self> {
+    /// Codes that were pinned in a previous cache using the same `base_dir` are pinned again.
+    /// Their modules are loaded into memory in a background thread.
+    pub fn new(options: CacheOptions) -> VmResult<Self> {
         let CacheOptions {
             base_dir,
             supported_features,
             memory_cache_size,
             instance_memory_limit,
+            operator_cost_table,
+            gas_config,
+            instance_pool_size,
+            wasm_limits,
         } = options;
 
         let state_path = base_dir.join(STATE_DIR);
@@ -122,49 +190,87 @@ where
             })?;
         }
 
-        let fs_cache = FileSystemCache::new(cache_path.join(MODULES_DIR))
+        let fs_cache = FileSystemCache::new(cache_path.join(MODULES_DIR), &operator_cost_table)
             .map_err(|e| VmError::cache_err(format!("Error file system cache: {}", e)))?;
+        let pinned_path = cache_path.join(PINNED_FILE);
+        let pinned_checksums = load_pinned_checksums(&pinned_path)?;
+        let inner = Mutex::new(CacheInner {
+            wasm_path,
+            instance_memory_limit,
+            pinned_memory_cache: PinnedMemoryCache::new(),
+            memory_cache: InMemoryCache::new(memory_cache_size),
+            fs_cache,
+            #[cfg(feature = "interpreter")]
+            pinned_interpreted_modules: HashMap::new(),
+            pinned_checksums: pinned_checksums.clone(),
+            pinned_path,
+            stats: Stats::default(),
+        });
+        let shared = Arc::new(CacheShared {
+            operator_cost_table,
+            inner,
+            loading: LoadingModules::default(),
+            metrics: Arc::new(MetricsRegistry::new()),
+        });
+
+        if !pinned_checksums.is_empty() {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                for checksum in pinned_checksums {
+                    // Skip codes that were unpinned in the meantime
+                    let pinned = shared
+                        .inner
+                        .lock()
+                        .unwrap()
+                        .pinned_checksums
+                        .contains(&checksum);
+                    if pinned {
+                        // Errors are ignored here. They show up once the code is used.
+                        let _ = W::pin_module(&shared, &checksum);
+                    }
+                }
+            });
+        }
+
         Ok(Cache {
             supported_features,
-            inner: Mutex::new(CacheInner {
-                wasm_path,
-                instance_memory_limit,
-                pinned_memory_cache: PinnedMemoryCache::new(),
-                memory_cache: InMemoryCache::new(memory_cache_size),
-                fs_cache,
-                stats: Stats::default(),
-            }),
+            wasm_limits,
+            gas_config,
+            shared,
             type_storage: PhantomData::<S>,
             type_api: PhantomData::<A>,
             type_querier: PhantomData::<Q>,
             type_vm: Default::default(),
             instantiation_lock: Mutex::new(()),
+            instance_pool: Mutex::new(InstancePool {
+                size: instance_pool_size,
+                initial_states: HashMap::new(),
+                unpoolable: HashSet::new(),
+                idle: HashMap::new(),
+            }),
         })
     }
 
     pub fn stats(&self) -> Stats {
-        self.inner.lock().unwrap().stats
+        self.shared.inner.lock().unwrap().stats
     }
 
     pub fn metrics(&self) -> Metrics {
-        let cache = self.inner.lock().unwrap();
+        let cache = self.shared.inner.lock().unwrap();
         Metrics {
             stats: cache.stats,
             elements_pinned_memory_cache: cache.pinned_memory_cache.len(),
             elements_memory_cache: cache.memory_cache.len(),
             size_pinned_memory_cache: cache.pinned_memory_cache.size(),
             size_memory_cache: cache.memory_cache.size(),
+            evictions_memory_cache: cache.memory_cache.evictions(),
         }
     }
 
-    pub fn save_wasm(&self, wasm: &[u8]) -> VmResult<Checksum> {
-        check_wasm(wasm, &self.supported_features)?;
-        let module = compile(wasm, None, &[])?;
-
-        let mut cache = self.inner.lock().unwrap();
-        let checksum = save_wasm_to_disk(&cache.wasm_path, wasm)?;
-        cache.fs_cache.store(&checksum, &module)?;
-        Ok(checksum)
+    /// Returns the registry that records compilations, instantiations and calls of the
+    /// instances created by this cache. See [`render_prometheus`](crate::render_prometheus).
+    pub fn metrics_registry(&self) -> Arc<MetricsRegistry> {
+        Arc::clone(&self.shared.metrics)
     }
 
     /// Retrieves a Wasm blob that was previously stored via save_wasm.
@@ -173,17 +279,7 @@ where
     ///
     /// If the given ID is not found or the content does not match the hash (=ID), an error is returned.
     pub fn load_wasm(&self, checksum: &Checksum) -> VmResult<Vec<u8>> {
-        self.load_wasm_with_path(&self.inner.lock().unwrap().wasm_path, checksum)
-    }
-
-    fn load_wasm_with_path(&self, wasm_path: &Path, checksum: &Checksum) -> VmResult<Vec<u8>> {
-        let code = load_wasm_from_disk(&wasm_path, checksum)?;
-        // verify hash matches (integrity check)
-        if Checksum::generate(&code) != *checksum {
-            Err(VmError::integrity_err())
-        } else {
-            Ok(code)
-        }
+        load_wasm_checked(&self.shared.inner.lock().unwrap().wasm_path, checksum)
     }
 
     /// Performs static anlyzation on this Wasm without compiling or instantiating it.
@@ -202,43 +298,48 @@ where
 
     /// Pins a Module that was previously stored via save_wasm.
     ///
-    /// The module is lookup first in the memory cache, and then in the file system cache.
+    /// The module is lookup first in the memory caches, and then in the file system cache.
     /// If not found, the code is loaded from the file system, compiled, and stored into the
-    /// pinned cache.
+    /// pinned cache. The checksum is persisted, such that the module is pinned again after a restart.
     /// If the given ID is not found, or the content does not match the hash (=ID), an error is returned.
     pub fn pin(&self, checksum: &Checksum) -> VmResult<()> {
-        let mut cache = self.inner.lock().unwrap();
-        if cache.pinned_memory_cache.has(checksum) {
-            return Ok(());
-        }
-
-        // Try to get module from the memory cache
-        if let Some(module) = cache.memory_cache.load(checksum)? {
-            cache.stats.hits_memory_cache += 1;
-            return cache
-                .pinned_memory_cache
-                .store(checksum, module.module, module.size);
-        }
-
-        // Try to get module from file system cache
-        let store = make_runtime_store(Some(cache.instance_memory_limit));
-        if let Some(module) = cache.fs_cache.load(checksum, &store)? {
-            cache.stats.hits_fs_cache += 1;
-            let module_size = loupe::size_of_val(&module);
-            return cache
-                .pinned_memory_cache
-                .store(checksum, module, module_size);
+        W::pin_module(&self.shared, checksum)?;
+        let mut cache = self.shared.inner.lock().unwrap();
+        if cache.pinned_checksums.insert(*checksum) {
+            save_pinned_checksums(&cache.pinned_path, &cache.pinned_checksums)?;
         }
+        Ok(())
+    }
 
-        // Re-compile from original Wasm bytecode
-        let code = self.load_wasm_with_path(&cache.wasm_path, checksum)?;
-        let module = compile(&code, Some(cache.instance_memory_limit), &[])?;
-        // Store into the fs cache too
-        cache.fs_cache.store(checksum, &module)?;
-        let module_size = loupe::size_of_val(&module);
-        cache
-            .pinned_memory_cache
-            .store(checksum, module, module_size)
+    /// Loads the modules of previously saved Wasm codes on a pool of background threads,
+    /// such that later calls of `get_instance` find them in the memory or file system cache.
+    ///
+    /// This returns immediately. Use [`WarmUp::join`] to wait until all modules are loaded.
+    pub fn warm(&self, checksums: &[Checksum]) -> WarmUp {
+        let queue = Arc::new(Mutex::new(checksums.to_vec()));
+        let thread_count = std::cmp::min(WARM_UP_THREADS, checksums.len());
+        let workers = (0..thread_count)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                let shared = Arc::clone(&self.shared);
+                thread::spawn(move || {
+                    let mut errors = Vec::new();
+                    loop {
+                        // The lock must not be held while the module is loaded
+                        let next = queue.lock().unwrap().pop();
+                        let checksum = match next {
+                            Some(checksum) => checksum,
+                            None => break,
+                        };
+                        if let Err(err) = W::warm_module(&shared, &checksum) {
+                            errors.push((checksum, err));
+                        }
+                    }
+                    errors
+                })
+            })
+            .collect();
+        WarmUp { workers }
     }
 
     /// Unpins a Module, i.e. removes it from the pinned memory cache.
@@ -246,56 +347,349 @@ where
     /// Not found IDs are silently ignored, and no integrity check (checksum validation) is done
     /// on the removed value.
     pub fn unpin(&self, checksum: &Checksum) -> VmResult<()> {
-        self.inner
-            .lock()
-            .unwrap()
-            .pinned_memory_cache
-            .remove(checksum)
+        let mut cache = self.shared.inner.lock().unwrap();
+        #[cfg(feature = "interpreter")]
+        cache.pinned_interpreted_modules.remove(checksum);
+        cache.pinned_memory_cache.remove(checksum)?;
+        if cache.pinned_checksums.remove(checksum) {
+            save_pinned_checksums(&cache.pinned_path, &cache.pinned_checksums)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the checksums of all pinned codes.
+    ///
+    /// This includes codes restored from a previous cache using the same `base_dir`,
+    /// even if their modules are still being loaded in the background.
+    pub fn pinned_checksums(&self) -> HashSet<Checksum> {
+        self.shared.inner.lock().unwrap().pinned_checksums.clone()
+    }
+
+    /// Removes a Wasm code that was previously stored via save_wasm along with its compiled
+    /// module from all caches. The code cannot be instantiated anymore until it is saved again.
+    ///
+    /// Not found IDs are silently ignored. Returns the disk space that was reclaimed.
+    pub fn remove_wasm(&self, checksum: &Checksum) -> VmResult<RemovalStats> {
+        {
+            let mut pool = self.instance_pool.lock().unwrap();
+            pool.initial_states.remove(checksum);
+            pool.unpoolable.remove(checksum);
+            pool.idle.remove(checksum);
+        }
+        let mut cache = self.shared.inner.lock().unwrap();
+        cache.pinned_memory_cache.remove(checksum)?;
+        cache.memory_cache.remove(checksum)?;
+        #[cfg(feature = "interpreter")]
+        cache.pinned_interpreted_modules.remove(checksum);
+        if cache.pinned_checksums.remove(checksum) {
+            save_pinned_checksums(&cache.pinned_path, &cache.pinned_checksums)?;
+        }
+        let mut stats = cache.fs_cache.remove(checksum)?;
+        stats += remove_wasm_from_disk(&cache.wasm_path, checksum)?;
+        Ok(stats)
+    }
+
+    /// Removes all compiled modules from the file system cache that are not contained in `keep`.
+    ///
+    /// Stored Wasm codes are not affected, such that removed modules can be re-compiled on demand.
+    /// Returns the disk space that was reclaimed.
+    pub fn prune_modules(&self, keep: &HashSet<Checksum>) -> VmResult<RemovalStats> {
+        let mut cache = self.shared.inner.lock().unwrap();
+        cache.fs_cache.prune(keep)
+    }
+
+    /// Removes all modules from the file system cache that were stored by older versions of
+    /// the module format, Wasmer or a different operator cost table.
+    ///
+    /// Don't call this while other caches with a different operator cost table use the same
+    /// base directory, since their modules are removed as well.
+    /// Returns the disk space that was reclaimed.
+    pub fn remove_stale_module_versions(&self) -> VmResult<RemovalStats> {
+        let mut cache = self.shared.inner.lock().unwrap();
+        cache.fs_cache.remove_stale_versions()
+    }
+}
+
+impl<A, S, Q> Cache<A, S, Q, WasmerInstance>
+where
+    A: BackendApi + 'static, // 'static is needed by `impl<…> Instance`
+    S: Storage + 'static,    // 'static is needed by `impl<…> Instance`
+    Q: Querier + 'static,    // 'static is needed by `impl<…> Instance`
+{
+    pub fn save_wasm(&self, wasm: &[u8]) -> VmResult<Checksum> {
+        check_wasm_with_limits(wasm, &self.supported_features, &self.wasm_limits)?;
+        let start = Instant::now();
+        let module = compile(wasm, None, &self.shared.operator_cost_table, &[])?;
+        let compile_time = start.elapsed();
+
+        let mut cache = self.shared.inner.lock().unwrap();
+        let checksum = save_wasm_to_disk(&cache.wasm_path, wasm)?;
+        cache.fs_cache.store(&checksum, &module)?;
+        self.shared
+            .metrics
+            .record_compilation(&checksum, compile_time);
+        Ok(checksum)
     }
 
     /// Returns an Instance tied to a previously saved Wasm.
     ///
     /// It takes a module from cache or Wasm code and instantiates it.
+    /// If instance pooling is enabled, an idle instance created with the same options is
+    /// reused instead with all parts of `backend`, see [`CacheOptions::instance_pool_size`].
     pub fn get_instance(
         &self,
         checksum: &Checksum,
         backend: Backend<A, S, Q>,
         options: InstanceOptions,
     ) -> VmResult<Instance<A, S, Q, WasmerInstance>> {
+        let gas_config = options.gas_config.unwrap_or(self.gas_config);
+        let max_stack_depth = options.max_stack_depth.unwrap_or(DEFAULT_MAX_STACK_DEPTH);
+        if let Some(mut instance) =
+            self.take_pooled_instance(checksum, options.print_debug, &gas_config)
+        {
+            instance.reuse(backend, options.gas_limit);
+            instance.set_max_stack_depth(max_stack_depth);
+            instance.set_metrics(self.metrics_registry());
+            return Ok(instance);
+        }
+
         let module = self.get_module(checksum)?;
-        let instance = Instance::from_module(
+        let start = Instant::now();
+        let mut instance = Instance::from_module(
             &module,
             backend,
             options.gas_limit,
             options.print_debug,
+            gas_config,
             None,
             Some(&self.instantiation_lock),
         )?;
+        instance.set_max_stack_depth(max_stack_depth);
+        self.shared.metrics.record_instantiation(start.elapsed());
+        instance.set_metrics(self.metrics_registry());
+        self.register_pooled_instance(checksum, &mut instance)?;
         Ok(instance)
     }
 
+    /// Returns an instance created by `get_instance` to the instance pool, such that it can be
+    /// reused by later calls of `get_instance` for the same code.
+    ///
+    /// The memory and the mutable globals of the instance are reset to their state right after
+    /// instantiation. Instances whose memory grew cannot be reset and are dropped, as are
+    /// instances exceeding [`CacheOptions::instance_pool_size`].
+    /// The backend is returned like in [`Instance::recycle`].
+    pub fn recycle_instance(
+        &self,
+        instance: Instance<A, S, Q, WasmerInstance>,
+    ) -> Option<Backend<A, S, Q>> {
+        let backend = instance.take_backend();
+        let checksum = match instance.pool_checksum {
+            Some(checksum) => checksum,
+            None => return backend,
+        };
+        let initial_state = self
+            .instance_pool
+            .lock()
+            .unwrap()
+            .initial_states
+            .get(&checksum)
+            .cloned();
+        if let Some(initial_state) = initial_state {
+            if instance.restore_state(&initial_state).is_ok() {
+                let mut pool = self.instance_pool.lock().unwrap();
+                let size = pool.size;
+                let idle = pool.idle.entry(checksum).or_default();
+                if idle.len() < size {
+                    idle.push(instance);
+                }
+            }
+        }
+        backend
+    }
+
+    /// Takes an idle instance of the given code that was created with the same options
+    fn take_pooled_instance(
+        &self,
+        checksum: &Checksum,
+        print_debug: bool,
+        gas_config: &GasConfig,
+    ) -> Option<Instance<A, S, Q, WasmerInstance>> {
+        let mut pool = self.instance_pool.lock().unwrap();
+        let idle = pool.idle.get_mut(checksum)?;
+        let position = idle
+            .iter()
+            .position(|instance| instance.has_options(print_debug, gas_config))?;
+        Some(idle.swap_remove(position))
+    }
+
+    /// Marks a new instance as poolable and captures the initial state of its code if needed.
+    ///
+    /// Instances can only be reset if all mutable globals of the code are exported. Codes whose
+    /// instances export a different number of globals are never pooled.
+    fn register_pooled_instance(
+        &self,
+        checksum: &Checksum,
+        instance: &mut Instance<A, S, Q, WasmerInstance>,
+    ) -> VmResult<()> {
+        {
+            let pool = self.instance_pool.lock().unwrap();
+            if pool.size == 0 || pool.unpoolable.contains(checksum) {
+                return Ok(());
+            }
+            if pool.initial_states.contains_key(checksum) {
+                instance.pool_checksum = Some(*checksum);
+                return Ok(());
+            }
+        }
+
+        let initial_state = instance.capture_state()?;
+        let expected_globals = count_mutable_globals(&self.load_wasm(checksum)?)?;
+        let mut pool = self.instance_pool.lock().unwrap();
+        if initial_state.globals.len() != expected_globals {
+            pool.unpoolable.insert(*checksum);
+            return Ok(());
+        }
+        pool.initial_states
+            .entry(*checksum)
+            .or_insert_with(|| Arc::new(initial_state));
+        instance.pool_checksum = Some(*checksum);
+        Ok(())
+    }
+
     /// Returns a module tied to a previously saved Wasm.
     /// Depending on availability, this is either generated from a memory cache, file system cache or Wasm code.
     /// This is part of `get_instance` but pulled out to reduce the locking time.
     fn get_module(&self, checksum: &Checksum) -> VmResult<wasmer::Module> {
-        let mut cache = self.inner.lock().unwrap();
-        // Try to get module from the pinned memory cache
-        if let Some(module) = cache.pinned_memory_cache.load(checksum)? {
-            cache.stats.hits_pinned_memory_cache += 1;
-            return Ok(module);
+        self.shared.load_wasmer_module(checksum)
+    }
+}
+
+#[cfg(feature = "interpreter")]
+impl<A, S, Q> Cache<A, S, Q, WasmiInstance>
+where
+    A: BackendApi + 'static, // 'static is needed by `impl<…> Instance`
+    S: Storage + 'static,    // 'static is needed by `impl<…> Instance`
+    Q: Querier + 'static,    // 'static is needed by `impl<…> Instance`
+{
+    /// Stores the Wasm code after checking it can be prepared for the interpreter.
+    /// In contrast to the Wasmer variant, prepared modules are not stored in the file system cache.
+    pub fn save_wasm(&self, wasm: &[u8]) -> VmResult<Checksum> {
+        check_wasm_with_limits(wasm, &self.supported_features, &self.wasm_limits)?;
+        let start = Instant::now();
+        compile_interpreted(wasm, None, &self.shared.operator_cost_table)?;
+        let compile_time = start.elapsed();
+
+        let cache = self.shared.inner.lock().unwrap();
+        let checksum = save_wasm_to_disk(&cache.wasm_path, wasm)?;
+        self.shared
+            .metrics
+            .record_compilation(&checksum, compile_time);
+        Ok(checksum)
+    }
+
+    /// Returns an Instance tied to a previously saved Wasm, executed in the interpreter.
+    ///
+    /// The module is taken from the pinned cache or prepared from Wasm code.
+    pub fn get_instance(
+        &self,
+        checksum: &Checksum,
+        backend: Backend<A, S, Q>,
+        options: InstanceOptions,
+    ) -> VmResult<Instance<A, S, Q, WasmiInstance>> {
+        let gas_config = options.gas_config.unwrap_or(self.gas_config);
+        let module = self.get_module(checksum)?;
+        let start = Instant::now();
+        let mut instance = Instance::from_wasmi_module(
+            module,
+            backend,
+            options.gas_limit,
+            options.print_debug,
+            gas_config,
+        )?;
+        instance.set_max_stack_depth(options.max_stack_depth.unwrap_or(DEFAULT_MAX_STACK_DEPTH));
+        self.shared.metrics.record_instantiation(start.elapsed());
+        instance.set_metrics(self.metrics_registry());
+        Ok(instance)
+    }
+
+    fn get_module(&self, checksum: &Checksum) -> VmResult<Arc<WasmiModule>> {
+        {
+            let mut cache = self.shared.inner.lock().unwrap();
+            if let Some(module) = cache.pinned_interpreted_modules.get(checksum).cloned() {
+                cache.stats.hits_pinned_memory_cache += 1;
+                return Ok(module);
+            }
+            cache.stats.misses += 1;
+        }
+        self.shared
+            .compile_interpreted_module(checksum)
+            .map(Arc::new)
+    }
+}
+
+/// A warm-up of the cache running in the background, see [`Cache::warm`].
+///
+/// Dropping this does not stop the warm-up.
+pub struct WarmUp {
+    workers: Vec<JoinHandle<Vec<(Checksum, VmError)>>>,
+}
+
+impl WarmUp {
+    /// Waits until all modules are loaded and returns the checksums that could not be loaded
+    /// along with the errors.
+    pub fn join(self) -> Vec<(Checksum, VmError)> {
+        self.workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("Warm-up thread panicked"))
+            .collect()
+    }
+}
+
+impl CacheShared {
+    /// Returns the Wasmer module of a previously saved Wasm. Depending on availability,
+    /// this is taken from a memory cache, the file system cache or compiled from Wasm code.
+    fn load_wasmer_module(&self, checksum: &Checksum) -> VmResult<Module> {
+        {
+            let mut cache = self.inner.lock().unwrap();
+            // Try to get module from the pinned memory cache
+            if let Some(module) = cache.pinned_memory_cache.load(checksum)? {
+                cache.stats.hits_pinned_memory_cache += 1;
+                return Ok(module);
+            }
         }
+        self.load_unpinned_wasmer_module(checksum)
+    }
 
-        // Get module from memory cache
-        if let Some(module) = cache.memory_cache.load(checksum)? {
-            cache.stats.hits_memory_cache += 1;
-            return Ok(module.module);
+    /// Like `load_wasmer_module` but without looking into the pinned memory cache.
+    ///
+    /// Loading from disk and compiling happens without holding the lock of `inner`,
+    /// such that other modules can be used in the meantime. Concurrent calls for the
+    /// same checksum wait for the first one and then find the module in the memory cache.
+    fn load_unpinned_wasmer_module(&self, checksum: &Checksum) -> VmResult<Module> {
+        if let Some(module) = self.load_wasmer_module_from_memory(checksum)? {
+            return Ok(module);
         }
+        let _loading = self.loading.start(checksum);
+        // Another thread might have loaded the module while we were waiting
+        if let Some(module) = self.load_wasmer_module_from_memory(checksum)? {
+            return Ok(module);
+        }
+
+        let (mut fs_cache, wasm_path, instance_memory_limit) = {
+            let cache = self.inner.lock().unwrap();
+            (
+                cache.fs_cache.clone(),
+                cache.wasm_path.clone(),
+                cache.instance_memory_limit,
+            )
+        };
 
         // Get module from file system cache
-        let store = make_runtime_store(Some(cache.instance_memory_limit));
-        if let Some(module) = cache.fs_cache.load(checksum, &store)? {
-            cache.stats.hits_fs_cache += 1;
+        let store = make_runtime_store(Some(instance_memory_limit));
+        if let Some(module) = fs_cache.load(checksum, &store)? {
             let module_size = loupe::size_of_val(&module);
+            let mut cache = self.inner.lock().unwrap();
+            cache.stats.hits_fs_cache += 1;
             cache
                 .memory_cache
                 .store(checksum, module.clone(), module_size)?;
@@ -307,16 +701,168 @@ where
         // This is needed for chains that upgrade their node software in a way that changes the module
         // serialization format. If you do not replay all transactions, previous calls of `save_wasm`
         // stored the old module format.
-        let wasm = self.load_wasm_with_path(&cache.wasm_path, checksum)?;
-        cache.stats.misses += 1;
-        let module = compile(&wasm, Some(cache.instance_memory_limit), &[])?;
-        cache.fs_cache.store(checksum, &module)?;
+        let wasm = load_wasm_checked(&wasm_path, checksum)?;
+        self.inner.lock().unwrap().stats.misses += 1;
+        let start = Instant::now();
+        let module = compile(
+            &wasm,
+            Some(instance_memory_limit),
+            &self.operator_cost_table,
+            &[],
+        )?;
+        self.metrics.record_compilation(checksum, start.elapsed());
+        fs_cache.store(checksum, &module)?;
         let module_size = loupe::size_of_val(&module);
-        cache
+        self.inner
+            .lock()
+            .unwrap()
             .memory_cache
             .store(checksum, module.clone(), module_size)?;
         Ok(module)
     }
+
+    fn load_wasmer_module_from_memory(&self, checksum: &Checksum) -> VmResult<Option<Module>> {
+        let mut cache = self.inner.lock().unwrap();
+        match cache.memory_cache.load(checksum)? {
+            Some(module) => {
+                cache.stats.hits_memory_cache += 1;
+                Ok(Some(module.module))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Prepares a previously saved Wasm for the interpreter without holding the lock of `inner`
+    #[cfg(feature = "interpreter")]
+    fn compile_interpreted_module(&self, checksum: &Checksum) -> VmResult<WasmiModule> {
+        let (wasm_path, instance_memory_limit) = {
+            let cache = self.inner.lock().unwrap();
+            (cache.wasm_path.clone(), cache.instance_memory_limit)
+        };
+        let wasm = load_wasm_checked(&wasm_path, checksum)?;
+        let start = Instant::now();
+        let module = compile_interpreted(
+            &wasm,
+            Some(instance_memory_limit),
+            &self.operator_cost_table,
+        )?;
+        self.metrics.record_compilation(checksum, start.elapsed());
+        Ok(module)
+    }
+}
+
+/// Ensures that every module is loaded or compiled by only one thread at a time
+#[derive(Default)]
+struct LoadingModules {
+    checksums: Mutex<HashSet<Checksum>>,
+    finished: Condvar,
+}
+
+impl LoadingModules {
+    /// Blocks until no other thread loads the module of the given checksum.
+    /// Other threads are blocked until the returned guard is dropped.
+    fn start(&self, checksum: &Checksum) -> LoadingGuard<'_> {
+        let mut checksums = self.checksums.lock().unwrap();
+        while checksums.contains(checksum) {
+            checksums = self.finished.wait(checksums).unwrap();
+        }
+        checksums.insert(*checksum);
+        LoadingGuard {
+            loading: self,
+            checksum: *checksum,
+        }
+    }
+}
+
+struct LoadingGuard<'a> {
+    loading: &'a LoadingModules,
+    checksum: Checksum,
+}
+
+impl Drop for LoadingGuard<'_> {
+    fn drop(&mut self) {
+        self.loading
+            .checksums
+            .lock()
+            .unwrap()
+            .remove(&self.checksum);
+        self.loading.finished.notify_all();
+    }
+}
+
+/// The parts of the cache that depend on the Wasm backend.
+///
+/// This is implemented for every backend a [`Cache`] can be used with.
+pub trait CacheBackend: WasmVM + 'static {
+    /// Stores the module of a previously saved Wasm in the pinned memory cache of this backend.
+    /// This is a no-op if the module is pinned already.
+    fn pin_module(shared: &CacheShared, checksum: &Checksum) -> VmResult<()>;
+
+    /// Prepares the module of a previously saved Wasm, such that the next instantiation is fast.
+    fn warm_module(shared: &CacheShared, checksum: &Checksum) -> VmResult<()>;
+}
+
+impl CacheBackend for WasmerInstance {
+    /// The module is lookup first in the memory cache, and then in the file system cache.
+    /// If not found, the code is loaded from the file system, compiled, and stored into the
+    /// pinned cache.
+    fn pin_module(shared: &CacheShared, checksum: &Checksum) -> VmResult<()> {
+        if shared
+            .inner
+            .lock()
+            .unwrap()
+            .pinned_memory_cache
+            .has(checksum)
+        {
+            return Ok(());
+        }
+
+        let module = shared.load_unpinned_wasmer_module(checksum)?;
+        let module_size = loupe::size_of_val(&module);
+        shared
+            .inner
+            .lock()
+            .unwrap()
+            .pinned_memory_cache
+            .store(checksum, module, module_size)
+    }
+
+    /// The module is stored in the file system cache and the memory cache.
+    fn warm_module(shared: &CacheShared, checksum: &Checksum) -> VmResult<()> {
+        shared.load_wasmer_module(checksum).map(|_| ())
+    }
+}
+
+#[cfg(feature = "interpreter")]
+impl CacheBackend for WasmiInstance {
+    /// The code is loaded from the file system and prepared for the interpreter.
+    fn pin_module(shared: &CacheShared, checksum: &Checksum) -> VmResult<()> {
+        if shared
+            .inner
+            .lock()
+            .unwrap()
+            .pinned_interpreted_modules
+            .contains_key(checksum)
+        {
+            return Ok(());
+        }
+
+        let module = Arc::new(shared.compile_interpreted_module(checksum)?);
+        shared
+            .inner
+            .lock()
+            .unwrap()
+            .pinned_interpreted_modules
+            .entry(*checksum)
+            .or_insert(module);
+        Ok(())
+    }
+
+    /// The interpreter only caches pinned modules, such that this merely checks
+    /// that the code can be prepared.
+    fn warm_module(shared: &CacheShared, checksum: &Checksum) -> VmResult<()> {
+        shared.compile_interpreted_module(checksum).map(|_| ())
+    }
 }
 
 unsafe impl<A, S, Q, W> Sync for Cache<A, S, Q, W>
@@ -360,6 +906,49 @@ fn save_wasm_to_disk(dir: impl Into<PathBuf>, wasm: &[u8]) -> VmResult<Checksum>
     Ok(checksum)
 }
 
+/// Loads the checksums written by `save_pinned_checksums`.
+/// Returns an empty set if nothing was pinned yet. Invalid lines are ignored.
+fn load_pinned_checksums(path: &Path) -> VmResult<HashSet<Checksum>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(e) => {
+            return Err(VmError::cache_err(format!(
+                "Error reading pinned checksums: {}",
+                e
+            )))
+        }
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| hex::decode(line.trim()).ok())
+        .filter_map(|data| Checksum::try_from(data.as_slice()).ok())
+        .collect())
+}
+
+/// Writes the given checksums hex encoded, one per line.
+/// The file is replaced atomically, such that an interrupted write does not lose pins.
+fn save_pinned_checksums(path: &Path, checksums: &HashSet<Checksum>) -> VmResult<()> {
+    let mut lines: Vec<String> = checksums.iter().map(|checksum| checksum.to_hex()).collect();
+    lines.sort();
+    let content: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, content)
+        .and_then(|_| std::fs::rename(&tmp_path, path))
+        .map_err(|e| VmError::cache_err(format!("Error writing pinned checksums: {}", e)))
+}
+
+fn load_wasm_checked(wasm_path: &Path, checksum: &Checksum) -> VmResult<Vec<u8>> {
+    let code = load_wasm_from_disk(&wasm_path, checksum)?;
+    // verify hash matches (integrity check)
+    if Checksum::generate(&code) != *checksum {
+        Err(VmError::integrity_err())
+    } else {
+        Ok(code)
+    }
+}
+
 fn load_wasm_from_disk(dir: impl Into<PathBuf>, checksum: &Checksum) -> VmResult<Vec<u8>> {
     // this requires the directory and file to exist
     let path = dir.into().join(checksum.to_hex());
@@ -372,12 +961,35 @@ fn load_wasm_from_disk(dir: impl Into<PathBuf>, checksum: &Checksum) -> VmResult
     Ok(wasm)
 }
 
+fn remove_wasm_from_disk(dir: impl Into<PathBuf>, checksum: &Checksum) -> VmResult<RemovalStats> {
+    let path = dir.into().join(checksum.to_hex());
+    let size = match path.metadata() {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(RemovalStats::default()),
+        Err(e) => {
+            return Err(VmError::cache_err(format!(
+                "Error reading Wasm file metadata: {}",
+                e
+            )))
+        }
+    };
+    remove_file(path)
+        .map_err(|e| VmError::cache_err(format!("Error removing Wasm file: {}", e)))?;
+    Ok(RemovalStats {
+        files: 1,
+        bytes: size,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::BackendError;
     use crate::calls::{call_execute, call_instantiate};
+    use crate::environment::LinearGasCost;
     use crate::errors::VmError;
     use crate::features::features_from_csv;
+    use crate::metrics::render_prometheus;
     use crate::testing::{mock_backend, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
     use cosmwasm_std::{coins, Empty};
     use std::fs::OpenOptions;
@@ -387,10 +999,14 @@ mod tests {
 
     const TESTING_GAS_LIMIT: u64 = 500_000_000_000; // ~0.5ms
     const TESTING_MEMORY_LIMIT: Size = Size::mebi(16);
-    const TESTING_OPTIONS: InstanceOptions = InstanceOptions {
-        gas_limit: TESTING_GAS_LIMIT,
-        print_debug: false,
-    };
+    fn testing_options() -> InstanceOptions {
+        InstanceOptions {
+            gas_limit: TESTING_GAS_LIMIT,
+            print_debug: false,
+            gas_config: None,
+            max_stack_depth: None,
+        }
+    }
     const TESTING_MEMORY_CACHE_SIZE: Size = Size::mebi(200);
 
     static CONTRACT: &[u8] = include_bytes!("../testdata/hackatom.wasm");
@@ -406,6 +1022,10 @@ mod tests {
             supported_features: default_features(),
             memory_cache_size: TESTING_MEMORY_CACHE_SIZE,
             instance_memory_limit: TESTING_MEMORY_LIMIT,
+            operator_cost_table: OperatorCostTable::default(),
+            gas_config: GasConfig::default(),
+            instance_pool_size: 0,
+            wasm_limits: WasmLimits::default(),
         }
     }
 
@@ -415,13 +1035,17 @@ mod tests {
             supported_features: features_from_csv("iterator,staking,stargate"),
             memory_cache_size: TESTING_MEMORY_CACHE_SIZE,
             instance_memory_limit: TESTING_MEMORY_LIMIT,
+            operator_cost_table: OperatorCostTable::default(),
+            gas_config: GasConfig::default(),
+            instance_pool_size: 0,
+            wasm_limits: WasmLimits::default(),
         }
     }
 
     #[test]
     fn save_wasm_works() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         cache.save_wasm(CONTRACT).unwrap();
     }
 
@@ -429,7 +1053,7 @@ mod tests {
     // This property is required when the same bytecode is uploaded multiple times
     fn save_wasm_allows_saving_multiple_times() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         cache.save_wasm(CONTRACT).unwrap();
         cache.save_wasm(CONTRACT).unwrap();
     }
@@ -449,7 +1073,7 @@ mod tests {
         .unwrap();
 
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let save_result = cache.save_wasm(&wasm);
         match save_result.unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
@@ -459,18 +1083,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn save_wasm_applies_wasm_limits() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(CacheOptions {
+                wasm_limits: WasmLimits {
+                    max_functions: 10,
+                    ..WasmLimits::default()
+                },
+                ..make_testing_options()
+            })
+            .unwrap();
+        match cache.save_wasm(CONTRACT).unwrap_err() {
+            VmError::StaticValidationErr { msg, .. } => {
+                assert!(msg.ends_with("functions, which exceeds the limit of 10."))
+            }
+            e => panic!("Unexpected error {:?}", e),
+        }
+    }
+
     #[test]
     fn save_wasm_fills_file_system_but_not_memory_cache() {
         // Who knows if and when the uploaded contract will be executed. Don't pollute
         // memory cache before the init call.
 
-        let cache: Cache<_, _, _, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+        let cache: Cache<_, _, _, WasmerInstance> = Cache::new(make_testing_options()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         let backend = mock_backend(&[]);
         let _ = cache
-            .get_instance(&checksum, backend, TESTING_OPTIONS)
+            .get_instance(&checksum, backend, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
         assert_eq!(cache.stats().hits_memory_cache, 0);
@@ -481,7 +1123,7 @@ mod tests {
     #[test]
     fn load_wasm_works() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         let restored = cache.load_wasm(&checksum).unwrap();
@@ -499,9 +1141,13 @@ mod tests {
                 supported_features: default_features(),
                 memory_cache_size: TESTING_MEMORY_CACHE_SIZE,
                 instance_memory_limit: TESTING_MEMORY_LIMIT,
+                operator_cost_table: OperatorCostTable::default(),
+                gas_config: GasConfig::default(),
+                instance_pool_size: 0,
+                wasm_limits: WasmLimits::default(),
             };
             let cache1: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-                unsafe { Cache::new(options1).unwrap() };
+                Cache::new(options1).unwrap();
             id = cache1.save_wasm(CONTRACT).unwrap();
         }
 
@@ -511,9 +1157,13 @@ mod tests {
                 supported_features: default_features(),
                 memory_cache_size: TESTING_MEMORY_CACHE_SIZE,
                 instance_memory_limit: TESTING_MEMORY_LIMIT,
+                operator_cost_table: OperatorCostTable::default(),
+                gas_config: GasConfig::default(),
+                instance_pool_size: 0,
+                wasm_limits: WasmLimits::default(),
             };
             let cache2: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-                unsafe { Cache::new(options2).unwrap() };
+                Cache::new(options2).unwrap();
             let restored = cache2.load_wasm(&id).unwrap();
             assert_eq!(restored, CONTRACT);
         }
@@ -522,7 +1172,7 @@ mod tests {
     #[test]
     fn load_wasm_errors_for_non_existent_id() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let checksum = Checksum::from([
             5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5,
             5, 5, 5,
@@ -545,9 +1195,13 @@ mod tests {
             supported_features: default_features(),
             memory_cache_size: TESTING_MEMORY_CACHE_SIZE,
             instance_memory_limit: TESTING_MEMORY_LIMIT,
+            operator_cost_table: OperatorCostTable::default(),
+            gas_config: GasConfig::default(),
+            instance_pool_size: 0,
+            wasm_limits: WasmLimits::default(),
         };
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(options).unwrap() };
+            Cache::new(options).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         // Corrupt cache file
@@ -570,11 +1224,11 @@ mod tests {
     #[test]
     fn get_instance_finds_cached_module() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
         let backend = mock_backend(&[]);
         let _instance = cache
-            .get_instance(&checksum, backend, TESTING_OPTIONS)
+            .get_instance(&checksum, backend, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
         assert_eq!(cache.stats().hits_memory_cache, 0);
@@ -582,10 +1236,43 @@ mod tests {
         assert_eq!(cache.stats().misses, 0);
     }
 
+    #[test]
+    fn get_instance_uses_gas_config_of_cache() {
+        let gas_config = GasConfig {
+            debug_cost: LinearGasCost {
+                base: 1,
+                per_item: 2,
+            },
+            ..GasConfig::default()
+        };
+        let options = CacheOptions {
+            gas_config,
+            ..make_testing_options()
+        };
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(options).unwrap();
+        let checksum = cache.save_wasm(CONTRACT).unwrap();
+
+        let instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert!(instance.has_options(false, &gas_config));
+
+        // The instance options override the config of the cache
+        let options = InstanceOptions {
+            gas_config: Some(GasConfig::default()),
+            ..testing_options()
+        };
+        let instance = cache
+            .get_instance(&checksum, mock_backend(&[]), options)
+            .unwrap();
+        assert!(instance.has_options(false, &GasConfig::default()));
+    }
+
     #[test]
     fn get_instance_finds_cached_modules_and_stores_to_memory() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
         let backend1 = mock_backend(&[]);
         let backend2 = mock_backend(&[]);
@@ -595,7 +1282,7 @@ mod tests {
 
         // from file system
         let _instance1 = cache
-            .get_instance(&checksum, backend1, TESTING_OPTIONS)
+            .get_instance(&checksum, backend1, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
         assert_eq!(cache.stats().hits_memory_cache, 0);
@@ -604,7 +1291,7 @@ mod tests {
 
         // from memory
         let _instance2 = cache
-            .get_instance(&checksum, backend2, TESTING_OPTIONS)
+            .get_instance(&checksum, backend2, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
         assert_eq!(cache.stats().hits_memory_cache, 1);
@@ -613,7 +1300,7 @@ mod tests {
 
         // from memory again
         let _instance3 = cache
-            .get_instance(&checksum, backend3, TESTING_OPTIONS)
+            .get_instance(&checksum, backend3, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
         assert_eq!(cache.stats().hits_memory_cache, 2);
@@ -629,7 +1316,7 @@ mod tests {
 
         // from pinned memory cache
         let _instance4 = cache
-            .get_instance(&checksum, backend4, TESTING_OPTIONS)
+            .get_instance(&checksum, backend4, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 1);
         assert_eq!(cache.stats().hits_memory_cache, 3);
@@ -638,7 +1325,7 @@ mod tests {
 
         // from pinned memory cache again
         let _instance5 = cache
-            .get_instance(&checksum, backend5, TESTING_OPTIONS)
+            .get_instance(&checksum, backend5, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 2);
         assert_eq!(cache.stats().hits_memory_cache, 3);
@@ -649,13 +1336,13 @@ mod tests {
     #[test]
     fn call_instantiate_on_cached_contract() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         // from file system
         {
             let mut instance = cache
-                .get_instance(&checksum, mock_backend(&[]), TESTING_OPTIONS)
+                .get_instance(&checksum, mock_backend(&[]), testing_options())
                 .unwrap();
             assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
             assert_eq!(cache.stats().hits_memory_cache, 0);
@@ -674,7 +1361,7 @@ mod tests {
         // from memory
         {
             let mut instance = cache
-                .get_instance(&checksum, mock_backend(&[]), TESTING_OPTIONS)
+                .get_instance(&checksum, mock_backend(&[]), testing_options())
                 .unwrap();
             assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
             assert_eq!(cache.stats().hits_memory_cache, 1);
@@ -695,7 +1382,7 @@ mod tests {
             cache.pin(&checksum).unwrap();
 
             let mut instance = cache
-                .get_instance(&checksum, mock_backend(&[]), TESTING_OPTIONS)
+                .get_instance(&checksum, mock_backend(&[]), testing_options())
                 .unwrap();
             assert_eq!(cache.stats().hits_pinned_memory_cache, 1);
             assert_eq!(cache.stats().hits_memory_cache, 2);
@@ -715,13 +1402,13 @@ mod tests {
     #[test]
     fn call_execute_on_cached_contract() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         // from file system
         {
             let mut instance = cache
-                .get_instance(&checksum, mock_backend(&[]), TESTING_OPTIONS)
+                .get_instance(&checksum, mock_backend(&[]), testing_options())
                 .unwrap();
             assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
             assert_eq!(cache.stats().hits_memory_cache, 0);
@@ -750,7 +1437,7 @@ mod tests {
         // from memory
         {
             let mut instance = cache
-                .get_instance(&checksum, mock_backend(&[]), TESTING_OPTIONS)
+                .get_instance(&checksum, mock_backend(&[]), testing_options())
                 .unwrap();
             assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
             assert_eq!(cache.stats().hits_memory_cache, 1);
@@ -781,7 +1468,7 @@ mod tests {
             cache.pin(&checksum).unwrap();
 
             let mut instance = cache
-                .get_instance(&checksum, mock_backend(&[]), TESTING_OPTIONS)
+                .get_instance(&checksum, mock_backend(&[]), testing_options())
                 .unwrap();
             assert_eq!(cache.stats().hits_pinned_memory_cache, 1);
             assert_eq!(cache.stats().hits_memory_cache, 2);
@@ -811,7 +1498,7 @@ mod tests {
     #[test]
     fn use_multiple_cached_instances_of_same_contract() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         // these differentiate the two instances of the same contract
@@ -820,7 +1507,7 @@ mod tests {
 
         // init instance 1
         let mut instance = cache
-            .get_instance(&checksum, backend1, TESTING_OPTIONS)
+            .get_instance(&checksum, backend1, testing_options())
             .unwrap();
         let info = mock_info("owner1", &coins(1000, "earth"));
         let msg = br#"{"verifier": "sue", "beneficiary": "mary"}"#;
@@ -832,7 +1519,7 @@ mod tests {
 
         // init instance 2
         let mut instance = cache
-            .get_instance(&checksum, backend2, TESTING_OPTIONS)
+            .get_instance(&checksum, backend2, testing_options())
             .unwrap();
         let info = mock_info("owner2", &coins(500, "earth"));
         let msg = br#"{"verifier": "bob", "beneficiary": "john"}"#;
@@ -844,7 +1531,7 @@ mod tests {
 
         // run contract 2 - just sanity check - results validate in contract unit tests
         let mut instance = cache
-            .get_instance(&checksum, backend2, TESTING_OPTIONS)
+            .get_instance(&checksum, backend2, testing_options())
             .unwrap();
         let info = mock_info("bob", &coins(15, "earth"));
         let msg = br#"{"release":{}}"#;
@@ -855,7 +1542,7 @@ mod tests {
 
         // run contract 1 - just sanity check - results validate in contract unit tests
         let mut instance = cache
-            .get_instance(&checksum, backend1, TESTING_OPTIONS)
+            .get_instance(&checksum, backend1, testing_options())
             .unwrap();
         let info = mock_info("sue", &coins(15, "earth"));
         let msg = br#"{"release":{}}"#;
@@ -868,7 +1555,7 @@ mod tests {
     #[test]
     fn resets_gas_when_reusing_instance() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         let backend1 = mock_backend(&[]);
@@ -876,7 +1563,7 @@ mod tests {
 
         // Init from module cache
         let mut instance1 = cache
-            .get_instance(&checksum, backend1, TESTING_OPTIONS)
+            .get_instance(&checksum, backend1, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
         assert_eq!(cache.stats().hits_memory_cache, 0);
@@ -894,7 +1581,7 @@ mod tests {
 
         // Init from memory cache
         let instance2 = cache
-            .get_instance(&checksum, backend2, TESTING_OPTIONS)
+            .get_instance(&checksum, backend2, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
         assert_eq!(cache.stats().hits_memory_cache, 1);
@@ -906,7 +1593,7 @@ mod tests {
     #[test]
     fn recovers_from_out_of_gas() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         let backend1 = mock_backend(&[]);
@@ -916,6 +1603,8 @@ mod tests {
         let options = InstanceOptions {
             gas_limit: 10,
             print_debug: false,
+            gas_config: None,
+            max_stack_depth: None,
         };
         let mut instance1 = cache.get_instance(&checksum, backend1, options).unwrap();
         assert_eq!(cache.stats().hits_fs_cache, 1);
@@ -936,6 +1625,8 @@ mod tests {
         let options = InstanceOptions {
             gas_limit: TESTING_GAS_LIMIT,
             print_debug: false,
+            gas_config: None,
+            max_stack_depth: None,
         };
         let mut instance2 = cache.get_instance(&checksum, backend2, options).unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
@@ -997,7 +1688,7 @@ mod tests {
     #[test]
     fn analyze_works() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_stargate_testing_options()).unwrap() };
+            Cache::new(make_stargate_testing_options()).unwrap();
 
         let checksum1 = cache.save_wasm(CONTRACT).unwrap();
         let report1 = cache.analyze(&checksum1).unwrap();
@@ -1027,13 +1718,13 @@ mod tests {
     #[test]
     fn pin_unpin_works() {
         let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
-            unsafe { Cache::new(make_testing_options()).unwrap() };
+            Cache::new(make_testing_options()).unwrap();
         let checksum = cache.save_wasm(CONTRACT).unwrap();
 
         // check not pinned
         let backend = mock_backend(&[]);
         let _instance = cache
-            .get_instance(&checksum, backend, TESTING_OPTIONS)
+            .get_instance(&checksum, backend, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
         assert_eq!(cache.stats().hits_memory_cache, 0);
@@ -1057,7 +1748,7 @@ mod tests {
         // check pinned
         let backend = mock_backend(&[]);
         let _instance = cache
-            .get_instance(&checksum, backend, TESTING_OPTIONS)
+            .get_instance(&checksum, backend, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 1);
         assert_eq!(cache.stats().hits_memory_cache, 1);
@@ -1070,7 +1761,7 @@ mod tests {
         // verify unpinned
         let backend = mock_backend(&[]);
         let _instance = cache
-            .get_instance(&checksum, backend, TESTING_OPTIONS)
+            .get_instance(&checksum, backend, testing_options())
             .unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 1);
         assert_eq!(cache.stats().hits_memory_cache, 2);
@@ -1084,4 +1775,572 @@ mod tests {
         let non_id = Checksum::generate(b"non_existent");
         cache.unpin(&non_id).unwrap();
     }
+
+    #[test]
+    #[cfg(feature = "interpreter")]
+    fn interpreter_cache_works() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmiInstance> =
+            Cache::new(make_testing_options()).unwrap();
+        let checksum = cache.save_wasm(CONTRACT).unwrap();
+
+        // prepared from Wasm
+        {
+            let mut instance = cache
+                .get_instance(&checksum, mock_backend(&[]), testing_options())
+                .unwrap();
+            assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
+            assert_eq!(cache.stats().misses, 1);
+
+            let info = mock_info("creator", &coins(1000, "earth"));
+            let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+            let res = call_instantiate::<_, _, _, Empty, _>(&mut instance, &mock_env(), &info, msg)
+                .unwrap();
+            let msgs = res.unwrap().messages;
+            assert_eq!(msgs.len(), 0);
+        }
+
+        // from pinned memory
+        {
+            cache.pin(&checksum).unwrap();
+
+            let mut instance = cache
+                .get_instance(&checksum, mock_backend(&[]), testing_options())
+                .unwrap();
+            assert_eq!(cache.stats().hits_pinned_memory_cache, 1);
+            assert_eq!(cache.stats().misses, 1);
+
+            let info = mock_info("creator", &coins(1000, "earth"));
+            let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+            let res = call_instantiate::<_, _, _, Empty, _>(&mut instance, &mock_env(), &info, msg)
+                .unwrap();
+            let msgs = res.unwrap().messages;
+            assert_eq!(msgs.len(), 0);
+        }
+
+        // unpinned
+        cache.unpin(&checksum).unwrap();
+        let _instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(cache.stats().hits_pinned_memory_cache, 1);
+        assert_eq!(cache.stats().misses, 2);
+    }
+
+    #[test]
+    fn remove_wasm_works() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(make_testing_options()).unwrap();
+        let checksum = cache.save_wasm(CONTRACT).unwrap();
+        cache.pin(&checksum).unwrap();
+        let _instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+
+        let stats = cache.remove_wasm(&checksum).unwrap();
+        assert_eq!(stats.files, 2);
+        assert!(stats.bytes > CONTRACT.len() as u64);
+        assert_eq!(cache.metrics().elements_pinned_memory_cache, 0);
+        assert_eq!(cache.metrics().elements_memory_cache, 0);
+
+        let err = cache.load_wasm(&checksum).unwrap_err();
+        assert!(matches!(err, VmError::CacheErr { .. }));
+        let err = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap_err();
+        assert!(matches!(err, VmError::CacheErr { .. }));
+
+        // removing again is a no-op
+        let stats = cache.remove_wasm(&checksum).unwrap();
+        assert_eq!(stats, RemovalStats::default());
+    }
+
+    #[test]
+    fn get_instance_compiles_module_once_for_concurrent_calls() {
+        let cache: Arc<Cache<MockApi, MockStorage, MockQuerier, WasmerInstance>> =
+            Arc::new(Cache::new(make_testing_options()).unwrap());
+        let checksum = cache.save_wasm(CONTRACT).unwrap();
+        // Force re-compilation from Wasm
+        cache.prune_modules(&HashSet::new()).unwrap();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                thread::spawn(move || {
+                    cache
+                        .get_instance(&checksum, mock_backend(&[]), testing_options())
+                        .unwrap();
+                })
+            })
+            .collect();
+        for handle in threads {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
+        assert_eq!(cache.stats().hits_memory_cache, 3);
+        assert_eq!(cache.stats().hits_fs_cache, 0);
+        assert_eq!(cache.stats().misses, 1);
+    }
+
+    #[test]
+    fn warm_works() {
+        let tmp_dir = TempDir::new().unwrap();
+        let options = CacheOptions {
+            base_dir: tmp_dir.path().to_path_buf(),
+            ..make_stargate_testing_options()
+        };
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(options.clone()).unwrap();
+        let checksum1 = cache.save_wasm(CONTRACT).unwrap();
+        let checksum2 = cache.save_wasm(IBC_CONTRACT).unwrap();
+        let non_id = Checksum::generate(b"non_existent");
+        // Force re-compilation from Wasm
+        cache.prune_modules(&HashSet::new()).unwrap();
+
+        let errors = cache.warm(&[checksum1, non_id, checksum2]).join();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, non_id);
+        match &errors[0].1 {
+            VmError::CacheErr { msg, .. } => {
+                assert!(msg.starts_with("Error opening Wasm file for reading:"))
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+        assert_eq!(cache.stats().misses, 2);
+        assert_eq!(cache.metrics().elements_memory_cache, 2);
+
+        // modules are taken from the memory cache
+        for checksum in [checksum1, checksum2].iter() {
+            let _instance = cache
+                .get_instance(checksum, mock_backend(&[]), testing_options())
+                .unwrap();
+        }
+        assert_eq!(cache.stats().hits_memory_cache, 2);
+        assert_eq!(cache.stats().misses, 2);
+
+        // and were stored in the file system cache
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(options).unwrap();
+        let _instance = cache
+            .get_instance(&checksum1, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(cache.stats().hits_fs_cache, 1);
+        assert_eq!(cache.stats().misses, 0);
+
+        // warming nothing is fine
+        assert!(cache.warm(&[]).join().is_empty());
+    }
+
+    #[test]
+    fn metrics_registry_works() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(make_testing_options()).unwrap();
+        let metrics = cache.metrics_registry();
+        let checksum = cache.save_wasm(CONTRACT).unwrap();
+        assert_eq!(metrics.compilations(&checksum).count, 1);
+
+        // Force re-compilation from Wasm
+        cache.prune_modules(&HashSet::new()).unwrap();
+        let mut instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(metrics.compilations(&checksum).count, 2);
+        assert_eq!(metrics.instantiations(), 1);
+
+        // instances record their calls in the registry of the cache
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        call_instantiate::<_, _, _, Empty, _>(&mut instance, &mock_env(), &info, msg)
+            .unwrap()
+            .unwrap();
+        assert_eq!(metrics.calls("instantiate").count, 1);
+
+        let out = render_prometheus(&cache.metrics(), &metrics);
+        assert!(out.contains("cosmwasm_vm_cache_misses_total 1\n"));
+        assert!(out.contains("cosmwasm_vm_compilations_total 2\n"));
+        assert!(out.contains("cosmwasm_vm_instantiation_seconds_count 1\n"));
+        assert!(out.contains("cosmwasm_vm_calls_total{entry_point=\"instantiate\"} 1\n"));
+    }
+
+    /// A contract with a mutable global and memory that is changed by `bump`
+    fn poolable_contract() -> Vec<u8> {
+        wat::parse_str(
+            r#"(module
+            (global $counter (mut i32) (i32.const 0))
+            (memory 1)
+            (export "memory" (memory 0))
+            (func (export "interface_version_8"))
+            (func (export "allocate") (param i32) (result i32) i32.const 0)
+            (func (export "deallocate") (param i32))
+            (func (export "instantiate") (param i32 i32 i32) (result i32) i32.const 0)
+            (func (export "bump") (result i32)
+                global.get $counter
+                i32.const 1
+                i32.add
+                global.set $counter
+                i32.const 8
+                i32.const 8
+                i32.load
+                i32.const 10
+                i32.add
+                i32.store
+                global.get $counter
+                i32.const 8
+                i32.load
+                i32.add)
+            (func (export "grow") (result i32)
+                i32.const 1
+                memory.grow)
+            )"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn recycle_instance_resets_pooled_instances() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(CacheOptions {
+                instance_pool_size: 1,
+                ..make_testing_options()
+            })
+            .unwrap();
+        let metrics = cache.metrics_registry();
+        let checksum = cache.save_wasm(&poolable_contract()).unwrap();
+
+        let instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        // global is 1 and memory is 10
+        assert_eq!(
+            instance.call_function1("bump", &[]).unwrap().i32(),
+            Some(11)
+        );
+        assert_eq!(
+            instance.call_function1("bump", &[]).unwrap().i32(),
+            Some(22)
+        );
+        assert!(instance.get_gas_left() < TESTING_GAS_LIMIT);
+        assert!(cache.recycle_instance(instance).is_some());
+
+        // the instance is reused in its initial state
+        let instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(metrics.instantiations(), 1);
+        assert_eq!(instance.get_gas_left(), TESTING_GAS_LIMIT);
+        assert_eq!(
+            instance.call_function1("bump", &[]).unwrap().i32(),
+            Some(11)
+        );
+
+        // instances with grown memory cannot be reset
+        instance.call_function1("grow", &[]).unwrap();
+        assert!(cache.recycle_instance(instance).is_some());
+        let instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(metrics.instantiations(), 2);
+        assert_eq!(
+            instance.call_function1("bump", &[]).unwrap().i32(),
+            Some(11)
+        );
+    }
+
+    #[test]
+    fn get_instance_uses_api_of_backend_for_pooled_instances() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(CacheOptions {
+                instance_pool_size: 1,
+                ..make_testing_options()
+            })
+            .unwrap();
+        let checksum = cache.save_wasm(&poolable_contract()).unwrap();
+
+        let instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert!(instance.api().canonical_address("foo").0.is_ok());
+        cache.recycle_instance(instance).unwrap();
+
+        let backend = Backend {
+            api: MockApi::new_failing("Temporarily unavailable"),
+            ..mock_backend(&[])
+        };
+        let instance = cache
+            .get_instance(&checksum, backend, testing_options())
+            .unwrap();
+        assert_eq!(cache.metrics_registry().instantiations(), 1);
+        match instance.api().canonical_address("foo").0.unwrap_err() {
+            BackendError::Unknown { msg } => assert_eq!(msg.unwrap(), "Temporarily unavailable"),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn get_instance_applies_max_stack_depth() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(CacheOptions {
+                instance_pool_size: 1,
+                ..make_testing_options()
+            })
+            .unwrap();
+        let checksum = cache.save_wasm(&poolable_contract()).unwrap();
+
+        let options = InstanceOptions {
+            max_stack_depth: Some(1000),
+            ..testing_options()
+        };
+        let instance = cache
+            .get_instance(&checksum, mock_backend(&[]), options)
+            .unwrap();
+        assert_eq!(instance.max_stack_depth(), 1000);
+        cache.recycle_instance(instance).unwrap();
+
+        // pooled instances use the limit of the new options
+        let instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(cache.metrics_registry().instantiations(), 1);
+        assert_eq!(instance.max_stack_depth(), DEFAULT_MAX_STACK_DEPTH);
+        cache.recycle_instance(instance).unwrap();
+
+        let instance = cache
+            .get_instance(&checksum, mock_backend(&[]), options)
+            .unwrap();
+        assert_eq!(instance.max_stack_depth(), 1000);
+    }
+
+    #[test]
+    fn instance_pool_is_bounded() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(CacheOptions {
+                instance_pool_size: 1,
+                ..make_testing_options()
+            })
+            .unwrap();
+        let metrics = cache.metrics_registry();
+        let checksum = cache.save_wasm(&poolable_contract()).unwrap();
+
+        let instance1 = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        let instance2 = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        cache.recycle_instance(instance1).unwrap();
+        cache.recycle_instance(instance2).unwrap();
+        assert_eq!(metrics.instantiations(), 2);
+
+        let _instance1 = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(metrics.instantiations(), 2);
+        let _instance2 = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(metrics.instantiations(), 3);
+    }
+
+    #[test]
+    fn instance_pool_can_be_disabled() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(make_testing_options()).unwrap();
+        let metrics = cache.metrics_registry();
+        let checksum = cache.save_wasm(&poolable_contract()).unwrap();
+
+        let instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert!(cache.recycle_instance(instance).is_some());
+        let _instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(metrics.instantiations(), 2);
+    }
+
+    #[test]
+    fn prune_modules_works() {
+        let options = make_stargate_testing_options();
+        let modules_path = options.base_dir.join(CACHE_DIR).join(MODULES_DIR);
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(options).unwrap();
+        let checksum1 = cache.save_wasm(CONTRACT).unwrap();
+        let checksum2 = cache.save_wasm(IBC_CONTRACT).unwrap();
+
+        // modules of an older version
+        let old_version = modules_path.join("v1");
+        create_dir_all(&old_version).unwrap();
+        std::fs::write(old_version.join(checksum1.to_hex()), [0u8; 42]).unwrap();
+
+        let keep = vec![checksum1].into_iter().collect();
+        let stats = cache.prune_modules(&keep).unwrap();
+        assert_eq!(stats.files, 1);
+        assert!(old_version.exists());
+
+        // Wasm is kept, such that pruned modules are re-compiled
+        let _instance = cache
+            .get_instance(&checksum1, mock_backend(&[]), testing_options())
+            .unwrap();
+        let _instance = cache
+            .get_instance(&checksum2, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(cache.stats().hits_fs_cache, 1);
+        assert_eq!(cache.stats().misses, 1);
+    }
+
+    #[test]
+    fn remove_stale_module_versions_works() {
+        let options = make_testing_options();
+        let modules_path = options.base_dir.join(CACHE_DIR).join(MODULES_DIR);
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(options).unwrap();
+        let checksum = cache.save_wasm(CONTRACT).unwrap();
+
+        // modules of an older version
+        let old_version = modules_path.join("v1");
+        create_dir_all(&old_version).unwrap();
+        std::fs::write(old_version.join(checksum.to_hex()), [0u8; 42]).unwrap();
+
+        let stats = cache.remove_stale_module_versions().unwrap();
+        assert_eq!(
+            stats,
+            RemovalStats {
+                files: 1,
+                bytes: 42
+            }
+        );
+        assert!(!old_version.exists());
+
+        // the module of the current version is kept
+        let _instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(cache.stats().hits_fs_cache, 1);
+        assert_eq!(cache.stats().misses, 0);
+    }
+
+    #[test]
+    fn get_instance_recompiles_corrupted_module() {
+        let options = make_testing_options();
+        let modules_path = options.base_dir.join(CACHE_DIR).join(MODULES_DIR);
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(options).unwrap();
+        let checksum = cache.save_wasm(CONTRACT).unwrap();
+
+        // flip a byte in the stored module
+        let version_dir = std::fs::read_dir(&modules_path)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let module_path = version_dir.join(checksum.to_hex());
+        let mut module = std::fs::read(&module_path).unwrap();
+        let last = module.len() - 1;
+        module[last] ^= 0xFF;
+        std::fs::write(&module_path, &module).unwrap();
+
+        let mut instance = cache
+            .get_instance(&checksum, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(cache.stats().hits_fs_cache, 0);
+        assert_eq!(cache.stats().misses, 1);
+
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        let res =
+            call_instantiate::<_, _, _, Empty, _>(&mut instance, &mock_env(), &info, msg).unwrap();
+        assert_eq!(res.unwrap().messages.len(), 0);
+
+        // the re-compiled module replaced the corrupted one
+        assert_ne!(std::fs::read(&module_path).unwrap(), module);
+    }
+
+    #[test]
+    fn pinned_checksums_works() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(make_testing_options()).unwrap();
+        assert_eq!(cache.pinned_checksums(), HashSet::new());
+
+        let checksum = cache.save_wasm(CONTRACT).unwrap();
+        cache.pin(&checksum).unwrap();
+        assert_eq!(cache.pinned_checksums(), HashSet::from_iter(vec![checksum]));
+
+        // failed pins are not recorded
+        let non_id = Checksum::generate(b"non_existent");
+        cache.pin(&non_id).unwrap_err();
+        assert_eq!(cache.pinned_checksums(), HashSet::from_iter(vec![checksum]));
+
+        cache.unpin(&checksum).unwrap();
+        assert_eq!(cache.pinned_checksums(), HashSet::new());
+
+        cache.pin(&checksum).unwrap();
+        cache.remove_wasm(&checksum).unwrap();
+        assert_eq!(cache.pinned_checksums(), HashSet::new());
+    }
+
+    #[test]
+    fn pins_are_restored_across_cache_instances() {
+        let tmp_dir = TempDir::new().unwrap();
+        let options = CacheOptions {
+            base_dir: tmp_dir.path().to_path_buf(),
+            ..make_stargate_testing_options()
+        };
+
+        let (checksum1, checksum2) = {
+            let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+                Cache::new(options.clone()).unwrap();
+            let checksum1 = cache.save_wasm(CONTRACT).unwrap();
+            let checksum2 = cache.save_wasm(IBC_CONTRACT).unwrap();
+            cache.pin(&checksum1).unwrap();
+            cache.pin(&checksum2).unwrap();
+            cache.unpin(&checksum2).unwrap();
+            (checksum1, checksum2)
+        };
+
+        let cache: Cache<MockApi, MockStorage, MockQuerier, WasmerInstance> =
+            Cache::new(options).unwrap();
+        assert_eq!(
+            cache.pinned_checksums(),
+            HashSet::from_iter(vec![checksum1])
+        );
+
+        // the module is warmed in the background
+        let mut waited = 0;
+        while cache.metrics().elements_pinned_memory_cache == 0 {
+            assert!(waited < 10_000, "Pinned module was not loaded in time");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            waited += 10;
+        }
+        assert_eq!(cache.metrics().elements_pinned_memory_cache, 1);
+
+        let _instance = cache
+            .get_instance(&checksum1, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(cache.stats().hits_pinned_memory_cache, 1);
+        assert_eq!(cache.stats().hits_fs_cache, 1);
+        assert_eq!(cache.stats().misses, 0);
+
+        let _instance = cache
+            .get_instance(&checksum2, mock_backend(&[]), testing_options())
+            .unwrap();
+        assert_eq!(cache.stats().hits_pinned_memory_cache, 1);
+    }
+
+    #[test]
+    fn load_pinned_checksums_ignores_invalid_lines() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join(PINNED_FILE);
+        assert_eq!(load_pinned_checksums(&path).unwrap(), HashSet::new());
+
+        let checksum = Checksum::generate(b"foo");
+        std::fs::write(&path, format!("{}\nnot hex\n\naabb\n", checksum)).unwrap();
+        assert_eq!(
+            load_pinned_checksums(&path).unwrap(),
+            HashSet::from_iter(vec![checksum])
+        );
+
+        save_pinned_checksums(&path, &HashSet::new()).unwrap();
+        assert_eq!(load_pinned_checksums(&path).unwrap(), HashSet::new());
+    }
 }
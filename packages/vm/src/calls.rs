@@ -587,12 +587,46 @@ where
 
 /// Calls a function with the given arguments.
 /// The exported function must return exactly one result (an offset to the result Region).
+///
+/// The gas used by the call is recorded in the instance's metrics registry, if any.
 pub(crate) fn call_raw<A, S, Q, W>(
     instance: &mut Instance<A, S, Q, W>,
     name: &str,
     args: &[&[u8]],
     result_max_length: usize,
 ) -> VmResult<Vec<u8>>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    W: WasmVM + 'static,
+{
+    if !instance.has_metrics() {
+        return call_with_regions(instance, name, args, result_max_length);
+    }
+    let gas_used_before = gas_used(instance);
+    let result = call_with_regions(instance, name, args, result_max_length);
+    instance.record_call(name, gas_used(instance).saturating_sub(gas_used_before));
+    result
+}
+
+fn gas_used<A, S, Q, W>(instance: &Instance<A, S, Q, W>) -> u64
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    W: WasmVM + 'static,
+{
+    let report = instance.create_gas_report();
+    report.limit.saturating_sub(report.remaining)
+}
+
+fn call_with_regions<A, S, Q, W>(
+    instance: &mut Instance<A, S, Q, W>,
+    name: &str,
+    args: &[&[u8]],
+    result_max_length: usize,
+) -> VmResult<Vec<u8>>
 where
     A: BackendApi + 'static,
     S: Storage + 'static,
@@ -616,8 +650,10 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::metrics::MetricsRegistry;
     use crate::testing::{mock_env, mock_info, mock_instance};
     use cosmwasm_std::{coins, Empty};
+    use std::sync::Arc;
 
     static CONTRACT: &[u8] = include_bytes!("../testdata/hackatom.wasm");
 
@@ -695,6 +731,33 @@ mod tests {
         assert_eq!(query_response.as_slice(), b"{\"verifier\":\"verifies\"}");
     }
 
+    #[test]
+    fn calls_are_recorded_in_metrics() {
+        let mut instance = mock_instance(CONTRACT, &[]);
+        let metrics = Arc::new(MetricsRegistry::new());
+        instance.set_metrics(metrics.clone());
+
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        let gas_before = instance.get_gas_left();
+        call_instantiate::<_, _, _, Empty, _>(&mut instance, &mock_env(), &info, msg)
+            .unwrap()
+            .unwrap();
+        let gas_used = gas_before - instance.get_gas_left();
+
+        let calls = metrics.calls("instantiate");
+        assert_eq!(calls.count, 1);
+        assert_eq!(calls.gas_used, gas_used);
+        assert_eq!(metrics.calls("query").count, 0);
+
+        let msg = br#"{"verifier":{}}"#;
+        call_query(&mut instance, &mock_env(), msg)
+            .unwrap()
+            .unwrap();
+        assert_eq!(metrics.calls("query").count, 1);
+        assert_eq!(metrics.calls("instantiate").count, 1);
+    }
+
     #[cfg(feature = "stargate")]
     mod ibc {
         use super::*;
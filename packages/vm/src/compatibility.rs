@@ -1,11 +1,14 @@
-use parity_wasm::elements::{External, ImportEntry, Module};
+use parity_wasm::elements::{External, ImportEntry, Module, Type};
+use serde::Serialize;
 use std::collections::BTreeSet;
 use std::collections::HashSet;
+use std::fmt;
 
 use crate::errors::{VmError, VmResult};
 use crate::features::required_features_from_module;
 use crate::limited::LimitedDisplay;
-use crate::static_analysis::{deserialize_wasm, ExportInfo};
+use crate::static_analysis::{deserialize_wasm, ExportInfo, REQUIRED_IBC_EXPORTS};
+use crate::wasm_backend::GLOBAL_EXPORT_PREFIX;
 
 /// Lists all imports we provide upon instantiating the instance in Instance::from_module()
 /// This should be updated when new imports are added
@@ -21,11 +24,48 @@ const SUPPORTED_IMPORTS: &[&str] = &[
     "env.ed25519_verify",
     "env.ed25519_batch_verify",
     "env.debug",
+    "env.abort",
     "env.query_chain",
     #[cfg(feature = "iterator")]
     "env.db_scan",
     #[cfg(feature = "iterator")]
     "env.db_next",
+    #[cfg(feature = "extended_storage")]
+    "env.db_read_many",
+    #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+    "env.db_next_key",
+    #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+    "env.db_next_value",
+    #[cfg(feature = "secp256r1")]
+    "env.secp256r1_verify",
+    #[cfg(feature = "secp256r1")]
+    "env.secp256r1_recover_pubkey",
+    #[cfg(feature = "hashes")]
+    "env.sha256",
+    #[cfg(feature = "hashes")]
+    "env.keccak256",
+    #[cfg(feature = "hashes")]
+    "env.ripemd160",
+    #[cfg(feature = "hashes")]
+    "env.blake2b",
+    #[cfg(feature = "bls12_381")]
+    "env.bls12_381_aggregate_g1",
+    #[cfg(feature = "bls12_381")]
+    "env.bls12_381_aggregate_g2",
+    #[cfg(feature = "bls12_381")]
+    "env.bls12_381_pairing_equality",
+    #[cfg(feature = "bls12_381")]
+    "env.bls12_381_aggregate_verify",
+    #[cfg(feature = "bls12_381")]
+    "env.bls12_381_hash_to_g1",
+    #[cfg(feature = "bls12_381")]
+    "env.bls12_381_hash_to_g2",
+    #[cfg(feature = "sr25519")]
+    "env.sr25519_verify",
+    #[cfg(feature = "secp256k1_schnorr")]
+    "env.secp256k1_schnorr_verify",
+    #[cfg(feature = "secp256k1_batch")]
+    "env.secp256k1_batch_verify",
 ];
 
 /// Lists all entry points we expect to be present when calling a contract.
@@ -40,112 +80,486 @@ const REQUIRED_EXPORTS: &[&str] = &[
     "instantiate",
 ];
 
+/// Optional entry points the VM calls in addition to `REQUIRED_EXPORTS` and `REQUIRED_IBC_EXPORTS`
+const OPTIONAL_EXPORTS: &[&str] = &["execute", "migrate", "sudo", "reply", "query"];
+
 const INTERFACE_VERSION_PREFIX: &str = "interface_version_";
 /// Only one version is supported right now. This could potentially turn into a list
 /// later, but maybe this never happens and new functionality is only added via features.
 const SUPPORTED_INTERFACE_VERSION: &str = "interface_version_8";
 
-const MEMORY_LIMIT: u32 = 512; // in pages
+const REQUIRES_PREFIX: &str = "requires_";
+
+/// Limits on the size and shape of contracts that are enforced by [`check_wasm`].
+/// Those allow rejecting contracts at upload that would result in huge artifacts
+/// or take very long to compile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WasmLimits {
+    /// The maximum initial size of the memory, in pages
+    pub initial_memory_pages: u32,
+    /// The maximum number of functions defined by the contract (not counting imports)
+    pub max_functions: usize,
+    /// The maximum number of parameters of a function type
+    pub max_function_params: usize,
+    /// The maximum initial size of a table, in elements
+    pub max_table_size: u32,
+    /// The maximum number of globals defined by the contract (not counting imports)
+    pub max_globals: usize,
+    /// The maximum number of data segments
+    pub max_data_segments: usize,
+    /// The maximum size of the Wasm bytecode, in bytes
+    pub max_code_size: usize,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        WasmLimits {
+            initial_memory_pages: 512,
+            max_functions: 20_000,
+            max_function_params: 100,
+            max_table_size: 2_500,
+            max_globals: 1_000,
+            max_data_segments: 1_000,
+            max_code_size: 3 * 1024 * 1024,
+        }
+    }
+}
+
+/// The part of a contract a violation was found in
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationCategory {
+    Deserialization,
+    CodeSize,
+    Memory,
+    Functions,
+    Tables,
+    Globals,
+    DataSegments,
+    InterfaceVersion,
+    Exports,
+    Imports,
+    Features,
+}
+
+impl fmt::Display for ViolationCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ViolationCategory::Deserialization => "deserialization",
+            ViolationCategory::CodeSize => "code_size",
+            ViolationCategory::Memory => "memory",
+            ViolationCategory::Functions => "functions",
+            ViolationCategory::Tables => "tables",
+            ViolationCategory::Globals => "globals",
+            ViolationCategory::DataSegments => "data_segments",
+            ViolationCategory::InterfaceVersion => "interface_version",
+            ViolationCategory::Exports => "exports",
+            ViolationCategory::Imports => "imports",
+            ViolationCategory::Features => "features",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The contract is rejected
+    Error,
+    /// The contract is accepted but probably does not work as intended
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// A single problem found by [`check_wasm_report`]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub category: ViolationCategory,
+    pub severity: Severity,
+    /// The message, which is the same as the one of the `StaticValidationErr` returned by [`check_wasm`]
+    pub message: String,
+    /// The names of the offending imports, exports or features, if any
+    pub names: Vec<String>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.severity, self.category, self.message)
+    }
+}
+
+/// All violations of a contract in the order of the checks
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// Returns true if the report contains no errors. Warnings are allowed.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Violation> {
+        self.violations
+            .iter()
+            .filter(|violation| violation.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Violation> {
+        self.violations
+            .iter()
+            .filter(|violation| violation.severity == Severity::Warning)
+    }
+
+    /// Converts the first error to a `StaticValidationErr`
+    pub fn into_result(self) -> VmResult<()> {
+        match self.errors().next() {
+            Some(error) => Err(VmError::static_validation_err(error.message.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn error(&mut self, category: ViolationCategory, message: impl Into<String>) {
+        self.error_with_names(category, message, Vec::new());
+    }
+
+    fn error_with_names(
+        &mut self,
+        category: ViolationCategory,
+        message: impl Into<String>,
+        names: Vec<String>,
+    ) {
+        self.violations.push(Violation {
+            category,
+            severity: Severity::Error,
+            message: message.into(),
+            names,
+        });
+    }
+
+    fn warning_with_names(
+        &mut self,
+        category: ViolationCategory,
+        message: impl Into<String>,
+        names: Vec<String>,
+    ) {
+        self.violations.push(Violation {
+            category,
+            severity: Severity::Warning,
+            message: message.into(),
+            names,
+        });
+    }
+
+    fn extend(&mut self, other: ValidationReport) {
+        self.violations.extend(other.violations);
+    }
+}
 
 /// Checks if the data is valid wasm and compatibility with the CosmWasm API (imports and exports)
+/// using the default [`WasmLimits`].
 pub fn check_wasm(wasm_code: &[u8], supported_features: &HashSet<String>) -> VmResult<()> {
-    let module = deserialize_wasm(wasm_code)?;
-    check_wasm_memories(&module)?;
-    check_interface_version(&module)?;
-    check_wasm_exports(&module)?;
-    check_wasm_imports(&module, SUPPORTED_IMPORTS)?;
-    check_wasm_features(&module, supported_features)?;
-    Ok(())
+    check_wasm_with_limits(wasm_code, supported_features, &WasmLimits::default())
 }
 
-fn check_wasm_memories(module: &Module) -> VmResult<()> {
+/// Like [`check_wasm`] but with custom limits
+pub fn check_wasm_with_limits(
+    wasm_code: &[u8],
+    supported_features: &HashSet<String>,
+    limits: &WasmLimits,
+) -> VmResult<()> {
+    check_wasm_report(wasm_code, supported_features, limits).into_result()
+}
+
+/// Runs all checks of [`check_wasm`] and reports every violation found instead of stopping
+/// at the first one. If the Wasm bytecode cannot be deserialized, no further checks are run.
+pub fn check_wasm_report(
+    wasm_code: &[u8],
+    supported_features: &HashSet<String>,
+    limits: &WasmLimits,
+) -> ValidationReport {
+    let mut report = check_wasm_code_size(wasm_code, limits);
+    let module = match deserialize_wasm(wasm_code) {
+        Ok(module) => module,
+        Err(err) => {
+            let message = match err {
+                VmError::StaticValidationErr { msg, .. } => msg,
+                err => err.to_string(),
+            };
+            report.error(ViolationCategory::Deserialization, message);
+            return report;
+        }
+    };
+    report.extend(check_wasm_memories(&module, limits));
+    report.extend(check_wasm_functions(&module, limits));
+    report.extend(check_wasm_tables(&module, limits));
+    report.extend(check_wasm_globals(&module, limits));
+    report.extend(check_wasm_data_segments(&module, limits));
+    report.extend(check_interface_version(&module));
+    report.extend(check_wasm_exports(&module));
+    report.extend(check_wasm_reserved_exports(&module));
+    report.extend(check_wasm_imports(&module, SUPPORTED_IMPORTS));
+    report.extend(check_wasm_features(&module, supported_features));
+    report.extend(check_wasm_unknown_exports(&module));
+    report
+}
+
+fn check_wasm_code_size(wasm_code: &[u8], limits: &WasmLimits) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    if wasm_code.len() > limits.max_code_size {
+        report.error(
+            ViolationCategory::CodeSize,
+            format!(
+                "Wasm contract size of {} bytes exceeds the limit of {} bytes.",
+                wasm_code.len(),
+                limits.max_code_size
+            ),
+        );
+    }
+    report
+}
+
+fn check_wasm_memories(module: &Module, wasm_limits: &WasmLimits) -> ValidationReport {
+    let mut report = ValidationReport::default();
     let section = match module.memory_section() {
         Some(section) => section,
         None => {
-            return Err(VmError::static_validation_err(
+            report.error(
+                ViolationCategory::Memory,
                 "Wasm contract doesn't have a memory section",
-            ));
+            );
+            return report;
         }
     };
 
     let memories = section.entries();
     if memories.len() != 1 {
-        return Err(VmError::static_validation_err(
+        report.error(
+            ViolationCategory::Memory,
             "Wasm contract must contain exactly one memory",
-        ));
+        );
+        return report;
     }
 
     let memory = memories[0];
     // println!("Memory: {:?}", memory);
     let limits = memory.limits();
 
-    if limits.initial() > MEMORY_LIMIT {
-        return Err(VmError::static_validation_err(format!(
-            "Wasm contract memory's minimum must not exceed {} pages.",
-            MEMORY_LIMIT
-        )));
+    if limits.initial() > wasm_limits.initial_memory_pages {
+        report.error(
+            ViolationCategory::Memory,
+            format!(
+                "Wasm contract memory's minimum must not exceed {} pages.",
+                wasm_limits.initial_memory_pages
+            ),
+        );
     }
 
     if limits.maximum() != None {
-        return Err(VmError::static_validation_err(
+        report.error(
+            ViolationCategory::Memory,
             "Wasm contract memory's maximum must be unset. The host will set it for you.",
-        ));
+        );
     }
-    Ok(())
+    report
 }
 
-fn check_interface_version(module: &Module) -> VmResult<()> {
-    let mut interface_version_exports = module
-        .exported_function_names(Some(INTERFACE_VERSION_PREFIX))
-        .into_iter();
-    if let Some(first_interface_version_export) = interface_version_exports.next() {
-        if interface_version_exports.next().is_some() {
-            Err(VmError::static_validation_err(
-                "Wasm contract contains more than one marker export: interface_version_*",
-            ))
-        } else {
-            // Exactly one interface version found
-
-            match first_interface_version_export.as_str() {
-                // Ok
-                SUPPORTED_INTERFACE_VERSION => Ok(()),
-                // Well known old versions for better error messages
-                "interface_version_6" => Err(VmError::static_validation_err(
-                    "Wasm contract has incompatible CosmWasm 0.15 marker export interface_version_6 (see https://github.com/CosmWasm/cosmwasm/blob/main/packages/vm/README.md)"
-                )),
-                "interface_version_5" => Err(VmError::static_validation_err(
-                    "Wasm contract has incompatible CosmWasm 0.14 marker export interface_version_5 (see https://github.com/CosmWasm/cosmwasm/blob/main/packages/vm/README.md)"
-                )),
-                // Unknown version
-                _ => Err(VmError::static_validation_err(
-                    "Wasm contract has unknown interface_version_* marker export (see https://github.com/CosmWasm/cosmwasm/blob/main/packages/vm/README.md)",
-                )),
+fn check_wasm_functions(module: &Module, limits: &WasmLimits) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let functions = module.function_section().map_or(0, |s| s.entries().len());
+    if functions > limits.max_functions {
+        report.error(
+            ViolationCategory::Functions,
+            format!(
+                "Wasm contract defines {} functions, which exceeds the limit of {}.",
+                functions, limits.max_functions
+            ),
+        );
+    }
+
+    if let Some(section) = module.type_section() {
+        for Type::Function(function_type) in section.types() {
+            let params = function_type.params().len();
+            if params > limits.max_function_params {
+                report.error(
+                    ViolationCategory::Functions,
+                    format!(
+                        "Wasm contract contains a function type with {} parameters, which exceeds the limit of {}.",
+                        params, limits.max_function_params
+                    ),
+                );
+                // One violation is enough to point the developer to the problem
+                break;
             }
         }
-    } else {
-        Err(VmError::static_validation_err(
-            "Wasm contract missing a required marker export: interface_version_*",
-        ))
     }
+    report
 }
 
-fn check_wasm_exports(module: &Module) -> VmResult<()> {
+fn check_wasm_tables(module: &Module, limits: &WasmLimits) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    if let Some(section) = module.table_section() {
+        for table in section.entries() {
+            if table.limits().initial() > limits.max_table_size {
+                report.error(
+                    ViolationCategory::Tables,
+                    format!(
+                        "Wasm contract table's minimum must not exceed {} elements.",
+                        limits.max_table_size
+                    ),
+                );
+            }
+        }
+    }
+    report
+}
+
+fn check_wasm_globals(module: &Module, limits: &WasmLimits) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let globals = module.global_section().map_or(0, |s| s.entries().len());
+    if globals > limits.max_globals {
+        report.error(
+            ViolationCategory::Globals,
+            format!(
+                "Wasm contract defines {} globals, which exceeds the limit of {}.",
+                globals, limits.max_globals
+            ),
+        );
+    }
+    report
+}
+
+fn check_wasm_data_segments(module: &Module, limits: &WasmLimits) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let segments = module.data_section().map_or(0, |s| s.entries().len());
+    if segments > limits.max_data_segments {
+        report.error(
+            ViolationCategory::DataSegments,
+            format!(
+                "Wasm contract contains {} data segments, which exceeds the limit of {}.",
+                segments, limits.max_data_segments
+            ),
+        );
+    }
+    report
+}
+
+fn check_interface_version(module: &Module) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let mut interface_version_exports: Vec<String> = module
+        .exported_function_names(Some(INTERFACE_VERSION_PREFIX))
+        .into_iter()
+        .collect();
+    interface_version_exports.sort();
+
+    let message = match interface_version_exports.as_slice() {
+        [] => "Wasm contract missing a required marker export: interface_version_*",
+        [version] => match version.as_str() {
+            // Ok
+            SUPPORTED_INTERFACE_VERSION => return report,
+            // Well known old versions for better error messages
+            "interface_version_6" => "Wasm contract has incompatible CosmWasm 0.15 marker export interface_version_6 (see https://github.com/CosmWasm/cosmwasm/blob/main/packages/vm/README.md)",
+            "interface_version_5" => "Wasm contract has incompatible CosmWasm 0.14 marker export interface_version_5 (see https://github.com/CosmWasm/cosmwasm/blob/main/packages/vm/README.md)",
+            // Unknown version
+            _ => "Wasm contract has unknown interface_version_* marker export (see https://github.com/CosmWasm/cosmwasm/blob/main/packages/vm/README.md)",
+        },
+        _ => "Wasm contract contains more than one marker export: interface_version_*",
+    };
+    report.error_with_names(
+        ViolationCategory::InterfaceVersion,
+        message,
+        interface_version_exports,
+    );
+    report
+}
+
+fn check_wasm_exports(module: &Module) -> ValidationReport {
+    let mut report = ValidationReport::default();
     let available_exports: HashSet<String> = module.exported_function_names(None);
     for required_export in REQUIRED_EXPORTS {
         if !available_exports.contains(*required_export) {
-            return Err(VmError::static_validation_err(format!(
-                "Wasm contract doesn't have required export: \"{}\". Exports required by VM: {:?}.",
-                required_export, REQUIRED_EXPORTS
-            )));
+            report.error_with_names(
+                ViolationCategory::Exports,
+                format!(
+                    "Wasm contract doesn't have required export: \"{}\". Exports required by VM: {:?}.",
+                    required_export, REQUIRED_EXPORTS
+                ),
+                vec![required_export.to_string()],
+            );
         }
     }
-    Ok(())
+    report
+}
+
+/// Rejects exports using the prefix of the exports the VM adds for mutable globals,
+/// since those would clash when the module is compiled.
+fn check_wasm_reserved_exports(module: &Module) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let reserved: BTreeSet<String> = module
+        .export_section()
+        .map(|section| section.entries())
+        .unwrap_or_default()
+        .iter()
+        .map(|export| export.field())
+        .filter(|name| name.starts_with(GLOBAL_EXPORT_PREFIX))
+        .map(|name| name.to_string())
+        .collect();
+    if !reserved.is_empty() {
+        report.error_with_names(
+            ViolationCategory::Exports,
+            format!(
+                "Wasm contract exports names with the reserved prefix \"{}\": {}",
+                GLOBAL_EXPORT_PREFIX,
+                reserved.to_string_limited(200)
+            ),
+            reserved.into_iter().collect(),
+        );
+    }
+    report
+}
+
+/// Warns about exported functions the VM never calls. Those are usually leftovers
+/// or entry points with a typo in the name.
+fn check_wasm_unknown_exports(module: &Module) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let unknown: BTreeSet<String> = module
+        .exported_function_names(None)
+        .into_iter()
+        .filter(|name| {
+            !REQUIRED_EXPORTS.contains(&name.as_str())
+                && !OPTIONAL_EXPORTS.contains(&name.as_str())
+                && !REQUIRED_IBC_EXPORTS.contains(&name.as_str())
+                && !name.starts_with(INTERFACE_VERSION_PREFIX)
+                && !name.starts_with(REQUIRES_PREFIX)
+        })
+        .collect();
+    if !unknown.is_empty() {
+        report.warning_with_names(
+            ViolationCategory::Exports,
+            format!(
+                "Wasm contract exports functions that are never called by the VM: {}",
+                unknown.to_string_limited(200)
+            ),
+            unknown.into_iter().collect(),
+        );
+    }
+    report
 }
 
 /// Checks if the import requirements of the contract are satisfied.
 /// When this is not the case, we either have an incompatibility between contract and VM
 /// or a error in the contract.
-fn check_wasm_imports(module: &Module, supported_imports: &[&str]) -> VmResult<()> {
+fn check_wasm_imports(module: &Module, supported_imports: &[&str]) -> ValidationReport {
+    let mut report = ValidationReport::default();
     let required_imports: Vec<ImportEntry> = module
         .import_section()
         .map_or(vec![], |import_section| import_section.entries().to_vec());
@@ -155,38 +569,52 @@ fn check_wasm_imports(module: &Module, supported_imports: &[&str]) -> VmResult<(
     for required_import in required_imports {
         let full_name = full_import_name(&required_import);
         if !supported_imports.contains(&full_name.as_str()) {
-            return Err(VmError::static_validation_err(format!(
-                "Wasm contract requires unsupported import: \"{}\". Required imports: {}. Available imports: {:?}.",
-                full_name, required_import_names.to_string_limited(200), supported_imports
-            )));
+            report.error_with_names(
+                ViolationCategory::Imports,
+                format!(
+                    "Wasm contract requires unsupported import: \"{}\". Required imports: {}. Available imports: {:?}.",
+                    full_name, required_import_names.to_string_limited(200), supported_imports
+                ),
+                vec![full_name],
+            );
+            continue;
         }
 
         match required_import.external() {
             External::Function(_) => {}, // ok
-            _ => return Err(VmError::static_validation_err(format!(
-                "Wasm contract requires non-function import: \"{}\". Right now, all supported imports are functions.",
-                full_name
-            ))),
+            _ => report.error_with_names(
+                ViolationCategory::Imports,
+                format!(
+                    "Wasm contract requires non-function import: \"{}\". Right now, all supported imports are functions.",
+                    full_name
+                ),
+                vec![full_name],
+            ),
         };
     }
-    Ok(())
+    report
 }
 
 fn full_import_name(ie: &ImportEntry) -> String {
     format!("{}.{}", ie.module(), ie.field())
 }
 
-fn check_wasm_features(module: &Module, supported_features: &HashSet<String>) -> VmResult<()> {
+fn check_wasm_features(module: &Module, supported_features: &HashSet<String>) -> ValidationReport {
+    let mut report = ValidationReport::default();
     let required_features = required_features_from_module(module);
     if !required_features.is_subset(supported_features) {
         // We switch to BTreeSet to get a sorted error message
         let unsupported: BTreeSet<_> = required_features.difference(supported_features).collect();
-        return Err(VmError::static_validation_err(format!(
-            "Wasm contract requires unsupported features: {}",
-            unsupported.to_string_limited(200)
-        )));
+        report.error_with_names(
+            ViolationCategory::Features,
+            format!(
+                "Wasm contract requires unsupported features: {}",
+                unsupported.to_string_limited(200)
+            ),
+            unsupported.into_iter().cloned().collect(),
+        );
     }
-    Ok(())
+    report
 }
 
 #[cfg(test)]
@@ -252,13 +680,17 @@ mod tests {
     #[test]
     fn check_wasm_memories_ok() {
         let wasm = wat::parse_str("(module (memory 1))").unwrap();
-        check_wasm_memories(&deserialize_wasm(&wasm).unwrap()).unwrap()
+        check_wasm_memories(&deserialize_wasm(&wasm).unwrap(), &WasmLimits::default())
+            .into_result()
+            .unwrap()
     }
 
     #[test]
     fn check_wasm_memories_no_memory() {
         let wasm = wat::parse_str("(module)").unwrap();
-        match check_wasm_memories(&deserialize_wasm(&wasm).unwrap()) {
+        match check_wasm_memories(&deserialize_wasm(&wasm).unwrap(), &WasmLimits::default())
+            .into_result()
+        {
             Err(VmError::StaticValidationErr { msg, .. }) => {
                 assert!(msg.starts_with("Wasm contract doesn't have a memory section"));
             }
@@ -282,7 +714,9 @@ mod tests {
         ))
         .unwrap();
 
-        match check_wasm_memories(&deserialize_wasm(&wasm).unwrap()) {
+        match check_wasm_memories(&deserialize_wasm(&wasm).unwrap(), &WasmLimits::default())
+            .into_result()
+        {
             Err(VmError::StaticValidationErr { msg, .. }) => {
                 assert!(msg.starts_with("Wasm contract must contain exactly one memory"));
             }
@@ -303,7 +737,9 @@ mod tests {
         ))
         .unwrap();
 
-        match check_wasm_memories(&deserialize_wasm(&wasm).unwrap()) {
+        match check_wasm_memories(&deserialize_wasm(&wasm).unwrap(), &WasmLimits::default())
+            .into_result()
+        {
             Err(VmError::StaticValidationErr { msg, .. }) => {
                 assert!(msg.starts_with("Wasm contract must contain exactly one memory"));
             }
@@ -315,10 +751,17 @@ mod tests {
     #[test]
     fn check_wasm_memories_initial_size() {
         let wasm_ok = wat::parse_str("(module (memory 512))").unwrap();
-        check_wasm_memories(&deserialize_wasm(&wasm_ok).unwrap()).unwrap();
+        check_wasm_memories(&deserialize_wasm(&wasm_ok).unwrap(), &WasmLimits::default())
+            .into_result()
+            .unwrap();
 
         let wasm_too_big = wat::parse_str("(module (memory 513))").unwrap();
-        match check_wasm_memories(&deserialize_wasm(&wasm_too_big).unwrap()) {
+        match check_wasm_memories(
+            &deserialize_wasm(&wasm_too_big).unwrap(),
+            &WasmLimits::default(),
+        )
+        .into_result()
+        {
             Err(VmError::StaticValidationErr { msg, .. }) => {
                 assert!(msg.starts_with("Wasm contract memory's minimum must not exceed 512 pages"));
             }
@@ -330,7 +773,12 @@ mod tests {
     #[test]
     fn check_wasm_memories_maximum_size() {
         let wasm_max = wat::parse_str("(module (memory 1 5))").unwrap();
-        match check_wasm_memories(&deserialize_wasm(&wasm_max).unwrap()) {
+        match check_wasm_memories(
+            &deserialize_wasm(&wasm_max).unwrap(),
+            &WasmLimits::default(),
+        )
+        .into_result()
+        {
             Err(VmError::StaticValidationErr { msg, .. }) => {
                 assert!(msg.starts_with("Wasm contract memory's maximum must be unset"));
             }
@@ -339,6 +787,255 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_wasm_report_passes_for_latest_contract() {
+        let report = check_wasm_report(CONTRACT, &default_features(), &WasmLimits::default());
+        assert!(report.is_valid());
+        assert_eq!(report.errors().count(), 0);
+    }
+
+    #[test]
+    fn check_wasm_report_lists_all_violations() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (import "env" "foo" (func (param i32) (result i32)))
+                (import "env" "db_read" (func (param i32) (result i32)))
+                (import "env" "bar" (func (param i32) (result i32)))
+                (type (func))
+                (func (type 0) nop)
+                (memory 3)
+                (export "allocate" (func 3))
+                (export "requires_sun" (func 3))
+                (export "add_one" (func 3))
+            )"#,
+        )
+        .unwrap();
+        let report = check_wasm_report(&wasm, &default_features(), &WasmLimits::default());
+        assert!(!report.is_valid());
+        let errors: Vec<(ViolationCategory, Vec<String>)> = report
+            .errors()
+            .map(|violation| (violation.category, violation.names.clone()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (ViolationCategory::InterfaceVersion, vec![]),
+                (ViolationCategory::Exports, vec!["deallocate".to_string()]),
+                (ViolationCategory::Exports, vec!["instantiate".to_string()]),
+                (ViolationCategory::Imports, vec!["env.foo".to_string()]),
+                (ViolationCategory::Imports, vec!["env.bar".to_string()]),
+                (ViolationCategory::Features, vec!["sun".to_string()]),
+            ]
+        );
+        let warnings: Vec<&Violation> = report.warnings().collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].category, ViolationCategory::Exports);
+        assert_eq!(warnings[0].names, vec!["add_one".to_string()]);
+
+        // the fail-fast variant returns the first error
+        match report.into_result().unwrap_err() {
+            VmError::StaticValidationErr { msg, .. } => assert_eq!(
+                msg,
+                "Wasm contract missing a required marker export: interface_version_*"
+            ),
+            e => panic!("Unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn check_wasm_report_stops_for_invalid_bytecode() {
+        let report = check_wasm_report(
+            b"not wasm",
+            &default_features(),
+            &WasmLimits {
+                max_code_size: 4,
+                ..WasmLimits::default()
+            },
+        );
+        let categories: Vec<ViolationCategory> = report
+            .violations
+            .iter()
+            .map(|violation| violation.category)
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                ViolationCategory::CodeSize,
+                ViolationCategory::Deserialization
+            ]
+        );
+        assert!(report.violations[1]
+            .message
+            .starts_with("Wasm bytecode could not be deserialized."));
+    }
+
+    #[test]
+    fn validation_report_serializes_to_json() {
+        let report = ValidationReport {
+            violations: vec![Violation {
+                category: ViolationCategory::Features,
+                severity: Severity::Error,
+                message: "Wasm contract requires unsupported features: {\"sun\"}".to_string(),
+                names: vec!["sun".to_string()],
+            }],
+        };
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"violations":[{"category":"features","severity":"error","message":"Wasm contract requires unsupported features: {\"sun\"}","names":["sun"]}]}"#
+        );
+        assert_eq!(
+            report.violations[0].to_string(),
+            "error (features): Wasm contract requires unsupported features: {\"sun\"}"
+        );
+    }
+
+    #[test]
+    fn check_wasm_with_limits_works() {
+        let limits = WasmLimits {
+            max_code_size: 1000,
+            ..WasmLimits::default()
+        };
+        match check_wasm_with_limits(CONTRACT, &default_features(), &limits) {
+            Err(VmError::StaticValidationErr { msg, .. }) => assert_eq!(
+                msg,
+                format!(
+                    "Wasm contract size of {} bytes exceeds the limit of 1000 bytes.",
+                    CONTRACT.len()
+                )
+            ),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("This must not succeed"),
+        };
+
+        let limits = WasmLimits {
+            max_code_size: CONTRACT.len(),
+            ..WasmLimits::default()
+        };
+        check_wasm_with_limits(CONTRACT, &default_features(), &limits).unwrap();
+    }
+
+    #[test]
+    fn check_wasm_functions_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (type (func (param i32 i32 i32)))
+                (func (type 0) nop)
+                (func (type 0) nop)
+            )"#,
+        )
+        .unwrap();
+        let module = deserialize_wasm(&wasm).unwrap();
+        check_wasm_functions(&module, &WasmLimits::default())
+            .into_result()
+            .unwrap();
+
+        let limits = WasmLimits {
+            max_functions: 1,
+            ..WasmLimits::default()
+        };
+        match check_wasm_functions(&module, &limits).into_result() {
+            Err(VmError::StaticValidationErr { msg, .. }) => assert_eq!(
+                msg,
+                "Wasm contract defines 2 functions, which exceeds the limit of 1."
+            ),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with too many functions"),
+        }
+
+        let limits = WasmLimits {
+            max_function_params: 2,
+            ..WasmLimits::default()
+        };
+        match check_wasm_functions(&module, &limits).into_result() {
+            Err(VmError::StaticValidationErr { msg, .. }) => assert_eq!(
+                msg,
+                "Wasm contract contains a function type with 3 parameters, which exceeds the limit of 2."
+            ),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with too many function parameters"),
+        }
+    }
+
+    #[test]
+    fn check_wasm_tables_works() {
+        let wasm_ok = wat::parse_str("(module (table 2500 funcref))").unwrap();
+        check_wasm_tables(&deserialize_wasm(&wasm_ok).unwrap(), &WasmLimits::default())
+            .into_result()
+            .unwrap();
+
+        let wasm_too_big = wat::parse_str("(module (table 2501 funcref))").unwrap();
+        match check_wasm_tables(
+            &deserialize_wasm(&wasm_too_big).unwrap(),
+            &WasmLimits::default(),
+        )
+        .into_result()
+        {
+            Err(VmError::StaticValidationErr { msg, .. }) => assert_eq!(
+                msg,
+                "Wasm contract table's minimum must not exceed 2500 elements."
+            ),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with too big table"),
+        }
+    }
+
+    #[test]
+    fn check_wasm_globals_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (global (mut i32) (i32.const 1))
+                (global i64 (i64.const 2))
+            )"#,
+        )
+        .unwrap();
+        let module = deserialize_wasm(&wasm).unwrap();
+        check_wasm_globals(&module, &WasmLimits::default())
+            .into_result()
+            .unwrap();
+
+        let limits = WasmLimits {
+            max_globals: 1,
+            ..WasmLimits::default()
+        };
+        match check_wasm_globals(&module, &limits).into_result() {
+            Err(VmError::StaticValidationErr { msg, .. }) => assert_eq!(
+                msg,
+                "Wasm contract defines 2 globals, which exceeds the limit of 1."
+            ),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with too many globals"),
+        }
+    }
+
+    #[test]
+    fn check_wasm_data_segments_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (memory 1)
+                (data (i32.const 0) "foo")
+                (data (i32.const 16) "bar")
+            )"#,
+        )
+        .unwrap();
+        let module = deserialize_wasm(&wasm).unwrap();
+        check_wasm_data_segments(&module, &WasmLimits::default())
+            .into_result()
+            .unwrap();
+
+        let limits = WasmLimits {
+            max_data_segments: 1,
+            ..WasmLimits::default()
+        };
+        match check_wasm_data_segments(&module, &limits).into_result() {
+            Err(VmError::StaticValidationErr { msg, .. }) => assert_eq!(
+                msg,
+                "Wasm contract contains 2 data segments, which exceeds the limit of 1."
+            ),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with too many data segments"),
+        }
+    }
+
     #[test]
     fn check_interface_version_works() {
         // valid
@@ -355,7 +1052,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        check_interface_version(&module).unwrap();
+        check_interface_version(&module).into_result().unwrap();
 
         // missing
         let wasm = wat::parse_str(
@@ -370,7 +1067,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        match check_interface_version(&module).unwrap_err() {
+        match check_interface_version(&module).into_result().unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 assert_eq!(
                     msg,
@@ -395,7 +1092,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        match check_interface_version(&module).unwrap_err() {
+        match check_interface_version(&module).into_result().unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 assert_eq!(
                     msg,
@@ -419,7 +1116,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        match check_interface_version(&module).unwrap_err() {
+        match check_interface_version(&module).into_result().unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 assert_eq!(msg, "Wasm contract has incompatible CosmWasm 0.15 marker export interface_version_6 (see https://github.com/CosmWasm/cosmwasm/blob/main/packages/vm/README.md)");
             }
@@ -440,7 +1137,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        match check_interface_version(&module).unwrap_err() {
+        match check_interface_version(&module).into_result().unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 assert_eq!(msg, "Wasm contract has unknown interface_version_* marker export (see https://github.com/CosmWasm/cosmwasm/blob/main/packages/vm/README.md)");
             }
@@ -463,7 +1160,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        check_wasm_exports(&module).unwrap();
+        check_wasm_exports(&module).into_result().unwrap();
 
         // this is invalid, as it doesn't any required export
         let wasm = wat::parse_str(
@@ -475,7 +1172,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        match check_wasm_exports(&module) {
+        match check_wasm_exports(&module).into_result() {
             Err(VmError::StaticValidationErr { msg, .. }) => {
                 assert!(msg.starts_with("Wasm contract doesn't have required export: \"allocate\""));
             }
@@ -494,7 +1191,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        match check_wasm_exports(&module) {
+        match check_wasm_exports(&module).into_result() {
             Err(VmError::StaticValidationErr { msg, .. }) => {
                 assert!(
                     msg.starts_with("Wasm contract doesn't have required export: \"deallocate\"")
@@ -508,7 +1205,7 @@ mod tests {
     #[test]
     fn check_wasm_exports_of_old_contract() {
         let module = deserialize_wasm(CONTRACT_0_7).unwrap();
-        match check_wasm_exports(&module) {
+        match check_wasm_exports(&module).into_result() {
             Err(VmError::StaticValidationErr { msg, .. }) => {
                 assert!(
                     msg.starts_with("Wasm contract doesn't have required export: \"instantiate\"")
@@ -519,6 +1216,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_wasm_reserved_exports_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (type (func))
+                (func (type 0) nop)
+                (global (mut i32) (i32.const 0))
+                (export "instantiate" (func 0))
+                (export "stack_pointer" (global 0))
+            )"#,
+        )
+        .unwrap();
+        let module = deserialize_wasm(&wasm).unwrap();
+        check_wasm_reserved_exports(&module).into_result().unwrap();
+
+        let wasm = wat::parse_str(
+            r#"(module
+                (type (func))
+                (func (type 0) nop)
+                (global (mut i32) (i32.const 0))
+                (export "instantiate" (func 0))
+                (export "cosmwasm_global_0" (global 0))
+            )"#,
+        )
+        .unwrap();
+        let module = deserialize_wasm(&wasm).unwrap();
+        match check_wasm_reserved_exports(&module).into_result() {
+            Err(VmError::StaticValidationErr { msg, .. }) => {
+                assert_eq!(
+                    msg,
+                    "Wasm contract exports names with the reserved prefix \"cosmwasm_global_\": {\"cosmwasm_global_0\"}"
+                );
+            }
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with reserved export"),
+        }
+    }
+
     #[test]
     fn check_wasm_imports_ok() {
         let wasm = wat::parse_str(
@@ -536,7 +1271,9 @@ mod tests {
         )"#,
         )
         .unwrap();
-        check_wasm_imports(&deserialize_wasm(&wasm).unwrap(), SUPPORTED_IMPORTS).unwrap();
+        check_wasm_imports(&deserialize_wasm(&wasm).unwrap(), SUPPORTED_IMPORTS)
+            .into_result()
+            .unwrap();
     }
 
     #[test]
@@ -567,7 +1304,8 @@ mod tests {
             "env.debug",
             "env.query_chain",
         ];
-        let result = check_wasm_imports(&deserialize_wasm(&wasm).unwrap(), supported_imports);
+        let result =
+            check_wasm_imports(&deserialize_wasm(&wasm).unwrap(), supported_imports).into_result();
         match result.unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 println!("{}", msg);
@@ -583,7 +1321,7 @@ mod tests {
     #[test]
     fn check_wasm_imports_of_old_contract() {
         let module = deserialize_wasm(CONTRACT_0_7).unwrap();
-        let result = check_wasm_imports(&module, SUPPORTED_IMPORTS);
+        let result = check_wasm_imports(&module, SUPPORTED_IMPORTS).into_result();
         match result.unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 assert!(
@@ -597,7 +1335,8 @@ mod tests {
     #[test]
     fn check_wasm_imports_wrong_type() {
         let wasm = wat::parse_str(r#"(module (import "env" "db_read" (memory 1 1)))"#).unwrap();
-        let result = check_wasm_imports(&deserialize_wasm(&wasm).unwrap(), SUPPORTED_IMPORTS);
+        let result =
+            check_wasm_imports(&deserialize_wasm(&wasm).unwrap(), SUPPORTED_IMPORTS).into_result();
         match result.unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 assert!(
@@ -633,7 +1372,9 @@ mod tests {
         .iter()
         .cloned()
         .collect();
-        check_wasm_features(&module, &supported).unwrap();
+        check_wasm_features(&module, &supported)
+            .into_result()
+            .unwrap();
     }
 
     #[test]
@@ -662,7 +1403,10 @@ mod tests {
         .iter()
         .cloned()
         .collect();
-        match check_wasm_features(&module, &supported).unwrap_err() {
+        match check_wasm_features(&module, &supported)
+            .into_result()
+            .unwrap_err()
+        {
             VmError::StaticValidationErr { msg, .. } => assert_eq!(
                 msg,
                 "Wasm contract requires unsupported features: {\"sun\"}"
@@ -679,7 +1423,10 @@ mod tests {
         .iter()
         .cloned()
         .collect();
-        match check_wasm_features(&module, &supported).unwrap_err() {
+        match check_wasm_features(&module, &supported)
+            .into_result()
+            .unwrap_err()
+        {
             VmError::StaticValidationErr { msg, .. } => assert_eq!(
                 msg,
                 "Wasm contract requires unsupported features: {\"sun\", \"water\"}"
@@ -689,7 +1436,10 @@ mod tests {
 
         // Support set 3
         let supported = ["freedom".to_string()].iter().cloned().collect();
-        match check_wasm_features(&module, &supported).unwrap_err() {
+        match check_wasm_features(&module, &supported)
+            .into_result()
+            .unwrap_err()
+        {
             VmError::StaticValidationErr { msg, .. } => assert_eq!(
                 msg,
                 "Wasm contract requires unsupported features: {\"nutrients\", \"sun\", \"water\"}"
@@ -699,7 +1449,10 @@ mod tests {
 
         // Support set 4
         let supported = [].iter().cloned().collect();
-        match check_wasm_features(&module, &supported).unwrap_err() {
+        match check_wasm_features(&module, &supported)
+            .into_result()
+            .unwrap_err()
+        {
             VmError::StaticValidationErr { msg, .. } => assert_eq!(
                 msg,
                 "Wasm contract requires unsupported features: {\"nutrients\", \"sun\", \"water\"}"
@@ -8,6 +8,7 @@ use wasmer_middlewares::metering::{get_remaining_points, set_remaining_points, M
 
 use crate::backend::{BackendApi, GasInfo, Querier, Storage};
 use crate::errors::{VmError, VmResult};
+use crate::tracer::{NoopTracer, Tracer};
 use crate::WasmVM;
 
 /// Never can never be instantiated.
@@ -17,19 +18,82 @@ pub enum Never {}
 
 /** gas config data */
 
-#[derive(Clone, PartialEq, Debug)]
+/// Linear gas cost model where the cost is linear in the number of items (e.g. bytes)
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LinearGasCost {
+    /// The flat part of the cost, charged once per call
+    pub base: u64,
+    /// The cost per item (e.g. per byte of input)
+    pub per_item: u64,
+}
+
+impl LinearGasCost {
+    pub fn total_cost(&self, items: u64) -> u64 {
+        self.base
+            .saturating_add(self.per_item.saturating_mul(items))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct GasConfig {
     /// Gas costs of VM (not Backend) provided functionality
     /// secp256k1 signature verification cost
     pub secp256k1_verify_cost: u64,
+    /// secp256k1 batch signature verification cost, per signature
+    pub secp256k1_batch_verify_cost: u64,
+    /// secp256k1 batch signature verification cost (single public key), per signature
+    pub secp256k1_batch_verify_one_pubkey_cost: u64,
     /// secp256k1 public key recovery cost
     pub secp256k1_recover_pubkey_cost: u64,
+    /// secp256r1 signature verification cost
+    pub secp256r1_verify_cost: u64,
+    /// secp256r1 public key recovery cost
+    pub secp256r1_recover_pubkey_cost: u64,
     /// ed25519 signature verification cost
     pub ed25519_verify_cost: u64,
     /// ed25519 batch signature verification cost
     pub ed25519_batch_verify_cost: u64,
     /// ed25519 batch signature verification cost (single public key)
     pub ed25519_batch_verify_one_pubkey_cost: u64,
+    /// sr25519 signature verification cost
+    pub sr25519_verify_cost: u64,
+    /// secp256k1 BIP-340 Schnorr signature verification cost
+    pub secp256k1_schnorr_verify_cost: u64,
+    /// address validation cost, per byte of the human readable input address
+    pub addr_validate_cost: LinearGasCost,
+    /// address canonicalization cost, per byte of the human readable input address
+    pub addr_canonicalize_cost: LinearGasCost,
+    /// address humanization cost, per byte of the canonical input address
+    pub addr_humanize_cost: LinearGasCost,
+    /// debug message cost, per byte of the message.
+    /// This is charged no matter if debug printing is enabled or not.
+    pub debug_cost: LinearGasCost,
+    /// iterator creation cost, per byte of the start and end keys
+    pub db_scan_cost: LinearGasCost,
+    /// iterator step cost, per byte of the returned key and value
+    pub db_next_cost: LinearGasCost,
+    /// db_read_many cost, per byte of the encoded keys. Reading the values is charged by the storage.
+    pub db_read_many_cost: LinearGasCost,
+    /// SHA-256 hashing cost, per byte of the input
+    pub sha256_cost: LinearGasCost,
+    /// Keccak-256 hashing cost, per byte of the input
+    pub keccak256_cost: LinearGasCost,
+    /// RIPEMD-160 hashing cost, per byte of the input
+    pub ripemd160_cost: LinearGasCost,
+    /// BLAKE2b hashing cost, per byte of the input
+    pub blake2b_cost: LinearGasCost,
+    /// BLS12-381 G1 aggregation cost, per point
+    pub bls12_381_aggregate_g1_cost: LinearGasCost,
+    /// BLS12-381 G2 aggregation cost, per point
+    pub bls12_381_aggregate_g2_cost: LinearGasCost,
+    /// BLS12-381 pairing equality check cost, per pairing on the left hand side
+    pub bls12_381_pairing_equality_cost: LinearGasCost,
+    /// BLS12-381 aggregate signature verification cost, per public key / message pair
+    pub bls12_381_aggregate_verify_cost: LinearGasCost,
+    /// BLS12-381 hash to G1 cost, per byte of the message
+    pub bls12_381_hash_to_g1_cost: LinearGasCost,
+    /// BLS12-381 hash to G2 cost, per byte of the message
+    pub bls12_381_hash_to_g2_cost: LinearGasCost,
 }
 
 impl Default for GasConfig {
@@ -39,14 +103,108 @@ impl Default for GasConfig {
         Self {
             // ~154 us in crypto benchmarks
             secp256k1_verify_cost: 154 * GAS_PER_US,
+            // Each signature still needs its own verification. The batch shares one scalar
+            // inversion among all signatures (~0.97 of secp256k1_verify_cost) and parses a
+            // single public key only once (~0.83 of secp256k1_verify_cost).
+            secp256k1_batch_verify_cost: 150 * GAS_PER_US,
+            secp256k1_batch_verify_one_pubkey_cost: 128 * GAS_PER_US,
             // ~162 us in crypto benchmarks
             secp256k1_recover_pubkey_cost: 162 * GAS_PER_US,
+            // ~600 us in crypto benchmarks
+            secp256r1_verify_cost: 600 * GAS_PER_US,
+            // ~700 us in crypto benchmarks
+            secp256r1_recover_pubkey_cost: 700 * GAS_PER_US,
             // ~63 us in crypto benchmarks
             ed25519_verify_cost: 63 * GAS_PER_US,
             // Gas cost factors, relative to ed25519_verify cost
             // From https://docs.rs/ed25519-zebra/2.2.0/ed25519_zebra/batch/index.html
             ed25519_batch_verify_cost: 63 * GAS_PER_US / 2,
             ed25519_batch_verify_one_pubkey_cost: 63 * GAS_PER_US / 4,
+            // ~80 us in crypto benchmarks
+            sr25519_verify_cost: 80 * GAS_PER_US,
+            // ~160 us in crypto benchmarks
+            secp256k1_schnorr_verify_cost: 160 * GAS_PER_US,
+            // Those are mostly linear in the input length. The base cost of
+            // 100 ns (10 ns for cheap operations) accounts for the call overhead,
+            // each byte costs 1 ns.
+            addr_validate_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: GAS_PER_US / 1000,
+            },
+            addr_canonicalize_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: GAS_PER_US / 1000,
+            },
+            addr_humanize_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: GAS_PER_US / 1000,
+            },
+            debug_cost: LinearGasCost {
+                base: GAS_PER_US / 100,
+                per_item: GAS_PER_US / 1000,
+            },
+            db_scan_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: GAS_PER_US / 1000,
+            },
+            db_next_cost: LinearGasCost {
+                base: GAS_PER_US / 100,
+                per_item: GAS_PER_US / 1000,
+            },
+            db_read_many_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: GAS_PER_US / 1000,
+            },
+            // The base cost of 100 ns accounts for the call overhead and writing the
+            // hash to the contract. The per byte costs are rounded up throughput
+            // measurements of the crypto benchmarks.
+            sha256_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: 4 * GAS_PER_US / 1000,
+            },
+            keccak256_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: 4 * GAS_PER_US / 1000,
+            },
+            ripemd160_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: 5 * GAS_PER_US / 1000,
+            },
+            blake2b_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: 2 * GAS_PER_US / 1000,
+            },
+            // Point decoding, including the subgroup check, dominates the aggregation.
+            // ~82 us per G1 point and ~155 us per G2 point in crypto benchmarks.
+            bls12_381_aggregate_g1_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: 85 * GAS_PER_US,
+            },
+            bls12_381_aggregate_g2_cost: LinearGasCost {
+                base: GAS_PER_US / 10,
+                per_item: 160 * GAS_PER_US,
+            },
+            // The base cost covers the final exponentiation and the pairing e(r, s).
+            // Each additional pairing (including point decoding) costs ~500 us.
+            bls12_381_pairing_equality_cost: LinearGasCost {
+                base: 1700 * GAS_PER_US,
+                per_item: 600 * GAS_PER_US,
+            },
+            // The base cost covers the final exponentiation and the signature pairing.
+            // Each public key / message pair needs a hash to G2 and a pairing (~860 us).
+            bls12_381_aggregate_verify_cost: LinearGasCost {
+                base: 800 * GAS_PER_US,
+                per_item: 900 * GAS_PER_US,
+            },
+            // ~98 us (G1) and ~294 us (G2) for short messages in crypto benchmarks
+            bls12_381_hash_to_g1_cost: LinearGasCost {
+                base: 100 * GAS_PER_US,
+                per_item: 4 * GAS_PER_US / 1000,
+            },
+            bls12_381_hash_to_g2_cost: LinearGasCost {
+                base: 300 * GAS_PER_US,
+                per_item: 4 * GAS_PER_US / 1000,
+            },
         }
     }
 }
@@ -56,7 +214,7 @@ impl Default for GasConfig {
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct GasState {
     /// Gas limit for the computation, including internally and externally used gas.
-    /// This is set when the Environment is created and never mutated.
+    /// This is set when the Environment is created and only changed by `Environment::reset`.
     pub gas_limit: u64,
     /// Tracking the gas used in the Cosmos SDK, in CosmWasm gas units.
     pub externally_used_gas: u64,
@@ -74,10 +232,9 @@ impl GasState {
 /// A environment that provides access to the ContextData.
 /// The environment is clonable but clones access the same underlying data.
 pub struct Environment<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> {
-    pub api: A,
     pub print_debug: bool,
     pub gas_config: GasConfig,
-    data: Arc<RwLock<ContextData<S, Q, W>>>,
+    data: Arc<RwLock<ContextData<A, S, Q, W>>>,
 }
 
 unsafe impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> Send for Environment<A, S, Q, W> {}
@@ -87,9 +244,8 @@ unsafe impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> Sync for Environme
 impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> Clone for Environment<A, S, Q, W> {
     fn clone(&self) -> Self {
         Environment {
-            api: self.api,
             print_debug: self.print_debug,
-            gas_config: self.gas_config.clone(),
+            gas_config: self.gas_config,
             data: self.data.clone(),
         }
     }
@@ -102,12 +258,11 @@ impl<A: BackendApi, S: Storage, Q: Querier> WasmerEnv for Environment<A, S, Q, W
 }
 
 impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> Environment<A, S, Q, W> {
-    pub fn new(api: A, gas_limit: u64, print_debug: bool) -> Self {
+    pub fn new(api: A, gas_limit: u64, print_debug: bool, gas_config: GasConfig) -> Self {
         Environment {
-            api,
             print_debug,
-            gas_config: GasConfig::default(),
-            data: Arc::new(RwLock::new(ContextData::new(gas_limit))),
+            gas_config,
+            data: Arc::new(RwLock::new(ContextData::new(api, gas_limit))),
         }
     }
 
@@ -196,7 +351,7 @@ impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> Environment<A, S, Q, W> {
 
     fn with_context_data_mut<C, R>(&self, callback: C) -> R
     where
-        C: FnOnce(&mut ContextData<S, Q, W>) -> R,
+        C: FnOnce(&mut ContextData<A, S, Q, W>) -> R,
     {
         let mut guard = self.data.as_ref().write().unwrap();
         let context_data = guard.borrow_mut();
@@ -205,7 +360,7 @@ impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> Environment<A, S, Q, W> {
 
     fn with_context_data<C, R>(&self, callback: C) -> R
     where
-        C: FnOnce(&ContextData<S, Q, W>) -> R,
+        C: FnOnce(&ContextData<A, S, Q, W>) -> R,
     {
         let guard = self.data.as_ref().read().unwrap();
         let context_data = guard.borrow();
@@ -246,6 +401,17 @@ impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> Environment<A, S, Q, W> {
         })
     }
 
+    /// Returns the tracer that is notified about import calls
+    pub fn tracer(&self) -> Arc<dyn Tracer> {
+        self.with_context_data(|context_data| context_data.tracer.clone())
+    }
+
+    pub fn set_tracer(&self, tracer: Arc<dyn Tracer>) {
+        self.with_context_data_mut(|context_data| {
+            context_data.tracer = tracer;
+        })
+    }
+
     /// Returns true iff the storage is set to readonly mode
     pub fn is_storage_readonly(&self) -> bool {
         self.with_context_data(|context_data| context_data.storage_readonly)
@@ -257,6 +423,19 @@ impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> Environment<A, S, Q, W> {
         })
     }
 
+    /// Returns a copy of the API. All clones of this environment share the same API.
+    pub fn api(&self) -> A {
+        self.with_context_data(|context_data| context_data.api.clone())
+    }
+
+    /// Replaces the API of this environment and all its clones.
+    /// This is used to reuse pooled instances with the API of the caller.
+    pub fn set_api(&self, api: A) {
+        self.with_context_data_mut(|context_data| {
+            context_data.api = api;
+        });
+    }
+
     /// Moves owned instances of storage and querier into the env.
     /// Should be followed by exactly one call to move_out when the instance is finished.
     pub fn move_in(&self, storage: S, querier: Q) {
@@ -266,6 +445,17 @@ impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> Environment<A, S, Q, W> {
         });
     }
 
+    /// Resets the gas state, the storage readonly flag and the tracer to those of a new
+    /// environment with the given gas limit. This is used to reuse pooled instances.
+    pub fn reset(&self, gas_limit: u64) {
+        self.with_context_data_mut(|context_data| {
+            context_data.gas_state = GasState::with_limit(gas_limit);
+            context_data.storage_readonly = true;
+            context_data.tracer = Arc::new(NoopTracer);
+        });
+        self.set_gas_left(gas_limit);
+    }
+
     /// Returns the original storage and querier as owned instances, and closes any remaining
     /// iterators. This is meant to be called when recycling the instance.
     pub fn move_out(&self) -> (Option<S>, Option<Q>) {
@@ -275,22 +465,26 @@ impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> Environment<A, S, Q, W> {
     }
 }
 
-pub struct ContextData<S: Storage, Q: Querier, W: WasmVM> {
+pub struct ContextData<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> {
+    api: A,
     gas_state: GasState,
     storage: Option<S>,
     storage_readonly: bool,
     querier: Option<Q>,
+    tracer: Arc<dyn Tracer>,
     /// A non-owning link to the wasmer instance
     wasm_instance: Option<NonNull<W>>,
 }
 
-impl<S: Storage, Q: Querier, W: WasmVM> ContextData<S, Q, W> {
-    pub fn new(gas_limit: u64) -> Self {
-        ContextData::<S, Q, W> {
+impl<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> ContextData<A, S, Q, W> {
+    pub fn new(api: A, gas_limit: u64) -> Self {
+        ContextData::<A, S, Q, W> {
+            api,
             gas_state: GasState::with_limit(gas_limit),
             storage: None,
             storage_readonly: true,
             querier: None,
+            tracer: Arc::new(NoopTracer),
             wasm_instance: None,
         }
     }
@@ -329,7 +523,7 @@ mod tests {
     use crate::errors::VmError;
     use crate::size::Size;
     use crate::testing::{MockApi, MockQuerier, MockStorage};
-    use crate::wasm_backend::compile;
+    use crate::wasm_backend::{compile, OperatorCostTable};
     use cosmwasm_std::{
         coins, from_binary, to_vec, AllBalanceResponse, BankQuery, Empty, QueryRequest,
     };
@@ -355,9 +549,15 @@ mod tests {
         Environment<MockApi, MockStorage, MockQuerier, WasmerInstance>,
         Box<WasmerInstance>,
     ) {
-        let env = Environment::new(MockApi::default(), gas_limit, false);
-
-        let module = compile(CONTRACT, TESTING_MEMORY_LIMIT, &[]).unwrap();
+        let env = Environment::new(MockApi::default(), gas_limit, false, GasConfig::default());
+
+        let module = compile(
+            CONTRACT,
+            TESTING_MEMORY_LIMIT,
+            &OperatorCostTable::default(),
+            &[],
+        )
+        .unwrap();
         let store = module.store();
         // we need stubs for all required imports
         let import_obj = imports! {
@@ -399,6 +599,20 @@ mod tests {
         env.move_in(storage, querier);
     }
 
+    #[test]
+    fn linear_gas_cost_total_cost_works() {
+        let cost = LinearGasCost {
+            base: 100,
+            per_item: 3,
+        };
+        assert_eq!(cost.total_cost(0), 100);
+        assert_eq!(cost.total_cost(1), 103);
+        assert_eq!(cost.total_cost(20), 160);
+
+        // saturates
+        assert_eq!(cost.total_cost(u64::MAX), u64::MAX);
+    }
+
     #[test]
     fn move_out_works() {
         let (env, _instance) = make_instance(TESTING_GAS_LIMIT);
@@ -27,11 +27,19 @@ pub enum CommunicationError {
     /// Whenever UTF-8 bytes cannot be decoded into a unicode string, e.g. in String::from_utf8 or str::from_utf8.
     #[error("Cannot decode UTF8 bytes into string: {}", msg)]
     InvalidUtf8 { msg: String },
+    #[error(
+        "Invalid section length. Got {}, but only {} bytes are left",
+        length,
+        remaining
+    )]
+    InvalidSectionLength { length: usize, remaining: usize },
     #[error("Region length too big. Got {}, limit {}", length, max_length)]
     // Note: this only checks length, not capacity
     RegionLengthTooBig { length: usize, max_length: usize },
     #[error("Region too small. Got {}, required {}", size, required)]
     RegionTooSmall { size: usize, required: usize },
+    #[error("Too many sections. Got {}, limit {}", count, max_count)]
+    TooManySections { count: usize, max_count: usize },
     #[error("Got a zero Wasm address")]
     ZeroAddress {},
 }
@@ -56,6 +64,10 @@ impl CommunicationError {
         }
     }
 
+    pub(crate) fn invalid_section_length(length: usize, remaining: usize) -> Self {
+        CommunicationError::InvalidSectionLength { length, remaining }
+    }
+
     pub(crate) fn region_length_too_big(length: usize, max_length: usize) -> Self {
         CommunicationError::RegionLengthTooBig { length, max_length }
     }
@@ -64,6 +76,10 @@ impl CommunicationError {
         CommunicationError::RegionTooSmall { size, required }
     }
 
+    pub(crate) fn too_many_sections(count: usize, max_count: usize) -> Self {
+        CommunicationError::TooManySections { count, max_count }
+    }
+
     pub(crate) fn zero_address() -> Self {
         CommunicationError::ZeroAddress {}
     }
@@ -105,6 +121,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn invalid_section_length_works() {
+        let error = CommunicationError::invalid_section_length(300, 12);
+        match error {
+            CommunicationError::InvalidSectionLength {
+                length, remaining, ..
+            } => {
+                assert_eq!(length, 300);
+                assert_eq!(remaining, 12);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn region_length_too_big_works() {
         let error = CommunicationError::region_length_too_big(50, 20);
@@ -131,6 +161,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn too_many_sections_works() {
+        let error = CommunicationError::too_many_sections(300, 256);
+        match error {
+            CommunicationError::TooManySections {
+                count, max_count, ..
+            } => {
+                assert_eq!(count, 300);
+                assert_eq!(max_count, 256);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn zero_address() {
         let error = CommunicationError::zero_address();
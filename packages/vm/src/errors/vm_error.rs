@@ -11,6 +11,12 @@ use crate::backend::BackendError;
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum VmError {
+    #[error("Aborted: {}", msg)]
+    Aborted {
+        msg: String,
+        #[cfg(feature = "backtraces")]
+        backtrace: Backtrace,
+    },
     #[error("Error calling into the VM's backend: {}", source)]
     BackendErr {
         source: BackendError,
@@ -122,6 +128,15 @@ pub enum VmError {
         #[cfg(feature = "backtraces")]
         backtrace: Backtrace,
     },
+    #[error(
+        "Maximum call stack depth of {} exceeded during contract execution",
+        limit
+    )]
+    StackDepthExceeded {
+        limit: u32,
+        #[cfg(feature = "backtraces")]
+        backtrace: Backtrace,
+    },
     #[error("Error during static Wasm validation: {}", msg)]
     StaticValidationErr {
         msg: String,
@@ -142,6 +157,14 @@ pub enum VmError {
 }
 
 impl VmError {
+    pub(crate) fn aborted(msg: impl Into<String>) -> Self {
+        VmError::Aborted {
+            msg: msg.into(),
+            #[cfg(feature = "backtraces")]
+            backtrace: Backtrace::capture(),
+        }
+    }
+
     pub(crate) fn backend_err(original: BackendError) -> Self {
         VmError::BackendErr {
             source: original,
@@ -269,7 +292,7 @@ impl VmError {
 
     // Creates a runtime error with the given message.
     // This is private since it is only needed when converting wasmer::RuntimeError
-    // to VmError.
+    // (or wasmi::Error) to VmError.
     fn runtime_err(msg: impl Into<String>) -> Self {
         VmError::RuntimeErr {
             msg: msg.into(),
@@ -278,6 +301,14 @@ impl VmError {
         }
     }
 
+    pub(crate) fn stack_depth_exceeded(limit: u32) -> Self {
+        VmError::StackDepthExceeded {
+            limit,
+            #[cfg(feature = "backtraces")]
+            backtrace: Backtrace::capture(),
+        }
+    }
+
     pub(crate) fn static_validation_err(msg: impl Into<String>) -> Self {
         VmError::StaticValidationErr {
             msg: msg.into(),
@@ -349,7 +380,30 @@ impl From<wasmer::RuntimeError> for VmError {
             &message,
             original.to_string()
         );
-        VmError::runtime_err(format!("Wasmer runtime error: {}", &message))
+        // Preserve aborts raised by the `abort` import such that the contract's panic message
+        // can be inspected by the caller
+        match original.downcast::<VmError>() {
+            Ok(VmError::Aborted { msg, .. }) => VmError::aborted(msg),
+            _ => VmError::runtime_err(format!("Wasmer runtime error: {}", &message)),
+        }
+    }
+}
+
+#[cfg(feature = "interpreter")]
+impl From<wasmi::Error> for VmError {
+    fn from(original: wasmi::Error) -> Self {
+        // Errors of imports are VmErrors. Use their message like it is done for Wasmer.
+        if let Some(VmError::Aborted { msg, .. }) = original
+            .as_host_error()
+            .and_then(|host_error| host_error.downcast_ref::<VmError>())
+        {
+            return VmError::aborted(msg.clone());
+        }
+        let message = match original.as_host_error() {
+            Some(host_error) => format!("RuntimeError: {}", host_error),
+            None => format!("RuntimeError: {}", original),
+        };
+        VmError::runtime_err(format!("Wasmi runtime error: {}", &message))
     }
 }
 
@@ -372,12 +426,27 @@ impl From<VmError> for wasmer::RuntimeError {
     }
 }
 
+/// Allows returning VmErrors from imports in the interpreter
+#[cfg(feature = "interpreter")]
+impl wasmi::HostError for VmError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     // constructors
 
+    #[test]
+    fn aborted_works() {
+        let error = VmError::aborted("panicked at 'oh no', src/contract.rs:42:5");
+        match error {
+            VmError::Aborted { msg, .. } => {
+                assert_eq!(msg, "panicked at 'oh no', src/contract.rs:42:5")
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn backend_err_works() {
         let error = VmError::backend_err(BackendError::unknown("something went wrong"));
@@ -541,6 +610,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn stack_depth_exceeded_works() {
+        let error = VmError::stack_depth_exceeded(1024);
+        match error {
+            VmError::StackDepthExceeded { limit, .. } => assert_eq!(limit, 1024),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn static_validation_err_works() {
         let error = VmError::static_validation_err("export xy missing");
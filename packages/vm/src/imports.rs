@@ -3,9 +3,25 @@
 use std::cmp::max;
 use std::convert::TryInto;
 
+#[cfg(feature = "secp256k1_batch")]
+use cosmwasm_crypto::secp256k1_batch_verify;
+#[cfg(feature = "hashes")]
+use cosmwasm_crypto::{blake2b, keccak256, ripemd160, sha256};
+#[cfg(feature = "bls12_381")]
+use cosmwasm_crypto::{
+    bls12_381_aggregate_g1, bls12_381_aggregate_g2, bls12_381_aggregate_verify,
+    bls12_381_hash_to_g1, bls12_381_hash_to_g2, bls12_381_pairing_equality, BLS12_381_G1_POINT_LEN,
+    BLS12_381_G2_POINT_LEN,
+};
 use cosmwasm_crypto::{
     ed25519_batch_verify, ed25519_verify, secp256k1_recover_pubkey, secp256k1_verify, CryptoError,
 };
+#[cfg(feature = "secp256k1_schnorr")]
+use cosmwasm_crypto::{secp256k1_schnorr_verify, SCHNORR_PUBKEY_LEN, SCHNORR_SIGNATURE_LEN};
+#[cfg(feature = "secp256r1")]
+use cosmwasm_crypto::{secp256r1_recover_pubkey, secp256r1_verify};
+#[cfg(feature = "sr25519")]
+use cosmwasm_crypto::{sr25519_verify, SR25519_PUBKEY_LEN};
 use cosmwasm_crypto::{
     ECDSA_PUBKEY_MAX_LEN, ECDSA_SIGNATURE_LEN, EDDSA_PUBKEY_LEN, MESSAGE_HASH_MAX_LEN,
 };
@@ -15,12 +31,15 @@ use cosmwasm_std::Order;
 
 use crate::backend::{BackendApi, BackendError, Querier, Storage};
 use crate::conversion::{ref_to_u32, to_u32};
+#[cfg(any(feature = "hashes", feature = "bls12_381"))]
+use crate::environment::LinearGasCost;
 use crate::environment::{process_gas_info, Environment};
 use crate::errors::{CommunicationError, VmError, VmResult};
-use crate::sections::decode_sections;
 #[allow(unused_imports)]
 use crate::sections::encode_sections;
+use crate::sections::try_decode_sections;
 use crate::serde::to_vec;
+use crate::tracer::{ImportTrace, TraceEvent};
 use crate::wasm::Memory;
 use crate::{GasInfo, WasmVM};
 
@@ -32,6 +51,11 @@ const MI: usize = 1024 * 1024;
 const MAX_LENGTH_DB_KEY: usize = 64 * KI;
 /// Max value length for db_write (when VM reads the value argument from Wasm memory)
 const MAX_LENGTH_DB_VALUE: usize = 128 * KI;
+/// Max number of keys for db_read_many.
+/// This is an arbitrary value, for performance / memory contraints. If you need to read a
+/// larger number of keys at once, let us know.
+#[cfg(feature = "extended_storage")]
+const MAX_COUNT_DB_READ_MANY: usize = 256;
 /// Typically 20 (Cosmos SDK, Ethereum), 32 (Nano, Substrate) or 54 (MockApi)
 const MAX_LENGTH_CANONICAL_ADDRESS: usize = 64;
 /// The max length of human address inputs (in bytes).
@@ -39,6 +63,12 @@ const MAX_LENGTH_CANONICAL_ADDRESS: usize = 64;
 /// is 90 characters and we're adding some safety margin around that for other formats.
 const MAX_LENGTH_HUMAN_ADDRESS: usize = 256;
 const MAX_LENGTH_QUERY_CHAIN_REQUEST: usize = 64 * KI;
+/// Max number of batch secp256k1 message hashes / signatures / public_keys.
+/// Every signature still needs its own verification, so this is lower than for Ed25519.
+/// This is an arbitrary value, for performance / memory contraints. If you need to batch-verify a
+/// larger number of signatures, let us know.
+#[cfg(feature = "secp256k1_batch")]
+const MAX_COUNT_SECP256K1_BATCH: usize = 128;
 /// Length of a serialized Ed25519  signature
 const MAX_LENGTH_ED25519_SIGNATURE: usize = 64;
 /// Max length of a Ed25519 message in bytes.
@@ -49,33 +79,126 @@ const MAX_LENGTH_ED25519_MESSAGE: usize = 128 * 1024;
 /// This is an arbitrary value, for performance / memory contraints. If you need to batch-verify a
 /// larger number of signatures, let us know.
 const MAX_COUNT_ED25519_BATCH: usize = 256;
+/// Length of a serialized sr25519 signature
+#[cfg(feature = "sr25519")]
+const MAX_LENGTH_SR25519_SIGNATURE: usize = 64;
+/// Max length of a sr25519 message in bytes.
+/// This is an arbitrary value, for performance / memory contraints. If you need to verify larger
+/// messages, let us know.
+#[cfg(feature = "sr25519")]
+const MAX_LENGTH_SR25519_MESSAGE: usize = 128 * KI;
+/// Max length of a BIP-340 Schnorr message in bytes.
+/// This is an arbitrary value, for performance / memory contraints. If you need to verify larger
+/// messages, let us know.
+#[cfg(feature = "secp256k1_schnorr")]
+const MAX_LENGTH_SECP256K1_SCHNORR_MESSAGE: usize = 128 * KI;
+/// Max length of the input of the hash functions (sha256, keccak256, ripemd160, blake2b) in bytes.
+/// This is an arbitrary value, for performance / memory contraints. If you need to hash
+/// larger inputs, let us know.
+#[cfg(feature = "hashes")]
+const MAX_LENGTH_HASH_INPUT: usize = 128 * KI;
+/// Max number of points for bls12_381_aggregate_g1/bls12_381_aggregate_g2.
+/// This is the size of Ethereum's sync committee. If you need to aggregate
+/// more points at once, let us know.
+#[cfg(feature = "bls12_381")]
+const MAX_COUNT_BLS12_381_AGGREGATE: usize = 512;
+/// Max number of pairings (point pairs or public key / message pairs) for
+/// bls12_381_pairing_equality/bls12_381_aggregate_verify.
+/// This is an arbitrary value, for performance / memory contraints. If you need to verify
+/// a larger number of pairings at once, let us know.
+#[cfg(feature = "bls12_381")]
+const MAX_COUNT_BLS12_381_PAIRING: usize = 64;
+/// Max length of a message for bls12_381_aggregate_verify and the hash-to-curve functions.
+/// This is an arbitrary value, for performance / memory contraints. If you need to hash
+/// larger messages, let us know.
+#[cfg(feature = "bls12_381")]
+const MAX_LENGTH_BLS12_381_MESSAGE: usize = 8 * KI;
+/// Max length of a domain separation tag. RFC 9380 requires tags of at most 255 bytes.
+#[cfg(feature = "bls12_381")]
+const MAX_LENGTH_BLS12_381_DST: usize = 255;
 
 /// Max length for a debug message
 const MAX_LENGTH_DEBUG: usize = 2 * MI;
 
+/// Max length for an abort message
+const MAX_LENGTH_ABORT: usize = 2 * MI;
+
 // Import implementations
 //
 // This block of do_* prefixed functions is tailored for Wasmer's
 // Function::new_native_with_env interface. Those require an env in the first
 // argument and cannot capture other variables. Thus everything is accessed
 // through the env.
+//
+// Every implementation runs inside of `traced`, which reports the call to the
+// instance's tracer.
 
 /// Reads a storage entry from the VM's storage into Wasm memory
 pub fn do_db_read<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     env: &Environment<A, S, Q, W>,
     key_ptr: u32,
 ) -> VmResult<u32> {
-    let key: Vec<u8> = env.memory().read_region(key_ptr, MAX_LENGTH_DB_KEY)?;
+    traced(env, "db_read", |trace| {
+        let key: Vec<u8> = env.memory().read_region(key_ptr, MAX_LENGTH_DB_KEY)?;
+        trace.bytes(&key);
+
+        let (result, gas_info) =
+            env.with_storage_from_context::<_, _>(|store| Ok(store.get(&key)))?;
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        let value = result?;
+
+        let out_data = match value {
+            Some(data) => data,
+            None => return Ok(0),
+        };
+        trace.result(&out_data);
+        write_to_contract::<A, S, Q, W>(env, &out_data)
+    })
+}
 
-    let (result, gas_info) = env.with_storage_from_context::<_, _>(|store| Ok(store.get(&key)))?;
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    let value = result?;
+/// Reads multiple storage entries from the VM's storage into Wasm memory.
+///
+/// The keys are read as sections (see `try_decode_sections`). The result contains one
+/// section per key with the value. An empty section means the key does not exist.
+#[cfg(feature = "extended_storage")]
+pub fn do_db_read_many<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    keys_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "db_read_many", |trace| {
+        // Charged before reading and decoding such that large or malformed key lists are paid for
+        let keys_length = env.memory().get_region(keys_ptr)?.length;
+        let gas_info = GasInfo::with_cost(
+            env.gas_config
+                .db_read_many_cost
+                .total_cost(keys_length as u64),
+        );
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+
+        let keys = env
+            .memory()
+            .read_region(keys_ptr, (MAX_LENGTH_DB_KEY + 4) * MAX_COUNT_DB_READ_MANY)?;
+        trace.bytes(&keys);
+
+        let keys = try_decode_sections(&keys, MAX_COUNT_DB_READ_MANY)?;
+        if let Some(key) = keys.iter().find(|key| key.len() > MAX_LENGTH_DB_KEY) {
+            return Err(
+                CommunicationError::region_length_too_big(key.len(), MAX_LENGTH_DB_KEY).into(),
+            );
+        }
 
-    let out_data = match value {
-        Some(data) => data,
-        None => return Ok(0),
-    };
-    write_to_contract::<A, S, Q, W>(env, &out_data)
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            let (result, gas_info) =
+                env.with_storage_from_context::<_, _>(|store| Ok(store.get(key)))?;
+            process_gas_info::<A, S, Q, W>(env, gas_info)?;
+            values.push(result?.unwrap_or_default());
+        }
+
+        let out_data = encode_sections(&values)?;
+        trace.result(&out_data);
+        write_to_contract::<A, S, Q, W>(env, &out_data)
+    })
 }
 
 /// Writes a storage entry from Wasm memory into the VM's storage
@@ -84,64 +207,86 @@ pub fn do_db_write<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     key_ptr: u32,
     value_ptr: u32,
 ) -> VmResult<()> {
-    if env.is_storage_readonly() {
-        return Err(VmError::write_access_denied());
-    }
+    traced(env, "db_write", |trace| {
+        if env.is_storage_readonly() {
+            return Err(VmError::write_access_denied());
+        }
 
-    let key = env.memory().read_region(key_ptr, MAX_LENGTH_DB_KEY)?;
-    let value = env.memory().read_region(value_ptr, MAX_LENGTH_DB_VALUE)?;
+        let key = env.memory().read_region(key_ptr, MAX_LENGTH_DB_KEY)?;
+        let value = env.memory().read_region(value_ptr, MAX_LENGTH_DB_VALUE)?;
+        trace.bytes(&key);
+        trace.bytes(&value);
 
-    let (result, gas_info) =
-        env.with_storage_from_context::<_, _>(|store| Ok(store.set(&key, &value)))?;
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    result?;
+        let (result, gas_info) =
+            env.with_storage_from_context::<_, _>(|store| Ok(store.set(&key, &value)))?;
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        result?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 pub fn do_db_remove<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     env: &Environment<A, S, Q, W>,
     key_ptr: u32,
 ) -> VmResult<()> {
-    if env.is_storage_readonly() {
-        return Err(VmError::write_access_denied());
-    }
+    traced(env, "db_remove", |trace| {
+        if env.is_storage_readonly() {
+            return Err(VmError::write_access_denied());
+        }
 
-    let key = env.memory().read_region(key_ptr, MAX_LENGTH_DB_KEY)?;
+        let key = env.memory().read_region(key_ptr, MAX_LENGTH_DB_KEY)?;
+        trace.bytes(&key);
 
-    let (result, gas_info) =
-        env.with_storage_from_context::<_, _>(|store| Ok(store.remove(&key)))?;
-    process_gas_info(env, gas_info)?;
-    result?;
+        let (result, gas_info) =
+            env.with_storage_from_context::<_, _>(|store| Ok(store.remove(&key)))?;
+        process_gas_info(env, gas_info)?;
+        result?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 pub fn do_addr_validate<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     env: &Environment<A, S, Q, W>,
     source_ptr: u32,
 ) -> VmResult<u32> {
-    let source_data = env
-        .memory()
-        .read_region(source_ptr, MAX_LENGTH_HUMAN_ADDRESS)?;
-    if source_data.is_empty() {
-        return write_to_contract::<A, S, Q, W>(env, b"Input is empty");
-    }
+    traced(env, "addr_validate", |trace| {
+        let source_data = env
+            .memory()
+            .read_region(source_ptr, MAX_LENGTH_HUMAN_ADDRESS)?;
+        trace.bytes(&source_data);
+        let gas_info = GasInfo::with_cost(
+            env.gas_config
+                .addr_validate_cost
+                .total_cost(source_data.len() as u64),
+        );
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        if source_data.is_empty() {
+            return write_error_to_contract::<A, S, Q, W>(env, trace, b"Input is empty");
+        }
 
-    let source_string = match String::from_utf8(source_data) {
-        Ok(s) => s,
-        Err(_) => return write_to_contract::<A, S, Q, W>(env, b"Input is not valid UTF-8"),
-    };
+        let source_string = match String::from_utf8(source_data) {
+            Ok(s) => s,
+            Err(_) => {
+                return write_error_to_contract::<A, S, Q, W>(
+                    env,
+                    trace,
+                    b"Input is not valid UTF-8",
+                )
+            }
+        };
 
-    let (result, gas_info) = env.api.canonical_address(&source_string);
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    match result {
-        Ok(_canonical) => Ok(0),
-        Err(BackendError::UserErr { msg, .. }) => {
-            Ok(write_to_contract::<A, S, Q, W>(env, msg.as_bytes())?)
+        let (result, gas_info) = env.api().canonical_address(&source_string);
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        match result {
+            Ok(_canonical) => Ok(0),
+            Err(BackendError::UserErr { msg, .. }) => {
+                write_error_to_contract::<A, S, Q, W>(env, trace, msg.as_bytes())
+            }
+            Err(err) => Err(VmError::from(err)),
         }
-        Err(err) => Err(VmError::from(err)),
-    }
+    })
 }
 
 pub fn do_addr_canonicalize<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
@@ -149,31 +294,47 @@ pub fn do_addr_canonicalize<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     source_ptr: u32,
     destination_ptr: u32,
 ) -> VmResult<u32> {
-    let source_data = env
-        .memory()
-        .read_region(source_ptr, MAX_LENGTH_HUMAN_ADDRESS)?;
-    if source_data.is_empty() {
-        return write_to_contract::<A, S, Q, W>(env, b"Input is empty");
-    }
+    traced(env, "addr_canonicalize", |trace| {
+        let source_data = env
+            .memory()
+            .read_region(source_ptr, MAX_LENGTH_HUMAN_ADDRESS)?;
+        trace.bytes(&source_data);
+        let gas_info = GasInfo::with_cost(
+            env.gas_config
+                .addr_canonicalize_cost
+                .total_cost(source_data.len() as u64),
+        );
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        if source_data.is_empty() {
+            return write_error_to_contract::<A, S, Q, W>(env, trace, b"Input is empty");
+        }
 
-    let source_string = match String::from_utf8(source_data) {
-        Ok(s) => s,
-        Err(_) => return write_to_contract::<A, S, Q, W>(env, b"Input is not valid UTF-8"),
-    };
+        let source_string = match String::from_utf8(source_data) {
+            Ok(s) => s,
+            Err(_) => {
+                return write_error_to_contract::<A, S, Q, W>(
+                    env,
+                    trace,
+                    b"Input is not valid UTF-8",
+                )
+            }
+        };
 
-    let (result, gas_info) = env.api.canonical_address(&source_string);
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    match result {
-        Ok(canonical) => {
-            env.memory()
-                .write_region(destination_ptr, canonical.as_slice())?;
-            Ok(0)
-        }
-        Err(BackendError::UserErr { msg, .. }) => {
-            Ok(write_to_contract::<A, S, Q, W>(env, msg.as_bytes())?)
+        let (result, gas_info) = env.api().canonical_address(&source_string);
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        match result {
+            Ok(canonical) => {
+                trace.result(&canonical);
+                env.memory()
+                    .write_region(destination_ptr, canonical.as_slice())?;
+                Ok(0)
+            }
+            Err(BackendError::UserErr { msg, .. }) => {
+                write_error_to_contract::<A, S, Q, W>(env, trace, msg.as_bytes())
+            }
+            Err(err) => Err(VmError::from(err)),
         }
-        Err(err) => Err(VmError::from(err)),
-    }
+    })
 }
 
 pub fn do_addr_humanize<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
@@ -181,23 +342,33 @@ pub fn do_addr_humanize<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     source_ptr: u32,
     destination_ptr: u32,
 ) -> VmResult<u32> {
-    let canonical = env
-        .memory()
-        .read_region(source_ptr, MAX_LENGTH_CANONICAL_ADDRESS)?;
-
-    let (result, gas_info) = env.api.human_address(&canonical);
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    match result {
-        Ok(human) => {
-            env.memory()
-                .write_region(destination_ptr, human.as_bytes())?;
-            Ok(0)
-        }
-        Err(BackendError::UserErr { msg, .. }) => {
-            Ok(write_to_contract::<A, S, Q, W>(env, msg.as_bytes())?)
+    traced(env, "addr_humanize", |trace| {
+        let canonical = env
+            .memory()
+            .read_region(source_ptr, MAX_LENGTH_CANONICAL_ADDRESS)?;
+        trace.bytes(&canonical);
+        let gas_info = GasInfo::with_cost(
+            env.gas_config
+                .addr_humanize_cost
+                .total_cost(canonical.len() as u64),
+        );
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+
+        let (result, gas_info) = env.api().human_address(&canonical);
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        match result {
+            Ok(human) => {
+                trace.result(human.as_bytes());
+                env.memory()
+                    .write_region(destination_ptr, human.as_bytes())?;
+                Ok(0)
+            }
+            Err(BackendError::UserErr { msg, .. }) => {
+                write_error_to_contract::<A, S, Q, W>(env, trace, msg.as_bytes())
+            }
+            Err(err) => Err(VmError::from(err)),
         }
-        Err(err) => Err(VmError::from(err)),
-    }
+    })
 }
 
 pub fn do_secp256k1_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
@@ -206,27 +377,88 @@ pub fn do_secp256k1_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     signature_ptr: u32,
     pubkey_ptr: u32,
 ) -> VmResult<u32> {
-    let hash = env.memory().read_region(hash_ptr, MESSAGE_HASH_MAX_LEN)?;
-    let signature = env
-        .memory()
-        .read_region(signature_ptr, ECDSA_SIGNATURE_LEN)?;
-    let pubkey = env.memory().read_region(pubkey_ptr, ECDSA_PUBKEY_MAX_LEN)?;
+    traced(env, "secp256k1_verify", |trace| {
+        let hash = env.memory().read_region(hash_ptr, MESSAGE_HASH_MAX_LEN)?;
+        let signature = env
+            .memory()
+            .read_region(signature_ptr, ECDSA_SIGNATURE_LEN)?;
+        let pubkey = env.memory().read_region(pubkey_ptr, ECDSA_PUBKEY_MAX_LEN)?;
+        trace.bytes(&hash);
+        trace.bytes(&signature);
+        trace.bytes(&pubkey);
+
+        let result = secp256k1_verify(&hash, &signature, &pubkey);
+        let gas_info = GasInfo::with_cost(env.gas_config.secp256k1_verify_cost);
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        Ok(result.map_or_else(
+            |err| match err {
+                CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidPubkeyFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::GenericErr { .. } => err.code(),
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidPoint { .. }
+                | CryptoError::InvalidRecoveryParam { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
+            |valid| if valid { 0 } else { 1 },
+        ))
+    })
+}
 
-    let result = secp256k1_verify(&hash, &signature, &pubkey);
-    let gas_info = GasInfo::with_cost(env.gas_config.secp256k1_verify_cost);
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    Ok(result.map_or_else(
-        |err| match err {
-            CryptoError::InvalidHashFormat { .. }
-            | CryptoError::InvalidPubkeyFormat { .. }
-            | CryptoError::InvalidSignatureFormat { .. }
-            | CryptoError::GenericErr { .. } => err.code(),
-            CryptoError::BatchErr { .. } | CryptoError::InvalidRecoveryParam { .. } => {
-                panic!("Error must not happen for this call")
-            }
-        },
-        |valid| if valid { 0 } else { 1 },
-    ))
+#[cfg(feature = "secp256k1_batch")]
+pub fn do_secp256k1_batch_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    hashes_ptr: u32,
+    signatures_ptr: u32,
+    public_keys_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "secp256k1_batch_verify", |trace| {
+        let hashes = env.memory().read_region(
+            hashes_ptr,
+            (MESSAGE_HASH_MAX_LEN + 4) * MAX_COUNT_SECP256K1_BATCH,
+        )?;
+        let signatures = env.memory().read_region(
+            signatures_ptr,
+            (ECDSA_SIGNATURE_LEN + 4) * MAX_COUNT_SECP256K1_BATCH,
+        )?;
+        let public_keys = env.memory().read_region(
+            public_keys_ptr,
+            (ECDSA_PUBKEY_MAX_LEN + 4) * MAX_COUNT_SECP256K1_BATCH,
+        )?;
+        trace.bytes(&hashes);
+        trace.bytes(&signatures);
+        trace.bytes(&public_keys);
+
+        let hashes = try_decode_sections(&hashes, MAX_COUNT_SECP256K1_BATCH)?;
+        let signatures = try_decode_sections(&signatures, MAX_COUNT_SECP256K1_BATCH)?;
+        let public_keys = try_decode_sections(&public_keys, MAX_COUNT_SECP256K1_BATCH)?;
+
+        // Every signature is verified on its own, so we charge before doing the work
+        let gas_cost = if public_keys.len() == 1 {
+            env.gas_config.secp256k1_batch_verify_one_pubkey_cost
+        } else {
+            env.gas_config.secp256k1_batch_verify_cost
+        } * signatures.len() as u64;
+        let gas_info = GasInfo::with_cost(max(gas_cost, env.gas_config.secp256k1_verify_cost));
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+
+        let result = secp256k1_batch_verify(&hashes, &signatures, &public_keys);
+        Ok(result.map_or_else(
+            |err| match err {
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidPubkeyFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::GenericErr { .. } => err.code(),
+                CryptoError::InvalidPoint { .. } | CryptoError::InvalidRecoveryParam { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
+            |valid| (!valid).into(),
+        ))
+    })
 }
 
 pub fn do_secp256k1_recover_pubkey<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
@@ -235,33 +467,122 @@ pub fn do_secp256k1_recover_pubkey<A: BackendApi, S: Storage, Q: Querier, W: Was
     signature_ptr: u32,
     recover_param: u32,
 ) -> VmResult<u64> {
-    let hash = env.memory().read_region(hash_ptr, MESSAGE_HASH_MAX_LEN)?;
-    let signature = env
-        .memory()
-        .read_region(signature_ptr, ECDSA_SIGNATURE_LEN)?;
-    let recover_param: u8 = match recover_param.try_into() {
-        Ok(rp) => rp,
-        Err(_) => return Ok((CryptoError::invalid_recovery_param().code() as u64) << 32),
-    };
+    traced(env, "secp256k1_recover_pubkey", |trace| {
+        let hash = env.memory().read_region(hash_ptr, MESSAGE_HASH_MAX_LEN)?;
+        let signature = env
+            .memory()
+            .read_region(signature_ptr, ECDSA_SIGNATURE_LEN)?;
+        trace.bytes(&hash);
+        trace.bytes(&signature);
+        trace.number(recover_param);
+        let recover_param: u8 = match recover_param.try_into() {
+            Ok(rp) => rp,
+            Err(_) => return Ok((CryptoError::invalid_recovery_param().code() as u64) << 32),
+        };
 
-    let result = secp256k1_recover_pubkey(&hash, &signature, recover_param);
-    let gas_info = GasInfo::with_cost(env.gas_config.secp256k1_recover_pubkey_cost);
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    match result {
-        Ok(pubkey) => {
-            let pubkey_ptr = write_to_contract::<A, S, Q, W>(env, pubkey.as_ref())?;
-            Ok(to_low_half(pubkey_ptr))
+        let result = secp256k1_recover_pubkey(&hash, &signature, recover_param);
+        let gas_info = GasInfo::with_cost(env.gas_config.secp256k1_recover_pubkey_cost);
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        match result {
+            Ok(pubkey) => {
+                trace.result(&pubkey);
+                let pubkey_ptr = write_to_contract::<A, S, Q, W>(env, pubkey.as_ref())?;
+                Ok(to_low_half(pubkey_ptr))
+            }
+            Err(err) => match err {
+                CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::InvalidRecoveryParam { .. }
+                | CryptoError::GenericErr { .. } => Ok(to_high_half(err.code())),
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidPoint { .. }
+                | CryptoError::InvalidPubkeyFormat { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
         }
-        Err(err) => match err {
-            CryptoError::InvalidHashFormat { .. }
-            | CryptoError::InvalidSignatureFormat { .. }
-            | CryptoError::InvalidRecoveryParam { .. }
-            | CryptoError::GenericErr { .. } => Ok(to_high_half(err.code())),
-            CryptoError::BatchErr { .. } | CryptoError::InvalidPubkeyFormat { .. } => {
-                panic!("Error must not happen for this call")
+    })
+}
+
+#[cfg(feature = "secp256r1")]
+pub fn do_secp256r1_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    hash_ptr: u32,
+    signature_ptr: u32,
+    pubkey_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "secp256r1_verify", |trace| {
+        let hash = env.memory().read_region(hash_ptr, MESSAGE_HASH_MAX_LEN)?;
+        let signature = env
+            .memory()
+            .read_region(signature_ptr, ECDSA_SIGNATURE_LEN)?;
+        let pubkey = env.memory().read_region(pubkey_ptr, ECDSA_PUBKEY_MAX_LEN)?;
+        trace.bytes(&hash);
+        trace.bytes(&signature);
+        trace.bytes(&pubkey);
+
+        let result = secp256r1_verify(&hash, &signature, &pubkey);
+        let gas_info = GasInfo::with_cost(env.gas_config.secp256r1_verify_cost);
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        Ok(result.map_or_else(
+            |err| match err {
+                CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidPubkeyFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::GenericErr { .. } => err.code(),
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidPoint { .. }
+                | CryptoError::InvalidRecoveryParam { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
+            |valid| if valid { 0 } else { 1 },
+        ))
+    })
+}
+
+#[cfg(feature = "secp256r1")]
+pub fn do_secp256r1_recover_pubkey<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    hash_ptr: u32,
+    signature_ptr: u32,
+    recover_param: u32,
+) -> VmResult<u64> {
+    traced(env, "secp256r1_recover_pubkey", |trace| {
+        let hash = env.memory().read_region(hash_ptr, MESSAGE_HASH_MAX_LEN)?;
+        let signature = env
+            .memory()
+            .read_region(signature_ptr, ECDSA_SIGNATURE_LEN)?;
+        trace.bytes(&hash);
+        trace.bytes(&signature);
+        trace.number(recover_param);
+        let recover_param: u8 = match recover_param.try_into() {
+            Ok(rp) => rp,
+            Err(_) => return Ok((CryptoError::invalid_recovery_param().code() as u64) << 32),
+        };
+
+        let result = secp256r1_recover_pubkey(&hash, &signature, recover_param);
+        let gas_info = GasInfo::with_cost(env.gas_config.secp256r1_recover_pubkey_cost);
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        match result {
+            Ok(pubkey) => {
+                trace.result(&pubkey);
+                let pubkey_ptr = write_to_contract::<A, S, Q, W>(env, pubkey.as_ref())?;
+                Ok(to_low_half(pubkey_ptr))
             }
-        },
-    }
+            Err(err) => match err {
+                CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::InvalidRecoveryParam { .. }
+                | CryptoError::GenericErr { .. } => Ok(to_high_half(err.code())),
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidPoint { .. }
+                | CryptoError::InvalidPubkeyFormat { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
+        }
+    })
 }
 
 pub fn do_ed25519_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
@@ -270,30 +591,36 @@ pub fn do_ed25519_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     signature_ptr: u32,
     pubkey_ptr: u32,
 ) -> VmResult<u32> {
-    let message = env
-        .memory()
-        .read_region(message_ptr, MAX_LENGTH_ED25519_MESSAGE)?;
-    let signature = env
-        .memory()
-        .read_region(signature_ptr, MAX_LENGTH_ED25519_SIGNATURE)?;
-    let pubkey = env.memory().read_region(pubkey_ptr, EDDSA_PUBKEY_LEN)?;
-
-    let result = ed25519_verify(&message, &signature, &pubkey);
-    let gas_info = GasInfo::with_cost(env.gas_config.ed25519_verify_cost);
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    Ok(result.map_or_else(
-        |err| match err {
-            CryptoError::InvalidPubkeyFormat { .. }
-            | CryptoError::InvalidSignatureFormat { .. }
-            | CryptoError::GenericErr { .. } => err.code(),
-            CryptoError::BatchErr { .. }
-            | CryptoError::InvalidHashFormat { .. }
-            | CryptoError::InvalidRecoveryParam { .. } => {
-                panic!("Error must not happen for this call")
-            }
-        },
-        |valid| if valid { 0 } else { 1 },
-    ))
+    traced(env, "ed25519_verify", |trace| {
+        let message = env
+            .memory()
+            .read_region(message_ptr, MAX_LENGTH_ED25519_MESSAGE)?;
+        let signature = env
+            .memory()
+            .read_region(signature_ptr, MAX_LENGTH_ED25519_SIGNATURE)?;
+        let pubkey = env.memory().read_region(pubkey_ptr, EDDSA_PUBKEY_LEN)?;
+        trace.bytes(&message);
+        trace.bytes(&signature);
+        trace.bytes(&pubkey);
+
+        let result = ed25519_verify(&message, &signature, &pubkey);
+        let gas_info = GasInfo::with_cost(env.gas_config.ed25519_verify_cost);
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        Ok(result.map_or_else(
+            |err| match err {
+                CryptoError::InvalidPubkeyFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::GenericErr { .. } => err.code(),
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidPoint { .. }
+                | CryptoError::InvalidRecoveryParam { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
+            |valid| if valid { 0 } else { 1 },
+        ))
+    })
 }
 
 pub fn do_ed25519_batch_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
@@ -302,57 +629,525 @@ pub fn do_ed25519_batch_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>
     signatures_ptr: u32,
     public_keys_ptr: u32,
 ) -> VmResult<u32> {
-    let messages = env.memory().read_region(
-        messages_ptr,
-        (MAX_LENGTH_ED25519_MESSAGE + 4) * MAX_COUNT_ED25519_BATCH,
-    )?;
-    let signatures = env.memory().read_region(
-        signatures_ptr,
-        (MAX_LENGTH_ED25519_SIGNATURE + 4) * MAX_COUNT_ED25519_BATCH,
-    )?;
-    let public_keys = env.memory().read_region(
-        public_keys_ptr,
-        (EDDSA_PUBKEY_LEN + 4) * MAX_COUNT_ED25519_BATCH,
-    )?;
-
-    let messages = decode_sections(&messages);
-    let signatures = decode_sections(&signatures);
-    let public_keys = decode_sections(&public_keys);
-
-    let result = ed25519_batch_verify(&messages, &signatures, &public_keys);
-    let gas_cost = if public_keys.len() == 1 {
-        env.gas_config.ed25519_batch_verify_one_pubkey_cost
-    } else {
-        env.gas_config.ed25519_batch_verify_cost
-    } * signatures.len() as u64;
-    let gas_info = GasInfo::with_cost(max(gas_cost, env.gas_config.ed25519_verify_cost));
+    traced(env, "ed25519_batch_verify", |trace| {
+        let messages = env.memory().read_region(
+            messages_ptr,
+            (MAX_LENGTH_ED25519_MESSAGE + 4) * MAX_COUNT_ED25519_BATCH,
+        )?;
+        let signatures = env.memory().read_region(
+            signatures_ptr,
+            (MAX_LENGTH_ED25519_SIGNATURE + 4) * MAX_COUNT_ED25519_BATCH,
+        )?;
+        let public_keys = env.memory().read_region(
+            public_keys_ptr,
+            (EDDSA_PUBKEY_LEN + 4) * MAX_COUNT_ED25519_BATCH,
+        )?;
+        trace.bytes(&messages);
+        trace.bytes(&signatures);
+        trace.bytes(&public_keys);
+
+        let messages = try_decode_sections(&messages, MAX_COUNT_ED25519_BATCH)?;
+        let signatures = try_decode_sections(&signatures, MAX_COUNT_ED25519_BATCH)?;
+        let public_keys = try_decode_sections(&public_keys, MAX_COUNT_ED25519_BATCH)?;
+
+        let result = ed25519_batch_verify(&messages, &signatures, &public_keys);
+        let gas_cost = if public_keys.len() == 1 {
+            env.gas_config.ed25519_batch_verify_one_pubkey_cost
+        } else {
+            env.gas_config.ed25519_batch_verify_cost
+        } * signatures.len() as u64;
+        let gas_info = GasInfo::with_cost(max(gas_cost, env.gas_config.ed25519_verify_cost));
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        Ok(result.map_or_else(
+            |err| match err {
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidPubkeyFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::GenericErr { .. } => err.code(),
+                CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidPoint { .. }
+                | CryptoError::InvalidRecoveryParam { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
+            |valid| (!valid).into(),
+        ))
+    })
+}
+
+#[cfg(feature = "sr25519")]
+pub fn do_sr25519_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    message_ptr: u32,
+    signature_ptr: u32,
+    pubkey_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "sr25519_verify", |trace| {
+        let message = env
+            .memory()
+            .read_region(message_ptr, MAX_LENGTH_SR25519_MESSAGE)?;
+        let signature = env
+            .memory()
+            .read_region(signature_ptr, MAX_LENGTH_SR25519_SIGNATURE)?;
+        let pubkey = env.memory().read_region(pubkey_ptr, SR25519_PUBKEY_LEN)?;
+        trace.bytes(&message);
+        trace.bytes(&signature);
+        trace.bytes(&pubkey);
+
+        let result = sr25519_verify(&message, &signature, &pubkey);
+        let gas_info = GasInfo::with_cost(env.gas_config.sr25519_verify_cost);
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        Ok(result.map_or_else(
+            |err| match err {
+                CryptoError::InvalidPubkeyFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::GenericErr { .. } => err.code(),
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidPoint { .. }
+                | CryptoError::InvalidRecoveryParam { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
+            |valid| if valid { 0 } else { 1 },
+        ))
+    })
+}
+
+#[cfg(feature = "secp256k1_schnorr")]
+pub fn do_secp256k1_schnorr_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    message_ptr: u32,
+    signature_ptr: u32,
+    pubkey_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "secp256k1_schnorr_verify", |trace| {
+        let message = env
+            .memory()
+            .read_region(message_ptr, MAX_LENGTH_SECP256K1_SCHNORR_MESSAGE)?;
+        let signature = env
+            .memory()
+            .read_region(signature_ptr, SCHNORR_SIGNATURE_LEN)?;
+        let pubkey = env.memory().read_region(pubkey_ptr, SCHNORR_PUBKEY_LEN)?;
+        trace.bytes(&message);
+        trace.bytes(&signature);
+        trace.bytes(&pubkey);
+
+        let result = secp256k1_schnorr_verify(&message, &signature, &pubkey);
+        let gas_info = GasInfo::with_cost(env.gas_config.secp256k1_schnorr_verify_cost);
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        Ok(result.map_or_else(
+            |err| match err {
+                CryptoError::InvalidPubkeyFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::GenericErr { .. } => err.code(),
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidPoint { .. }
+                | CryptoError::InvalidRecoveryParam { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
+            |valid| if valid { 0 } else { 1 },
+        ))
+    })
+}
+
+/// Aggregates a list of compressed G1 points (encoded with `sections`) into their sum.
+///
+/// Returns a region containing the 48 byte compressed sum in the low half of the result
+/// or an error code in the high half.
+#[cfg(feature = "bls12_381")]
+pub fn do_bls12_381_aggregate_g1<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    points_ptr: u32,
+) -> VmResult<u64> {
+    traced(env, "bls12_381_aggregate_g1", |trace| {
+        aggregate_to_contract(
+            env,
+            trace,
+            points_ptr,
+            BLS12_381_G1_POINT_LEN,
+            env.gas_config.bls12_381_aggregate_g1_cost,
+            |points| bls12_381_aggregate_g1(points).map(|sum| sum.to_vec()),
+        )
+    })
+}
+
+/// Aggregates a list of compressed G2 points (encoded with `sections`) into their sum.
+///
+/// Returns a region containing the 96 byte compressed sum in the low half of the result
+/// or an error code in the high half.
+#[cfg(feature = "bls12_381")]
+pub fn do_bls12_381_aggregate_g2<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    points_ptr: u32,
+) -> VmResult<u64> {
+    traced(env, "bls12_381_aggregate_g2", |trace| {
+        aggregate_to_contract(
+            env,
+            trace,
+            points_ptr,
+            BLS12_381_G2_POINT_LEN,
+            env.gas_config.bls12_381_aggregate_g2_cost,
+            |points| bls12_381_aggregate_g2(points).map(|sum| sum.to_vec()),
+        )
+    })
+}
+
+/// Reads a list of points of length `point_len` from the contract, charges `cost` per point
+/// and writes the aggregated point back to the contract.
+#[cfg(feature = "bls12_381")]
+fn aggregate_to_contract<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    trace: &mut ImportTrace,
+    points_ptr: u32,
+    point_len: usize,
+    cost: LinearGasCost,
+    aggregate: impl FnOnce(&[&[u8]]) -> Result<Vec<u8>, CryptoError>,
+) -> VmResult<u64> {
+    let points = env
+        .memory()
+        .read_region(points_ptr, (point_len + 4) * MAX_COUNT_BLS12_381_AGGREGATE)?;
+    trace.bytes(&points);
+
+    let points = try_decode_sections(&points, MAX_COUNT_BLS12_381_AGGREGATE)?;
+    let gas_info = GasInfo::with_cost(cost.total_cost(points.len() as u64));
     process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    Ok(result.map_or_else(
-        |err| match err {
+
+    match aggregate(&points) {
+        Ok(sum) => {
+            trace.result(&sum);
+            let sum_ptr = write_to_contract::<A, S, Q, W>(env, &sum)?;
+            Ok(to_low_half(sum_ptr))
+        }
+        Err(err) => match err {
             CryptoError::BatchErr { .. }
+            | CryptoError::InvalidPoint { .. }
+            | CryptoError::GenericErr { .. } => Ok(to_high_half(err.code())),
+            CryptoError::InvalidHashFormat { .. }
             | CryptoError::InvalidPubkeyFormat { .. }
             | CryptoError::InvalidSignatureFormat { .. }
-            | CryptoError::GenericErr { .. } => err.code(),
-            CryptoError::InvalidHashFormat { .. } | CryptoError::InvalidRecoveryParam { .. } => {
+            | CryptoError::InvalidRecoveryParam { .. } => {
                 panic!("Error must not happen for this call")
             }
         },
-        |valid| (!valid).into(),
-    ))
+    }
+}
+
+/// Checks the pairing equation e(p_1, q_1) * ... * e(p_n, q_n) = e(r, s) for the
+/// compressed G1 points `ps` and G2 points `qs` (both encoded with `sections`)
+/// and the compressed points `r` (G1) and `s` (G2).
+///
+/// Returns 0 if the equation holds, 1 if it does not and an error code otherwise.
+#[cfg(feature = "bls12_381")]
+pub fn do_bls12_381_pairing_equality<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    ps_ptr: u32,
+    qs_ptr: u32,
+    r_ptr: u32,
+    s_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "bls12_381_pairing_equality", |trace| {
+        let ps = env.memory().read_region(
+            ps_ptr,
+            (BLS12_381_G1_POINT_LEN + 4) * MAX_COUNT_BLS12_381_PAIRING,
+        )?;
+        let qs = env.memory().read_region(
+            qs_ptr,
+            (BLS12_381_G2_POINT_LEN + 4) * MAX_COUNT_BLS12_381_PAIRING,
+        )?;
+        let r = env.memory().read_region(r_ptr, BLS12_381_G1_POINT_LEN)?;
+        let s = env.memory().read_region(s_ptr, BLS12_381_G2_POINT_LEN)?;
+        trace.bytes(&ps);
+        trace.bytes(&qs);
+        trace.bytes(&r);
+        trace.bytes(&s);
+
+        let ps = try_decode_sections(&ps, MAX_COUNT_BLS12_381_PAIRING)?;
+        let qs = try_decode_sections(&qs, MAX_COUNT_BLS12_381_PAIRING)?;
+
+        // Pairing is expensive, so we charge before doing the work
+        let gas_cost = env
+            .gas_config
+            .bls12_381_pairing_equality_cost
+            .total_cost(max(ps.len(), qs.len()) as u64);
+        process_gas_info::<A, S, Q, W>(env, GasInfo::with_cost(gas_cost))?;
+
+        let result = bls12_381_pairing_equality(&ps, &qs, &r, &s);
+        Ok(result.map_or_else(
+            |err| match err {
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidPoint { .. }
+                | CryptoError::GenericErr { .. } => err.code(),
+                CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidPubkeyFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::InvalidRecoveryParam { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
+            |valid| (!valid).into(),
+        ))
+    })
+}
+
+/// Verifies an aggregate BLS signature (G2) of distinct messages by the given public keys (G1).
+/// Public keys and messages are encoded with `sections`. `dst` is the domain separation tag
+/// used for hashing the messages to G2.
+///
+/// Returns 0 if the signature is valid, 1 if it is not and an error code otherwise.
+#[cfg(feature = "bls12_381")]
+pub fn do_bls12_381_aggregate_verify<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    public_keys_ptr: u32,
+    messages_ptr: u32,
+    signature_ptr: u32,
+    dst_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "bls12_381_aggregate_verify", |trace| {
+        let public_keys = env.memory().read_region(
+            public_keys_ptr,
+            (BLS12_381_G1_POINT_LEN + 4) * MAX_COUNT_BLS12_381_PAIRING,
+        )?;
+        let messages = env.memory().read_region(
+            messages_ptr,
+            (MAX_LENGTH_BLS12_381_MESSAGE + 4) * MAX_COUNT_BLS12_381_PAIRING,
+        )?;
+        let signature = env
+            .memory()
+            .read_region(signature_ptr, BLS12_381_G2_POINT_LEN)?;
+        let dst = env
+            .memory()
+            .read_region(dst_ptr, MAX_LENGTH_BLS12_381_DST)?;
+        trace.bytes(&public_keys);
+        trace.bytes(&messages);
+        trace.bytes(&signature);
+        trace.bytes(&dst);
+
+        let public_keys = try_decode_sections(&public_keys, MAX_COUNT_BLS12_381_PAIRING)?;
+        let messages = try_decode_sections(&messages, MAX_COUNT_BLS12_381_PAIRING)?;
+
+        // Pairing is expensive, so we charge before doing the work
+        let gas_cost = env
+            .gas_config
+            .bls12_381_aggregate_verify_cost
+            .total_cost(max(public_keys.len(), messages.len()) as u64);
+        process_gas_info::<A, S, Q, W>(env, GasInfo::with_cost(gas_cost))?;
+
+        let result = bls12_381_aggregate_verify(&public_keys, &messages, &signature, &dst);
+        Ok(result.map_or_else(
+            |err| match err {
+                CryptoError::BatchErr { .. }
+                | CryptoError::InvalidPoint { .. }
+                | CryptoError::InvalidPubkeyFormat { .. }
+                | CryptoError::InvalidSignatureFormat { .. }
+                | CryptoError::GenericErr { .. } => err.code(),
+                CryptoError::InvalidHashFormat { .. }
+                | CryptoError::InvalidRecoveryParam { .. } => {
+                    panic!("Error must not happen for this call")
+                }
+            },
+            |valid| (!valid).into(),
+        ))
+    })
+}
+
+/// Hashes the message to a G1 point using the domain separation tag `dst` and
+/// returns a region containing the 48 byte compressed point
+#[cfg(feature = "bls12_381")]
+pub fn do_bls12_381_hash_to_g1<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    msg_ptr: u32,
+    dst_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "bls12_381_hash_to_g1", |trace| {
+        hash_to_curve_to_contract(
+            env,
+            trace,
+            msg_ptr,
+            dst_ptr,
+            env.gas_config.bls12_381_hash_to_g1_cost,
+            |msg, dst| bls12_381_hash_to_g1(msg, dst).to_vec(),
+        )
+    })
+}
+
+/// Hashes the message to a G2 point using the domain separation tag `dst` and
+/// returns a region containing the 96 byte compressed point
+#[cfg(feature = "bls12_381")]
+pub fn do_bls12_381_hash_to_g2<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    msg_ptr: u32,
+    dst_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "bls12_381_hash_to_g2", |trace| {
+        hash_to_curve_to_contract(
+            env,
+            trace,
+            msg_ptr,
+            dst_ptr,
+            env.gas_config.bls12_381_hash_to_g2_cost,
+            |msg, dst| bls12_381_hash_to_g2(msg, dst).to_vec(),
+        )
+    })
+}
+
+/// Like [`hash_to_contract`] for hash-to-curve functions, which take a domain separation tag
+/// in addition to the message. `cost` is charged per byte of the message.
+#[cfg(feature = "bls12_381")]
+fn hash_to_curve_to_contract<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    trace: &mut ImportTrace,
+    msg_ptr: u32,
+    dst_ptr: u32,
+    cost: LinearGasCost,
+    hash: impl FnOnce(&[u8], &[u8]) -> Vec<u8>,
+) -> VmResult<u32> {
+    let msg = env
+        .memory()
+        .read_region(msg_ptr, MAX_LENGTH_BLS12_381_MESSAGE)?;
+    let dst = env
+        .memory()
+        .read_region(dst_ptr, MAX_LENGTH_BLS12_381_DST)?;
+    trace.bytes(&msg);
+    trace.bytes(&dst);
+
+    let gas_info = GasInfo::with_cost(cost.total_cost(msg.len() as u64));
+    process_gas_info::<A, S, Q, W>(env, gas_info)?;
+
+    let point = hash(&msg, &dst);
+    trace.result(&point);
+    write_to_contract::<A, S, Q, W>(env, &point)
+}
+
+/// Hashes the input with SHA-256 and returns a region containing the 32 byte hash
+#[cfg(feature = "hashes")]
+pub fn do_sha256<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    data_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "sha256", |trace| {
+        hash_to_contract(env, trace, data_ptr, env.gas_config.sha256_cost, |data| {
+            sha256(data).to_vec()
+        })
+    })
+}
+
+/// Hashes the input with Keccak-256 and returns a region containing the 32 byte hash
+#[cfg(feature = "hashes")]
+pub fn do_keccak256<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    data_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "keccak256", |trace| {
+        hash_to_contract(
+            env,
+            trace,
+            data_ptr,
+            env.gas_config.keccak256_cost,
+            |data| keccak256(data).to_vec(),
+        )
+    })
+}
+
+/// Hashes the input with RIPEMD-160 and returns a region containing the 20 byte hash
+#[cfg(feature = "hashes")]
+pub fn do_ripemd160<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    data_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "ripemd160", |trace| {
+        hash_to_contract(
+            env,
+            trace,
+            data_ptr,
+            env.gas_config.ripemd160_cost,
+            |data| ripemd160(data).to_vec(),
+        )
+    })
+}
+
+/// Hashes the input with BLAKE2b and returns a region containing the 64 byte hash
+#[cfg(feature = "hashes")]
+pub fn do_blake2b<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    data_ptr: u32,
+) -> VmResult<u32> {
+    traced(env, "blake2b", |trace| {
+        hash_to_contract(env, trace, data_ptr, env.gas_config.blake2b_cost, |data| {
+            blake2b(data).to_vec()
+        })
+    })
+}
+
+/// Reads the input of a hash function from the contract, charges `cost` per byte of it
+/// and writes the hash back to the contract.
+#[cfg(feature = "hashes")]
+fn hash_to_contract<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    trace: &mut ImportTrace,
+    data_ptr: u32,
+    cost: LinearGasCost,
+    hash: impl FnOnce(&[u8]) -> Vec<u8>,
+) -> VmResult<u32> {
+    let data = env.memory().read_region(data_ptr, MAX_LENGTH_HASH_INPUT)?;
+    trace.bytes(&data);
+
+    let gas_info = GasInfo::with_cost(cost.total_cost(data.len() as u64));
+    process_gas_info::<A, S, Q, W>(env, gas_info)?;
+
+    let out_data = hash(&data);
+    trace.result(&out_data);
+    write_to_contract::<A, S, Q, W>(env, &out_data)
 }
 
 /// Prints a debug message to console.
-/// This does not charge gas, so debug printing should be disabled when used in a blockchain module.
+/// Gas is charged by message length no matter if printing is enabled or not. Still, debug
+/// printing should be disabled when used in a blockchain module.
 pub fn do_debug<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     env: &Environment<A, S, Q, W>,
     message_ptr: u32,
 ) -> VmResult<()> {
-    if env.print_debug {
-        let message_data = env.memory().read_region(message_ptr, MAX_LENGTH_DEBUG)?;
+    traced(env, "debug", |trace| {
+        // Only the Region is read here such that long messages do not cause an error
+        // when printing and tracing are disabled.
+        let message_length = env.memory().get_region(message_ptr)?.length;
+        let gas_info =
+            GasInfo::with_cost(env.gas_config.debug_cost.total_cost(message_length as u64));
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+
+        if env.print_debug || trace.is_enabled() {
+            let message_data = env.memory().read_region(message_ptr, MAX_LENGTH_DEBUG)?;
+            trace.bytes(&message_data);
+            if env.print_debug {
+                let msg = String::from_utf8_lossy(&message_data);
+                println!("{}", msg);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Aborts the contract execution with the given message.
+/// cosmwasm-std calls this from its panic handler with the panic message and location,
+/// which is then returned to the caller as a `VmError::Aborted`.
+pub fn do_abort<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    message_ptr: u32,
+) -> VmResult<()> {
+    traced(env, "abort", |trace| {
+        // Charged like debug messages since the message is read and copied into the error
+        let message_length = env.memory().get_region(message_ptr)?.length;
+        let gas_info =
+            GasInfo::with_cost(env.gas_config.debug_cost.total_cost(message_length as u64));
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+
+        let message_data = env.memory().read_region(message_ptr, MAX_LENGTH_ABORT)?;
+        trace.bytes(&message_data);
         let msg = String::from_utf8_lossy(&message_data);
-        println!("{}", msg);
-    }
-    Ok(())
+        Err(VmError::aborted(msg))
+    })
 }
 
 /// Creates a Region in the contract, writes the given data to it and returns the memory location
@@ -370,21 +1165,36 @@ fn write_to_contract<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     Ok(target_ptr)
 }
 
+/// Like [`write_to_contract`] for error messages that are reported back to the contract.
+/// The message is recorded as the result of the traced call.
+fn write_error_to_contract<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    trace: &mut ImportTrace,
+    message: &[u8],
+) -> VmResult<u32> {
+    trace.result(message);
+    write_to_contract::<A, S, Q, W>(env, message)
+}
+
 pub fn do_query_chain<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     env: &Environment<A, S, Q, W>,
     request_ptr: u32,
 ) -> VmResult<u32> {
-    let request = env
-        .memory()
-        .read_region(request_ptr, MAX_LENGTH_QUERY_CHAIN_REQUEST)?;
-
-    let gas_remaining = env.get_gas_left();
-    let (result, gas_info) = env.with_querier_from_context::<_, _>(|querier| {
-        Ok(querier.query_raw(&request, gas_remaining))
-    })?;
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    let serialized = to_vec(&result?)?;
-    write_to_contract::<A, S, Q, W>(env, &serialized)
+    traced(env, "query_chain", |trace| {
+        let request = env
+            .memory()
+            .read_region(request_ptr, MAX_LENGTH_QUERY_CHAIN_REQUEST)?;
+        trace.bytes(&request);
+
+        let gas_remaining = env.get_gas_left();
+        let (result, gas_info) = env.with_querier_from_context::<_, _>(|querier| {
+            Ok(querier.query_raw(&request, gas_remaining))
+        })?;
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        let serialized = to_vec(&result?)?;
+        trace.result(&serialized);
+        write_to_contract::<A, S, Q, W>(env, &serialized)
+    })
 }
 
 #[cfg(feature = "iterator")]
@@ -394,20 +1204,30 @@ pub fn do_db_scan<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     end_ptr: u32,
     order: i32,
 ) -> VmResult<u32> {
-    let start = env
-        .memory()
-        .maybe_read_region(start_ptr, MAX_LENGTH_DB_KEY)?;
-    let end = env.memory().maybe_read_region(end_ptr, MAX_LENGTH_DB_KEY)?;
-    let order: Order = order
-        .try_into()
-        .map_err(|_| CommunicationError::invalid_order(order))?;
-
-    let (result, gas_info) = env.with_storage_from_context::<_, _>(|store| {
-        Ok(store.scan(start.as_deref(), end.as_deref(), order))
-    })?;
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
-    let iterator_id = result?;
-    Ok(iterator_id)
+    traced(env, "db_scan", |trace| {
+        let start = env
+            .memory()
+            .maybe_read_region(start_ptr, MAX_LENGTH_DB_KEY)?;
+        let end = env.memory().maybe_read_region(end_ptr, MAX_LENGTH_DB_KEY)?;
+        trace.maybe_bytes(start.as_deref());
+        trace.maybe_bytes(end.as_deref());
+        trace.number(order);
+        let order: Order = order
+            .try_into()
+            .map_err(|_| CommunicationError::invalid_order(order))?;
+        let keys_length =
+            start.as_ref().map_or(0, |s| s.len()) + end.as_ref().map_or(0, |e| e.len());
+        let gas_info =
+            GasInfo::with_cost(env.gas_config.db_scan_cost.total_cost(keys_length as u64));
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+
+        let (result, gas_info) = env.with_storage_from_context::<_, _>(|store| {
+            Ok(store.scan(start.as_deref(), end.as_deref(), order))
+        })?;
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        let iterator_id = result?;
+        Ok(iterator_id)
+    })
 }
 
 #[cfg(feature = "iterator")]
@@ -415,29 +1235,127 @@ pub fn do_db_next<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
     env: &Environment<A, S, Q, W>,
     iterator_id: u32,
 ) -> VmResult<u32> {
-    let (result, gas_info) =
-        env.with_storage_from_context::<_, _>(|store| Ok(store.next(iterator_id)))?;
-    process_gas_info::<A, S, Q, W>(env, gas_info)?;
+    traced(env, "db_next", |trace| {
+        trace.number(iterator_id);
+        let (result, gas_info) =
+            env.with_storage_from_context::<_, _>(|store| Ok(store.next(iterator_id)))?;
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+
+        // Empty key will later be treated as _no more element_.
+        let (key, value) = result?.unwrap_or_else(|| (Vec::<u8>::new(), Vec::<u8>::new()));
+        let gas_info = GasInfo::with_cost(
+            env.gas_config
+                .db_next_cost
+                .total_cost((key.len() + value.len()) as u64),
+        );
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
 
-    // Empty key will later be treated as _no more element_.
-    let (key, value) = result?.unwrap_or_else(|| (Vec::<u8>::new(), Vec::<u8>::new()));
+        let out_data = encode_sections(&[key, value])?;
+        trace.result(&out_data);
+        write_to_contract::<A, S, Q, W>(env, &out_data)
+    })
+}
 
-    let out_data = encode_sections(&[key, value])?;
-    write_to_contract::<A, S, Q, W>(env, &out_data)
+/// Returns the key of the next element of the iterator, or 0 if there are no more elements
+#[cfg(all(feature = "iterator", feature = "extended_storage"))]
+pub fn do_db_next_key<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    iterator_id: u32,
+) -> VmResult<u32> {
+    traced(env, "db_next_key", |trace| {
+        trace.number(iterator_id);
+        let (result, gas_info) =
+            env.with_storage_from_context::<_, _>(|store| Ok(store.next_key(iterator_id)))?;
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        write_next_part_to_contract(env, trace, result?)
+    })
 }
 
-/// Returns the data shifted by 32 bits towards the most significant bit.
-///
-/// This is independent of endianness. But to get the idea, it would be
-/// `data || 0x00000000` in big endian representation.
-#[inline]
-fn to_high_half(data: u32) -> u64 {
-    // See https://stackoverflow.com/a/58956419/2013738 to understand
-    // why this is endianness agnostic.
-    (data as u64) << 32
+/// Returns the value of the next element of the iterator, or 0 if there are no more elements
+#[cfg(all(feature = "iterator", feature = "extended_storage"))]
+pub fn do_db_next_value<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    iterator_id: u32,
+) -> VmResult<u32> {
+    traced(env, "db_next_value", |trace| {
+        trace.number(iterator_id);
+        let (result, gas_info) =
+            env.with_storage_from_context::<_, _>(|store| Ok(store.next_value(iterator_id)))?;
+        process_gas_info::<A, S, Q, W>(env, gas_info)?;
+        write_next_part_to_contract(env, trace, result?)
+    })
 }
 
-/// Returns the data copied to the 4 least significant bytes.
+/// Charges the iterator step for the key or value returned by `db_next_key`/`db_next_value`
+/// and writes it to the contract.
+#[cfg(all(feature = "iterator", feature = "extended_storage"))]
+fn write_next_part_to_contract<A: BackendApi, S: Storage, Q: Querier, W: WasmVM>(
+    env: &Environment<A, S, Q, W>,
+    trace: &mut ImportTrace,
+    part: Option<Vec<u8>>,
+) -> VmResult<u32> {
+    let out_data = part.unwrap_or_default();
+    let gas_info = GasInfo::with_cost(
+        env.gas_config
+            .db_next_cost
+            .total_cost(out_data.len() as u64),
+    );
+    process_gas_info::<A, S, Q, W>(env, gas_info)?;
+
+    trace.result(&out_data);
+    // Empty data is treated as _no more element_. The contract gets a null pointer in this case.
+    if out_data.is_empty() {
+        return Ok(0);
+    }
+    write_to_contract::<A, S, Q, W>(env, &out_data)
+}
+
+/// Runs an import implementation and reports the call to the instance's tracer,
+/// including the gas used in between.
+fn traced<A: BackendApi, S: Storage, Q: Querier, W: WasmVM, T>(
+    env: &Environment<A, S, Q, W>,
+    import: &'static str,
+    implementation: impl FnOnce(&mut ImportTrace) -> VmResult<T>,
+) -> VmResult<T> {
+    let tracer = env.tracer();
+    if !tracer.enabled() {
+        return implementation(&mut ImportTrace::new(false));
+    }
+
+    let gas_left_before = env.get_gas_left();
+    let externally_used_before = env.with_gas_state(|gas_state| gas_state.externally_used_gas);
+
+    let mut trace = ImportTrace::new(true);
+    let result = implementation(&mut trace);
+
+    let gas_used = gas_left_before.saturating_sub(env.get_gas_left());
+    let gas_used_externally = env
+        .with_gas_state(|gas_state| gas_state.externally_used_gas)
+        .saturating_sub(externally_used_before);
+    let (args, result_size) = trace.into_parts();
+    tracer.trace(&TraceEvent {
+        import,
+        args,
+        result_size,
+        gas_used_internally: gas_used.saturating_sub(gas_used_externally),
+        gas_used_externally,
+        error: result.as_ref().err().map(|err| err.to_string()),
+    });
+    result
+}
+
+/// Returns the data shifted by 32 bits towards the most significant bit.
+///
+/// This is independent of endianness. But to get the idea, it would be
+/// `data || 0x00000000` in big endian representation.
+#[inline]
+fn to_high_half(data: u32) -> u64 {
+    // See https://stackoverflow.com/a/58956419/2013738 to understand
+    // why this is endianness agnostic.
+    (data as u64) << 32
+}
+
+/// Returns the data copied to the 4 least significant bytes.
 ///
 /// This is independent of endianness. But to get the idea, it would be
 /// `0x00000000 || data` in big endian representation.
@@ -458,9 +1376,10 @@ mod tests {
     use wasmer::{imports, Function, Instance as WasmerInstance};
 
     use crate::backend::{BackendError, Storage};
+    use crate::environment::GasConfig;
     use crate::size::Size;
     use crate::testing::{MockApi, MockQuerier, MockStorage};
-    use crate::wasm_backend::compile;
+    use crate::wasm_backend::{compile, OperatorCostTable};
 
     static CONTRACT: &[u8] = include_bytes!("../testdata/hackatom.wasm");
 
@@ -476,27 +1395,90 @@ mod tests {
     const INIT_DENOM: &str = "TOKEN";
 
     const TESTING_GAS_LIMIT: u64 = 500_000_000_000; // ~0.5ms
+    /// A higher limit for tests of imports that cost more than TESTING_GAS_LIMIT in total,
+    /// like secp256r1 or BLS12-381 operations and batch verifications
+    #[cfg(any(
+        feature = "secp256r1",
+        feature = "secp256k1_batch",
+        feature = "bls12_381"
+    ))]
+    const EXPENSIVE_TESTING_GAS_LIMIT: u64 = 5_000_000_000_000; // ~5ms
     const TESTING_MEMORY_LIMIT: Option<Size> = Some(Size::mebi(16));
 
     const ECDSA_HASH_HEX: &str = "5ae8317d34d1e595e3fa7247db80c0af4320cce1116de187f8f7e2e099c0d8d0";
     const ECDSA_SIG_HEX: &str = "207082eb2c3dfa0b454e0906051270ba4074ac93760ba9e7110cd9471475111151eb0dbbc9920e72146fb564f99d039802bf6ef2561446eb126ef364d21ee9c4";
     const ECDSA_PUBKEY_HEX: &str = "04051c1ee2190ecfb174bfe4f90763f2b4ff7517b70a2aec1876ebcfd644c4633fb03f3cfbd94b1f376e34592d9d41ccaf640bb751b00a1fadeb0c01157769eb73";
 
+    // Test vector from RFC 6979, A.2.5 (ECDSA, 256 Bits (Prime Field)), with SHA-256 and message "sample"
+    #[cfg(feature = "secp256r1")]
+    const SECP256R1_HASH_HEX: &str =
+        "af2bdbe1aa9b6ec1e2ade1d694f41fc71a831d0268e9891562113d8a62add1bf";
+    #[cfg(feature = "secp256r1")]
+    const SECP256R1_SIG_HEX: &str = "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8";
+    #[cfg(feature = "secp256r1")]
+    const SECP256R1_PUBKEY_HEX: &str = "0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299";
+
+    // Generated with blst from the key material [1u8; 32] and [2u8; 32]
+    #[cfg(feature = "bls12_381")]
+    const BLS12_381_DST_G2: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+    #[cfg(feature = "bls12_381")]
+    const BLS12_381_PUBKEY1_HEX: &str = "95a254501b7733239ed3cec4d56737977bd09ede881d8a234560e83e5525017add3b1dcc3eabfb85e12a4131b19c253b";
+    #[cfg(feature = "bls12_381")]
+    const BLS12_381_PUBKEY2_HEX: &str = "ac80a5e08c712d5f08f0306ad743f7d8c215d982489b84a1d6ba805733d94c006e8938f9089a75db3ffa135af33bc69a";
+    #[cfg(feature = "bls12_381")]
+    const BLS12_381_PUBKEY_SUM_HEX: &str = "af9c7a267f7990fc590f743837a3b7e5c171128f0127279e8df2886ccfef3439c5d77f4330bb1a9659af3e378d6a161c";
+    // Aggregate signature of "block 1" by key 1 and "block 2" by key 2
+    #[cfg(feature = "bls12_381")]
+    const BLS12_381_AGGREGATE_SIG_HEX: &str = "b680170e37ab63624d0b166031c13d55a807b38277cfa10262b9c82fe1e4e758eb63527a5df49adf54ce584ba00eb45b1631b7980bdcaa9eddc6db0019333084dfa94cedce9405cc53611217a25c7b2d5717af77424b820563c1293496fda35d";
+
     const EDDSA_MSG_HEX: &str = "";
     const EDDSA_SIG_HEX: &str = "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b";
     const EDDSA_PUBKEY_HEX: &str =
         "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
 
+    #[cfg(feature = "sr25519")]
+    const SR25519_MSG: &[u8] = b"cosmwasm";
+    #[cfg(feature = "sr25519")]
+    const SR25519_SIG_HEX: &str = "fce73cea3fcc441e702a09d189a022e258eeaf49961e74b9b9e1642db618420df2187c1edf8ef0e5adcf50875cb1aa33e9421b71a97baf268a0cdb81a97f0f86";
+    #[cfg(feature = "sr25519")]
+    const SR25519_PUBKEY_HEX: &str =
+        "7c0f469d3bd340bae718203fa30ca071a5e37c751e891dbded837b213d45d91d";
+
+    // BIP-340 test vector 1
+    #[cfg(feature = "secp256k1_schnorr")]
+    const SCHNORR_MSG_HEX: &str =
+        "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89";
+    #[cfg(feature = "secp256k1_schnorr")]
+    const SCHNORR_SIG_HEX: &str = "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a";
+    #[cfg(feature = "secp256k1_schnorr")]
+    const SCHNORR_PUBKEY_HEX: &str =
+        "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659";
+
     fn make_instance(
         api: MockApi,
     ) -> (
         Environment<MockApi, MockStorage, MockQuerier, WasmerInstance>,
         Box<WasmerInstance>,
     ) {
-        let gas_limit = TESTING_GAS_LIMIT;
-        let env = Environment::new(api, gas_limit, false);
+        make_instance_with_gas_limit(api, TESTING_GAS_LIMIT)
+    }
+
+    fn make_instance_with_gas_limit(
+        api: MockApi,
+        gas_limit: u64,
+    ) -> (
+        Environment<MockApi, MockStorage, MockQuerier, WasmerInstance>,
+        Box<WasmerInstance>,
+    ) {
+        let env = Environment::new(api, gas_limit, false, GasConfig::default());
 
-        let module = compile(CONTRACT, TESTING_MEMORY_LIMIT, &[]).unwrap();
+        let module = compile(
+            CONTRACT,
+            TESTING_MEMORY_LIMIT,
+            &OperatorCostTable::default(),
+            &[],
+        )
+        .unwrap();
         let store = module.store();
         // we need stubs for all required imports
         let import_obj = imports! {
@@ -611,6 +1593,85 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "extended_storage")]
+    fn do_db_read_many_works() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+        leave_default_data(&env);
+
+        let keys = encode_sections(&[KEY2.to_vec(), b"nope".to_vec(), KEY1.to_vec()]).unwrap();
+        let keys_ptr = write_data(&env, &keys);
+        let values_ptr = do_db_read_many(&env, keys_ptr).unwrap();
+        assert_eq!(
+            force_read(&env, values_ptr),
+            [VALUE2, b"\0\0\0\x05", b"\0\0\0\0", VALUE1, b"\0\0\0\x06"].concat()
+        );
+
+        // no keys
+        let keys_ptr = write_data(&env, b"");
+        let values_ptr = do_db_read_many(&env, keys_ptr).unwrap();
+        assert_eq!(force_read(&env, values_ptr), b"");
+    }
+
+    #[test]
+    #[cfg(feature = "extended_storage")]
+    fn do_db_read_many_fails_for_too_many_keys() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+        leave_default_data(&env);
+
+        let keys = vec![KEY1.to_vec(); MAX_COUNT_DB_READ_MANY + 1];
+        let keys_ptr = write_data(&env, &encode_sections(&keys).unwrap());
+        match do_db_read_many(&env, keys_ptr).unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::TooManySections { count, max_count },
+                ..
+            } => {
+                assert_eq!(count, MAX_COUNT_DB_READ_MANY + 1);
+                assert_eq!(max_count, MAX_COUNT_DB_READ_MANY);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "extended_storage")]
+    fn do_db_read_many_fails_for_invalid_section_length() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+        leave_default_data(&env);
+
+        // the length suffix claims more bytes than there are in front of it
+        let keys_ptr = write_data(&env, b"\xAA\0\0\x01\x00");
+        match do_db_read_many(&env, keys_ptr).unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::InvalidSectionLength { length, remaining },
+                ..
+            } => {
+                assert_eq!(length, 256);
+                assert_eq!(remaining, 1);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "extended_storage")]
+    fn do_db_read_many_charges_by_keys_length() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+        leave_default_data(&env);
+
+        // all keys are missing, so only the keys are charged by the VM
+        let keys = encode_sections(&[b"nope".to_vec(), b"nada".to_vec()]).unwrap();
+        let keys_ptr = write_data(&env, &keys);
+        let gas_before = env.get_gas_left();
+        do_db_read_many(&env, keys_ptr).unwrap();
+        let used = gas_before - env.get_gas_left();
+        assert!(used >= GasConfig::default().db_read_many_cost.total_cost(16));
+    }
+
     #[test]
     fn do_db_write_works() {
         let api = MockApi::default();
@@ -855,6 +1916,29 @@ mod tests {
         assert_eq!(res, 0);
     }
 
+    #[test]
+    fn do_addr_validate_charges_by_input_length() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let source_ptr1 = write_data(&env, b"foo");
+        let source_ptr2 = write_data(&env, b"foobar");
+
+        leave_default_data(&env);
+
+        let gas_before = env.get_gas_left();
+        do_addr_validate(&env, source_ptr1).unwrap();
+        let used1 = gas_before - env.get_gas_left();
+
+        let gas_before = env.get_gas_left();
+        do_addr_validate(&env, source_ptr2).unwrap();
+        let used2 = gas_before - env.get_gas_left();
+
+        let cost = GasConfig::default().addr_validate_cost;
+        assert!(used1 >= cost.total_cost(3));
+        assert_eq!(used2 - used1, 3 * cost.per_item);
+    }
+
     #[test]
     fn do_addr_validate_reports_invalid_input_back_to_contract() {
         let api = MockApi::default();
@@ -1256,408 +2340,1023 @@ mod tests {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
-        let hash_ptr = write_data(&env, &hash);
-        let mut sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let hash_ptr = write_data(&env, &hash);
+        let mut sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        // extend / break sig
+        sig.push(0x00);
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        let result = do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr);
+        match result.unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::RegionLengthTooBig { length, .. },
+                ..
+            } => assert_eq!(length, ECDSA_SIGNATURE_LEN + 1),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn do_secp256k1_verify_shorter_sig_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let hash_ptr = write_data(&env, &hash);
+        let mut sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        // reduce / break sig
+        sig.pop();
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            4 // mapped InvalidSignatureFormat
+        )
+    }
+
+    #[test]
+    fn do_secp256k1_verify_wrong_pubkey_format_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let hash_ptr = write_data(&env, &hash);
+        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let mut pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        // alter pubkey format
+        pubkey[0] ^= 0x01;
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            5 // mapped InvalidPubkeyFormat
+        )
+    }
+
+    #[test]
+    fn do_secp256k1_verify_wrong_pubkey_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let hash_ptr = write_data(&env, &hash);
+        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let mut pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        // alter pubkey
+        pubkey[1] ^= 0x01;
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            10 // mapped GenericErr
+        )
+    }
+
+    #[test]
+    fn do_secp256k1_verify_larger_pubkey_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let hash_ptr = write_data(&env, &hash);
+        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let mut pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        // extend / break pubkey
+        pubkey.push(0x00);
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        let result = do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr);
+        match result.unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::RegionLengthTooBig { length, .. },
+                ..
+            } => assert_eq!(length, ECDSA_PUBKEY_MAX_LEN + 1),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn do_secp256k1_verify_shorter_pubkey_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let hash_ptr = write_data(&env, &hash);
+        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let mut pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        // reduce / break pubkey
+        pubkey.pop();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            5 // mapped InvalidPubkeyFormat
+        )
+    }
+
+    #[test]
+    fn do_secp256k1_verify_empty_pubkey_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let hash_ptr = write_data(&env, &hash);
+        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = vec![];
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            5 // mapped InvalidPubkeyFormat
+        )
+    }
+
+    #[test]
+    fn do_secp256k1_verify_wrong_data_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let hash = vec![0x22; MESSAGE_HASH_MAX_LEN];
+        let hash_ptr = write_data(&env, &hash);
+        let sig = vec![0x22; ECDSA_SIGNATURE_LEN];
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = vec![0x04; ECDSA_PUBKEY_MAX_LEN];
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            10 // mapped GenericErr
+        )
+    }
+
+    #[cfg(feature = "secp256k1_batch")]
+    #[test]
+    fn do_secp256k1_batch_verify_works() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance_with_gas_limit(api, EXPENSIVE_TESTING_GAS_LIMIT);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+
+        // Equal number of hashes, signatures and public keys
+        let hashes_ptr = write_data(
+            &env,
+            &encode_sections(&[hash.clone(), hash.clone()]).unwrap(),
+        );
+        let sigs_ptr = write_data(&env, &encode_sections(&[sig.clone(), sig.clone()]).unwrap());
+        let pubkeys_ptr = write_data(
+            &env,
+            &encode_sections(&[pubkey.clone(), pubkey.clone()]).unwrap(),
+        );
+        assert_eq!(
+            do_secp256k1_batch_verify(&env, hashes_ptr, sigs_ptr, pubkeys_ptr).unwrap(),
+            0
+        );
+
+        // One public key
+        let pubkeys_ptr = write_data(&env, &encode_sections(&[pubkey]).unwrap());
+        assert_eq!(
+            do_secp256k1_batch_verify(&env, hashes_ptr, sigs_ptr, pubkeys_ptr).unwrap(),
+            0
+        );
+
+        // One hash
+        let hashes_ptr = write_data(&env, &encode_sections(&[hash]).unwrap());
+        assert_eq!(
+            do_secp256k1_batch_verify(&env, hashes_ptr, sigs_ptr, pubkeys_ptr).unwrap(),
+            0
+        );
+    }
+
+    #[cfg(feature = "secp256k1_batch")]
+    #[test]
+    fn do_secp256k1_batch_verify_wrong_sig_verify_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let mut wrong_sig = sig.clone();
+        // alter sig
+        wrong_sig[0] ^= 0x01;
+        let pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+
+        let hashes_ptr = write_data(&env, &encode_sections(&[hash]).unwrap());
+        let sigs_ptr = write_data(&env, &encode_sections(&[sig, wrong_sig]).unwrap());
+        let pubkeys_ptr = write_data(&env, &encode_sections(&[pubkey.clone(), pubkey]).unwrap());
+        assert_eq!(
+            do_secp256k1_batch_verify(&env, hashes_ptr, sigs_ptr, pubkeys_ptr).unwrap(),
+            1
+        );
+    }
+
+    #[cfg(feature = "secp256k1_batch")]
+    #[test]
+    fn do_secp256k1_batch_verify_errors() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance_with_gas_limit(api, EXPENSIVE_TESTING_GAS_LIMIT);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        let hashes_ptr = write_data(&env, &encode_sections(&[hash.clone()]).unwrap());
+        let sigs_ptr = write_data(&env, &encode_sections(&[sig.clone()]).unwrap());
+        let pubkeys_ptr = write_data(&env, &encode_sections(&[pubkey.clone()]).unwrap());
+
+        // mismatched number of hashes / signatures / public keys
+        let two_hashes_ptr = write_data(
+            &env,
+            &encode_sections(&[hash.clone(), hash.clone()]).unwrap(),
+        );
+        let two_pubkeys_ptr = write_data(
+            &env,
+            &encode_sections(&[pubkey.clone(), pubkey.clone()]).unwrap(),
+        );
+        assert_eq!(
+            do_secp256k1_batch_verify(&env, two_hashes_ptr, sigs_ptr, two_pubkeys_ptr).unwrap(),
+            7 // mapped BatchErr
+        );
+
+        // shorter hash
+        let short_hashes_ptr = write_data(&env, &encode_sections(&[hash[1..].to_vec()]).unwrap());
+        assert_eq!(
+            do_secp256k1_batch_verify(&env, short_hashes_ptr, sigs_ptr, pubkeys_ptr).unwrap(),
+            3 // mapped InvalidHashFormat
+        );
+
+        // shorter signature
+        let short_sigs_ptr = write_data(&env, &encode_sections(&[sig[1..].to_vec()]).unwrap());
+        assert_eq!(
+            do_secp256k1_batch_verify(&env, hashes_ptr, short_sigs_ptr, pubkeys_ptr).unwrap(),
+            4 // mapped InvalidSignatureFormat
+        );
+
+        // wrong public key format
+        let mut wrong_pubkey = pubkey;
+        wrong_pubkey[0] = 0x01;
+        let wrong_pubkeys_ptr = write_data(&env, &encode_sections(&[wrong_pubkey]).unwrap());
+        assert_eq!(
+            do_secp256k1_batch_verify(&env, hashes_ptr, sigs_ptr, wrong_pubkeys_ptr).unwrap(),
+            5 // mapped InvalidPubkeyFormat
+        );
+    }
+
+    #[cfg(feature = "secp256k1_batch")]
+    #[test]
+    fn do_secp256k1_batch_verify_fails_for_invalid_sections() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        let hashes_ptr = write_data(&env, &encode_sections(&[hash]).unwrap());
+        let pubkeys_ptr = write_data(&env, &encode_sections(&[pubkey]).unwrap());
+
+        // the length suffix claims more bytes than there are in front of it
+        let malformed_sigs_ptr = write_data(&env, &[sig.as_slice(), b"\0\0\x01\0"].concat());
+        match do_secp256k1_batch_verify(&env, hashes_ptr, malformed_sigs_ptr, pubkeys_ptr)
+            .unwrap_err()
+        {
+            VmError::CommunicationErr {
+                source: CommunicationError::InvalidSectionLength { length, remaining },
+                ..
+            } => {
+                assert_eq!(length, 256);
+                assert_eq!(remaining, 64);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // empty sections fit into the region many times
+        let empty_sigs_ptr = write_data(&env, &vec![0u8; 4 * (MAX_COUNT_SECP256K1_BATCH + 1)]);
+        match do_secp256k1_batch_verify(&env, hashes_ptr, empty_sigs_ptr, pubkeys_ptr).unwrap_err()
+        {
+            VmError::CommunicationErr {
+                source: CommunicationError::TooManySections { count, max_count },
+                ..
+            } => {
+                assert_eq!(count, MAX_COUNT_SECP256K1_BATCH + 1);
+                assert_eq!(max_count, MAX_COUNT_SECP256K1_BATCH);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[cfg(feature = "secp256k1_batch")]
+    #[test]
+    fn do_secp256k1_batch_verify_charges_per_signature() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance_with_gas_limit(api, EXPENSIVE_TESTING_GAS_LIMIT);
+
+        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
+        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        let hashes_ptr = write_data(&env, &encode_sections(&[hash]).unwrap());
+        let sigs_ptr = write_data(&env, &encode_sections(&vec![sig; 4]).unwrap());
+        let pubkeys_ptr = write_data(&env, &encode_sections(&vec![pubkey.clone(); 4]).unwrap());
+        let one_pubkey_ptr = write_data(&env, &encode_sections(&[pubkey]).unwrap());
+        let gas_config = GasConfig::default();
+
+        let gas_before = env.get_gas_left();
+        do_secp256k1_batch_verify(&env, hashes_ptr, sigs_ptr, pubkeys_ptr).unwrap();
+        let used = gas_before - env.get_gas_left();
+        assert_eq!(used, 4 * gas_config.secp256k1_batch_verify_cost);
+        assert!(used < 4 * gas_config.secp256k1_verify_cost);
+
+        let gas_before = env.get_gas_left();
+        do_secp256k1_batch_verify(&env, hashes_ptr, sigs_ptr, one_pubkey_ptr).unwrap();
+        let used = gas_before - env.get_gas_left();
+        assert_eq!(used, 4 * gas_config.secp256k1_batch_verify_one_pubkey_cost);
+    }
+
+    #[test]
+    fn do_secp256k1_recover_pubkey_works() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        // https://gist.github.com/webmaster128/130b628d83621a33579751846699ed15
+        let hash = hex!("5ae8317d34d1e595e3fa7247db80c0af4320cce1116de187f8f7e2e099c0d8d0");
+        let sig = hex!("45c0b7f8c09a9e1f1cea0c25785594427b6bf8f9f878a8af0b1abbb48e16d0920d8becd0c220f67c51217eecfd7184ef0732481c843857e6bc7fc095c4f6b788");
+        let recovery_param = 1;
+        let expected = hex!("044a071e8a6e10aada2b8cf39fa3b5fb3400b04e99ea8ae64ceea1a977dbeaf5d5f8c8fbd10b71ab14cd561f7df8eb6da50f8a8d81ba564342244d26d1d4211595");
+
+        let hash_ptr = write_data(&env, &hash);
+        let sig_ptr = write_data(&env, &sig);
+        let result = do_secp256k1_recover_pubkey(&env, hash_ptr, sig_ptr, recovery_param).unwrap();
+        let error = result >> 32;
+        let pubkey_ptr: u32 = (result & 0xFFFFFFFF).try_into().unwrap();
+        assert_eq!(error, 0);
+        assert_eq!(force_read(&env, pubkey_ptr), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "secp256r1")]
+    fn do_secp256r1_verify_works() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance_with_gas_limit(api, EXPENSIVE_TESTING_GAS_LIMIT);
+
+        let hash = hex::decode(SECP256R1_HASH_HEX).unwrap();
+        let hash_ptr = write_data(&env, &hash);
+        let sig = hex::decode(SECP256R1_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(SECP256R1_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256r1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "secp256r1")]
+    fn do_secp256r1_verify_wrong_hash_verify_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance_with_gas_limit(api, EXPENSIVE_TESTING_GAS_LIMIT);
+
+        let mut hash = hex::decode(SECP256R1_HASH_HEX).unwrap();
+        // alter hash
+        hash[0] ^= 0x01;
+        let hash_ptr = write_data(&env, &hash);
+        let sig = hex::decode(SECP256R1_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(SECP256R1_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256r1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "secp256r1")]
+    fn do_secp256r1_verify_wrong_pubkey_format_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance_with_gas_limit(api, EXPENSIVE_TESTING_GAS_LIMIT);
+
+        let hash = hex::decode(SECP256R1_HASH_HEX).unwrap();
+        let hash_ptr = write_data(&env, &hash);
+        let sig = hex::decode(SECP256R1_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let mut pubkey = hex::decode(SECP256R1_PUBKEY_HEX).unwrap();
+        // alter pubkey format
+        pubkey[0] ^= 0x01;
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256r1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            5 // mapped InvalidPubkeyFormat
+        )
+    }
+
+    #[test]
+    #[cfg(feature = "secp256r1")]
+    fn do_secp256r1_recover_pubkey_works() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance_with_gas_limit(api, EXPENSIVE_TESTING_GAS_LIMIT);
+
+        let hash = hex::decode(SECP256R1_HASH_HEX).unwrap();
+        let sig = hex::decode(SECP256R1_SIG_HEX).unwrap();
+        let recovery_param = 0;
+        let expected = hex::decode(SECP256R1_PUBKEY_HEX).unwrap();
+
+        let hash_ptr = write_data(&env, &hash);
+        let sig_ptr = write_data(&env, &sig);
+        let result = do_secp256r1_recover_pubkey(&env, hash_ptr, sig_ptr, recovery_param).unwrap();
+        let error = result >> 32;
+        let pubkey_ptr: u32 = (result & 0xFFFFFFFF).try_into().unwrap();
+        assert_eq!(error, 0);
+        assert_eq!(force_read(&env, pubkey_ptr), expected);
+
+        // invalid recovery param
+        let result = do_secp256r1_recover_pubkey(&env, hash_ptr, sig_ptr, 2).unwrap();
+        assert_eq!(result >> 32, 6); // mapped InvalidRecoveryParam
+    }
+
+    #[test]
+    fn do_ed25519_verify_works() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg_ptr = write_data(&env, &msg);
+        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn do_ed25519_verify_wrong_msg_verify_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let mut msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        // alter msg
+        msg.push(0x01);
+        let msg_ptr = write_data(&env, &msg);
+        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn do_ed25519_verify_larger_msg_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let mut msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        // extend / break msg
+        msg.extend_from_slice(&[0x00; MAX_LENGTH_ED25519_MESSAGE + 1]);
+        let msg_ptr = write_data(&env, &msg);
+        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        let result = do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr);
+        match result.unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::RegionLengthTooBig { length, .. },
+                ..
+            } => assert_eq!(length, msg.len()),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn do_ed25519_verify_wrong_sig_verify_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg_ptr = write_data(&env, &msg);
+        let mut sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        // alter sig
+        sig[0] ^= 0x01;
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn do_ed25519_verify_larger_sig_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg_ptr = write_data(&env, &msg);
+        let mut sig = hex::decode(EDDSA_SIG_HEX).unwrap();
         // extend / break sig
         sig.push(0x00);
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
         let pubkey_ptr = write_data(&env, &pubkey);
 
-        let result = do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr);
+        let result = do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr);
         match result.unwrap_err() {
             VmError::CommunicationErr {
                 source: CommunicationError::RegionLengthTooBig { length, .. },
                 ..
-            } => assert_eq!(length, ECDSA_SIGNATURE_LEN + 1),
+            } => assert_eq!(length, MAX_LENGTH_ED25519_SIGNATURE + 1),
             e => panic!("Unexpected error: {:?}", e),
         }
     }
 
     #[test]
-    fn do_secp256k1_verify_shorter_sig_fails() {
+    fn do_ed25519_verify_shorter_sig_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
-        let hash_ptr = write_data(&env, &hash);
-        let mut sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg_ptr = write_data(&env, &msg);
+        let mut sig = hex::decode(EDDSA_SIG_HEX).unwrap();
         // reduce / break sig
         sig.pop();
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
             4 // mapped InvalidSignatureFormat
         )
     }
 
     #[test]
-    fn do_secp256k1_verify_wrong_pubkey_format_fails() {
-        let api = MockApi::default();
-        let (env, mut _instance) = make_instance(api);
-
-        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
-        let hash_ptr = write_data(&env, &hash);
-        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
-        let sig_ptr = write_data(&env, &sig);
-        let mut pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
-        // alter pubkey format
-        pubkey[0] ^= 0x01;
-        let pubkey_ptr = write_data(&env, &pubkey);
-
-        assert_eq!(
-            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
-            5 // mapped InvalidPubkeyFormat
-        )
-    }
-
-    #[test]
-    fn do_secp256k1_verify_wrong_pubkey_fails() {
+    fn do_ed25519_verify_wrong_pubkey_verify_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
-        let hash_ptr = write_data(&env, &hash);
-        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg_ptr = write_data(&env, &msg);
+        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let mut pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        let mut pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
         // alter pubkey
         pubkey[1] ^= 0x01;
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
-            10 // mapped GenericErr
-        )
+            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            1
+        );
     }
 
     #[test]
-    fn do_secp256k1_verify_larger_pubkey_fails() {
+    fn do_ed25519_verify_larger_pubkey_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
-        let hash_ptr = write_data(&env, &hash);
-        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg_ptr = write_data(&env, &msg);
+        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let mut pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        let mut pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
         // extend / break pubkey
         pubkey.push(0x00);
         let pubkey_ptr = write_data(&env, &pubkey);
 
-        let result = do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr);
+        let result = do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr);
         match result.unwrap_err() {
             VmError::CommunicationErr {
                 source: CommunicationError::RegionLengthTooBig { length, .. },
                 ..
-            } => assert_eq!(length, ECDSA_PUBKEY_MAX_LEN + 1),
+            } => assert_eq!(length, EDDSA_PUBKEY_LEN + 1),
             e => panic!("Unexpected error: {:?}", e),
         }
     }
 
     #[test]
-    fn do_secp256k1_verify_shorter_pubkey_fails() {
+    fn do_ed25519_verify_shorter_pubkey_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
-        let hash_ptr = write_data(&env, &hash);
-        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg_ptr = write_data(&env, &msg);
+        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let mut pubkey = hex::decode(ECDSA_PUBKEY_HEX).unwrap();
+        let mut pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
         // reduce / break pubkey
         pubkey.pop();
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
             5 // mapped InvalidPubkeyFormat
         )
     }
 
     #[test]
-    fn do_secp256k1_verify_empty_pubkey_fails() {
+    fn do_ed25519_verify_empty_pubkey_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let hash = hex::decode(ECDSA_HASH_HEX).unwrap();
-        let hash_ptr = write_data(&env, &hash);
-        let sig = hex::decode(ECDSA_SIG_HEX).unwrap();
+        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg_ptr = write_data(&env, &msg);
+        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
         let pubkey = vec![];
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
             5 // mapped InvalidPubkeyFormat
         )
     }
 
     #[test]
-    fn do_secp256k1_verify_wrong_data_fails() {
+    fn do_ed25519_verify_wrong_data_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let hash = vec![0x22; MESSAGE_HASH_MAX_LEN];
-        let hash_ptr = write_data(&env, &hash);
-        let sig = vec![0x22; ECDSA_SIGNATURE_LEN];
+        let msg = vec![0x22; MESSAGE_HASH_MAX_LEN];
+        let msg_ptr = write_data(&env, &msg);
+        let sig = vec![0x22; MAX_LENGTH_ED25519_SIGNATURE];
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = vec![0x04; ECDSA_PUBKEY_MAX_LEN];
+        let pubkey = vec![0x04; EDDSA_PUBKEY_LEN];
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_secp256k1_verify(&env, hash_ptr, sig_ptr, pubkey_ptr).unwrap(),
-            10 // mapped GenericErr
+            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            1 // verification failure
         )
     }
 
+    #[cfg(feature = "sr25519")]
     #[test]
-    fn do_secp256k1_recover_pubkey_works() {
+    fn do_sr25519_verify_works() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        // https://gist.github.com/webmaster128/130b628d83621a33579751846699ed15
-        let hash = hex!("5ae8317d34d1e595e3fa7247db80c0af4320cce1116de187f8f7e2e099c0d8d0");
-        let sig = hex!("45c0b7f8c09a9e1f1cea0c25785594427b6bf8f9f878a8af0b1abbb48e16d0920d8becd0c220f67c51217eecfd7184ef0732481c843857e6bc7fc095c4f6b788");
-        let recovery_param = 1;
-        let expected = hex!("044a071e8a6e10aada2b8cf39fa3b5fb3400b04e99ea8ae64ceea1a977dbeaf5d5f8c8fbd10b71ab14cd561f7df8eb6da50f8a8d81ba564342244d26d1d4211595");
-
-        let hash_ptr = write_data(&env, &hash);
+        let msg = SR25519_MSG.to_vec();
+        let msg_ptr = write_data(&env, &msg);
+        let sig = hex::decode(SR25519_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let result = do_secp256k1_recover_pubkey(&env, hash_ptr, sig_ptr, recovery_param).unwrap();
-        let error = result >> 32;
-        let pubkey_ptr: u32 = (result & 0xFFFFFFFF).try_into().unwrap();
-        assert_eq!(error, 0);
-        assert_eq!(force_read(&env, pubkey_ptr), expected);
+        let pubkey = hex::decode(SR25519_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_sr25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            0
+        );
     }
 
+    #[cfg(feature = "sr25519")]
     #[test]
-    fn do_ed25519_verify_works() {
+    fn do_sr25519_verify_wrong_msg_verify_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let mut msg = SR25519_MSG.to_vec();
+        // alter msg
+        msg.push(0x01);
         let msg_ptr = write_data(&env, &msg);
-        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let sig = hex::decode(SR25519_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
+        let pubkey = hex::decode(SR25519_PUBKEY_HEX).unwrap();
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
-            0
+            do_sr25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            1
         );
     }
 
+    #[cfg(feature = "sr25519")]
     #[test]
-    fn do_ed25519_verify_wrong_msg_verify_fails() {
+    fn do_sr25519_verify_wrong_sig_verify_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let mut msg = hex::decode(EDDSA_MSG_HEX).unwrap();
-        // alter msg
-        msg.push(0x01);
+        let msg = SR25519_MSG.to_vec();
         let msg_ptr = write_data(&env, &msg);
-        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let mut sig = hex::decode(SR25519_SIG_HEX).unwrap();
+        // alter sig
+        sig[0] ^= 0x01;
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
+        let pubkey = hex::decode(SR25519_PUBKEY_HEX).unwrap();
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            do_sr25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
             1
         );
     }
 
+    #[cfg(feature = "sr25519")]
     #[test]
-    fn do_ed25519_verify_larger_msg_fails() {
+    fn do_sr25519_verify_larger_sig_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let mut msg = hex::decode(EDDSA_MSG_HEX).unwrap();
-        // extend / break msg
-        msg.extend_from_slice(&[0x00; MAX_LENGTH_ED25519_MESSAGE + 1]);
+        let msg = SR25519_MSG.to_vec();
         let msg_ptr = write_data(&env, &msg);
-        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let mut sig = hex::decode(SR25519_SIG_HEX).unwrap();
+        // extend / break sig
+        sig.push(0x00);
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
+        let pubkey = hex::decode(SR25519_PUBKEY_HEX).unwrap();
         let pubkey_ptr = write_data(&env, &pubkey);
 
-        let result = do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr);
+        let result = do_sr25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr);
         match result.unwrap_err() {
             VmError::CommunicationErr {
                 source: CommunicationError::RegionLengthTooBig { length, .. },
                 ..
-            } => assert_eq!(length, msg.len()),
+            } => assert_eq!(length, MAX_LENGTH_SR25519_SIGNATURE + 1),
             e => panic!("Unexpected error: {:?}", e),
         }
     }
 
+    #[cfg(feature = "sr25519")]
     #[test]
-    fn do_ed25519_verify_wrong_sig_verify_fails() {
+    fn do_sr25519_verify_shorter_sig_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg = SR25519_MSG.to_vec();
         let msg_ptr = write_data(&env, &msg);
-        let mut sig = hex::decode(EDDSA_SIG_HEX).unwrap();
-        // alter sig
-        sig[0] ^= 0x01;
+        let mut sig = hex::decode(SR25519_SIG_HEX).unwrap();
+        // reduce / break sig
+        sig.pop();
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
+        let pubkey = hex::decode(SR25519_PUBKEY_HEX).unwrap();
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            do_sr25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            4 // mapped InvalidSignatureFormat
+        )
+    }
+
+    #[cfg(feature = "sr25519")]
+    #[test]
+    fn do_sr25519_verify_wrong_pubkey_verify_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let msg = SR25519_MSG.to_vec();
+        let msg_ptr = write_data(&env, &msg);
+        let sig = hex::decode(SR25519_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let mut pubkey = hex::decode(SR25519_PUBKEY_HEX).unwrap();
+        // alter pubkey
+        pubkey[1] ^= 0x01;
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_sr25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
             1
         );
     }
 
+    #[cfg(feature = "sr25519")]
     #[test]
-    fn do_ed25519_verify_larger_sig_fails() {
+    fn do_sr25519_verify_larger_pubkey_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg = SR25519_MSG.to_vec();
         let msg_ptr = write_data(&env, &msg);
-        let mut sig = hex::decode(EDDSA_SIG_HEX).unwrap();
-        // extend / break sig
-        sig.push(0x00);
+        let sig = hex::decode(SR25519_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
+        let mut pubkey = hex::decode(SR25519_PUBKEY_HEX).unwrap();
+        // extend / break pubkey
+        pubkey.push(0x00);
         let pubkey_ptr = write_data(&env, &pubkey);
 
-        let result = do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr);
+        let result = do_sr25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr);
         match result.unwrap_err() {
             VmError::CommunicationErr {
                 source: CommunicationError::RegionLengthTooBig { length, .. },
                 ..
-            } => assert_eq!(length, MAX_LENGTH_ED25519_SIGNATURE + 1),
+            } => assert_eq!(length, SR25519_PUBKEY_LEN + 1),
             e => panic!("Unexpected error: {:?}", e),
         }
     }
 
+    #[cfg(feature = "sr25519")]
     #[test]
-    fn do_ed25519_verify_shorter_sig_fails() {
+    fn do_sr25519_verify_shorter_pubkey_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg = SR25519_MSG.to_vec();
         let msg_ptr = write_data(&env, &msg);
-        let mut sig = hex::decode(EDDSA_SIG_HEX).unwrap();
-        // reduce / break sig
-        sig.pop();
+        let sig = hex::decode(SR25519_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
+        let mut pubkey = hex::decode(SR25519_PUBKEY_HEX).unwrap();
+        // reduce / break pubkey
+        pubkey.pop();
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
-            4 // mapped InvalidSignatureFormat
+            do_sr25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            5 // mapped InvalidPubkeyFormat
         )
     }
 
+    #[cfg(feature = "secp256k1_schnorr")]
     #[test]
-    fn do_ed25519_verify_wrong_pubkey_verify_fails() {
+    fn do_secp256k1_schnorr_verify_works() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
         let msg_ptr = write_data(&env, &msg);
-        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let sig = hex::decode(SCHNORR_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let mut pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
-        // alter pubkey
-        pubkey[1] ^= 0x01;
+        let pubkey = hex::decode(SCHNORR_PUBKEY_HEX).unwrap();
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            do_secp256k1_schnorr_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            0
+        );
+    }
+
+    #[cfg(feature = "secp256k1_schnorr")]
+    #[test]
+    fn do_secp256k1_schnorr_verify_wrong_msg_verify_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let mut msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
+        // alter msg
+        msg.push(0x01);
+        let msg_ptr = write_data(&env, &msg);
+        let sig = hex::decode(SCHNORR_SIG_HEX).unwrap();
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(SCHNORR_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256k1_schnorr_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
             1
         );
     }
 
+    #[cfg(feature = "secp256k1_schnorr")]
     #[test]
-    fn do_ed25519_verify_larger_pubkey_fails() {
+    fn do_secp256k1_schnorr_verify_wrong_sig_verify_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
         let msg_ptr = write_data(&env, &msg);
-        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let mut sig = hex::decode(SCHNORR_SIG_HEX).unwrap();
+        // alter sig
+        sig[0] ^= 0x01;
         let sig_ptr = write_data(&env, &sig);
-        let mut pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
-        // extend / break pubkey
-        pubkey.push(0x00);
+        let pubkey = hex::decode(SCHNORR_PUBKEY_HEX).unwrap();
         let pubkey_ptr = write_data(&env, &pubkey);
 
-        let result = do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr);
+        assert_eq!(
+            do_secp256k1_schnorr_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            1
+        );
+    }
+
+    #[cfg(feature = "secp256k1_schnorr")]
+    #[test]
+    fn do_secp256k1_schnorr_verify_larger_sig_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
+        let msg_ptr = write_data(&env, &msg);
+        let mut sig = hex::decode(SCHNORR_SIG_HEX).unwrap();
+        // extend / break sig
+        sig.push(0x00);
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(SCHNORR_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        let result = do_secp256k1_schnorr_verify(&env, msg_ptr, sig_ptr, pubkey_ptr);
         match result.unwrap_err() {
             VmError::CommunicationErr {
                 source: CommunicationError::RegionLengthTooBig { length, .. },
                 ..
-            } => assert_eq!(length, EDDSA_PUBKEY_LEN + 1),
+            } => assert_eq!(length, SCHNORR_SIGNATURE_LEN + 1),
             e => panic!("Unexpected error: {:?}", e),
         }
     }
 
+    #[cfg(feature = "secp256k1_schnorr")]
+    #[test]
+    fn do_secp256k1_schnorr_verify_shorter_sig_fails() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
+        let msg_ptr = write_data(&env, &msg);
+        let mut sig = hex::decode(SCHNORR_SIG_HEX).unwrap();
+        // reduce / break sig
+        sig.pop();
+        let sig_ptr = write_data(&env, &sig);
+        let pubkey = hex::decode(SCHNORR_PUBKEY_HEX).unwrap();
+        let pubkey_ptr = write_data(&env, &pubkey);
+
+        assert_eq!(
+            do_secp256k1_schnorr_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            4 // mapped InvalidSignatureFormat
+        )
+    }
+
+    #[cfg(feature = "secp256k1_schnorr")]
     #[test]
-    fn do_ed25519_verify_shorter_pubkey_fails() {
+    fn do_secp256k1_schnorr_verify_wrong_pubkey_verify_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
         let msg_ptr = write_data(&env, &msg);
-        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let sig = hex::decode(SCHNORR_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let mut pubkey = hex::decode(EDDSA_PUBKEY_HEX).unwrap();
-        // reduce / break pubkey
-        pubkey.pop();
+        let mut pubkey = hex::decode(SCHNORR_PUBKEY_HEX).unwrap();
+        // alter pubkey
+        pubkey[1] ^= 0x01;
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
-            5 // mapped InvalidPubkeyFormat
-        )
+            do_secp256k1_schnorr_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            1
+        );
     }
 
+    #[cfg(feature = "secp256k1_schnorr")]
     #[test]
-    fn do_ed25519_verify_empty_pubkey_fails() {
+    fn do_secp256k1_schnorr_verify_larger_pubkey_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
         let msg_ptr = write_data(&env, &msg);
-        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let sig = hex::decode(SCHNORR_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = vec![];
+        let mut pubkey = hex::decode(SCHNORR_PUBKEY_HEX).unwrap();
+        // extend / break pubkey
+        pubkey.push(0x00);
         let pubkey_ptr = write_data(&env, &pubkey);
 
-        assert_eq!(
-            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
-            5 // mapped InvalidPubkeyFormat
-        )
+        let result = do_secp256k1_schnorr_verify(&env, msg_ptr, sig_ptr, pubkey_ptr);
+        match result.unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::RegionLengthTooBig { length, .. },
+                ..
+            } => assert_eq!(length, SCHNORR_PUBKEY_LEN + 1),
+            e => panic!("Unexpected error: {:?}", e),
+        }
     }
 
+    #[cfg(feature = "secp256k1_schnorr")]
     #[test]
-    fn do_ed25519_verify_wrong_data_fails() {
+    fn do_secp256k1_schnorr_verify_shorter_pubkey_fails() {
         let api = MockApi::default();
         let (env, mut _instance) = make_instance(api);
 
-        let msg = vec![0x22; MESSAGE_HASH_MAX_LEN];
+        let msg = hex::decode(SCHNORR_MSG_HEX).unwrap();
         let msg_ptr = write_data(&env, &msg);
-        let sig = vec![0x22; MAX_LENGTH_ED25519_SIGNATURE];
+        let sig = hex::decode(SCHNORR_SIG_HEX).unwrap();
         let sig_ptr = write_data(&env, &sig);
-        let pubkey = vec![0x04; EDDSA_PUBKEY_LEN];
+        let mut pubkey = hex::decode(SCHNORR_PUBKEY_HEX).unwrap();
+        // reduce / break pubkey
+        pubkey.pop();
         let pubkey_ptr = write_data(&env, &pubkey);
 
         assert_eq!(
-            do_ed25519_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
-            1 // verification failure
+            do_secp256k1_schnorr_verify(&env, msg_ptr, sig_ptr, pubkey_ptr).unwrap(),
+            5 // mapped InvalidPubkeyFormat
         )
     }
 
@@ -1925,4 +3624,392 @@ mod tests {
             e => panic!("Unexpected error: {:?}", e),
         }
     }
+
+    #[test]
+    #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+    fn do_db_next_key_and_do_db_next_value_work() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        leave_default_data(&env);
+
+        let id = do_db_scan(&env, 0, 0, Order::Ascending.into()).unwrap();
+
+        // Entry 1
+        let key_ptr = do_db_next_key(&env, id).unwrap();
+        assert_eq!(force_read(&env, key_ptr), KEY1);
+
+        // Entry 2
+        let value_ptr = do_db_next_value(&env, id).unwrap();
+        assert_eq!(force_read(&env, value_ptr), VALUE2);
+
+        // End
+        assert_eq!(do_db_next_key(&env, id).unwrap(), 0);
+        assert_eq!(do_db_next_value(&env, id).unwrap(), 0);
+
+        let non_existent_id = 42u32;
+        match do_db_next_key(&env, non_existent_id).unwrap_err() {
+            VmError::BackendErr {
+                source: BackendError::IteratorDoesNotExist { id, .. },
+                ..
+            } => assert_eq!(id, non_existent_id),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[cfg(feature = "bls12_381")]
+    #[test]
+    fn do_bls12_381_aggregate_g1_works() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let points = encode_sections(&[
+            hex::decode(BLS12_381_PUBKEY1_HEX).unwrap(),
+            hex::decode(BLS12_381_PUBKEY2_HEX).unwrap(),
+        ])
+        .unwrap();
+        let points_ptr = write_data(&env, &points);
+        let result = do_bls12_381_aggregate_g1(&env, points_ptr).unwrap();
+        let error = result >> 32;
+        let sum_ptr: u32 = (result & 0xFFFFFFFF).try_into().unwrap();
+        assert_eq!(error, 0);
+        assert_eq!(
+            force_read(&env, sum_ptr),
+            hex::decode(BLS12_381_PUBKEY_SUM_HEX).unwrap()
+        );
+    }
+
+    #[cfg(feature = "bls12_381")]
+    #[test]
+    fn do_bls12_381_aggregate_g1_fails_for_invalid_points() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let mut point = hex::decode(BLS12_381_PUBKEY1_HEX).unwrap();
+        point[47] ^= 0x01;
+        let points_ptr = write_data(&env, &encode_sections(&[point]).unwrap());
+        let result = do_bls12_381_aggregate_g1(&env, points_ptr).unwrap();
+        assert_eq!(result >> 32, 8); // mapped InvalidPoint
+
+        let points_ptr = write_data(&env, &[]);
+        let result = do_bls12_381_aggregate_g1(&env, points_ptr).unwrap();
+        assert_eq!(result >> 32, 7); // mapped BatchErr
+    }
+
+    #[cfg(feature = "bls12_381")]
+    #[test]
+    fn do_bls12_381_aggregate_verify_works() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance_with_gas_limit(api, EXPENSIVE_TESTING_GAS_LIMIT);
+
+        let public_keys = encode_sections(&[
+            hex::decode(BLS12_381_PUBKEY1_HEX).unwrap(),
+            hex::decode(BLS12_381_PUBKEY2_HEX).unwrap(),
+        ])
+        .unwrap();
+        let messages = encode_sections(&[b"block 1".to_vec(), b"block 2".to_vec()]).unwrap();
+        let public_keys_ptr = write_data(&env, &public_keys);
+        let messages_ptr = write_data(&env, &messages);
+        let sig_ptr = write_data(&env, &hex::decode(BLS12_381_AGGREGATE_SIG_HEX).unwrap());
+        let dst_ptr = write_data(&env, BLS12_381_DST_G2);
+
+        assert_eq!(
+            do_bls12_381_aggregate_verify(&env, public_keys_ptr, messages_ptr, sig_ptr, dst_ptr)
+                .unwrap(),
+            0
+        );
+
+        // wrong messages
+        let messages = encode_sections(&[b"block 2".to_vec(), b"block 1".to_vec()]).unwrap();
+        let messages_ptr = write_data(&env, &messages);
+        assert_eq!(
+            do_bls12_381_aggregate_verify(&env, public_keys_ptr, messages_ptr, sig_ptr, dst_ptr)
+                .unwrap(),
+            1
+        );
+
+        // mismatched number of messages
+        let messages = encode_sections(&[b"block 1".to_vec()]).unwrap();
+        let messages_ptr = write_data(&env, &messages);
+        assert_eq!(
+            do_bls12_381_aggregate_verify(&env, public_keys_ptr, messages_ptr, sig_ptr, dst_ptr)
+                .unwrap(),
+            7 // mapped BatchErr
+        );
+    }
+
+    #[cfg(feature = "bls12_381")]
+    #[test]
+    fn do_bls12_381_pairing_equality_works() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance_with_gas_limit(api, EXPENSIVE_TESTING_GAS_LIMIT);
+
+        // drand style: e(signature, g2) = e(H(m), public_key), generated with blst from the
+        // key material [7u8; 32] and the message "round 1234"
+        let signature = hex!("878a05500169c0675d5b0cd235f1b7d2c5163964e200747e89349ad0e8218e637f0d46f35cee9999550b2d9947e8bc37");
+        let g2_generator = hex!("93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8");
+        let public_key = hex!("8038bfe033bc328ea36bb7c3438bc5a27a0dc880506277e116c8b842ed0c1ea78d32c90b04afbca59bd828c1e6c5e3f319274412f2e9eecf7334114b02847693e9d997f1aa9f936d90cae8946df6593033431513e210880bcda015da1b61f6f5");
+        let message_point = bls12_381_hash_to_g1(
+            b"round 1234",
+            b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_",
+        );
+
+        let ps_ptr = write_data(&env, &encode_sections(&[signature.to_vec()]).unwrap());
+        let qs_ptr = write_data(&env, &encode_sections(&[g2_generator.to_vec()]).unwrap());
+        let r_ptr = write_data(&env, &message_point);
+        let s_ptr = write_data(&env, &public_key);
+        assert_eq!(
+            do_bls12_381_pairing_equality(&env, ps_ptr, qs_ptr, r_ptr, s_ptr).unwrap(),
+            0
+        );
+
+        // wrong message
+        let other_point = bls12_381_hash_to_g1(
+            b"round 1235",
+            b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_",
+        );
+        let r_ptr = write_data(&env, &other_point);
+        assert_eq!(
+            do_bls12_381_pairing_equality(&env, ps_ptr, qs_ptr, r_ptr, s_ptr).unwrap(),
+            1
+        );
+
+        // G2 point where a G1 point is expected
+        let r_ptr = write_data(&env, &public_key[..48]);
+        assert_eq!(
+            do_bls12_381_pairing_equality(&env, ps_ptr, qs_ptr, r_ptr, s_ptr).unwrap(),
+            8 // mapped InvalidPoint
+        );
+    }
+
+    #[cfg(feature = "bls12_381")]
+    #[test]
+    fn do_bls12_381_pairing_equality_fails_for_invalid_sections() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let r_ptr = write_data(&env, &[0u8; 48]);
+        let s_ptr = write_data(&env, &[0u8; 96]);
+        let qs_ptr = write_data(&env, b"");
+
+        // empty sections fit into a region sized for MAX_COUNT_BLS12_381_PAIRING points many times
+        let ps_ptr = write_data(&env, &vec![0u8; 4 * (MAX_COUNT_BLS12_381_PAIRING + 1)]);
+        match do_bls12_381_pairing_equality(&env, ps_ptr, qs_ptr, r_ptr, s_ptr).unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::TooManySections { count, max_count },
+                ..
+            } => {
+                assert_eq!(count, MAX_COUNT_BLS12_381_PAIRING + 1);
+                assert_eq!(max_count, MAX_COUNT_BLS12_381_PAIRING);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // the length suffix claims more bytes than there are in front of it
+        let ps_ptr = write_data(&env, &[&[0u8; 48][..], b"\0\0\0\x31"].concat());
+        match do_bls12_381_pairing_equality(&env, ps_ptr, qs_ptr, r_ptr, s_ptr).unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::InvalidSectionLength { length, remaining },
+                ..
+            } => {
+                assert_eq!(length, 49);
+                assert_eq!(remaining, 48);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[cfg(feature = "bls12_381")]
+    #[test]
+    fn do_bls12_381_hash_to_g1_works() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        // Test vector from RFC 9380, J.9.1
+        let msg_ptr = write_data(&env, b"abc");
+        let dst_ptr = write_data(&env, b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_");
+        let point_ptr = do_bls12_381_hash_to_g1(&env, msg_ptr, dst_ptr).unwrap();
+        assert_eq!(
+            force_read(&env, point_ptr),
+            hex!("83567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903")
+        );
+    }
+
+    #[cfg(feature = "bls12_381")]
+    #[test]
+    fn do_bls12_381_hash_to_g2_works() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        // Test vector from RFC 9380, J.10.1
+        let msg_ptr = write_data(&env, b"abc");
+        let dst_ptr = write_data(&env, b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_");
+        let point_ptr = do_bls12_381_hash_to_g2(&env, msg_ptr, dst_ptr).unwrap();
+        assert_eq!(
+            force_read(&env, point_ptr),
+            hex!("939cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd802c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6")
+        );
+    }
+
+    #[cfg(feature = "hashes")]
+    #[test]
+    fn do_sha256_works() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let data_ptr = write_data(&env, b"abc");
+        let hash_ptr = do_sha256(&env, data_ptr).unwrap();
+        assert_eq!(
+            force_read(&env, hash_ptr),
+            hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+    }
+
+    #[cfg(feature = "hashes")]
+    #[test]
+    fn do_keccak256_works() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let data_ptr = write_data(&env, b"abc");
+        let hash_ptr = do_keccak256(&env, data_ptr).unwrap();
+        assert_eq!(
+            force_read(&env, hash_ptr),
+            hex!("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45")
+        );
+    }
+
+    #[cfg(feature = "hashes")]
+    #[test]
+    fn do_ripemd160_works() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let data_ptr = write_data(&env, b"abc");
+        let hash_ptr = do_ripemd160(&env, data_ptr).unwrap();
+        assert_eq!(
+            force_read(&env, hash_ptr),
+            hex!("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc")
+        );
+    }
+
+    #[cfg(feature = "hashes")]
+    #[test]
+    fn do_blake2b_works() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let data_ptr = write_data(&env, b"abc");
+        let hash_ptr = do_blake2b(&env, data_ptr).unwrap();
+        assert_eq!(
+            force_read(&env, hash_ptr),
+            hex!("ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923")
+        );
+    }
+
+    #[cfg(feature = "hashes")]
+    #[test]
+    fn do_sha256_fails_for_large_input() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let data_ptr = write_data(&env, &vec![0x42; MAX_LENGTH_HASH_INPUT + 1]);
+        match do_sha256(&env, data_ptr).unwrap_err() {
+            VmError::CommunicationErr {
+                source:
+                    CommunicationError::RegionLengthTooBig {
+                        length, max_length, ..
+                    },
+                ..
+            } => {
+                assert_eq!(length, MAX_LENGTH_HASH_INPUT + 1);
+                assert_eq!(max_length, MAX_LENGTH_HASH_INPUT);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[cfg(feature = "hashes")]
+    #[test]
+    fn do_sha256_runs_out_of_gas() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let data = vec![0x42; 1000];
+        let data_ptr = write_data(&env, &data);
+        let cost = GasConfig::default().sha256_cost.total_cost(1000);
+        env.set_gas_left(cost - 1);
+        match do_sha256(&env, data_ptr).unwrap_err() {
+            VmError::GasDepletion { .. } => {}
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn do_debug_charges_by_message_length() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let message_ptr = write_data(&env, b"here we go");
+
+        leave_default_data(&env);
+
+        let gas_before = env.get_gas_left();
+        do_debug(&env, message_ptr).unwrap();
+        let used = gas_before - env.get_gas_left();
+        assert_eq!(used, GasConfig::default().debug_cost.total_cost(10));
+    }
+
+    #[test]
+    fn do_abort_works() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let message_ptr = write_data(
+            &env,
+            b"panicked at 'attempt to subtract with overflow', src/contract.rs:42:5",
+        );
+
+        leave_default_data(&env);
+
+        let result = do_abort(&env, message_ptr);
+        match result.unwrap_err() {
+            VmError::Aborted { msg, .. } => assert_eq!(
+                msg,
+                "panicked at 'attempt to subtract with overflow', src/contract.rs:42:5"
+            ),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn do_abort_charges_by_message_length() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let message_ptr = write_data(&env, b"panicked at 'oh no'");
+
+        leave_default_data(&env);
+
+        let gas_before = env.get_gas_left();
+        do_abort(&env, message_ptr).unwrap_err();
+        let used = gas_before - env.get_gas_left();
+        assert_eq!(used, GasConfig::default().debug_cost.total_cost(19));
+    }
+
+    #[test]
+    fn do_abort_replaces_invalid_utf8() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let message_ptr = write_data(&env, b"panicked \xff");
+
+        leave_default_data(&env);
+
+        let result = do_abort(&env, message_ptr);
+        match result.unwrap_err() {
+            VmError::Aborted { msg, .. } => assert_eq!(msg, "panicked \u{fffd}"),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
 }
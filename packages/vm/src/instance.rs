@@ -1,24 +1,50 @@
 use std::collections::{HashMap, HashSet};
 use std::ptr::NonNull;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
-use wasmer::{Exports, Function, ImportObject, Instance as WasmerInstance, Module, Val};
+use wasmer::{
+    Exports, Function, ImportObject, Instance as WasmerInstance, Module, Val, WASM_PAGE_SIZE,
+};
 
 use crate::backend::{Backend, BackendApi, Querier, Storage};
+use crate::checksum::Checksum;
 use crate::conversion::{ref_to_u32, to_u32};
-use crate::environment::Environment;
+use crate::environment::{Environment, GasConfig, GasState};
 use crate::errors::{CommunicationError, VmError, VmResult};
 use crate::features::required_features_from_module;
+#[cfg(feature = "extended_storage")]
+use crate::imports::do_db_read_many;
+#[cfg(feature = "secp256k1_batch")]
+use crate::imports::do_secp256k1_batch_verify;
+#[cfg(feature = "secp256k1_schnorr")]
+use crate::imports::do_secp256k1_schnorr_verify;
+#[cfg(feature = "sr25519")]
+use crate::imports::do_sr25519_verify;
 use crate::imports::{
-    do_addr_canonicalize, do_addr_humanize, do_addr_validate, do_db_read, do_db_remove,
+    do_abort, do_addr_canonicalize, do_addr_humanize, do_addr_validate, do_db_read, do_db_remove,
     do_db_write, do_debug, do_ed25519_batch_verify, do_ed25519_verify, do_query_chain,
     do_secp256k1_recover_pubkey, do_secp256k1_verify,
 };
+#[cfg(feature = "hashes")]
+use crate::imports::{do_blake2b, do_keccak256, do_ripemd160, do_sha256};
+#[cfg(feature = "bls12_381")]
+use crate::imports::{
+    do_bls12_381_aggregate_g1, do_bls12_381_aggregate_g2, do_bls12_381_aggregate_verify,
+    do_bls12_381_hash_to_g1, do_bls12_381_hash_to_g2, do_bls12_381_pairing_equality,
+};
 #[cfg(feature = "iterator")]
 use crate::imports::{do_db_next, do_db_scan};
+#[cfg(all(feature = "iterator", feature = "extended_storage"))]
+use crate::imports::{do_db_next_key, do_db_next_value};
+#[cfg(feature = "secp256r1")]
+use crate::imports::{do_secp256r1_recover_pubkey, do_secp256r1_verify};
+use crate::metrics::MetricsRegistry;
 use crate::size::Size;
+use crate::tracer::Tracer;
 use crate::wasm::Memory;
-use crate::wasm_backend::compile;
+use crate::wasm_backend::{compile, OperatorCostTable, DEFAULT_MAX_STACK_DEPTH};
+#[cfg(feature = "interpreter")]
+use crate::wasm_backend::{compile_interpreted, WasmiInstance, WasmiModule};
 use crate::WasmVM;
 
 #[derive(Copy, Clone, Debug)]
@@ -32,12 +58,28 @@ pub struct GasReport {
     /// The amount of gas that was spend and metered internally (i.e. by executing Wasm and calling
     /// API methods which are not metered externally)
     pub used_internally: u64,
+    /// The size of the contract's memory in pages when the instance was created
+    pub initial_memory_pages: usize,
+    /// The current size of the contract's memory in pages. Since Wasm memory never shrinks,
+    /// this is also the largest size reached so far.
+    pub memory_pages: usize,
 }
 
 #[derive(Copy, Clone, Debug)]
 pub struct InstanceOptions {
     pub gas_limit: u64,
     pub print_debug: bool,
+    /// Gas prices for the functionality the VM provides to the contract (crypto, address
+    /// handling, debug, iterators). When unset, instances created by a cache use
+    /// [`CacheOptions::gas_config`](crate::CacheOptions::gas_config) and all others use
+    /// `GasConfig::default()`.
+    pub gas_config: Option<GasConfig>,
+    /// The maximum stack depth of the contract, measured in Wasm values. Every call adds the
+    /// parameters, locals and maximum operand stack height of the called function plus a
+    /// constant overhead. Exceeding it aborts the execution with a `VmError::StackDepthExceeded`.
+    /// When unset, `DEFAULT_MAX_STACK_DEPTH` is used. Since the limit affects the outcome of
+    /// contract executions, all nodes of a chain must use the same value.
+    pub max_stack_depth: Option<u32>,
 }
 
 pub struct Instance<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> {
@@ -48,6 +90,39 @@ pub struct Instance<A: BackendApi, S: Storage, Q: Querier, W: WasmVM> {
     /// This instance should only be accessed via the Environment, which provides safe access.
     _inner: Box<W>,
     env: Environment<A, S, Q, W>,
+    /// Receives the gas used per entry point if set
+    metrics: Option<Arc<MetricsRegistry>>,
+    /// The checksum of the code if this instance was created by a cache with instance pooling
+    pub(crate) pool_checksum: Option<Checksum>,
+    /// The memory size in pages after instantiation. Pooled instances are only reused
+    /// if their memory did not grow, so this stays valid for them.
+    initial_memory_pages: usize,
+}
+
+/// The memory and the mutable globals of an instance
+#[derive(Clone)]
+pub(crate) struct InstanceState {
+    memory: Vec<u8>,
+    globals: Vec<Val>,
+}
+
+/// A copy of the linear memory, the mutable globals and the gas state of an instance,
+/// created by [`Instance::snapshot`].
+///
+/// Storage and querier are not part of the snapshot. Those can be copied separately,
+/// e.g. by cloning a `MockStorage`.
+#[derive(Clone)]
+pub struct InstanceSnapshot {
+    state: InstanceState,
+    gas_state: GasState,
+    gas_left: u64,
+}
+
+impl InstanceSnapshot {
+    /// The size of the captured memory in bytes
+    pub fn memory_size(&self) -> usize {
+        self.state.memory.len()
+    }
 }
 
 impl<A, S, Q> Instance<A, S, Q, WasmerInstance>
@@ -61,12 +136,13 @@ where
         backend: Backend<A, S, Q>,
         gas_limit: u64,
         print_debug: bool,
+        gas_config: GasConfig,
         extra_imports: Option<HashMap<&str, Exports>>,
         instantiation_lock: Option<&Mutex<()>>,
     ) -> VmResult<Self> {
         let store = module.store();
 
-        let env = Environment::new(backend.api, gas_limit, print_debug);
+        let env = Environment::new(backend.api, gas_limit, print_debug, gas_config);
 
         let mut import_obj = ImportObject::new();
         let mut env_imports = Exports::new();
@@ -130,11 +206,41 @@ where
             Function::new_native_with_env(store, env.clone(), do_secp256k1_verify),
         );
 
+        // Verifies a batch of message hashes against a batch of signatures with a batch of public keys,
+        // using the secp256k1 ECDSA parametrization.
+        // Returns 0 on verification success (all batches verify correctly), 1 on verification failure, and values
+        // greater than 1 in case of error.
+        // Ownership of input pointers is not transferred to the host.
+        #[cfg(feature = "secp256k1_batch")]
+        env_imports.insert(
+            "secp256k1_batch_verify",
+            Function::new_native_with_env(store, env.clone(), do_secp256k1_batch_verify),
+        );
+
         env_imports.insert(
             "secp256k1_recover_pubkey",
             Function::new_native_with_env(store, env.clone(), do_secp256k1_recover_pubkey),
         );
 
+        // Verifies message hashes against a signature with a public key, using the secp256r1 (NIST P-256) ECDSA parametrization.
+        // Returns 0 on verification success, 1 on verification failure, and values greater than 1 in case of error.
+        // Ownership of input pointers is not transferred to the host.
+        #[cfg(feature = "secp256r1")]
+        env_imports.insert(
+            "secp256r1_verify",
+            Function::new_native_with_env(store, env.clone(), do_secp256r1_verify),
+        );
+
+        // Recovers a secp256r1 public key from a message hash and a signature.
+        // Returns the error code in the high 32 bits and the address of a region containing the
+        // uncompressed public key in the low 32 bits.
+        // Ownership of input pointers is not transferred to the host.
+        #[cfg(feature = "secp256r1")]
+        env_imports.insert(
+            "secp256r1_recover_pubkey",
+            Function::new_native_with_env(store, env.clone(), do_secp256r1_recover_pubkey),
+        );
+
         // Verifies a message against a signature with a public key, using the ed25519 EdDSA scheme.
         // Returns 0 on verification success, 1 on verification failure, and values greater than 1 in case of error.
         // Ownership of input pointers is not transferred to the host.
@@ -153,6 +259,103 @@ where
             Function::new_native_with_env(store, env.clone(), do_ed25519_batch_verify),
         );
 
+        // Verifies a message against a signature with a public key, using the sr25519 Schnorr scheme
+        // with the "substrate" signing context.
+        // Returns 0 on verification success, 1 on verification failure, and values greater than 1 in case of error.
+        // Ownership of input pointers is not transferred to the host.
+        #[cfg(feature = "sr25519")]
+        env_imports.insert(
+            "sr25519_verify",
+            Function::new_native_with_env(store, env.clone(), do_sr25519_verify),
+        );
+
+        // Verifies a message against a BIP-340 Schnorr signature with an x-only public key,
+        // over the secp256k1 elliptic curve.
+        // Returns 0 on verification success, 1 on verification failure, and values greater than 1 in case of error.
+        // Ownership of input pointers is not transferred to the host.
+        #[cfg(feature = "secp256k1_schnorr")]
+        env_imports.insert(
+            "secp256k1_schnorr_verify",
+            Function::new_native_with_env(store, env.clone(), do_secp256k1_schnorr_verify),
+        );
+
+        // Aggregates a list of BLS12-381 G1 / G2 points, encoded with `sections`, into their sum.
+        // Returns the error code in the high 32 bits and the address of a region containing the
+        // compressed sum in the low 32 bits.
+        // Ownership of the input pointer is not transferred to the host.
+        #[cfg(feature = "bls12_381")]
+        env_imports.insert(
+            "bls12_381_aggregate_g1",
+            Function::new_native_with_env(store, env.clone(), do_bls12_381_aggregate_g1),
+        );
+
+        #[cfg(feature = "bls12_381")]
+        env_imports.insert(
+            "bls12_381_aggregate_g2",
+            Function::new_native_with_env(store, env.clone(), do_bls12_381_aggregate_g2),
+        );
+
+        // Checks the BLS12-381 pairing equation e(p_1, q_1) * ... * e(p_n, q_n) = e(r, s).
+        // The lists of G1 points p and G2 points q are encoded with `sections`.
+        // Returns 0 if the equation holds, 1 if it does not, and values greater than 1 in case of error.
+        // Ownership of input pointers is not transferred to the host.
+        #[cfg(feature = "bls12_381")]
+        env_imports.insert(
+            "bls12_381_pairing_equality",
+            Function::new_native_with_env(store, env.clone(), do_bls12_381_pairing_equality),
+        );
+
+        // Verifies an aggregate BLS12-381 signature (G2) of distinct messages by a list of public keys (G1).
+        // Public keys and messages are encoded with `sections`.
+        // Returns 0 on verification success, 1 on verification failure, and values greater than 1 in case of error.
+        // Ownership of input pointers is not transferred to the host.
+        #[cfg(feature = "bls12_381")]
+        env_imports.insert(
+            "bls12_381_aggregate_verify",
+            Function::new_native_with_env(store, env.clone(), do_bls12_381_aggregate_verify),
+        );
+
+        // Hashes a message to a BLS12-381 G1 / G2 point using a domain separation tag.
+        // Returns a region containing the compressed point.
+        // Ownership of input pointers is not transferred to the host.
+        // Ownership of the result region is transferred to the contract.
+        #[cfg(feature = "bls12_381")]
+        env_imports.insert(
+            "bls12_381_hash_to_g1",
+            Function::new_native_with_env(store, env.clone(), do_bls12_381_hash_to_g1),
+        );
+
+        #[cfg(feature = "bls12_381")]
+        env_imports.insert(
+            "bls12_381_hash_to_g2",
+            Function::new_native_with_env(store, env.clone(), do_bls12_381_hash_to_g2),
+        );
+
+        // Hash functions. Each takes a pointer to a region with the input data and returns
+        // a region containing the hash.
+        // Ownership of the input pointer is not transferred to the host.
+        // Ownership of the result region is transferred to the contract.
+        #[cfg(feature = "hashes")]
+        env_imports.insert(
+            "sha256",
+            Function::new_native_with_env(store, env.clone(), do_sha256),
+        );
+        #[cfg(feature = "hashes")]
+        env_imports.insert(
+            "keccak256",
+            Function::new_native_with_env(store, env.clone(), do_keccak256),
+        );
+        #[cfg(feature = "hashes")]
+        env_imports.insert(
+            "ripemd160",
+            Function::new_native_with_env(store, env.clone(), do_ripemd160),
+        );
+        #[cfg(feature = "hashes")]
+        env_imports.insert(
+            "blake2b",
+            Function::new_native_with_env(store, env.clone(), do_blake2b),
+        );
+
         // Allows the contract to emit debug logs that the host can either process or ignore.
         // This is never written to chain.
         // Takes a pointer argument of a memory region that must contain an UTF-8 encoded string.
@@ -162,6 +365,15 @@ where
             Function::new_native_with_env(store, env.clone(), do_debug),
         );
 
+        // Aborts the contract execution with an error message provided by the contract.
+        // cosmwasm-std calls this from its panic handler with the panic message and location.
+        // Takes a pointer argument of a memory region that must contain an UTF-8 encoded string.
+        // Ownership of the input pointer is not transferred to the host.
+        env_imports.insert(
+            "abort",
+            Function::new_native_with_env(store, env.clone(), do_abort),
+        );
+
         env_imports.insert(
             "query_chain",
             Function::new_native_with_env(store, env.clone(), do_query_chain),
@@ -190,6 +402,35 @@ where
             Function::new_native_with_env(store, env.clone(), do_db_next),
         );
 
+        // Reads the values of multiple keys at once.
+        // The keys are encoded as sections (see try_decode_sections), e.g. `key1 || key1len || key2 || key2len`.
+        // Returns a region containing one section per key with its value. An empty section means the key does not exist.
+        // Ownership of the keys pointer is not transferred to the host.
+        // Ownership of the result region is transferred to the contract.
+        #[cfg(feature = "extended_storage")]
+        env_imports.insert(
+            "db_read_many",
+            Function::new_native_with_env(store, env.clone(), do_db_read_many),
+        );
+
+        // Get the key of the next element of iterator with ID `iterator_id` without loading its value.
+        // Returns 0 if there are no more elements, the address of a region containing the key otherwise.
+        // Ownership of the result region is transferred to the contract.
+        #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+        env_imports.insert(
+            "db_next_key",
+            Function::new_native_with_env(store, env.clone(), do_db_next_key),
+        );
+
+        // Get the value of the next element of iterator with ID `iterator_id` without returning its key.
+        // Returns 0 if there are no more elements, the address of a region containing the value otherwise.
+        // Ownership of the result region is transferred to the contract.
+        #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+        env_imports.insert(
+            "db_next_value",
+            Function::new_native_with_env(store, env.clone(), do_db_next_value),
+        );
+
         import_obj.register("env", env_imports);
 
         if let Some(extra_imports) = extra_imports {
@@ -212,10 +453,14 @@ where
         env.set_wasm_instance(Some(instance_ptr));
         env.set_gas_left(gas_limit);
         env.move_in(backend.storage, backend.querier);
-        let instance = Instance {
+        let mut instance = Instance {
             _inner: wasmer_instance,
             env,
+            metrics: None,
+            pool_checksum: None,
+            initial_memory_pages: 0,
         };
+        instance.initial_memory_pages = instance.memory_pages();
         Ok(instance)
     }
 
@@ -227,15 +472,73 @@ where
         options: InstanceOptions,
         memory_limit: Option<Size>,
     ) -> VmResult<Self> {
-        let module = compile(code, memory_limit, &[])?;
-        Instance::from_module(
+        let module = compile(code, memory_limit, &OperatorCostTable::default(), &[])?;
+        let mut instance = Instance::from_module(
             &module,
             backend,
             options.gas_limit,
             options.print_debug,
+            options.gas_config.unwrap_or_default(),
             None,
             None,
-        )
+        )?;
+        instance.set_max_stack_depth(options.max_stack_depth.unwrap_or(DEFAULT_MAX_STACK_DEPTH));
+        Ok(instance)
+    }
+}
+
+#[cfg(feature = "interpreter")]
+impl<A, S, Q> Instance<A, S, Q, WasmiInstance>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+{
+    pub(crate) fn from_wasmi_module(
+        module: Arc<WasmiModule>,
+        backend: Backend<A, S, Q>,
+        gas_limit: u64,
+        print_debug: bool,
+        gas_config: GasConfig,
+    ) -> VmResult<Self> {
+        let env = Environment::new(backend.api, gas_limit, print_debug, gas_config);
+        let wasmi_instance = Box::from(WasmiInstance::new(module, env.clone())?);
+
+        let instance_ptr = NonNull::from(wasmi_instance.as_ref());
+        env.set_wasm_instance(Some(instance_ptr));
+        env.set_gas_left(gas_limit);
+        env.move_in(backend.storage, backend.querier);
+        let mut instance = Instance {
+            _inner: wasmi_instance,
+            env,
+            metrics: None,
+            pool_checksum: None,
+            initial_memory_pages: 0,
+        };
+        instance.initial_memory_pages = instance.memory_pages();
+        Ok(instance)
+    }
+
+    /// Like [`Instance::from_code`] but executes the contract in the wasmi interpreter
+    /// instead of compiling it with Wasmer. Operators are metered with the given table,
+    /// which should be the one the chain configured for its cache.
+    pub fn from_wasmi_code(
+        code: &[u8],
+        backend: Backend<A, S, Q>,
+        options: InstanceOptions,
+        memory_limit: Option<Size>,
+        operator_cost_table: &OperatorCostTable,
+    ) -> VmResult<Self> {
+        let module = compile_interpreted(code, memory_limit, operator_cost_table)?;
+        let mut instance = Instance::from_wasmi_module(
+            Arc::new(module),
+            backend,
+            options.gas_limit,
+            options.print_debug,
+            options.gas_config.unwrap_or_default(),
+        )?;
+        instance.set_max_stack_depth(options.max_stack_depth.unwrap_or(DEFAULT_MAX_STACK_DEPTH));
+        Ok(instance)
     }
 }
 
@@ -246,15 +549,31 @@ where
     Q: Querier + 'static, // 'static is needed here to allow using this in an Environment that is cloned into closures
     W: WasmVM + 'static,
 {
-    pub fn api(&self) -> &A {
-        &self.env.api
+    pub fn api(&self) -> A {
+        self.env.api()
     }
 
     /// Decomposes this instance into its components.
     /// External dependencies are returned for reuse, the rest is dropped.
     pub fn recycle(self) -> Option<Backend<A, S, Q>> {
+        self.take_backend()
+    }
+
+    /// Sets the maximum stack depth of the contract, see [`InstanceOptions::max_stack_depth`].
+    /// Instances start with the limit of their options.
+    pub fn set_max_stack_depth(&mut self, max_depth: u32) {
+        self._inner.set_max_stack_depth(max_depth);
+    }
+
+    /// Returns the maximum stack depth of the contract
+    pub fn max_stack_depth(&self) -> u32 {
+        self._inner.max_stack_depth()
+    }
+
+    /// Moves the external dependencies out of this instance
+    pub(crate) fn take_backend(&self) -> Option<Backend<A, S, Q>> {
         if let (Some(storage), Some(querier)) = self.env.move_out() {
-            let api = self.env.api;
+            let api = self.env.api();
             Some(Backend {
                 api,
                 storage,
@@ -265,6 +584,65 @@ where
         }
     }
 
+    /// Prepares an instance taken from a pool for its next use with the given backend.
+    pub(crate) fn reuse(&self, backend: Backend<A, S, Q>, gas_limit: u64) {
+        self.env.reset(gas_limit);
+        self.env.set_api(backend.api);
+        self.env.move_in(backend.storage, backend.querier);
+    }
+
+    /// Returns true if this instance was created with the given debug flag and gas config
+    pub(crate) fn has_options(&self, print_debug: bool, gas_config: &GasConfig) -> bool {
+        self.env.print_debug == print_debug && self.env.gas_config == *gas_config
+    }
+
+    /// Copies the memory and the mutable globals of this instance
+    pub(crate) fn capture_state(&self) -> VmResult<InstanceState> {
+        Ok(InstanceState {
+            memory: self.env.memory().read_all(),
+            globals: self.env.with_wasm_instance(|instance| instance.globals())?,
+        })
+    }
+
+    /// Restores the memory and the mutable globals of this instance. This fails if the memory
+    /// grew since the state was captured, since Wasm memory cannot shrink.
+    pub(crate) fn restore_state(&self, state: &InstanceState) -> VmResult<()> {
+        self.env.memory().write_all(&state.memory)?;
+        self.env
+            .with_wasm_instance(|instance| instance.set_globals(&state.globals))
+    }
+
+    /// Captures the linear memory, the mutable globals and the gas state of this instance,
+    /// such that it can be rewound to this point with [`Instance::restore`]. This must not be
+    /// called during a call of the contract.
+    pub fn snapshot(&self) -> VmResult<InstanceSnapshot> {
+        Ok(InstanceSnapshot {
+            state: self.capture_state()?,
+            gas_state: self.env.with_gas_state(|gas_state| gas_state.clone()),
+            gas_left: self.env.get_gas_left(),
+        })
+    }
+
+    /// Rewinds this instance to the given snapshot taken from the same instance.
+    ///
+    /// Since Wasm memory cannot shrink, this fails if the memory grew since the snapshot
+    /// was taken. Then the instance is left unchanged and a new instance is needed.
+    pub fn restore(&mut self, snapshot: &InstanceSnapshot) -> VmResult<()> {
+        let memory_size = self.memory_pages() * WASM_PAGE_SIZE;
+        if memory_size != snapshot.memory_size() {
+            return Err(VmError::generic_err(format!(
+                "Cannot restore a snapshot of {} bytes of memory into a memory of {} bytes",
+                snapshot.memory_size(),
+                memory_size
+            )));
+        }
+        self.restore_state(&snapshot.state)?;
+        self.env
+            .with_gas_state_mut(|gas_state| *gas_state = snapshot.gas_state.clone());
+        self.env.set_gas_left(snapshot.gas_left);
+        Ok(())
+    }
+
     /// Returns the features required by this contract.
     ///
     /// This is not needed for production because we can do static analysis
@@ -306,6 +684,8 @@ where
                 .gas_limit
                 .saturating_sub(state.externally_used_gas)
                 .saturating_sub(gas_left),
+            initial_memory_pages: self.initial_memory_pages,
+            memory_pages: self.memory_pages(),
         }
     }
 
@@ -316,6 +696,30 @@ where
         self.env.set_storage_readonly(new_value);
     }
 
+    /// Attaches a tracer that is called for every import the contract calls.
+    /// Instances start with a [`NoopTracer`](crate::NoopTracer).
+    pub fn set_tracer(&mut self, tracer: Arc<dyn Tracer>) {
+        self.env.set_tracer(tracer);
+    }
+
+    /// Attaches a metrics registry that records the gas used by every call of an entry point.
+    /// Instances created by a [`Cache`](crate::Cache) use the registry of the cache.
+    pub fn set_metrics(&mut self, metrics: Arc<MetricsRegistry>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Returns true if a metrics registry is attached
+    pub(crate) fn has_metrics(&self) -> bool {
+        self.metrics.is_some()
+    }
+
+    /// Records a call of the given entry point in the attached metrics registry, if any
+    pub(crate) fn record_call(&self, entry_point: &str, gas_used: u64) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_call(entry_point, gas_used);
+        }
+    }
+
     pub fn with_storage<F: FnOnce(&mut S) -> VmResult<T>, T>(&mut self, func: F) -> VmResult<T> {
         self.env.with_storage_from_context::<F, T>(func)
     }
@@ -374,6 +778,7 @@ pub fn instance_from_module<A, S, Q>(
     backend: Backend<A, S, Q>,
     gas_limit: u64,
     print_debug: bool,
+    gas_config: GasConfig,
     extra_imports: Option<HashMap<&str, Exports>>,
 ) -> VmResult<Instance<A, S, Q, WasmerInstance>>
 where
@@ -381,7 +786,15 @@ where
     S: Storage + 'static, // 'static is needed here to allow using this in an Environment that is cloned into closures
     Q: Querier + 'static,
 {
-    Instance::from_module(module, backend, gas_limit, print_debug, extra_imports, None)
+    Instance::from_module(
+        module,
+        backend,
+        gas_limit,
+        print_debug,
+        gas_config,
+        extra_imports,
+        None,
+    )
 }
 
 #[cfg(test)]
@@ -398,6 +811,7 @@ mod tests {
         mock_instance_with_balances, mock_instance_with_failing_api, mock_instance_with_gas_limit,
         mock_instance_with_options, MockInstanceOptions,
     };
+    use crate::tracer::{TraceEvent, TraceValue};
     use cosmwasm_std::{
         coin, coins, from_binary, AllBalanceResponse, BalanceResponse, BankQuery, Empty,
         QueryRequest,
@@ -455,7 +869,7 @@ mod tests {
 
         let backend = mock_backend(&[]);
         let (instance_options, memory_limit) = mock_instance_options();
-        let module = compile(&wasm, memory_limit, &[]).unwrap();
+        let module = compile(&wasm, memory_limit, &OperatorCostTable::default(), &[]).unwrap();
 
         #[derive(wasmer::WasmerEnv, Clone)]
         struct MyEnv {
@@ -480,6 +894,7 @@ mod tests {
             backend,
             instance_options.gas_limit,
             false,
+            instance_options.gas_config.unwrap_or_default(),
             Some(extra_imports),
             None,
         )
@@ -598,6 +1013,64 @@ mod tests {
         }
     }
 
+    /// A contract that aborts with a message, like the panic handler of cosmwasm-std does
+    const ABORT_WAT: &str = r#"(module
+        (import "env" "abort" (func $abort (param i32)))
+        (memory 1)
+        (export "memory" (memory 0))
+        ;; a Region with offset 64, capacity 55 and length 55
+        (data (i32.const 32) "\40\00\00\00\37\00\00\00\37\00\00\00")
+        (data (i32.const 64) "attempt to subtract with overflow at src/contract.rs:42")
+        (func (export "interface_version_8"))
+        (func (export "instantiate") (param i32 i32 i32) (result i32) i32.const 0)
+        (func (export "allocate") (param i32) (result i32) i32.const 0)
+        (func (export "deallocate") (param i32))
+        (func (export "fail")
+            i32.const 32
+            call $abort)
+    )"#;
+
+    #[test]
+    fn abort_returns_message() {
+        let wasm = wat::parse_str(ABORT_WAT).unwrap();
+        let mut instance = mock_instance_with_gas_limit(&wasm, 1_000_000_000);
+
+        match instance.call_function0("fail", &[]).unwrap_err() {
+            VmError::Aborted { msg, .. } => {
+                assert_eq!(
+                    msg,
+                    "attempt to subtract with overflow at src/contract.rs:42"
+                )
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "interpreter")]
+    fn wasmi_abort_returns_message() {
+        let wasm = wat::parse_str(ABORT_WAT).unwrap();
+        let (options, memory_limit) = mock_instance_options();
+        let mut instance = Instance::from_wasmi_code(
+            &wasm,
+            mock_backend(&[]),
+            options,
+            memory_limit,
+            &OperatorCostTable::default(),
+        )
+        .unwrap();
+
+        match instance.call_function0("fail", &[]).unwrap_err() {
+            VmError::Aborted { msg, .. } => {
+                assert_eq!(
+                    msg,
+                    "attempt to subtract with overflow at src/contract.rs:42"
+                )
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
     #[test]
     fn read_memory_errors_when_when_length_is_too_long() {
         let length = 6;
@@ -684,6 +1157,240 @@ mod tests {
         assert_eq!(instance.memory_pages(), 19);
     }
 
+    #[test]
+    fn restore_state_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (global $counter (mut i32) (i32.const 5))
+                (memory 1)
+                (export "memory" (memory 0))
+                (func (export "interface_version_8"))
+                (func (export "instantiate") (param i32 i32 i32) (result i32) i32.const 0)
+                (func (export "allocate") (param i32) (result i32) i32.const 0)
+                (func (export "deallocate") (param i32))
+                (func (export "bump")
+                    global.get $counter
+                    i32.const 1
+                    i32.add
+                    global.set $counter
+                    i32.const 100
+                    global.get $counter
+                    i32.store)
+                (func (export "grow")
+                    i32.const 1
+                    memory.grow
+                    drop)
+            )"#,
+        )
+        .unwrap();
+        let instance = mock_instance(&wasm, &[]);
+        let initial = instance.capture_state().unwrap();
+        assert_eq!(initial.memory.len(), 65536);
+        assert_eq!(initial.globals.len(), 1);
+        assert_eq!(initial.globals[0].i32(), Some(5));
+
+        instance.call_function0("bump", &[]).unwrap();
+        let changed = instance.capture_state().unwrap();
+        assert_eq!(changed.globals[0].i32(), Some(6));
+        assert_eq!(changed.memory[100], 6);
+
+        instance.restore_state(&initial).unwrap();
+        let restored = instance.capture_state().unwrap();
+        assert_eq!(restored.memory, initial.memory);
+        assert_eq!(restored.globals[0].i32(), Some(5));
+
+        // grown memory cannot be restored
+        instance.call_function0("grow", &[]).unwrap();
+        instance.restore_state(&initial).unwrap_err();
+    }
+
+    #[test]
+    fn snapshot_and_restore_work() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (global $counter (mut i32) (i32.const 5))
+                (memory 1)
+                (export "memory" (memory 0))
+                (func (export "interface_version_8"))
+                (func (export "instantiate") (param i32 i32 i32) (result i32) i32.const 0)
+                (func (export "allocate") (param i32) (result i32) i32.const 0)
+                (func (export "deallocate") (param i32))
+                (func (export "bump")
+                    global.get $counter
+                    i32.const 1
+                    i32.add
+                    global.set $counter
+                    i32.const 100
+                    global.get $counter
+                    i32.store)
+                (func (export "grow")
+                    i32.const 1
+                    memory.grow
+                    drop)
+            )"#,
+        )
+        .unwrap();
+        let mut instance = mock_instance_with_gas_limit(&wasm, 1_000_000_000);
+        let snapshot = instance.snapshot().unwrap();
+        assert_eq!(snapshot.memory_size(), 65536);
+        let report_before = instance.create_gas_report();
+
+        instance.call_function0("bump", &[]).unwrap();
+        instance.call_function0("bump", &[]).unwrap();
+        let gas_used = report_before.remaining - instance.get_gas_left();
+        assert!(gas_used > 0);
+
+        instance.restore(&snapshot).unwrap();
+        let restored = instance.capture_state().unwrap();
+        assert_eq!(restored.memory[100], 0);
+        assert_eq!(restored.globals[0].i32(), Some(5));
+        assert_eq!(instance.get_gas_left(), report_before.remaining);
+
+        // the same calls lead to the same state and gas usage again
+        instance.call_function0("bump", &[]).unwrap();
+        instance.call_function0("bump", &[]).unwrap();
+        assert_eq!(report_before.remaining - instance.get_gas_left(), gas_used);
+        assert_eq!(instance.capture_state().unwrap().memory[100], 7);
+
+        // a snapshot can be restored multiple times
+        instance.restore(&snapshot).unwrap();
+        assert_eq!(instance.capture_state().unwrap().globals[0].i32(), Some(5));
+
+        // grown memory cannot be restored and the instance is unchanged
+        instance.call_function0("bump", &[]).unwrap();
+        instance.call_function0("grow", &[]).unwrap();
+        let err = instance.restore(&snapshot).unwrap_err();
+        assert!(err.to_string().contains(
+            "Cannot restore a snapshot of 65536 bytes of memory into a memory of 131072 bytes"
+        ));
+        assert_eq!(instance.capture_state().unwrap().globals[0].i32(), Some(6));
+    }
+
+    #[test]
+    fn snapshot_and_restore_rewind_contract_executions() {
+        use crate::testing::{MockApi, MockQuerier, MockStorage};
+        type TestInstance = Instance<MockApi, MockStorage, MockQuerier, WasmerInstance>;
+
+        let mut instance = mock_instance(CONTRACT, &[]);
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        call_instantiate::<_, _, _, Empty, WasmerInstance>(&mut instance, &mock_env(), &info, msg)
+            .unwrap()
+            .unwrap();
+
+        let execute = |instance: &mut TestInstance| {
+            let info = mock_info("verifies", &coins(15, "earth"));
+            let msg = br#"{"release":{}}"#;
+            let res =
+                call_execute::<_, _, _, Empty, WasmerInstance>(instance, &mock_env(), &info, msg)
+                    .unwrap()
+                    .unwrap();
+            (res, instance.create_gas_report().used_internally)
+        };
+
+        // warm up the allocator of the contract, such that further executions
+        // do not grow the memory
+        execute(&mut instance);
+
+        let snapshot = instance.snapshot().unwrap();
+        let storage_snapshot = instance
+            .with_storage(|storage| Ok(storage.clone()))
+            .unwrap();
+
+        let (res1, gas1) = execute(&mut instance);
+        for _ in 0..3 {
+            instance.restore(&snapshot).unwrap();
+            instance
+                .with_storage(|storage| {
+                    *storage = storage_snapshot.clone();
+                    Ok(())
+                })
+                .unwrap();
+            let (res, gas) = execute(&mut instance);
+            assert_eq!(res, res1);
+            assert_eq!(gas, gas1);
+        }
+    }
+
+    #[test]
+    /// A contract whose `recurse` export calls itself `n` times. Every call has a frame
+    /// weight of `RECURSE_WEIGHT`.
+    fn recursive_contract() -> Vec<u8> {
+        wat::parse_str(
+            r#"(module
+                (memory 1)
+                (export "memory" (memory 0))
+                (func (export "interface_version_8"))
+                (func (export "instantiate") (param i32 i32 i32) (result i32) i32.const 0)
+                (func (export "allocate") (param i32) (result i32) i32.const 0)
+                (func (export "deallocate") (param i32))
+                (func $recurse (export "recurse") (param $n i32)
+                    local.get $n
+                    if
+                        local.get $n
+                        i32.const 1
+                        i32.sub
+                        call $recurse
+                    end)
+            )"#,
+        )
+        .unwrap()
+    }
+
+    /// The base weight plus one parameter and at most two operands
+    const RECURSE_WEIGHT: u32 = 64 + 1 + 2;
+
+    #[test]
+    fn max_stack_depth_works() {
+        let mut instance = mock_instance(&recursive_contract(), &[]);
+        assert_eq!(instance.max_stack_depth(), DEFAULT_MAX_STACK_DEPTH);
+        let max_calls = DEFAULT_MAX_STACK_DEPTH / RECURSE_WEIGHT;
+        assert_eq!(max_calls, 978);
+        instance
+            .call_function0("recurse", &[(max_calls as i32).into()])
+            .unwrap();
+        match instance
+            .call_function0("recurse", &[(max_calls as i32 + 1).into()])
+            .unwrap_err()
+        {
+            VmError::StackDepthExceeded { limit, .. } => {
+                assert_eq!(limit, DEFAULT_MAX_STACK_DEPTH)
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        instance.set_max_stack_depth(10 * RECURSE_WEIGHT);
+        assert_eq!(instance.max_stack_depth(), 10 * RECURSE_WEIGHT);
+        // the depth is reset after the failed call
+        instance.call_function0("recurse", &[10.into()]).unwrap();
+        match instance
+            .call_function0("recurse", &[11.into()])
+            .unwrap_err()
+        {
+            VmError::StackDepthExceeded { limit, .. } => assert_eq!(limit, 10 * RECURSE_WEIGHT),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn from_code_applies_max_stack_depth() {
+        let (mut options, memory_limit) = mock_instance_options();
+        options.max_stack_depth = Some(10 * RECURSE_WEIGHT);
+        let mut instance = Instance::from_code(
+            &recursive_contract(),
+            mock_backend(&[]),
+            options,
+            memory_limit,
+        )
+        .unwrap();
+        assert_eq!(instance.max_stack_depth(), 10 * RECURSE_WEIGHT);
+        instance.call_function0("recurse", &[10.into()]).unwrap();
+        let err = instance
+            .call_function0("recurse", &[11.into()])
+            .unwrap_err();
+        assert!(matches!(err, VmError::StackDepthExceeded { .. }));
+    }
+
     #[test]
     fn get_gas_left_works() {
         let instance = mock_instance_with_gas_limit(CONTRACT, 123321);
@@ -701,6 +1408,8 @@ mod tests {
         assert_eq!(report1.used_internally, 0);
         assert_eq!(report1.limit, LIMIT);
         assert_eq!(report1.remaining, LIMIT);
+        assert_eq!(report1.initial_memory_pages, 17);
+        assert_eq!(report1.memory_pages, 17);
 
         // init contract
         let info = mock_info("creator", &coins(1000, "earth"));
@@ -711,7 +1420,10 @@ mod tests {
 
         let report2 = instance.create_gas_report();
         assert_eq!(report2.used_externally, 73);
-        assert_eq!(report2.used_internally, 5775750110);
+        assert_eq!(
+            report2.used_internally,
+            6016750110 + memory_grow_cost(report2.memory_pages - 17)
+        );
         assert_eq!(report2.limit, LIMIT);
         assert_eq!(
             report2.remaining,
@@ -719,6 +1431,79 @@ mod tests {
         );
     }
 
+    /// A contract that grows its memory by the given number of pages
+    const GROW_WAT: &str = r#"(module
+        (memory 1)
+        (export "memory" (memory 0))
+        (func (export "interface_version_8"))
+        (func (export "instantiate") (param i32 i32 i32) (result i32) i32.const 0)
+        (func (export "allocate") (param i32) (result i32) i32.const 0)
+        (func (export "deallocate") (param i32))
+        (func (export "grow") (param i32)
+            local.get 0
+            memory.grow
+            drop)
+    )"#;
+
+    /// The fee for growing the memory by the given number of pages with the default cost table
+    fn memory_grow_cost(pages: usize) -> u64 {
+        pages as u64 * OperatorCostTable::default().memory_grow_per_page
+    }
+
+    #[test]
+    fn create_gas_report_tracks_memory_pages() {
+        let wasm = wat::parse_str(GROW_WAT).unwrap();
+        let instance = mock_instance(&wasm, &[]);
+        let report = instance.create_gas_report();
+        assert_eq!(report.initial_memory_pages, 1);
+        assert_eq!(report.memory_pages, 1);
+
+        instance.call_function0("grow", &[2.into()]).unwrap();
+        let report = instance.create_gas_report();
+        assert_eq!(report.initial_memory_pages, 1);
+        assert_eq!(report.memory_pages, 3);
+
+        // failed growth does not count (the memory limit is 256 pages)
+        instance.call_function0("grow", &[300.into()]).unwrap();
+        let report = instance.create_gas_report();
+        assert_eq!(report.memory_pages, 3);
+    }
+
+    #[test]
+    fn memory_grow_is_charged_per_page() {
+        const LIMIT: u64 = 1_000_000_000_000;
+        let wasm = wat::parse_str(GROW_WAT).unwrap();
+        let cost_table = OperatorCostTable {
+            memory_grow_per_page: 1_000_000,
+            ..OperatorCostTable::default()
+        };
+        let module = compile(&wasm, None, &cost_table, &[]).unwrap();
+        let instance = Instance::from_module(
+            &module,
+            mock_backend(&[]),
+            LIMIT,
+            false,
+            GasConfig::default(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        instance.call_function0("grow", &[0.into()]).unwrap();
+        let base_cost = LIMIT - instance.get_gas_left();
+        instance.call_function0("grow", &[3.into()]).unwrap();
+        let used = LIMIT - base_cost - instance.get_gas_left();
+        assert_eq!(used, base_cost + 3 * 1_000_000);
+
+        // out of gas before memory grows
+        instance.env.set_gas_left(3 * 1_000_000 - 1);
+        match instance.call_function0("grow", &[3.into()]).unwrap_err() {
+            VmError::GasDepletion { .. } => {}
+            e => panic!("Unexpected error: {:?}", e),
+        }
+        assert_eq!(instance.create_gas_report().memory_pages, 4);
+    }
+
     #[test]
     fn set_storage_readonly_works() {
         let mut instance = mock_instance(CONTRACT, &[]);
@@ -900,7 +1685,66 @@ mod tests {
             .unwrap();
 
         let init_used = orig_gas - instance.get_gas_left();
-        assert_eq!(init_used, 5775750183);
+        let pages_grown = instance.memory_pages() - 17;
+        assert_eq!(init_used, 6016750183 + memory_grow_cost(pages_grown));
+    }
+
+    #[test]
+    fn set_tracer_works() {
+        #[derive(Default)]
+        struct CollectingTracer {
+            events: Mutex<Vec<TraceEvent>>,
+        }
+
+        impl Tracer for CollectingTracer {
+            fn trace(&self, event: &TraceEvent) {
+                self.events.lock().unwrap().push(event.clone());
+            }
+        }
+
+        let mut instance = mock_instance(CONTRACT, &[]);
+        let tracer = Arc::new(CollectingTracer::default());
+        instance.set_tracer(tracer.clone());
+
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        call_instantiate::<_, _, _, Empty, WasmerInstance>(&mut instance, &mock_env(), &info, msg)
+            .unwrap()
+            .unwrap();
+
+        let events = tracer.events.lock().unwrap();
+        let imports: Vec<_> = events.iter().map(|event| event.import).collect();
+        assert_eq!(
+            imports,
+            vec!["debug", "addr_validate", "addr_validate", "db_write"]
+        );
+
+        let debug = &events[0];
+        assert_eq!(
+            debug.args,
+            vec![TraceValue::Bytes("here we go 🚀".as_bytes().to_vec())]
+        );
+        assert_eq!(debug.result_size, 0);
+        assert_eq!(debug.gas_used_internally, 25_000_000);
+        assert_eq!(debug.gas_used_externally, 0);
+
+        let validate = &events[1];
+        assert_eq!(validate.args, vec![TraceValue::Bytes(b"verifies".to_vec())]);
+        assert_eq!(validate.gas_used_internally, 108_000_055);
+        assert_eq!(validate.gas_used_externally, 0);
+        assert_eq!(validate.error, None);
+
+        let write = &events[3];
+        assert_eq!(write.args.len(), 2);
+        assert_eq!(write.gas_used_internally, 0);
+        assert_eq!(write.gas_used_externally, 73);
+
+        // tracing does not change gas consumption
+        let report = instance.create_gas_report();
+        assert_eq!(
+            report.used_internally,
+            6016750110 + memory_grow_cost(report.memory_pages - 17)
+        );
     }
 
     #[test]
@@ -916,6 +1760,7 @@ mod tests {
 
         // run contract - just sanity check - results validate in contract unit tests
         let gas_before_execute = instance.get_gas_left();
+        let pages_before_execute = instance.memory_pages();
         let info = mock_info("verifies", &coins(15, "earth"));
         let msg = br#"{"release":{}}"#;
         call_execute::<_, _, _, Empty, WasmerInstance>(&mut instance, &mock_env(), &info, msg)
@@ -923,7 +1768,8 @@ mod tests {
             .unwrap();
 
         let execute_used = gas_before_execute - instance.get_gas_left();
-        assert_eq!(execute_used, 8627053606);
+        let pages_grown = instance.memory_pages() - pages_before_execute;
+        assert_eq!(execute_used, 8627053606 + memory_grow_cost(pages_grown));
     }
 
     #[test]
@@ -960,6 +1806,7 @@ mod tests {
 
         // run contract - just sanity check - results validate in contract unit tests
         let gas_before_query = instance.get_gas_left();
+        let pages_before_query = instance.memory_pages();
         // we need to encode the key in base64
         let msg = br#"{"verifier":{}}"#;
         let res = call_query(&mut instance, &mock_env(), msg).unwrap();
@@ -967,6 +1814,161 @@ mod tests {
         assert_eq!(answer.as_slice(), b"{\"verifier\":\"verifies\"}");
 
         let query_used = gas_before_query - instance.get_gas_left();
-        assert_eq!(query_used, 4438350006);
+        let pages_grown = instance.memory_pages() - pages_before_query;
+        assert_eq!(query_used, 4438350006 + memory_grow_cost(pages_grown));
+    }
+
+    #[test]
+    #[cfg(feature = "interpreter")]
+    fn wasmi_instance_behaves_like_wasmer_instance() {
+        let (options, memory_limit) = mock_instance_options();
+        let mut wasmer_instance =
+            Instance::from_code(CONTRACT, mock_backend(&[]), options, memory_limit).unwrap();
+        let mut wasmi_instance = Instance::from_wasmi_code(
+            CONTRACT,
+            mock_backend(&[]),
+            options,
+            memory_limit,
+            &OperatorCostTable::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            wasmi_instance.required_features(),
+            wasmer_instance.required_features()
+        );
+        assert_eq!(
+            wasmi_instance.memory_pages(),
+            wasmer_instance.memory_pages()
+        );
+
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        let wasmer_res = call_instantiate::<_, _, _, Empty, WasmerInstance>(
+            &mut wasmer_instance,
+            &mock_env(),
+            &info,
+            msg,
+        )
+        .unwrap();
+        let wasmi_res = call_instantiate::<_, _, _, Empty, WasmiInstance>(
+            &mut wasmi_instance,
+            &mock_env(),
+            &info,
+            msg,
+        )
+        .unwrap();
+        assert_eq!(wasmi_res, wasmer_res);
+
+        let msg = br#"{"verifier":{}}"#;
+        let wasmer_res = call_query(&mut wasmer_instance, &mock_env(), msg).unwrap();
+        let wasmi_res = call_query(&mut wasmi_instance, &mock_env(), msg).unwrap();
+        assert_eq!(wasmi_res, wasmer_res);
+
+        // gas is metered but block boundaries differ from the Wasmer metering
+        let report = wasmi_instance.create_gas_report();
+        assert!(report.used_internally > 0);
+        assert_eq!(
+            report.used_externally,
+            wasmer_instance.create_gas_report().used_externally
+        );
+
+        // contract errors are the same
+        let info = mock_info("someone else", &coins(15, "earth"));
+        let msg = br#"{"release":{}}"#;
+        let wasmer_res = call_execute::<_, _, _, Empty, WasmerInstance>(
+            &mut wasmer_instance,
+            &mock_env(),
+            &info,
+            msg,
+        )
+        .unwrap();
+        let wasmi_res = call_execute::<_, _, _, Empty, WasmiInstance>(
+            &mut wasmi_instance,
+            &mock_env(),
+            &info,
+            msg,
+        )
+        .unwrap();
+        assert_eq!(wasmi_res, wasmer_res);
+    }
+
+    #[test]
+    #[cfg(feature = "interpreter")]
+    fn wasmi_instance_uses_operator_cost_table() {
+        let (options, memory_limit) = mock_instance_options();
+        let mut gas_used = vec![];
+        for cost in [150_000, 300_000] {
+            let mut instance = Instance::from_wasmi_code(
+                CONTRACT,
+                mock_backend(&[]),
+                options,
+                memory_limit,
+                &OperatorCostTable::flat(cost),
+            )
+            .unwrap();
+            let info = mock_info("creator", &coins(1000, "earth"));
+            let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+            call_instantiate::<_, _, _, Empty, WasmiInstance>(
+                &mut instance,
+                &mock_env(),
+                &info,
+                msg,
+            )
+            .unwrap()
+            .unwrap();
+            gas_used.push(instance.create_gas_report().used_internally);
+        }
+        assert!(gas_used[1] > gas_used[0]);
+    }
+
+    #[test]
+    #[cfg(feature = "interpreter")]
+    fn wasmi_instance_limits_stack_depth() {
+        let (mut options, memory_limit) = mock_instance_options();
+        options.max_stack_depth = Some(10 * RECURSE_WEIGHT);
+        let mut instance = Instance::from_wasmi_code(
+            &recursive_contract(),
+            mock_backend(&[]),
+            options,
+            memory_limit,
+            &OperatorCostTable::default(),
+        )
+        .unwrap();
+        assert_eq!(instance.max_stack_depth(), 10 * RECURSE_WEIGHT);
+        instance.call_function0("recurse", &[10.into()]).unwrap();
+        match instance
+            .call_function0("recurse", &[11.into()])
+            .unwrap_err()
+        {
+            VmError::StackDepthExceeded { limit, .. } => assert_eq!(limit, 10 * RECURSE_WEIGHT),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "interpreter")]
+    fn wasmi_instance_enforces_gas_limit() {
+        let (mut options, memory_limit) = mock_instance_options();
+        options.gas_limit = 20_000;
+        let mut instance = Instance::from_wasmi_code(
+            CONTRACT,
+            mock_backend(&[]),
+            options,
+            memory_limit,
+            &OperatorCostTable::default(),
+        )
+        .unwrap();
+
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        let err = call_instantiate::<_, _, _, Empty, WasmiInstance>(
+            &mut instance,
+            &mock_env(),
+            &info,
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, VmError::GasDepletion { .. }));
+        assert_eq!(instance.get_gas_left(), 0);
     }
 }
@@ -13,19 +13,23 @@ mod imports;
 mod instance;
 mod limited;
 mod memory;
+mod metrics;
 mod modules;
+mod recording;
 mod sections;
 mod serde;
 mod size;
 mod static_analysis;
 pub mod testing;
+mod tracer;
+mod transactional;
 mod wasm;
 mod wasm_backend;
 
 pub use crate::backend::{
     Backend, BackendApi, BackendError, BackendResult, GasInfo, Querier, Storage,
 };
-pub use crate::cache::{AnalysisReport, Cache, CacheOptions, Metrics, Stats};
+pub use crate::cache::{AnalysisReport, Cache, CacheOptions, Metrics, Stats, WarmUp};
 pub use crate::calls::{
     call_execute, call_execute_raw, call_instantiate, call_instantiate_raw, call_migrate,
     call_migrate_raw, call_query, call_query_raw, call_reply, call_reply_raw, call_sudo,
@@ -39,15 +43,30 @@ pub use crate::calls::{
     call_ibc_packet_receive_raw, call_ibc_packet_timeout, call_ibc_packet_timeout_raw,
 };
 pub use crate::checksum::Checksum;
+pub use crate::compatibility::{
+    Severity, ValidationReport, Violation, ViolationCategory, WasmLimits,
+};
+pub use crate::environment::{GasConfig, LinearGasCost};
 pub use crate::errors::{
     CommunicationError, CommunicationResult, RegionValidationError, RegionValidationResult,
     VmError, VmResult,
 };
 pub use crate::features::features_from_csv;
-pub use crate::instance::{GasReport, Instance, InstanceOptions};
+pub use crate::instance::{GasReport, Instance, InstanceOptions, InstanceSnapshot};
+pub use crate::metrics::{render_prometheus, Calls, Compilations, MetricsRegistry};
+pub use crate::modules::RemovalStats;
+pub use crate::recording::{
+    record_backend, replay_backend, RecordedCall, Recorder, Recording, RecordingApi,
+    RecordingQuerier, RecordingStorage, Replay, ReplayApi, ReplayQuerier, ReplayStorage,
+};
 pub use crate::serde::{from_slice, to_vec};
 pub use crate::size::Size;
+pub use crate::tracer::{JsonLinesTracer, NoopTracer, TraceEvent, TraceValue, Tracer};
+pub use crate::transactional::{TransactionalGasConfig, TransactionalStorage};
 pub use crate::wasm::WasmVM;
+pub use crate::wasm_backend::{OperatorCostTable, DEFAULT_MAX_STACK_DEPTH};
+#[cfg(feature = "interpreter")]
+pub use crate::wasm_backend::{WasmiInstance, WasmiModule};
 
 #[doc(hidden)]
 pub mod internals {
@@ -56,7 +75,7 @@ pub mod internals {
     //! Please don't use any of these types directly, as
     //! they might change frequently or be removed in the future.
 
-    pub use crate::compatibility::check_wasm;
+    pub use crate::compatibility::{check_wasm, check_wasm_report, check_wasm_with_limits};
     pub use crate::instance::instance_from_module;
     pub use crate::wasm_backend::{compile, make_runtime_store};
 }
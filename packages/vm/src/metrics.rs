@@ -0,0 +1,438 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::cache::Metrics;
+use crate::checksum::Checksum;
+
+/// Upper bounds of the instantiation latency buckets, in seconds
+const INSTANTIATION_BUCKETS: [f64; 10] = [
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1,
+];
+
+/// Collects metrics of compilations, instantiations and contract calls.
+///
+/// A registry is created by every [`Cache`](crate::Cache) and attached to all instances
+/// it creates. Use [`render_prometheus`] to export it.
+///
+/// Compilations are exported as totals over all codes unless
+/// [`MetricsRegistry::set_checksum_labels`] is enabled, since a label per checksum
+/// has an unbounded number of values.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    inner: Mutex<Registry>,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    compilations: HashMap<Checksum, Compilations>,
+    instantiation_buckets: [u64; INSTANTIATION_BUCKETS.len()],
+    instantiation_count: u64,
+    instantiation_seconds: f64,
+    calls: BTreeMap<String, Calls>,
+    checksum_labels: bool,
+}
+
+/// The compilations of one checksum
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Compilations {
+    pub count: u64,
+    pub seconds: f64,
+}
+
+/// The calls of one entry point
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Calls {
+    pub count: u64,
+    pub gas_used: u64,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the compilation of a Wasm code to a module
+    pub fn record_compilation(&self, checksum: &Checksum, duration: Duration) {
+        let mut registry = self.inner.lock().unwrap();
+        let compilations = registry.compilations.entry(*checksum).or_default();
+        compilations.count += 1;
+        compilations.seconds += duration.as_secs_f64();
+    }
+
+    /// Records the time it took to create an instance from a module
+    pub fn record_instantiation(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let mut registry = self.inner.lock().unwrap();
+        if let Some(bucket) = INSTANTIATION_BUCKETS
+            .iter()
+            .position(|upper_bound| seconds <= *upper_bound)
+        {
+            registry.instantiation_buckets[bucket] += 1;
+        }
+        registry.instantiation_count += 1;
+        registry.instantiation_seconds += seconds;
+    }
+
+    /// Records a call of a contract entry point, e.g. `execute`, along with the gas it used.
+    /// Failed calls are recorded too.
+    pub fn record_call(&self, entry_point: &str, gas_used: u64) {
+        let mut registry = self.inner.lock().unwrap();
+        let calls = registry.calls.entry(entry_point.to_string()).or_default();
+        calls.count += 1;
+        calls.gas_used = calls.gas_used.saturating_add(gas_used);
+    }
+
+    /// Enables or disables the `checksum` label of the compilation metrics in
+    /// [`render_prometheus`]. Disabled by default.
+    pub fn set_checksum_labels(&self, enabled: bool) {
+        self.inner.lock().unwrap().checksum_labels = enabled;
+    }
+
+    /// Returns the compilations recorded for the given checksum
+    pub fn compilations(&self, checksum: &Checksum) -> Compilations {
+        let registry = self.inner.lock().unwrap();
+        registry
+            .compilations
+            .get(checksum)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of recorded instantiations
+    pub fn instantiations(&self) -> u64 {
+        self.inner.lock().unwrap().instantiation_count
+    }
+
+    /// Returns the calls recorded for the given entry point
+    pub fn calls(&self, entry_point: &str) -> Calls {
+        let registry = self.inner.lock().unwrap();
+        registry.calls.get(entry_point).copied().unwrap_or_default()
+    }
+}
+
+/// Renders the cache metrics and the registry in the Prometheus text exposition format (version 0.0.4).
+///
+/// All metric names are prefixed with `cosmwasm_vm_`.
+pub fn render_prometheus(cache: &Metrics, registry: &MetricsRegistry) -> String {
+    let mut out = String::new();
+    // Writing to a String does not fail
+    write_cache_metrics(&mut out, cache).unwrap();
+    write_registry(&mut out, &registry.inner.lock().unwrap()).unwrap();
+    out
+}
+
+fn write_cache_metrics(out: &mut String, cache: &Metrics) -> std::fmt::Result {
+    let stats = &cache.stats;
+    write_header(
+        out,
+        "cache_hits_total",
+        "counter",
+        "Number of modules taken from a cache",
+    )?;
+    writeln!(
+        out,
+        "cosmwasm_vm_cache_hits_total{{cache=\"pinned_memory\"}} {}",
+        stats.hits_pinned_memory_cache
+    )?;
+    writeln!(
+        out,
+        "cosmwasm_vm_cache_hits_total{{cache=\"memory\"}} {}",
+        stats.hits_memory_cache
+    )?;
+    writeln!(
+        out,
+        "cosmwasm_vm_cache_hits_total{{cache=\"fs\"}} {}",
+        stats.hits_fs_cache
+    )?;
+    write_header(
+        out,
+        "cache_misses_total",
+        "counter",
+        "Number of modules compiled from Wasm because they were not found in any cache",
+    )?;
+    writeln!(out, "cosmwasm_vm_cache_misses_total {}", stats.misses)?;
+    write_header(
+        out,
+        "cache_elements",
+        "gauge",
+        "Number of modules in a memory cache",
+    )?;
+    writeln!(
+        out,
+        "cosmwasm_vm_cache_elements{{cache=\"pinned_memory\"}} {}",
+        cache.elements_pinned_memory_cache
+    )?;
+    writeln!(
+        out,
+        "cosmwasm_vm_cache_elements{{cache=\"memory\"}} {}",
+        cache.elements_memory_cache
+    )?;
+    write_header(
+        out,
+        "cache_size_bytes",
+        "gauge",
+        "Size of the modules in a memory cache",
+    )?;
+    writeln!(
+        out,
+        "cosmwasm_vm_cache_size_bytes{{cache=\"pinned_memory\"}} {}",
+        cache.size_pinned_memory_cache
+    )?;
+    writeln!(
+        out,
+        "cosmwasm_vm_cache_size_bytes{{cache=\"memory\"}} {}",
+        cache.size_memory_cache
+    )?;
+    write_header(
+        out,
+        "memory_cache_evictions_total",
+        "counter",
+        "Number of modules evicted from the memory cache to make room for others",
+    )?;
+    writeln!(
+        out,
+        "cosmwasm_vm_memory_cache_evictions_total {}",
+        cache.evictions_memory_cache
+    )
+}
+
+fn write_registry(out: &mut String, registry: &Registry) -> std::fmt::Result {
+    if registry.checksum_labels {
+        write_compilations_by_checksum(out, registry)?;
+    } else {
+        let mut total = Compilations::default();
+        for compilations in registry.compilations.values() {
+            total.count += compilations.count;
+            total.seconds += compilations.seconds;
+        }
+        write_header(
+            out,
+            "compilations_total",
+            "counter",
+            "Number of compilations from Wasm",
+        )?;
+        writeln!(out, "cosmwasm_vm_compilations_total {}", total.count)?;
+        write_header(
+            out,
+            "compile_seconds_total",
+            "counter",
+            "Time spent compiling Wasm",
+        )?;
+        writeln!(out, "cosmwasm_vm_compile_seconds_total {}", total.seconds)?;
+    }
+
+    write_header(
+        out,
+        "instantiation_seconds",
+        "histogram",
+        "Time spent creating instances from modules",
+    )?;
+    let mut cumulative = 0;
+    for (upper_bound, count) in INSTANTIATION_BUCKETS
+        .iter()
+        .zip(registry.instantiation_buckets.iter())
+    {
+        cumulative += count;
+        writeln!(
+            out,
+            "cosmwasm_vm_instantiation_seconds_bucket{{le=\"{}\"}} {}",
+            upper_bound, cumulative
+        )?;
+    }
+    writeln!(
+        out,
+        "cosmwasm_vm_instantiation_seconds_bucket{{le=\"+Inf\"}} {}",
+        registry.instantiation_count
+    )?;
+    writeln!(
+        out,
+        "cosmwasm_vm_instantiation_seconds_sum {}",
+        registry.instantiation_seconds
+    )?;
+    writeln!(
+        out,
+        "cosmwasm_vm_instantiation_seconds_count {}",
+        registry.instantiation_count
+    )?;
+
+    write_header(
+        out,
+        "calls_total",
+        "counter",
+        "Number of contract calls, by entry point",
+    )?;
+    for (entry_point, calls) in registry.calls.iter() {
+        writeln!(
+            out,
+            "cosmwasm_vm_calls_total{{entry_point=\"{}\"}} {}",
+            entry_point, calls.count
+        )?;
+    }
+    write_header(
+        out,
+        "gas_used_total",
+        "counter",
+        "Gas used by contract calls, by entry point",
+    )?;
+    for (entry_point, calls) in registry.calls.iter() {
+        writeln!(
+            out,
+            "cosmwasm_vm_gas_used_total{{entry_point=\"{}\"}} {}",
+            entry_point, calls.gas_used
+        )?;
+    }
+    Ok(())
+}
+
+fn write_compilations_by_checksum(out: &mut String, registry: &Registry) -> std::fmt::Result {
+    // Sort by checksum for a stable output
+    let mut compilations: Vec<(String, Compilations)> = registry
+        .compilations
+        .iter()
+        .map(|(checksum, compilations)| (checksum.to_hex(), *compilations))
+        .collect();
+    compilations.sort_by(|a, b| a.0.cmp(&b.0));
+    write_header(
+        out,
+        "compilations_total",
+        "counter",
+        "Number of compilations from Wasm, by checksum",
+    )?;
+    for (checksum, compilations) in compilations.iter() {
+        writeln!(
+            out,
+            "cosmwasm_vm_compilations_total{{checksum=\"{}\"}} {}",
+            checksum, compilations.count
+        )?;
+    }
+    write_header(
+        out,
+        "compile_seconds_total",
+        "counter",
+        "Time spent compiling Wasm, by checksum",
+    )?;
+    for (checksum, compilations) in compilations.iter() {
+        writeln!(
+            out,
+            "cosmwasm_vm_compile_seconds_total{{checksum=\"{}\"}} {}",
+            checksum, compilations.seconds
+        )?;
+    }
+    Ok(())
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) -> std::fmt::Result {
+    writeln!(out, "# HELP cosmwasm_vm_{} {}", name, help)?;
+    writeln!(out, "# TYPE cosmwasm_vm_{} {}", name, kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::Stats;
+
+    fn empty_cache_metrics() -> Metrics {
+        Metrics {
+            stats: Stats::default(),
+            elements_pinned_memory_cache: 0,
+            elements_memory_cache: 0,
+            size_pinned_memory_cache: 0,
+            size_memory_cache: 0,
+            evictions_memory_cache: 0,
+        }
+    }
+
+    #[test]
+    fn record_works() {
+        let registry = MetricsRegistry::new();
+        let checksum = Checksum::generate(b"code");
+        registry.record_compilation(&checksum, Duration::from_millis(250));
+        registry.record_compilation(&checksum, Duration::from_millis(500));
+        registry.record_instantiation(Duration::from_micros(300));
+        registry.record_call("execute", 1000);
+        registry.record_call("execute", 500);
+
+        assert_eq!(
+            registry.compilations(&checksum),
+            Compilations {
+                count: 2,
+                seconds: 0.75
+            }
+        );
+        assert_eq!(
+            registry.compilations(&Checksum::generate(b"other")),
+            Compilations::default()
+        );
+        assert_eq!(registry.instantiations(), 1);
+        assert_eq!(
+            registry.calls("execute"),
+            Calls {
+                count: 2,
+                gas_used: 1500
+            }
+        );
+        assert_eq!(registry.calls("query"), Calls::default());
+    }
+
+    #[test]
+    fn render_prometheus_works() {
+        let registry = MetricsRegistry::new();
+        let checksum = Checksum::generate(b"code");
+        registry.record_compilation(&checksum, Duration::from_millis(250));
+        registry.record_instantiation(Duration::from_micros(300));
+        registry.record_instantiation(Duration::from_secs(1));
+        registry.record_call("query", 42);
+        let mut cache = empty_cache_metrics();
+        cache.stats.hits_fs_cache = 3;
+        cache.evictions_memory_cache = 2;
+
+        let out = render_prometheus(&cache, &registry);
+        assert!(out.contains(
+            "# HELP cosmwasm_vm_cache_hits_total Number of modules taken from a cache\n\
+             # TYPE cosmwasm_vm_cache_hits_total counter\n"
+        ));
+        assert!(out.contains("cosmwasm_vm_cache_hits_total{cache=\"fs\"} 3\n"));
+        assert!(out.contains("cosmwasm_vm_memory_cache_evictions_total 2\n"));
+        assert!(out.contains("cosmwasm_vm_compile_seconds_total 0.25\n"));
+        assert!(!out.contains("checksum="));
+        assert!(out.contains("cosmwasm_vm_instantiation_seconds_bucket{le=\"0.00025\"} 0\n"));
+        assert!(out.contains("cosmwasm_vm_instantiation_seconds_bucket{le=\"0.0005\"} 1\n"));
+        assert!(out.contains("cosmwasm_vm_instantiation_seconds_bucket{le=\"0.1\"} 1\n"));
+        assert!(out.contains("cosmwasm_vm_instantiation_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(out.contains("cosmwasm_vm_instantiation_seconds_count 2\n"));
+        assert!(out.contains("cosmwasm_vm_calls_total{entry_point=\"query\"} 1\n"));
+        assert!(out.contains("cosmwasm_vm_gas_used_total{entry_point=\"query\"} 42\n"));
+    }
+
+    #[test]
+    fn render_prometheus_sums_compilations_of_all_checksums() {
+        let registry = MetricsRegistry::new();
+        registry.record_compilation(&Checksum::generate(b"a"), Duration::from_millis(250));
+        registry.record_compilation(&Checksum::generate(b"b"), Duration::from_millis(500));
+
+        let out = render_prometheus(&empty_cache_metrics(), &registry);
+        assert!(out.contains("cosmwasm_vm_compilations_total 2\n"));
+        assert!(out.contains("cosmwasm_vm_compile_seconds_total 0.75\n"));
+    }
+
+    #[test]
+    fn render_prometheus_supports_checksum_labels() {
+        let registry = MetricsRegistry::new();
+        let checksum = Checksum::generate(b"code");
+        registry.record_compilation(&checksum, Duration::from_millis(250));
+        registry.set_checksum_labels(true);
+
+        let out = render_prometheus(&empty_cache_metrics(), &registry);
+        assert!(out.contains(&format!(
+            "cosmwasm_vm_compilations_total{{checksum=\"{}\"}} 1\n",
+            checksum.to_hex()
+        )));
+        assert!(out.contains(&format!(
+            "cosmwasm_vm_compile_seconds_total{{checksum=\"{}\"}} 0.25\n",
+            checksum.to_hex()
+        )));
+        assert!(!out.contains("cosmwasm_vm_compilations_total 1\n"));
+    }
+}
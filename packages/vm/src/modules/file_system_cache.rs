@@ -1,13 +1,19 @@
+use std::collections::HashSet;
+use std::convert::TryInto;
 use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
 
-use wasmer::{DeserializeError, Module, Store};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use wasmer::{Module, Store, Target};
 
 use crate::checksum::Checksum;
 use crate::errors::{VmError, VmResult};
 
 use crate::modules::current_wasmer_module_version;
+use crate::wasm_backend::{compiler_name, OperatorCostTable};
 
 /// Bump this version whenever the module system changes in a way
 /// that old stored modules would be corrupt when loaded in the new system.
@@ -32,109 +38,294 @@ use crate::modules::current_wasmer_module_version;
 ///   Version for cosmwasm_vm 1.0.0-beta5 / wasmvm 1.0.0-beta6 that ships with Wasmer 2.1.1.
 /// - **v3**:<br>
 ///   Version for Wasmer 2.2.0 which contains a [module breaking change to 2.1.x](https://github.com/wasmerio/wasmer/pull/2747).
-const MODULE_SERIALIZATION_VERSION: &str = "v3";
+/// - **v4**:<br>
+///   Every module file starts with an [`ArtifactHeader`] that is verified before deserialization.
+///   Modules export all mutable globals, such that instances can be reset, and limit the stack
+///   depth using the `StackLimiter` middleware, which weights every call by the frame size of
+///   the callee.
+const MODULE_SERIALIZATION_VERSION: &str = "v4";
+
+/// The first bytes of every module file, followed by the big endian encoded length
+/// of the JSON encoded [`ArtifactHeader`], the header and the serialized module.
+const ARTIFACT_MAGIC: &[u8] = b"cwmodule";
+
+/// Describes the serialized module following it in a module file.
+///
+/// A module is only deserialized if its hash and the environment it was compiled in match.
+/// This detects corrupted files as well as modules compiled for a different target
+/// or configuration. It does not protect against an attacker that can write to the cache
+/// directory, since they can update the header as well.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct ArtifactHeader {
+    /// Hex encoded SHA-256 hash of the serialized module
+    module_hash: String,
+    wasmer_module_version: u32,
+    /// The target triple and CPU features the module was compiled for
+    target: String,
+    /// The module serialization version, compiler and operator cost table version
+    compile_config: String,
+}
+
+/// The disk space reclaimed by removing files
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RemovalStats {
+    /// The number of removed files
+    pub files: u64,
+    /// The total size of the removed files in bytes
+    pub bytes: u64,
+}
+
+impl AddAssign for RemovalStats {
+    fn add_assign(&mut self, other: Self) {
+        self.files += other.files;
+        self.bytes += other.bytes;
+    }
+}
 
 /// Representation of a directory that contains compiled Wasm artifacts.
+#[derive(Clone)]
 pub struct FileSystemCache {
     /// The base path this cache operates in. Within this path, versioned directories are created.
     /// A sophisticated version of this cache might be able to read multiple input versions in the future.
     base_path: PathBuf,
     wasmer_module_version: u32,
+    /// The version of the operator cost table the stored modules are metered with.
+    /// Modules metered with different tables are stored in different directories.
+    cost_table_version: String,
+    /// The target of the host, see [`ArtifactHeader::target`]
+    target: String,
 }
 
 impl FileSystemCache {
     /// Construct a new `FileSystemCache` around the specified directory.
     /// The contents of the cache are stored in sub-versioned directories.
-    ///
-    /// # Safety
-    ///
-    /// This method is unsafe because there's no way to ensure the artifacts
-    /// stored in this cache haven't been corrupted or tampered with.
-    pub unsafe fn new(path: impl Into<PathBuf>) -> io::Result<Self> {
-        let wasmer_module_version = current_wasmer_module_version();
-
+    /// The version of `cost_table` is part of the directory name, such that
+    /// modules compiled with a different operator cost table are never loaded.
+    pub fn new(path: impl Into<PathBuf>, cost_table: &OperatorCostTable) -> io::Result<Self> {
         let path: PathBuf = path.into();
         if path.exists() {
             let metadata = path.metadata()?;
-            if metadata.is_dir() {
-                if !metadata.permissions().readonly() {
-                    Ok(Self {
-                        base_path: path,
-                        wasmer_module_version,
-                    })
-                } else {
-                    // This directory is readonly.
-                    Err(io::Error::new(
-                        io::ErrorKind::PermissionDenied,
-                        format!("the supplied path is readonly: {}", path.display()),
-                    ))
-                }
-            } else {
+            if !metadata.is_dir() {
                 // This path points to a file.
-                Err(io::Error::new(
+                return Err(io::Error::new(
                     io::ErrorKind::PermissionDenied,
                     format!(
                         "the supplied path already points to a file: {}",
                         path.display()
                     ),
-                ))
+                ));
+            }
+            if metadata.permissions().readonly() {
+                // This directory is readonly.
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("the supplied path is readonly: {}", path.display()),
+                ));
             }
         } else {
             // Create the directory and any parent directories if they don't yet exist.
             fs::create_dir_all(&path)?;
-            Ok(Self {
-                base_path: path,
-                wasmer_module_version,
-            })
         }
+
+        let target = Target::default();
+        Ok(Self {
+            base_path: path,
+            wasmer_module_version: current_wasmer_module_version(),
+            cost_table_version: cost_table.version(),
+            target: format!("{} {:?}", target.triple(), target.cpu_features()),
+        })
     }
 
     /// Loads a serialized module from the file system and returns a module (i.e. artifact + store),
     /// along with the size of the serialized module.
+    ///
+    /// Module files that are corrupted or were compiled in a different environment
+    /// are treated like missing modules, such that they get re-compiled.
     pub fn load(&self, checksum: &Checksum, store: &Store) -> VmResult<Option<Module>> {
         let filename = checksum.to_hex();
         let file_path = self.latest_modules_path().join(filename);
 
-        let result = unsafe { Module::deserialize_from_file(store, &file_path) };
-        match result {
-            Ok(module) => Ok(Some(module)),
-            Err(DeserializeError::Io(err)) => match err.kind() {
-                io::ErrorKind::NotFound => Ok(None),
-                _ => Err(VmError::cache_err(format!(
+        let content = match fs::read(&file_path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(VmError::cache_err(format!(
                     "Error opening module file: {}",
                     err
-                ))),
-            },
-            Err(err) => Err(VmError::cache_err(format!(
-                "Error deserializing module: {}",
-                err
-            ))),
-        }
+                )))
+            }
+        };
+        let serialized = match split_artifact(&content) {
+            Some((header, serialized)) if header == self.artifact_header(serialized) => serialized,
+            _ => return Ok(None),
+        };
+
+        // This is safe since the module was serialized by `store` in the same environment
+        // and verified against its hash.
+        let module = unsafe { Module::deserialize(store, serialized) }
+            .map_err(|e| VmError::cache_err(format!("Error deserializing module: {}", e)))?;
+        Ok(Some(module))
     }
 
-    /// Stores a serialized module to the file system. Returns the size of the serialized module.
+    /// Stores a serialized module to the file system.
     pub fn store(&mut self, checksum: &Checksum, module: &Module) -> VmResult<()> {
         let modules_dir = self.latest_modules_path();
         fs::create_dir_all(&modules_dir)
             .map_err(|e| VmError::cache_err(format!("Error creating directory: {}", e)))?;
         let filename = checksum.to_hex();
         let path = modules_dir.join(filename);
-        module
-            .serialize_to_file(path)
+
+        let serialized = module.serialize()?;
+        let header = serde_json::to_vec(&self.artifact_header(&serialized))
+            .map_err(|e| VmError::cache_err(format!("Error serializing header: {}", e)))?;
+        let mut content =
+            Vec::with_capacity(ARTIFACT_MAGIC.len() + 4 + header.len() + serialized.len());
+        content.extend_from_slice(ARTIFACT_MAGIC);
+        content.extend_from_slice(&(header.len() as u32).to_be_bytes());
+        content.extend_from_slice(&header);
+        content.extend_from_slice(&serialized);
+
+        // Write to a temporary file first, such that an interrupted write
+        // never leaves a partial module behind.
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, &content)
+            .and_then(|_| fs::rename(&tmp_path, &path))
             .map_err(|e| VmError::cache_err(format!("Error writing module to disk: {}", e)))?;
         Ok(())
     }
 
+    /// The header for the given serialized module in the current environment
+    fn artifact_header(&self, serialized: &[u8]) -> ArtifactHeader {
+        ArtifactHeader {
+            module_hash: hex::encode(Sha256::digest(serialized)),
+            wasmer_module_version: self.wasmer_module_version,
+            target: self.target.clone(),
+            compile_config: format!(
+                "{}-{}-costs-{}",
+                MODULE_SERIALIZATION_VERSION,
+                compiler_name(),
+                self.cost_table_version
+            ),
+        }
+    }
+
+    /// Removes the module of the given checksum. Not found modules are silently ignored.
+    pub fn remove(&mut self, checksum: &Checksum) -> VmResult<RemovalStats> {
+        let path = self.latest_modules_path().join(checksum.to_hex());
+        remove_path(&path)
+    }
+
+    /// Removes all modules that are not contained in `keep`.
+    /// Temporary files of modules that are currently being stored are left alone.
+    pub fn prune(&mut self, keep: &HashSet<Checksum>) -> VmResult<RemovalStats> {
+        let keep: HashSet<String> = keep.iter().map(|checksum| checksum.to_hex()).collect();
+        let mut stats = RemovalStats::default();
+        for path in list_dir(&self.latest_modules_path())? {
+            if path.extension() == Some("tmp".as_ref()) {
+                continue;
+            }
+            let filename = path.file_name().and_then(|name| name.to_str());
+            if !filename.map_or(false, |name| keep.contains(name)) {
+                stats += remove_path(&path)?;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Removes the directories of all module versions other than the latest one.
+    ///
+    /// Those are left behind when `MODULE_SERIALIZATION_VERSION`, the Wasmer module version or
+    /// the operator cost table change and are never loaded again. Don't use this when multiple
+    /// caches with different operator cost tables share the same directory.
+    pub fn remove_stale_versions(&mut self) -> VmResult<RemovalStats> {
+        let latest = self.latest_modules_path();
+        let mut stats = RemovalStats::default();
+        for path in list_dir(&self.base_path)? {
+            if path != latest {
+                stats += remove_path(&path)?;
+            }
+        }
+        Ok(stats)
+    }
+
     /// The path to the latest version of the modules.
     fn latest_modules_path(&self) -> PathBuf {
         let version = format!(
-            "{}-wasmer{}",
-            MODULE_SERIALIZATION_VERSION, self.wasmer_module_version
+            "{}-wasmer{}-costs-{}",
+            MODULE_SERIALIZATION_VERSION, self.wasmer_module_version, self.cost_table_version
         );
         self.base_path.join(version)
     }
 }
 
+/// Splits the content of a module file into the header and the serialized module.
+/// Returns None if the content is not in the expected format.
+fn split_artifact(content: &[u8]) -> Option<(ArtifactHeader, &[u8])> {
+    let rest = content.strip_prefix(ARTIFACT_MAGIC)?;
+    if rest.len() < 4 {
+        return None;
+    }
+    let (header_len, rest) = rest.split_at(4);
+    let header_len = u32::from_be_bytes(header_len.try_into().unwrap()) as usize;
+    if rest.len() < header_len {
+        return None;
+    }
+    let (header, serialized) = rest.split_at(header_len);
+    let header = serde_json::from_slice(header).ok()?;
+    Some((header, serialized))
+}
+
+/// Returns the paths of all entries of the given directory, or nothing if it does not exist.
+fn list_dir(dir: &Path) -> VmResult<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(VmError::cache_err(format!(
+                "Error reading directory {}: {}",
+                dir.display(),
+                err
+            )))
+        }
+    };
+    entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()
+        .map_err(|e| VmError::cache_err(format!("Error reading directory entry: {}", e)))
+}
+
+/// Removes a file or a directory including its contents. Paths that do not exist are ignored.
+fn remove_path(path: &Path) -> VmResult<RemovalStats> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(RemovalStats::default()),
+        Err(err) => {
+            return Err(VmError::cache_err(format!(
+                "Error reading metadata of {}: {}",
+                path.display(),
+                err
+            )))
+        }
+    };
+
+    if metadata.is_dir() {
+        let mut stats = RemovalStats::default();
+        for entry in list_dir(path)? {
+            stats += remove_path(&entry)?;
+        }
+        fs::remove_dir(path)
+            .map_err(|e| VmError::cache_err(format!("Error removing directory: {}", e)))?;
+        Ok(stats)
+    } else {
+        fs::remove_file(path)
+            .map_err(|e| VmError::cache_err(format!("Error removing module file: {}", e)))?;
+        Ok(RemovalStats {
+            files: 1,
+            bytes: metadata.len(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,7 +349,8 @@ mod tests {
     #[test]
     fn file_system_cache_run() {
         let tmp_dir = TempDir::new().unwrap();
-        let mut cache = unsafe { FileSystemCache::new(tmp_dir.path()).unwrap() };
+        let mut cache =
+            FileSystemCache::new(tmp_dir.path(), &OperatorCostTable::default()).unwrap();
 
         // Create module
         let wasm = wat::parse_str(SOME_WAT).unwrap();
@@ -170,7 +362,7 @@ mod tests {
         assert!(cached.is_none());
 
         // Store module
-        let module = compile(&wasm, None, &[]).unwrap();
+        let module = compile(&wasm, None, &OperatorCostTable::default(), &[]).unwrap();
         cache.store(&checksum, &module).unwrap();
 
         // Load module
@@ -194,21 +386,209 @@ mod tests {
     #[test]
     fn file_system_cache_store_uses_expected_path() {
         let tmp_dir = TempDir::new().unwrap();
-        let mut cache = unsafe { FileSystemCache::new(tmp_dir.path()).unwrap() };
+        let mut cache =
+            FileSystemCache::new(tmp_dir.path(), &OperatorCostTable::default()).unwrap();
 
         // Create module
         let wasm = wat::parse_str(SOME_WAT).unwrap();
         let checksum = Checksum::generate(&wasm);
 
         // Store module
-        let module = compile(&wasm, None, &[]).unwrap();
+        let module = compile(&wasm, None, &OperatorCostTable::default(), &[]).unwrap();
         cache.store(&checksum, &module).unwrap();
 
         let file_path = format!(
-            "{}/v3-wasmer1/{}",
+            "{}/v4-wasmer1-costs-02c26bde/{}",
             tmp_dir.path().to_string_lossy(),
             checksum
         );
         let _serialized_module = fs::read(file_path).unwrap();
     }
+
+    #[test]
+    fn file_system_cache_separates_cost_tables() {
+        let tmp_dir = TempDir::new().unwrap();
+        let mut cache =
+            FileSystemCache::new(tmp_dir.path(), &OperatorCostTable::default()).unwrap();
+
+        let wasm = wat::parse_str(SOME_WAT).unwrap();
+        let checksum = Checksum::generate(&wasm);
+        let module = compile(&wasm, None, &OperatorCostTable::default(), &[]).unwrap();
+        cache.store(&checksum, &module).unwrap();
+
+        // Same directory, other cost table
+        let other_table = OperatorCostTable {
+            integer_arithmetic: 1,
+            ..OperatorCostTable::default()
+        };
+        let other_cache = FileSystemCache::new(tmp_dir.path(), &other_table).unwrap();
+        let store = make_runtime_store(TESTING_MEMORY_LIMIT);
+        let cached = other_cache.load(&checksum, &store).unwrap();
+        assert!(cached.is_none());
+
+        // Same cost table again
+        let cache = FileSystemCache::new(tmp_dir.path(), &OperatorCostTable::default()).unwrap();
+        let cached = cache.load(&checksum, &store).unwrap();
+        assert!(cached.is_some());
+    }
+
+    #[test]
+    fn file_system_cache_remove_works() {
+        let tmp_dir = TempDir::new().unwrap();
+        let mut cache =
+            FileSystemCache::new(tmp_dir.path(), &OperatorCostTable::default()).unwrap();
+
+        let wasm = wat::parse_str(SOME_WAT).unwrap();
+        let checksum = Checksum::generate(&wasm);
+        let module = compile(&wasm, None, &OperatorCostTable::default(), &[]).unwrap();
+        cache.store(&checksum, &module).unwrap();
+        let file_size = fs::metadata(cache.latest_modules_path().join(checksum.to_hex()))
+            .unwrap()
+            .len();
+
+        let stats = cache.remove(&checksum).unwrap();
+        assert_eq!(
+            stats,
+            RemovalStats {
+                files: 1,
+                bytes: file_size
+            }
+        );
+        let store = make_runtime_store(TESTING_MEMORY_LIMIT);
+        assert!(cache.load(&checksum, &store).unwrap().is_none());
+
+        // removing again is a no-op
+        let stats = cache.remove(&checksum).unwrap();
+        assert_eq!(stats, RemovalStats::default());
+    }
+
+    #[test]
+    fn file_system_cache_prune_works() {
+        let tmp_dir = TempDir::new().unwrap();
+        let mut cache =
+            FileSystemCache::new(tmp_dir.path(), &OperatorCostTable::default()).unwrap();
+
+        // prune before anything was stored
+        let stats = cache.prune(&HashSet::new()).unwrap();
+        assert_eq!(stats, RemovalStats::default());
+
+        let wasm1 = wat::parse_str(SOME_WAT).unwrap();
+        let checksum1 = Checksum::generate(&wasm1);
+        let module1 = compile(&wasm1, None, &OperatorCostTable::default(), &[]).unwrap();
+        cache.store(&checksum1, &module1).unwrap();
+        let wasm2 = wat::parse_str(r#"(module (func (export "nop")))"#).unwrap();
+        let checksum2 = Checksum::generate(&wasm2);
+        let module2 = compile(&wasm2, None, &OperatorCostTable::default(), &[]).unwrap();
+        cache.store(&checksum2, &module2).unwrap();
+        let file_size2 = fs::metadata(cache.latest_modules_path().join(checksum2.to_hex()))
+            .unwrap()
+            .len();
+
+        // a module that is currently being stored
+        let tmp_path = cache.latest_modules_path().join("aabb.tmp");
+        fs::write(&tmp_path, [0u8; 10]).unwrap();
+
+        let keep = vec![checksum1].into_iter().collect();
+        let stats = cache.prune(&keep).unwrap();
+        assert_eq!(
+            stats,
+            RemovalStats {
+                files: 1,
+                bytes: file_size2
+            }
+        );
+        assert!(tmp_path.exists());
+
+        let store = make_runtime_store(TESTING_MEMORY_LIMIT);
+        assert!(cache.load(&checksum1, &store).unwrap().is_some());
+        assert!(cache.load(&checksum2, &store).unwrap().is_none());
+    }
+
+    #[test]
+    fn file_system_cache_remove_stale_versions_works() {
+        let tmp_dir = TempDir::new().unwrap();
+
+        // modules of an older serialization version and an other cost table
+        let old_version = tmp_dir.path().join("v2-wasmer1");
+        fs::create_dir_all(&old_version).unwrap();
+        fs::write(old_version.join("aabb"), [0u8; 100]).unwrap();
+        fs::write(old_version.join("ccdd"), [0u8; 23]).unwrap();
+        let other_table = OperatorCostTable {
+            integer_arithmetic: 1,
+            ..OperatorCostTable::default()
+        };
+        let mut other_cache = FileSystemCache::new(tmp_dir.path(), &other_table).unwrap();
+        let wasm = wat::parse_str(SOME_WAT).unwrap();
+        let checksum = Checksum::generate(&wasm);
+        let module = compile(&wasm, None, &other_table, &[]).unwrap();
+        other_cache.store(&checksum, &module).unwrap();
+        let other_size = fs::metadata(other_cache.latest_modules_path().join(checksum.to_hex()))
+            .unwrap()
+            .len();
+
+        let mut cache =
+            FileSystemCache::new(tmp_dir.path(), &OperatorCostTable::default()).unwrap();
+        let module = compile(&wasm, None, &OperatorCostTable::default(), &[]).unwrap();
+        cache.store(&checksum, &module).unwrap();
+
+        let stats = cache.remove_stale_versions().unwrap();
+        assert_eq!(
+            stats,
+            RemovalStats {
+                files: 3,
+                bytes: 123 + other_size
+            }
+        );
+        assert!(!old_version.exists());
+        assert!(!other_cache.latest_modules_path().exists());
+
+        // the latest version is untouched
+        let store = make_runtime_store(TESTING_MEMORY_LIMIT);
+        assert!(cache.load(&checksum, &store).unwrap().is_some());
+    }
+
+    #[test]
+    fn file_system_cache_load_ignores_invalid_modules() {
+        let tmp_dir = TempDir::new().unwrap();
+        let mut cache =
+            FileSystemCache::new(tmp_dir.path(), &OperatorCostTable::default()).unwrap();
+
+        let wasm = wat::parse_str(SOME_WAT).unwrap();
+        let checksum = Checksum::generate(&wasm);
+        let module = compile(&wasm, None, &OperatorCostTable::default(), &[]).unwrap();
+        cache.store(&checksum, &module).unwrap();
+        let file_path = cache.latest_modules_path().join(checksum.to_hex());
+        let original = fs::read(&file_path).unwrap();
+        let store = make_runtime_store(TESTING_MEMORY_LIMIT);
+
+        // corrupted module
+        let mut corrupted = original.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0x01;
+        fs::write(&file_path, &corrupted).unwrap();
+        assert!(cache.load(&checksum, &store).unwrap().is_none());
+
+        // truncated file
+        fs::write(&file_path, &original[..20]).unwrap();
+        assert!(cache.load(&checksum, &store).unwrap().is_none());
+
+        // module without header (stored by an older version)
+        fs::write(&file_path, module.serialize().unwrap()).unwrap();
+        assert!(cache.load(&checksum, &store).unwrap().is_none());
+
+        // compiled for an other target
+        let (mut header, serialized) = split_artifact(&original).unwrap();
+        header.target = "riscv64gc-unknown-linux-gnu EnumSet()".to_string();
+        let header = serde_json::to_vec(&header).unwrap();
+        let mut other_target = ARTIFACT_MAGIC.to_vec();
+        other_target.extend_from_slice(&(header.len() as u32).to_be_bytes());
+        other_target.extend_from_slice(&header);
+        other_target.extend_from_slice(serialized);
+        fs::write(&file_path, &other_target).unwrap();
+        assert!(cache.load(&checksum, &store).unwrap().is_none());
+
+        // valid module
+        fs::write(&file_path, &original).unwrap();
+        assert!(cache.load(&checksum, &store).unwrap().is_some());
+    }
 }
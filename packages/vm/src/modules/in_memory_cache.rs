@@ -28,6 +28,8 @@ impl WeightScale<Checksum, SizedModule> for SizeScale {
 /// An in-memory module cache
 pub struct InMemoryCache {
     modules: Option<CLruCache<Checksum, SizedModule, RandomState, SizeScale>>,
+    /// The number of modules that were pushed out to make room for others
+    evictions: u64,
 }
 
 impl InMemoryCache {
@@ -46,14 +48,18 @@ impl InMemoryCache {
             } else {
                 None
             },
+            evictions: 0,
         }
     }
 
     pub fn store(&mut self, checksum: &Checksum, module: Module, size: usize) -> VmResult<()> {
         if let Some(modules) = &mut self.modules {
+            let is_new = modules.peek(checksum).is_none();
+            let len_before = modules.len();
             modules
                 .put_with_weight(*checksum, SizedModule { module, size })
                 .map_err(|e| VmError::cache_err(format!("{:?}", e)))?;
+            self.evictions += (len_before + is_new as usize - modules.len()) as u64;
         }
         Ok(())
     }
@@ -70,6 +76,14 @@ impl InMemoryCache {
         }
     }
 
+    /// Removes a module from the cache. Not found modules are silently ignored.
+    pub fn remove(&mut self, checksum: &Checksum) -> VmResult<()> {
+        if let Some(modules) = &mut self.modules {
+            modules.pop(checksum);
+        }
+        Ok(())
+    }
+
     /// Returns the number of elements in the cache.
     pub fn len(&self) -> usize {
         self.modules
@@ -88,13 +102,19 @@ impl InMemoryCache {
             .map(|modules| modules.weight())
             .unwrap_or_default()
     }
+
+    /// Returns the number of modules that were evicted from the cache
+    /// to make room for others since its creation.
+    pub fn evictions(&self) -> u64 {
+        self.evictions
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::size::Size;
-    use crate::wasm_backend::compile;
+    use crate::wasm_backend::{compile, OperatorCostTable};
     use std::mem;
     use wasmer::{imports, Instance as WasmerInstance};
     use wasmer_middlewares::metering::set_remaining_points;
@@ -142,7 +162,7 @@ mod tests {
         assert!(cache_entry.is_none());
 
         // Compile module
-        let original = compile(&wasm, None, &[]).unwrap();
+        let original = compile(&wasm, None, &OperatorCostTable::default(), &[]).unwrap();
 
         // Ensure original module can be executed
         {
@@ -210,24 +230,38 @@ mod tests {
         let checksum3 = Checksum::generate(&wasm3);
 
         assert_eq!(cache.len(), 0);
+        assert_eq!(cache.evictions(), 0);
 
         // Add 1
         cache
-            .store(&checksum1, compile(&wasm1, None, &[]).unwrap(), 900_000)
+            .store(
+                &checksum1,
+                compile(&wasm1, None, &OperatorCostTable::default(), &[]).unwrap(),
+                900_000,
+            )
             .unwrap();
         assert_eq!(cache.len(), 1);
 
         // Add 2
         cache
-            .store(&checksum2, compile(&wasm2, None, &[]).unwrap(), 900_000)
+            .store(
+                &checksum2,
+                compile(&wasm2, None, &OperatorCostTable::default(), &[]).unwrap(),
+                900_000,
+            )
             .unwrap();
         assert_eq!(cache.len(), 2);
 
         // Add 3 (pushes out the previous two)
         cache
-            .store(&checksum3, compile(&wasm3, None, &[]).unwrap(), 1_500_000)
+            .store(
+                &checksum3,
+                compile(&wasm3, None, &OperatorCostTable::default(), &[]).unwrap(),
+                1_500_000,
+            )
             .unwrap();
         assert_eq!(cache.len(), 1);
+        assert_eq!(cache.evictions(), 2);
     }
 
     #[test]
@@ -273,19 +307,31 @@ mod tests {
 
         // Add 1
         cache
-            .store(&checksum1, compile(&wasm1, None, &[]).unwrap(), 900_000)
+            .store(
+                &checksum1,
+                compile(&wasm1, None, &OperatorCostTable::default(), &[]).unwrap(),
+                900_000,
+            )
             .unwrap();
         assert_eq!(cache.size(), 900_000);
 
         // Add 2
         cache
-            .store(&checksum2, compile(&wasm2, None, &[]).unwrap(), 800_000)
+            .store(
+                &checksum2,
+                compile(&wasm2, None, &OperatorCostTable::default(), &[]).unwrap(),
+                800_000,
+            )
             .unwrap();
         assert_eq!(cache.size(), 1_700_000);
 
         // Add 3 (pushes out the previous two)
         cache
-            .store(&checksum3, compile(&wasm3, None, &[]).unwrap(), 1_500_000)
+            .store(
+                &checksum3,
+                compile(&wasm3, None, &OperatorCostTable::default(), &[]).unwrap(),
+                1_500_000,
+            )
             .unwrap();
         assert_eq!(cache.size(), 1_500_000);
     }
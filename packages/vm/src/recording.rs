@@ -0,0 +1,668 @@
+//! Recording and replaying of backend interactions.
+//!
+//! A backend wrapped with [`record_backend`] logs every call into the API, the storage and
+//! the querier together with the response and its [`GasInfo`]. Such a [`Recording`] can be
+//! saved to a file and fed back into an instance using [`replay_backend`], which reproduces
+//! the exact execution (including gas usage and iterator IDs) without access to the chain.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Binary, ContractResult, SystemResult};
+#[cfg(feature = "iterator")]
+use cosmwasm_std::{Order, Record};
+
+use crate::backend::{Backend, BackendApi, BackendError, BackendResult, GasInfo, Querier, Storage};
+use crate::errors::{VmError, VmResult};
+use crate::serde::to_vec;
+
+/// A call into the backend together with the backend's response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedCall {
+    CanonicalAddress {
+        human: String,
+        result: Result<Binary, BackendError>,
+        gas_info: GasInfo,
+    },
+    HumanAddress {
+        canonical: Binary,
+        result: Result<String, BackendError>,
+        gas_info: GasInfo,
+    },
+    Get {
+        key: Binary,
+        result: Result<Option<Binary>, BackendError>,
+        gas_info: GasInfo,
+    },
+    Scan {
+        start: Option<Binary>,
+        end: Option<Binary>,
+        /// The order as passed over FFI (1 = ascending, 2 = descending)
+        order: i32,
+        result: Result<u32, BackendError>,
+        gas_info: GasInfo,
+    },
+    Next {
+        iterator_id: u32,
+        result: Result<Option<(Binary, Binary)>, BackendError>,
+        gas_info: GasInfo,
+    },
+    Set {
+        key: Binary,
+        value: Binary,
+        result: Result<(), BackendError>,
+        gas_info: GasInfo,
+    },
+    Remove {
+        key: Binary,
+        result: Result<(), BackendError>,
+        gas_info: GasInfo,
+    },
+    QueryRaw {
+        request: Binary,
+        gas_limit: u64,
+        result: Result<SystemResult<ContractResult<Binary>>, BackendError>,
+        gas_info: GasInfo,
+    },
+}
+
+impl RecordedCall {
+    /// The name of the backend method that was called
+    pub fn method(&self) -> &'static str {
+        match self {
+            RecordedCall::CanonicalAddress { .. } => "canonical_address",
+            RecordedCall::HumanAddress { .. } => "human_address",
+            RecordedCall::Get { .. } => "get",
+            RecordedCall::Scan { .. } => "scan",
+            RecordedCall::Next { .. } => "next",
+            RecordedCall::Set { .. } => "set",
+            RecordedCall::Remove { .. } => "remove",
+            RecordedCall::QueryRaw { .. } => "query_raw",
+        }
+    }
+}
+
+/// All backend calls of one or more contract executions, in the order they happened
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    pub calls: Vec<RecordedCall>,
+}
+
+impl Recording {
+    /// Writes the recording as JSON to the given file
+    pub fn save(&self, path: impl AsRef<Path>) -> VmResult<()> {
+        let path = path.as_ref();
+        let data = to_vec(self)?;
+        fs::write(path, data).map_err(|e| {
+            VmError::generic_err(format!(
+                "Error writing recording to {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Reads a recording that was written by [`Recording::save`]
+    pub fn load(path: impl AsRef<Path>) -> VmResult<Self> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|e| {
+            VmError::generic_err(format!(
+                "Error reading recording from {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_slice(&data).map_err(|e| VmError::parse_err("Recording", e))
+    }
+}
+
+/// Wraps the given backend such that all calls into it are recorded.
+///
+/// The API, the storage and the querier share one log, which is freed once the backend
+/// and the returned [`Recorder`] are dropped. Recording is meant for investigating
+/// individual executions and should not be enabled for every call.
+pub fn record_backend<A: BackendApi, S: Storage, Q: Querier>(
+    backend: Backend<A, S, Q>,
+) -> (
+    Backend<RecordingApi<A>, RecordingStorage<S>, RecordingQuerier<Q>>,
+    Recorder,
+) {
+    let recorder = Recorder {
+        calls: Arc::new(Mutex::new(Vec::new())),
+    };
+    let backend = Backend {
+        api: RecordingApi {
+            inner: backend.api,
+            recorder: recorder.clone(),
+        },
+        storage: RecordingStorage {
+            inner: backend.storage,
+            recorder: recorder.clone(),
+        },
+        querier: RecordingQuerier {
+            inner: backend.querier,
+            recorder: recorder.clone(),
+        },
+    };
+    (backend, recorder)
+}
+
+/// Access to the calls recorded by a backend created with [`record_backend`]
+#[derive(Clone)]
+pub struct Recorder {
+    calls: Arc<Mutex<Vec<RecordedCall>>>,
+}
+
+impl Recorder {
+    fn record(&self, call: RecordedCall) {
+        self.calls.lock().unwrap().push(call);
+    }
+
+    /// Returns all calls recorded so far
+    pub fn recording(&self) -> Recording {
+        Recording {
+            calls: self.calls.lock().unwrap().clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RecordingApi<A: BackendApi> {
+    inner: A,
+    recorder: Recorder,
+}
+
+impl<A: BackendApi> BackendApi for RecordingApi<A> {
+    fn canonical_address(&self, human: &str) -> BackendResult<Vec<u8>> {
+        let (result, gas_info) = self.inner.canonical_address(human);
+        self.recorder.record(RecordedCall::CanonicalAddress {
+            human: human.to_string(),
+            result: result.clone().map(Binary::from),
+            gas_info,
+        });
+        (result, gas_info)
+    }
+
+    fn human_address(&self, canonical: &[u8]) -> BackendResult<String> {
+        let (result, gas_info) = self.inner.human_address(canonical);
+        self.recorder.record(RecordedCall::HumanAddress {
+            canonical: Binary::from(canonical),
+            result: result.clone(),
+            gas_info,
+        });
+        (result, gas_info)
+    }
+}
+
+pub struct RecordingStorage<S: Storage> {
+    inner: S,
+    recorder: Recorder,
+}
+
+impl<S: Storage> RecordingStorage<S> {
+    /// Returns the wrapped storage, e.g. to commit the changes of an execution
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Storage> Storage for RecordingStorage<S> {
+    fn get(&self, key: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        let (result, gas_info) = self.inner.get(key);
+        self.recorder.record(RecordedCall::Get {
+            key: Binary::from(key),
+            result: result.clone().map(|value| value.map(Binary::from)),
+            gas_info,
+        });
+        (result, gas_info)
+    }
+
+    #[cfg(feature = "iterator")]
+    fn scan(
+        &mut self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> BackendResult<u32> {
+        let (result, gas_info) = self.inner.scan(start, end, order);
+        self.recorder.record(RecordedCall::Scan {
+            start: start.map(Binary::from),
+            end: end.map(Binary::from),
+            order: order as i32,
+            result: result.clone(),
+            gas_info,
+        });
+        (result, gas_info)
+    }
+
+    #[cfg(feature = "iterator")]
+    fn next(&mut self, iterator_id: u32) -> BackendResult<Option<Record>> {
+        let (result, gas_info) = self.inner.next(iterator_id);
+        self.recorder.record(RecordedCall::Next {
+            iterator_id,
+            result: result
+                .clone()
+                .map(|record| record.map(|(key, value)| (Binary::from(key), Binary::from(value)))),
+            gas_info,
+        });
+        (result, gas_info)
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()> {
+        let (result, gas_info) = self.inner.set(key, value);
+        self.recorder.record(RecordedCall::Set {
+            key: Binary::from(key),
+            value: Binary::from(value),
+            result: result.clone(),
+            gas_info,
+        });
+        (result, gas_info)
+    }
+
+    fn remove(&mut self, key: &[u8]) -> BackendResult<()> {
+        let (result, gas_info) = self.inner.remove(key);
+        self.recorder.record(RecordedCall::Remove {
+            key: Binary::from(key),
+            result: result.clone(),
+            gas_info,
+        });
+        (result, gas_info)
+    }
+}
+
+pub struct RecordingQuerier<Q: Querier> {
+    inner: Q,
+    recorder: Recorder,
+}
+
+impl<Q: Querier> RecordingQuerier<Q> {
+    /// Returns the wrapped querier
+    pub fn into_inner(self) -> Q {
+        self.inner
+    }
+}
+
+impl<Q: Querier> Querier for RecordingQuerier<Q> {
+    fn query_raw(
+        &self,
+        request: &[u8],
+        gas_limit: u64,
+    ) -> BackendResult<SystemResult<ContractResult<Binary>>> {
+        let (result, gas_info) = self.inner.query_raw(request, gas_limit);
+        self.recorder.record(RecordedCall::QueryRaw {
+            request: Binary::from(request),
+            gas_limit,
+            result: result.clone(),
+            gas_info,
+        });
+        (result, gas_info)
+    }
+}
+
+/// Creates a backend that answers every call with the next call of the given recording.
+///
+/// The calls must arrive in the recorded order with the recorded arguments. Otherwise the
+/// execution diverged from the recorded one and the backend returns an error, which aborts
+/// the contract execution.
+///
+/// Like in [`record_backend`], the remaining calls are stored in a log shared by the API,
+/// the storage and the querier.
+pub fn replay_backend(
+    recording: Recording,
+) -> (Backend<ReplayApi, ReplayStorage, ReplayQuerier>, Replay) {
+    let replay = Replay {
+        calls: Arc::new(Mutex::new(recording.calls.into())),
+    };
+    let backend = Backend {
+        api: ReplayApi {
+            replay: replay.clone(),
+        },
+        storage: ReplayStorage {
+            replay: replay.clone(),
+        },
+        querier: ReplayQuerier {
+            replay: replay.clone(),
+        },
+    };
+    (backend, replay)
+}
+
+/// Access to the state of a backend created with [`replay_backend`]
+#[derive(Clone)]
+pub struct Replay {
+    calls: Arc<Mutex<VecDeque<RecordedCall>>>,
+}
+
+impl Replay {
+    fn next_call(&self) -> Option<RecordedCall> {
+        self.calls.lock().unwrap().pop_front()
+    }
+
+    /// The number of recorded calls that were not replayed yet.
+    /// This is 0 once the recorded execution was reproduced completely.
+    pub fn remaining_calls(&self) -> usize {
+        self.calls.lock().unwrap().len()
+    }
+}
+
+/// The response of the replay backend for a call that does not match the recording
+fn diverged<T>(method: &str, recorded: Option<RecordedCall>) -> BackendResult<T> {
+    let msg = match recorded {
+        Some(call) if call.method() == method => format!(
+            "Replay diverged from recording: {} called with different arguments",
+            method
+        ),
+        Some(call) => format!(
+            "Replay diverged from recording: {} called but {} was recorded",
+            method,
+            call.method()
+        ),
+        None => format!(
+            "Replay diverged from recording: {} called after the end of the recording",
+            method
+        ),
+    };
+    (Err(BackendError::unknown(msg)), GasInfo::free())
+}
+
+#[derive(Clone)]
+pub struct ReplayApi {
+    replay: Replay,
+}
+
+impl BackendApi for ReplayApi {
+    fn canonical_address(&self, human: &str) -> BackendResult<Vec<u8>> {
+        match self.replay.next_call() {
+            Some(RecordedCall::CanonicalAddress {
+                human: recorded_human,
+                result,
+                gas_info,
+            }) if recorded_human == human => (result.map(Vec::from), gas_info),
+            other => diverged("canonical_address", other),
+        }
+    }
+
+    fn human_address(&self, canonical: &[u8]) -> BackendResult<String> {
+        match self.replay.next_call() {
+            Some(RecordedCall::HumanAddress {
+                canonical: recorded_canonical,
+                result,
+                gas_info,
+            }) if recorded_canonical.as_slice() == canonical => (result, gas_info),
+            other => diverged("human_address", other),
+        }
+    }
+}
+
+pub struct ReplayStorage {
+    replay: Replay,
+}
+
+impl Storage for ReplayStorage {
+    fn get(&self, key: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        match self.replay.next_call() {
+            Some(RecordedCall::Get {
+                key: recorded_key,
+                result,
+                gas_info,
+            }) if recorded_key.as_slice() == key => {
+                (result.map(|value| value.map(Vec::from)), gas_info)
+            }
+            other => diverged("get", other),
+        }
+    }
+
+    #[cfg(feature = "iterator")]
+    fn scan(
+        &mut self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> BackendResult<u32> {
+        match self.replay.next_call() {
+            Some(RecordedCall::Scan {
+                start: recorded_start,
+                end: recorded_end,
+                order: recorded_order,
+                result,
+                gas_info,
+            }) if recorded_start.as_ref().map(Binary::as_slice) == start
+                && recorded_end.as_ref().map(Binary::as_slice) == end
+                && recorded_order == order as i32 =>
+            {
+                (result, gas_info)
+            }
+            other => diverged("scan", other),
+        }
+    }
+
+    #[cfg(feature = "iterator")]
+    fn next(&mut self, iterator_id: u32) -> BackendResult<Option<Record>> {
+        match self.replay.next_call() {
+            Some(RecordedCall::Next {
+                iterator_id: recorded_iterator_id,
+                result,
+                gas_info,
+            }) if recorded_iterator_id == iterator_id => (
+                result.map(|record| record.map(|(key, value)| (key.into(), value.into()))),
+                gas_info,
+            ),
+            other => diverged("next", other),
+        }
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()> {
+        match self.replay.next_call() {
+            Some(RecordedCall::Set {
+                key: recorded_key,
+                value: recorded_value,
+                result,
+                gas_info,
+            }) if recorded_key.as_slice() == key && recorded_value.as_slice() == value => {
+                (result, gas_info)
+            }
+            other => diverged("set", other),
+        }
+    }
+
+    fn remove(&mut self, key: &[u8]) -> BackendResult<()> {
+        match self.replay.next_call() {
+            Some(RecordedCall::Remove {
+                key: recorded_key,
+                result,
+                gas_info,
+            }) if recorded_key.as_slice() == key => (result, gas_info),
+            other => diverged("remove", other),
+        }
+    }
+}
+
+pub struct ReplayQuerier {
+    replay: Replay,
+}
+
+impl Querier for ReplayQuerier {
+    fn query_raw(
+        &self,
+        request: &[u8],
+        gas_limit: u64,
+    ) -> BackendResult<SystemResult<ContractResult<Binary>>> {
+        match self.replay.next_call() {
+            Some(RecordedCall::QueryRaw {
+                request: recorded_request,
+                gas_limit: recorded_gas_limit,
+                result,
+                gas_info,
+            }) if recorded_request.as_slice() == request && recorded_gas_limit == gas_limit => {
+                (result, gas_info)
+            }
+            other => diverged("query_raw", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calls::{call_execute, call_instantiate};
+    use crate::testing::{mock_backend, mock_env, mock_info, mock_instance_options};
+    use crate::Instance;
+    use cosmwasm_std::{coins, Empty, Response};
+    use tempfile::TempDir;
+    use wasmer::Instance as WasmerInstance;
+
+    static CONTRACT: &[u8] = include_bytes!("../testdata/hackatom.wasm");
+
+    fn run_contract<A, S, Q>(
+        backend: Backend<A, S, Q>,
+    ) -> (Response, Response, u64, Backend<A, S, Q>)
+    where
+        A: BackendApi + 'static,
+        S: Storage + 'static,
+        Q: Querier + 'static,
+    {
+        let (instance_options, memory_limit) = mock_instance_options();
+        let mut instance =
+            Instance::from_code(CONTRACT, backend, instance_options, memory_limit).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        let instantiate_response = call_instantiate::<_, _, _, Empty, WasmerInstance>(
+            &mut instance,
+            &mock_env(),
+            &info,
+            msg,
+        )
+        .unwrap()
+        .unwrap();
+
+        let info = mock_info("verifies", &coins(15, "earth"));
+        let msg = br#"{"release":{}}"#;
+        let execute_response =
+            call_execute::<_, _, _, Empty, WasmerInstance>(&mut instance, &mock_env(), &info, msg)
+                .unwrap()
+                .unwrap();
+
+        let gas_used = instance.create_gas_report().used_internally;
+        let backend = instance.recycle().unwrap();
+        (instantiate_response, execute_response, gas_used, backend)
+    }
+
+    #[test]
+    fn recording_can_be_replayed() {
+        let (backend, recorder) = record_backend(mock_backend(&coins(1000, "earth")));
+        let (recorded_instantiate, recorded_execute, recorded_gas, _) = run_contract(backend);
+        let recording = recorder.recording();
+        let methods: Vec<_> = recording.calls.iter().map(|call| call.method()).collect();
+        assert_eq!(
+            methods,
+            vec![
+                "canonical_address",
+                "canonical_address",
+                "set",
+                "get",
+                "query_raw"
+            ]
+        );
+
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("recording.json");
+        recording.save(&path).unwrap();
+        let loaded = Recording::load(&path).unwrap();
+        assert_eq!(loaded, recording);
+
+        let (backend, replay) = replay_backend(loaded);
+        assert_eq!(replay.remaining_calls(), 5);
+        let (instantiate, execute, gas, _) = run_contract(backend);
+        assert_eq!(instantiate, recorded_instantiate);
+        assert_eq!(execute, recorded_execute);
+        assert_eq!(gas, recorded_gas);
+        assert_eq!(replay.remaining_calls(), 0);
+    }
+
+    #[test]
+    fn recording_is_freed_with_backend() {
+        let (backend, recorder) = record_backend(mock_backend(&[]));
+        assert_eq!(Arc::strong_count(&recorder.calls), 4);
+        drop(backend);
+        assert_eq!(Arc::strong_count(&recorder.calls), 1);
+
+        let (backend, replay) = replay_backend(Recording::default());
+        assert_eq!(Arc::strong_count(&replay.calls), 4);
+        drop(backend);
+        assert_eq!(Arc::strong_count(&replay.calls), 1);
+    }
+
+    #[test]
+    fn replay_reports_divergence() {
+        let (backend, replay) = replay_backend(Recording {
+            calls: vec![RecordedCall::Get {
+                key: Binary::from(b"foo"),
+                result: Ok(Some(Binary::from(b"bar"))),
+                gas_info: GasInfo::new(3, 4),
+            }],
+        });
+        let mut storage = backend.storage;
+
+        // different arguments
+        let (result, gas_info) = storage.get(b"other");
+        match result.unwrap_err() {
+            BackendError::Unknown { msg } => assert_eq!(
+                msg.unwrap(),
+                "Replay diverged from recording: get called with different arguments"
+            ),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+        assert_eq!(gas_info, GasInfo::free());
+        assert_eq!(replay.remaining_calls(), 0);
+
+        // end of recording
+        let (result, _) = storage.set(b"foo", b"bar");
+        match result.unwrap_err() {
+            BackendError::Unknown { msg } => assert_eq!(
+                msg.unwrap(),
+                "Replay diverged from recording: set called after the end of the recording"
+            ),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn replay_works_for_matching_calls() {
+        let (backend, replay) = replay_backend(Recording {
+            calls: vec![
+                RecordedCall::Get {
+                    key: Binary::from(b"foo"),
+                    result: Ok(Some(Binary::from(b"bar"))),
+                    gas_info: GasInfo::new(3, 4),
+                },
+                RecordedCall::HumanAddress {
+                    canonical: Binary::from(b"foo"),
+                    result: Err(BackendError::user_err("Invalid input")),
+                    gas_info: GasInfo::with_cost(5),
+                },
+            ],
+        });
+
+        let (result, gas_info) = backend.storage.get(b"foo");
+        assert_eq!(result.unwrap(), Some(b"bar".to_vec()));
+        assert_eq!(gas_info, GasInfo::new(3, 4));
+
+        // calls of different backend parts share one recording
+        let (result, gas_info) = backend.api.canonical_address("foo");
+        match result.unwrap_err() {
+            BackendError::Unknown { msg } => assert_eq!(
+                msg.unwrap(),
+                "Replay diverged from recording: canonical_address called but human_address was recorded"
+            ),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+        assert_eq!(gas_info, GasInfo::free());
+        assert_eq!(replay.remaining_calls(), 0);
+    }
+}
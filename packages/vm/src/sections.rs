@@ -1,27 +1,41 @@
 use crate::conversion::to_u32;
-use crate::errors::VmResult;
+use crate::errors::{CommunicationError, CommunicationResult, VmResult};
 
-/// Decodes sections of data into multiple slices.
+/// Decodes sections of data provided by the contract into multiple slices.
 ///
 /// Each encoded section is suffixed by a section length, encoded as big endian uint32.
+/// An error is returned when a section length exceeds the data left. Decoding stops as soon
+/// as more than `max_count` sections are found, which bounds the work done for data full of
+/// empty sections.
 ///
 /// See also: `encode_section`.
-#[allow(dead_code)]
-pub fn decode_sections(data: &[u8]) -> Vec<&[u8]> {
+pub fn try_decode_sections(data: &[u8], max_count: usize) -> CommunicationResult<Vec<&[u8]>> {
     let mut result: Vec<&[u8]> = vec![];
     let mut remaining_len = data.len();
     while remaining_len >= 4 {
+        if result.len() == max_count {
+            return Err(CommunicationError::too_many_sections(
+                max_count + 1,
+                max_count,
+            ));
+        }
         let tail_len = u32::from_be_bytes([
             data[remaining_len - 4],
             data[remaining_len - 3],
             data[remaining_len - 2],
             data[remaining_len - 1],
         ]) as usize;
+        if tail_len > remaining_len - 4 {
+            return Err(CommunicationError::invalid_section_length(
+                tail_len,
+                remaining_len - 4,
+            ));
+        }
         result.push(&data[remaining_len - 4 - tail_len..remaining_len - 4]);
         remaining_len -= 4 + tail_len;
     }
     result.reverse();
-    result
+    Ok(result)
 }
 
 /// Encodes multiple sections of data into one vector.
@@ -56,56 +70,119 @@ mod tests {
     use super::*;
 
     #[test]
-    fn decode_sections_works_for_empty_sections() {
-        let dec = decode_sections(&[]);
+    fn try_decode_sections_works_for_empty_sections() {
+        let dec = try_decode_sections(&[], usize::MAX).unwrap();
         assert_eq!(dec.len(), 0);
-        let dec = decode_sections(b"\0\0\0\0");
+        let dec = try_decode_sections(b"\0\0\0\0", usize::MAX).unwrap();
         assert_eq!(dec, &[&[0u8; 0]]);
-        let dec = decode_sections(b"\0\0\0\0\0\0\0\0");
+        let dec = try_decode_sections(b"\0\0\0\0\0\0\0\0", usize::MAX).unwrap();
         assert_eq!(dec, &[&[0u8; 0]; 2]);
-        let dec = decode_sections(b"\0\0\0\0\0\0\0\0\0\0\0\0");
+        let dec = try_decode_sections(b"\0\0\0\0\0\0\0\0\0\0\0\0", usize::MAX).unwrap();
         assert_eq!(dec, &[&[0u8; 0]; 3]);
         // ignores "trailing" stuff
-        let dec = decode_sections(b"\0\0\0\0\0\0\0\0\0\0\0");
+        let dec = try_decode_sections(b"\0\0\0\0\0\0\0\0\0\0\0", usize::MAX).unwrap();
         assert_eq!(dec, &[&[0u8; 0]; 2]);
     }
 
     #[test]
-    fn decode_sections_works_for_one_element() {
-        let dec = decode_sections(b"\xAA\0\0\0\x01");
+    fn try_decode_sections_works_for_one_element() {
+        let dec = try_decode_sections(b"\xAA\0\0\0\x01", usize::MAX).unwrap();
         assert_eq!(dec, &[vec![0xAA]]);
-        let dec = decode_sections(b"\xAA\xBB\0\0\0\x02");
+        let dec = try_decode_sections(b"\xAA\xBB\0\0\0\x02", usize::MAX).unwrap();
         assert_eq!(dec, &[vec![0xAA, 0xBB]]);
-        let dec = decode_sections(b"\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\0\0\x01\x15");
+        let dec = try_decode_sections(b"\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\0\0\x01\x15", usize::MAX).unwrap();
         assert_eq!(dec, &[vec![0x9D; 277]]);
     }
 
     #[test]
-    fn decode_sections_works_for_two_elements() {
+    fn try_decode_sections_works_for_two_elements() {
         let data = b"\xAA\0\0\0\x01\xBB\xCC\0\0\0\x02".to_vec();
-        assert_eq!(decode_sections(&data), &[vec![0xAA], vec![0xBB, 0xCC]]);
+        assert_eq!(
+            try_decode_sections(&data, usize::MAX).unwrap(),
+            &[vec![0xAA], vec![0xBB, 0xCC]]
+        );
         let data = b"\xDE\xEF\x62\0\0\0\x03\0\0\0\0".to_vec();
-        assert_eq!(decode_sections(&data), &[vec![0xDE, 0xEF, 0x62], vec![]]);
+        assert_eq!(
+            try_decode_sections(&data, usize::MAX).unwrap(),
+            &[vec![0xDE, 0xEF, 0x62], vec![]]
+        );
         let data = b"\0\0\0\0\xDE\xEF\x62\0\0\0\x03".to_vec();
-        assert_eq!(decode_sections(&data), &[vec![], vec![0xDE, 0xEF, 0x62]]);
+        assert_eq!(
+            try_decode_sections(&data, usize::MAX).unwrap(),
+            &[vec![], vec![0xDE, 0xEF, 0x62]]
+        );
         let data = b"\0\0\0\0\0\0\0\0".to_vec();
-        assert_eq!(decode_sections(&data), &[vec![0u8; 0], vec![]]);
+        assert_eq!(
+            try_decode_sections(&data, usize::MAX).unwrap(),
+            &[vec![0u8; 0], vec![]]
+        );
         let data = b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\0\0\0\x13\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\x9D\0\0\x01\x15".to_vec();
-        assert_eq!(decode_sections(&data), &[vec![0xFF; 19], vec![0x9D; 277]]);
+        assert_eq!(
+            try_decode_sections(&data, usize::MAX).unwrap(),
+            &[vec![0xFF; 19], vec![0x9D; 277]]
+        );
     }
 
     #[test]
-    fn decode_sections_works_for_multiple_elements() {
-        let dec = decode_sections(b"\xAA\0\0\0\x01");
+    fn try_decode_sections_works_for_multiple_elements() {
+        let dec = try_decode_sections(b"\xAA\0\0\0\x01", usize::MAX).unwrap();
         assert_eq!(dec, &[vec![0xAA]]);
-        let dec = decode_sections(b"\xAA\0\0\0\x01\xDE\xDE\0\0\0\x02");
+        let dec = try_decode_sections(b"\xAA\0\0\0\x01\xDE\xDE\0\0\0\x02", usize::MAX).unwrap();
         assert_eq!(dec, &[vec![0xAA], vec![0xDE, 0xDE]]);
-        let dec = decode_sections(b"\xAA\0\0\0\x01\xDE\xDE\0\0\0\x02\0\0\0\0");
+        let dec =
+            try_decode_sections(b"\xAA\0\0\0\x01\xDE\xDE\0\0\0\x02\0\0\0\0", usize::MAX).unwrap();
         assert_eq!(dec, &[vec![0xAA], vec![0xDE, 0xDE], vec![]]);
-        let dec = decode_sections(b"\xAA\0\0\0\x01\xDE\xDE\0\0\0\x02\0\0\0\0\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\0\0\0\x13");
+        let dec = try_decode_sections(b"\xAA\0\0\0\x01\xDE\xDE\0\0\0\x02\0\0\0\0\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\0\0\0\x13", usize::MAX).unwrap();
         assert_eq!(dec, &[vec![0xAA], vec![0xDE, 0xDE], vec![], vec![0xFF; 19]]);
     }
 
+    #[test]
+    fn try_decode_sections_works_within_max_count() {
+        let dec = try_decode_sections(b"", 2).unwrap();
+        assert_eq!(dec.len(), 0);
+        let dec = try_decode_sections(b"\0\0\0\0\0\0\0\0", 2).unwrap();
+        assert_eq!(dec, &[&[0u8; 0]; 2]);
+        let dec = try_decode_sections(b"\xAA\0\0\0\x01\xDE\xDE\0\0\0\x02", 2).unwrap();
+        assert_eq!(dec, &[vec![0xAA], vec![0xDE, 0xDE]]);
+        // ignores "trailing" stuff
+        let dec = try_decode_sections(b"\0\0\xAA\0\0\0\x01", 2).unwrap();
+        assert_eq!(dec, &[vec![0xAA]]);
+    }
+
+    #[test]
+    fn try_decode_sections_fails_for_invalid_section_length() {
+        // the length suffix claims 2 bytes, but only 1 is left
+        let err = try_decode_sections(b"\xAA\0\0\0\x02", 2).unwrap_err();
+        match err {
+            CommunicationError::InvalidSectionLength { length, remaining } => {
+                assert_eq!(length, 2);
+                assert_eq!(remaining, 1);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+        // a length suffix close to u32::MAX must not overflow
+        let err = try_decode_sections(b"\xAA\0\0\0\x01\xFF\xFF\xFF\xFF", 2).unwrap_err();
+        match err {
+            CommunicationError::InvalidSectionLength { length, remaining } => {
+                assert_eq!(length, u32::MAX as usize);
+                assert_eq!(remaining, 5);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn try_decode_sections_stops_after_max_count() {
+        let err = try_decode_sections(&[0u8; 4 * 1000], 2).unwrap_err();
+        match err {
+            CommunicationError::TooManySections { count, max_count } => {
+                assert_eq!(count, 3);
+                assert_eq!(max_count, 2);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn encode_sections_works_for_empty_sections() {
         let enc = encode_sections(&[]).unwrap();
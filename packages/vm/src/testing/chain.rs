@@ -0,0 +1,1014 @@
+//! An in-process chain simulator for integration tests that involve more than one contract.
+//!
+//! In contrast to `mock_instance`, which runs a single contract against a static querier, a
+//! `MockChain` stores codes, instantiates contracts and executes the messages they emit
+//! (bank transfers, Wasm messages and submessages including replies). Every contract gets its own
+//! `MockStorage`. Queries from a contract are answered by the chain, which allows contracts to
+//! query each other.
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::rc::Rc;
+
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, AllBalanceResponse, BalanceResponse, BankMsg, BankQuery, Binary,
+    BlockInfo, Coin, ContractInfo, ContractInfoResponse, ContractResult, CosmosMsg, Empty, Env,
+    Event, MessageInfo, QueryRequest, Reply, ReplyOn, Response, SubMsg, SubMsgExecutionResponse,
+    SubMsgResult, SystemError, SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+#[cfg(feature = "iterator")]
+use cosmwasm_std::{Order, Record};
+use thiserror::Error;
+use wasmer::{Instance as WasmerInstance, Module};
+
+use crate::calls::{call_execute, call_instantiate, call_migrate, call_query, call_reply};
+use crate::compatibility::check_wasm;
+use crate::environment::GasConfig;
+use crate::errors::{VmError, VmResult};
+use crate::instance::Instance;
+use crate::size::Size;
+use crate::wasm_backend::{compile, OperatorCostTable};
+use crate::{Backend, BackendError, BackendResult, GasInfo, Querier, Storage};
+
+use super::instance::MockInstanceOptions;
+use super::mock::{mock_env, MockApi};
+use super::storage::MockStorage;
+
+/// Gas charged for queries that are answered by the chain without running a contract
+const GAS_COST_QUERY_FLAT: u64 = 100_000;
+
+pub type ChainResult<T> = core::result::Result<T, ChainError>;
+
+#[derive(Error, Debug)]
+pub enum ChainError {
+    #[error("Error executing contract: {0}")]
+    Vm(#[from] VmError),
+    #[error("Contract returned an error: {msg}")]
+    Contract { msg: String },
+    #[error("No code with ID {code_id}")]
+    NoSuchCode { code_id: u64 },
+    #[error("No contract with address {addr}")]
+    NoSuchContract { addr: String },
+    #[error("Insufficient funds: {address} cannot spend {amount}")]
+    InsufficientFunds { address: String, amount: Coin },
+    #[error("Unauthorized: {msg}")]
+    Unauthorized { msg: String },
+    #[error("Unsupported message: {kind}")]
+    UnsupportedMessage { kind: String },
+}
+
+/// The result of a successfully executed message
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChainResponse {
+    /// All events emitted by the message and the messages it triggered, in execution order
+    pub events: Vec<Event>,
+    pub data: Option<Binary>,
+}
+
+type ChainInstance = Instance<MockApi, ContractStorage, ChainQuerier, WasmerInstance>;
+
+#[derive(Clone)]
+struct ContractData {
+    code_id: u64,
+    creator: String,
+    admin: Option<String>,
+    /// Shared between the chain and all instances of this contract that are currently running
+    storage: Rc<RefCell<MockStorage>>,
+}
+
+struct ChainState {
+    /// Compiled codes. The code ID is the index + 1.
+    codes: Vec<Module>,
+    contracts: BTreeMap<String, ContractData>,
+    /// Balances by address and denom
+    balances: BTreeMap<String, BTreeMap<String, Uint128>>,
+    /// The number of contracts instantiated so far. Used to derive contract addresses.
+    contract_count: u64,
+    block: BlockInfo,
+    /// Gas limit for a transaction, i.e. one top-level call including all messages it triggers
+    gas_limit: u64,
+    /// Gas left in the current transaction
+    gas_left: u64,
+    supported_features: HashSet<String>,
+    memory_limit: Option<Size>,
+    print_debug: bool,
+}
+
+impl ChainState {
+    fn code(&self, code_id: u64) -> ChainResult<&Module> {
+        code_id
+            .checked_sub(1)
+            .and_then(|index| self.codes.get(index as usize))
+            .ok_or(ChainError::NoSuchCode { code_id })
+    }
+
+    fn contract(&self, addr: &str) -> ChainResult<&ContractData> {
+        self.contracts
+            .get(addr)
+            .ok_or_else(|| ChainError::NoSuchContract {
+                addr: addr.to_string(),
+            })
+    }
+
+    fn contract_mut(&mut self, addr: &str) -> ChainResult<&mut ContractData> {
+        self.contracts
+            .get_mut(addr)
+            .ok_or_else(|| ChainError::NoSuchContract {
+                addr: addr.to_string(),
+            })
+    }
+}
+
+/// A copy of all state that is rolled back when a message fails
+struct Snapshot {
+    contracts: BTreeMap<String, ContractData>,
+    storages: BTreeMap<String, MockStorage>,
+    balances: BTreeMap<String, BTreeMap<String, Uint128>>,
+    contract_count: u64,
+}
+
+/// A simulated chain with a bank and a Wasm module.
+///
+/// Every public call (`instantiate`, `execute`, `migrate`) is a transaction: it either succeeds
+/// as a whole or leaves no trace. Submessages are executed in their own nested transaction such
+/// that a failed submessage with `ReplyOn::Error`/`ReplyOn::Always` is rolled back before
+/// the reply is dispatched, like in wasmd.
+///
+/// All contract calls of a transaction share one gas budget. The `gas_limit` of submessages
+/// is not taken into account.
+#[derive(Clone)]
+pub struct MockChain {
+    state: Rc<RefCell<ChainState>>,
+}
+
+impl MockChain {
+    pub fn new() -> Self {
+        Self::with_gas_limit(MockInstanceOptions::default().gas_limit)
+    }
+
+    /// Creates a chain in which every transaction can use up to `gas_limit` gas
+    pub fn with_gas_limit(gas_limit: u64) -> Self {
+        let options = MockInstanceOptions::default();
+        let state = ChainState {
+            codes: Vec::new(),
+            contracts: BTreeMap::new(),
+            balances: BTreeMap::new(),
+            contract_count: 0,
+            block: mock_env().block,
+            gas_limit,
+            gas_left: gas_limit,
+            supported_features: options.supported_features,
+            memory_limit: options.memory_limit,
+            print_debug: options.print_debug,
+        };
+        MockChain {
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    /// Checks and compiles the given Wasm code and returns the new code ID
+    pub fn store_code(&self, wasm: &[u8]) -> ChainResult<u64> {
+        let mut state = self.state.borrow_mut();
+        check_wasm(wasm, &state.supported_features)?;
+        let module = compile(wasm, state.memory_limit, &OperatorCostTable::default(), &[])?;
+        state.codes.push(module);
+        Ok(state.codes.len() as u64)
+    }
+
+    /// Sets the balance of the given address, replacing the previous one
+    pub fn set_balance(&self, address: &str, balance: &[Coin]) {
+        let balance = balance
+            .iter()
+            .map(|coin| (coin.denom.clone(), coin.amount))
+            .collect();
+        self.state
+            .borrow_mut()
+            .balances
+            .insert(address.to_string(), balance);
+    }
+
+    /// Returns all non-zero balances of the given address, sorted by denom
+    pub fn balance(&self, address: &str) -> Vec<Coin> {
+        let state = self.state.borrow();
+        match state.balances.get(address) {
+            Some(balance) => balance
+                .iter()
+                .filter(|(_, amount)| !amount.is_zero())
+                .map(|(denom, amount)| Coin {
+                    denom: denom.clone(),
+                    amount: *amount,
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn block(&self) -> BlockInfo {
+        self.state.borrow().block.clone()
+    }
+
+    pub fn set_block(&self, block: BlockInfo) {
+        self.state.borrow_mut().block = block;
+    }
+
+    /// Instantiates a new contract from the given code and returns its address
+    pub fn instantiate(
+        &self,
+        code_id: u64,
+        sender: &str,
+        msg: &[u8],
+        funds: &[Coin],
+        admin: Option<&str>,
+    ) -> ChainResult<(Addr, ChainResponse)> {
+        self.transaction(|| {
+            self.instantiate_inner(sender, code_id, msg, funds, admin.map(String::from))
+        })
+    }
+
+    pub fn execute(
+        &self,
+        sender: &str,
+        contract_addr: &str,
+        msg: &[u8],
+        funds: &[Coin],
+    ) -> ChainResult<ChainResponse> {
+        self.transaction(|| self.execute_inner(sender, contract_addr, msg, funds))
+    }
+
+    /// Migrates the contract to a new code. Only the contract's admin can do this.
+    pub fn migrate(
+        &self,
+        sender: &str,
+        contract_addr: &str,
+        new_code_id: u64,
+        msg: &[u8],
+    ) -> ChainResult<ChainResponse> {
+        self.transaction(|| self.migrate_inner(sender, contract_addr, new_code_id, msg))
+    }
+
+    /// Performs a smart query against the given contract
+    pub fn query(&self, contract_addr: &str, msg: &[u8]) -> ChainResult<Binary> {
+        let gas_limit = self.state.borrow().gas_limit;
+        let (result, _gas_used) = self.run_query(contract_addr, msg, gas_limit)?;
+        result?
+            .into_result()
+            .map_err(|msg| ChainError::Contract { msg })
+    }
+
+    /// Runs a top-level call with a fresh gas budget
+    fn transaction<T>(&self, action: impl FnOnce() -> ChainResult<T>) -> ChainResult<T> {
+        {
+            let mut state = self.state.borrow_mut();
+            state.gas_left = state.gas_limit;
+        }
+        self.atomically(action)
+    }
+
+    /// Runs the action and rolls back all state changes if it fails
+    fn atomically<T>(&self, action: impl FnOnce() -> ChainResult<T>) -> ChainResult<T> {
+        let snapshot = self.snapshot();
+        let result = action();
+        if result.is_err() {
+            self.restore(snapshot);
+        }
+        result
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let state = self.state.borrow();
+        Snapshot {
+            contracts: state.contracts.clone(),
+            storages: state
+                .contracts
+                .iter()
+                .map(|(addr, contract)| (addr.clone(), contract.storage.borrow().clone()))
+                .collect(),
+            balances: state.balances.clone(),
+            contract_count: state.contract_count,
+        }
+    }
+
+    fn restore(&self, snapshot: Snapshot) {
+        let mut state = self.state.borrow_mut();
+        for (addr, storage) in snapshot.storages {
+            if let Some(contract) = snapshot.contracts.get(&addr) {
+                *contract.storage.borrow_mut() = storage;
+            }
+        }
+        state.contracts = snapshot.contracts;
+        state.balances = snapshot.balances;
+        state.contract_count = snapshot.contract_count;
+    }
+
+    fn env(&self, contract_addr: &str) -> Env {
+        Env {
+            block: self.block(),
+            transaction: None,
+            contract: ContractInfo {
+                address: Addr::unchecked(contract_addr),
+            },
+        }
+    }
+
+    fn instance(&self, contract_addr: &str, gas_limit: u64) -> ChainResult<ChainInstance> {
+        let (module, storage, print_debug) = {
+            let state = self.state.borrow();
+            let contract = state.contract(contract_addr)?;
+            let module = state.code(contract.code_id)?.clone();
+            (module, Rc::clone(&contract.storage), state.print_debug)
+        };
+        let backend = Backend {
+            api: MockApi::default(),
+            storage: ContractStorage { storage },
+            querier: ChainQuerier {
+                chain: self.clone(),
+            },
+        };
+        let instance = Instance::from_module(
+            &module,
+            backend,
+            gas_limit,
+            print_debug,
+            GasConfig::default(),
+            None,
+            None,
+        )?;
+        Ok(instance)
+    }
+
+    /// Calls into a contract using the gas left in the current transaction
+    fn call_contract<F>(&self, contract_addr: &str, call: F) -> ChainResult<Response>
+    where
+        F: FnOnce(&mut ChainInstance, &Env) -> VmResult<ContractResult<Response>>,
+    {
+        let gas_left = self.state.borrow().gas_left;
+        let mut instance = self.instance(contract_addr, gas_left)?;
+        let env = self.env(contract_addr);
+        let result = call(&mut instance, &env);
+        let gas_used = gas_left.saturating_sub(instance.get_gas_left());
+        {
+            let mut state = self.state.borrow_mut();
+            state.gas_left = state.gas_left.saturating_sub(gas_used);
+        }
+        result?
+            .into_result()
+            .map_err(|msg| ChainError::Contract { msg })
+    }
+
+    fn run_query(
+        &self,
+        contract_addr: &str,
+        msg: &[u8],
+        gas_limit: u64,
+    ) -> ChainResult<(VmResult<ContractResult<Binary>>, u64)> {
+        let mut instance = self.instance(contract_addr, gas_limit)?;
+        let env = self.env(contract_addr);
+        let result = call_query(&mut instance, &env, msg);
+        let gas_used = gas_limit.saturating_sub(instance.get_gas_left());
+        Ok((result, gas_used))
+    }
+
+    fn instantiate_inner(
+        &self,
+        sender: &str,
+        code_id: u64,
+        msg: &[u8],
+        funds: &[Coin],
+        admin: Option<String>,
+    ) -> ChainResult<(Addr, ChainResponse)> {
+        let address = {
+            let mut state = self.state.borrow_mut();
+            state.code(code_id)?;
+            let address = format!("contract{}", state.contract_count);
+            state.contract_count += 1;
+            let contract = ContractData {
+                code_id,
+                creator: sender.to_string(),
+                admin,
+                storage: Rc::new(RefCell::new(MockStorage::new())),
+            };
+            state.contracts.insert(address.clone(), contract);
+            address
+        };
+        self.transfer(sender, &address, funds)?;
+
+        let info = MessageInfo {
+            sender: Addr::unchecked(sender),
+            funds: funds.to_vec(),
+        };
+        let response = self.call_contract(&address, |instance, env| {
+            call_instantiate::<_, _, _, Empty, _>(instance, env, &info, msg)
+        })?;
+        let response = self.process_response(&address, "instantiate", response)?;
+        Ok((Addr::unchecked(address), response))
+    }
+
+    fn execute_inner(
+        &self,
+        sender: &str,
+        contract_addr: &str,
+        msg: &[u8],
+        funds: &[Coin],
+    ) -> ChainResult<ChainResponse> {
+        self.state.borrow().contract(contract_addr)?;
+        self.transfer(sender, contract_addr, funds)?;
+
+        let info = MessageInfo {
+            sender: Addr::unchecked(sender),
+            funds: funds.to_vec(),
+        };
+        let response = self.call_contract(contract_addr, |instance, env| {
+            call_execute::<_, _, _, Empty, _>(instance, env, &info, msg)
+        })?;
+        self.process_response(contract_addr, "execute", response)
+    }
+
+    fn migrate_inner(
+        &self,
+        sender: &str,
+        contract_addr: &str,
+        new_code_id: u64,
+        msg: &[u8],
+    ) -> ChainResult<ChainResponse> {
+        {
+            let mut state = self.state.borrow_mut();
+            state.code(new_code_id)?;
+            let contract = state.contract_mut(contract_addr)?;
+            if contract.admin.as_deref() != Some(sender) {
+                return Err(ChainError::Unauthorized {
+                    msg: format!("{} is not the admin of {}", sender, contract_addr),
+                });
+            }
+            contract.code_id = new_code_id;
+        }
+
+        let response = self.call_contract(contract_addr, |instance, env| {
+            call_migrate::<_, _, _, Empty, _>(instance, env, msg)
+        })?;
+        self.process_response(contract_addr, "migrate", response)
+    }
+
+    fn update_admin(
+        &self,
+        sender: &str,
+        contract_addr: &str,
+        new_admin: Option<String>,
+    ) -> ChainResult<ChainResponse> {
+        let mut state = self.state.borrow_mut();
+        let contract = state.contract_mut(contract_addr)?;
+        if contract.admin.as_deref() != Some(sender) {
+            return Err(ChainError::Unauthorized {
+                msg: format!("{} is not the admin of {}", sender, contract_addr),
+            });
+        }
+        contract.admin = new_admin;
+        Ok(ChainResponse::default())
+    }
+
+    fn reply(&self, contract_addr: &str, reply: Reply) -> ChainResult<ChainResponse> {
+        let response = self.call_contract(contract_addr, |instance, env| {
+            call_reply::<_, _, _, Empty, _>(instance, env, &reply)
+        })?;
+        self.process_response(contract_addr, "reply", response)
+    }
+
+    /// Converts a contract response into events and executes all messages it contains
+    fn process_response(
+        &self,
+        contract_addr: &str,
+        action: &str,
+        response: Response,
+    ) -> ChainResult<ChainResponse> {
+        let mut events = vec![Event::new(action).add_attribute("_contract_address", contract_addr)];
+        if !response.attributes.is_empty() {
+            events.push(
+                Event::new("wasm")
+                    .add_attribute("_contract_address", contract_addr)
+                    .add_attributes(response.attributes),
+            );
+        }
+        events.extend(response.events.into_iter().map(|event| {
+            Event::new(format!("wasm-{}", event.ty))
+                .add_attribute("_contract_address", contract_addr)
+                .add_attributes(event.attributes)
+        }));
+
+        let mut data = response.data;
+        for msg in response.messages {
+            let mut sub_response = self.execute_submsg(contract_addr, msg)?;
+            events.append(&mut sub_response.events);
+            // data set in a reply overrides the data of the original response
+            if sub_response.data.is_some() {
+                data = sub_response.data;
+            }
+        }
+        Ok(ChainResponse { events, data })
+    }
+
+    fn execute_submsg(&self, contract_addr: &str, msg: SubMsg) -> ChainResult<ChainResponse> {
+        let SubMsg {
+            id, msg, reply_on, ..
+        } = msg;
+        match self.atomically(|| self.dispatch(contract_addr, msg)) {
+            Ok(response) => match reply_on {
+                ReplyOn::Always | ReplyOn::Success => {
+                    let reply = Reply {
+                        id,
+                        result: SubMsgResult::Ok(SubMsgExecutionResponse {
+                            events: response.events.clone(),
+                            data: response.data,
+                        }),
+                    };
+                    let mut reply_response = self.reply(contract_addr, reply)?;
+                    let mut events = response.events;
+                    events.append(&mut reply_response.events);
+                    Ok(ChainResponse {
+                        events,
+                        data: reply_response.data,
+                    })
+                }
+                ReplyOn::Error | ReplyOn::Never => Ok(ChainResponse {
+                    events: response.events,
+                    data: None,
+                }),
+            },
+            Err(err) => match reply_on {
+                ReplyOn::Always | ReplyOn::Error => {
+                    let reply = Reply {
+                        id,
+                        result: SubMsgResult::Err(err.to_string()),
+                    };
+                    self.reply(contract_addr, reply)
+                }
+                ReplyOn::Success | ReplyOn::Never => Err(err),
+            },
+        }
+    }
+
+    /// Executes a message emitted by the contract `sender`
+    fn dispatch(&self, sender: &str, msg: CosmosMsg) -> ChainResult<ChainResponse> {
+        match msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                self.transfer(sender, &to_address, &amount)?;
+                let event = Event::new("transfer")
+                    .add_attribute("recipient", to_address)
+                    .add_attribute("sender", sender)
+                    .add_attribute("amount", coins_to_string(&amount));
+                Ok(ChainResponse {
+                    events: vec![event],
+                    data: None,
+                })
+            }
+            CosmosMsg::Bank(BankMsg::Burn { amount }) => {
+                self.burn(sender, &amount)?;
+                let event = Event::new("burn")
+                    .add_attribute("burner", sender)
+                    .add_attribute("amount", coins_to_string(&amount));
+                Ok(ChainResponse {
+                    events: vec![event],
+                    data: None,
+                })
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) => self.execute_inner(sender, &contract_addr, &msg, &funds),
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin,
+                code_id,
+                msg,
+                funds,
+                ..
+            }) => {
+                let (address, response) =
+                    self.instantiate_inner(sender, code_id, &msg, &funds, admin)?;
+                Ok(ChainResponse {
+                    events: response.events,
+                    data: Some(encode_instantiate_response(
+                        address.as_str(),
+                        response.data.as_ref(),
+                    )),
+                })
+            }
+            CosmosMsg::Wasm(WasmMsg::Migrate {
+                contract_addr,
+                new_code_id,
+                msg,
+            }) => self.migrate_inner(sender, &contract_addr, new_code_id, &msg),
+            CosmosMsg::Wasm(WasmMsg::UpdateAdmin {
+                contract_addr,
+                admin,
+            }) => self.update_admin(sender, &contract_addr, Some(admin)),
+            CosmosMsg::Wasm(WasmMsg::ClearAdmin { contract_addr }) => {
+                self.update_admin(sender, &contract_addr, None)
+            }
+            other => Err(ChainError::UnsupportedMessage {
+                kind: format!("{:?}", other),
+            }),
+        }
+    }
+
+    fn transfer(&self, from: &str, to: &str, amount: &[Coin]) -> ChainResult<()> {
+        self.burn(from, amount)?;
+        let mut state = self.state.borrow_mut();
+        let balance = state.balances.entry(to.to_string()).or_default();
+        for coin in amount {
+            *balance.entry(coin.denom.clone()).or_default() += coin.amount;
+        }
+        Ok(())
+    }
+
+    fn burn(&self, from: &str, amount: &[Coin]) -> ChainResult<()> {
+        let mut state = self.state.borrow_mut();
+        let balance = state.balances.entry(from.to_string()).or_default();
+        for coin in amount {
+            let available = balance.entry(coin.denom.clone()).or_default();
+            *available =
+                available
+                    .checked_sub(coin.amount)
+                    .map_err(|_| ChainError::InsufficientFunds {
+                        address: from.to_string(),
+                        amount: coin.clone(),
+                    })?;
+        }
+        Ok(())
+    }
+
+    fn handle_query(
+        &self,
+        request: QueryRequest<Empty>,
+        gas_limit: u64,
+    ) -> BackendResult<SystemResult<ContractResult<Binary>>> {
+        let flat_gas = GasInfo::with_externally_used(GAS_COST_QUERY_FLAT);
+        match request {
+            QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
+                let amount = self
+                    .balance(&address)
+                    .into_iter()
+                    .find(|coin| coin.denom == denom)
+                    .unwrap_or_else(|| Coin::new(0, denom));
+                let response = to_binary(&BalanceResponse { amount });
+                (Ok(SystemResult::Ok(response.into())), flat_gas)
+            }
+            QueryRequest::Bank(BankQuery::AllBalances { address }) => {
+                let amount = self.balance(&address);
+                let response = to_binary(&AllBalanceResponse { amount });
+                (Ok(SystemResult::Ok(response.into())), flat_gas)
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match self.run_query(&contract_addr, &msg, gas_limit) {
+                    Ok((Ok(result), gas_used)) => (
+                        Ok(SystemResult::Ok(result)),
+                        GasInfo::with_externally_used(gas_used),
+                    ),
+                    Ok((Err(VmError::GasDepletion { .. }), gas_used)) => (
+                        Err(BackendError::out_of_gas()),
+                        GasInfo::with_externally_used(gas_used),
+                    ),
+                    Ok((Err(err), gas_used)) => (
+                        Err(BackendError::unknown(err)),
+                        GasInfo::with_externally_used(gas_used),
+                    ),
+                    Err(ChainError::NoSuchContract { addr }) => (
+                        Ok(SystemResult::Err(SystemError::NoSuchContract { addr })),
+                        flat_gas,
+                    ),
+                    Err(err) => (Err(BackendError::unknown(err)), flat_gas),
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
+                let storage = match self.state.borrow().contract(&contract_addr) {
+                    Ok(contract) => Rc::clone(&contract.storage),
+                    Err(_) => {
+                        return (
+                            Ok(SystemResult::Err(SystemError::NoSuchContract {
+                                addr: contract_addr,
+                            })),
+                            flat_gas,
+                        )
+                    }
+                };
+                let (result, mut gas_info) = storage.borrow().get(&key);
+                gas_info += flat_gas;
+                match result {
+                    Ok(value) => (
+                        Ok(SystemResult::Ok(ContractResult::Ok(Binary::from(
+                            value.unwrap_or_default(),
+                        )))),
+                        gas_info,
+                    ),
+                    Err(err) => (Err(err), gas_info),
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::ContractInfo { contract_addr }) => {
+                let state = self.state.borrow();
+                match state.contract(&contract_addr) {
+                    Ok(contract) => {
+                        let mut response =
+                            ContractInfoResponse::new(contract.code_id, contract.creator.clone());
+                        response.admin = contract.admin.clone();
+                        let response = to_binary(&response);
+                        (Ok(SystemResult::Ok(response.into())), flat_gas)
+                    }
+                    Err(_) => (
+                        Ok(SystemResult::Err(SystemError::NoSuchContract {
+                            addr: contract_addr,
+                        })),
+                        flat_gas,
+                    ),
+                }
+            }
+            other => (
+                Ok(SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: format!("{:?}", other),
+                })),
+                flat_gas,
+            ),
+        }
+    }
+}
+
+impl Default for MockChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn coins_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|coin| coin.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Encodes the data of an instantiate message the way wasmd does, i.e. as a protobuf
+/// `MsgInstantiateContractResponse { string address = 1; bytes data = 2; }`.
+fn encode_instantiate_response(address: &str, data: Option<&Binary>) -> Binary {
+    let mut out = Vec::new();
+    encode_protobuf_bytes(&mut out, 1, address.as_bytes());
+    if let Some(data) = data {
+        encode_protobuf_bytes(&mut out, 2, data.as_slice());
+    }
+    Binary::from(out)
+}
+
+fn encode_protobuf_bytes(out: &mut Vec<u8>, field_number: u8, value: &[u8]) {
+    // wire type 2 (length-delimited)
+    out.push((field_number << 3) | 2);
+    let mut length = value.len();
+    while length >= 0x80 {
+        out.push((length as u8 & 0x7f) | 0x80);
+        length >>= 7;
+    }
+    out.push(length as u8);
+    out.extend_from_slice(value);
+}
+
+/// A view on the storage of one contract
+struct ContractStorage {
+    storage: Rc<RefCell<MockStorage>>,
+}
+
+impl Storage for ContractStorage {
+    fn get(&self, key: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        self.storage.borrow().get(key)
+    }
+
+    #[cfg(feature = "iterator")]
+    fn scan(
+        &mut self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> BackendResult<u32> {
+        self.storage.borrow_mut().scan(start, end, order)
+    }
+
+    #[cfg(feature = "iterator")]
+    fn next(&mut self, iterator_id: u32) -> BackendResult<Option<Record>> {
+        self.storage.borrow_mut().next(iterator_id)
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()> {
+        self.storage.borrow_mut().set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) -> BackendResult<()> {
+        self.storage.borrow_mut().remove(key)
+    }
+}
+
+/// A querier that answers bank queries from the chain's balances and Wasm queries
+/// by running the target contract
+struct ChainQuerier {
+    chain: MockChain,
+}
+
+impl Querier for ChainQuerier {
+    fn query_raw(
+        &self,
+        request: &[u8],
+        gas_limit: u64,
+    ) -> BackendResult<SystemResult<ContractResult<Binary>>> {
+        match from_slice::<QueryRequest<Empty>>(request) {
+            Ok(parsed) => self.chain.handle_query(parsed, gas_limit),
+            Err(err) => (
+                Ok(SystemResult::Err(SystemError::InvalidRequest {
+                    error: err.to_string(),
+                    request: Binary::from(request),
+                })),
+                GasInfo::with_externally_used(GAS_COST_QUERY_FLAT),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coins, from_binary, to_vec};
+
+    static CONTRACT: &[u8] = include_bytes!("../../testdata/hackatom.wasm");
+
+    const INIT_MSG: &[u8] = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+
+    fn setup(funds: &[Coin]) -> (MockChain, Addr) {
+        let chain = MockChain::new();
+        chain.set_balance("creator", &coins(1000, "earth"));
+        let code_id = chain.store_code(CONTRACT).unwrap();
+        let (address, _) = chain
+            .instantiate(code_id, "creator", INIT_MSG, funds, Some("admin"))
+            .unwrap();
+        (chain, address)
+    }
+
+    #[test]
+    fn store_code_assigns_incrementing_ids() {
+        let chain = MockChain::new();
+        assert_eq!(chain.store_code(CONTRACT).unwrap(), 1);
+        assert_eq!(chain.store_code(CONTRACT).unwrap(), 2);
+    }
+
+    #[test]
+    fn instantiate_and_query_works() {
+        let (chain, address) = setup(&coins(100, "earth"));
+        assert_eq!(address.as_str(), "contract0");
+        assert_eq!(chain.balance("contract0"), coins(100, "earth"));
+        assert_eq!(chain.balance("creator"), coins(900, "earth"));
+
+        let response = chain
+            .query(address.as_str(), br#"{"verifier":{}}"#)
+            .unwrap();
+        assert_eq!(response.as_slice(), br#"{"verifier":"verifies"}"#);
+    }
+
+    #[test]
+    fn instantiate_fails_for_unknown_code() {
+        let chain = MockChain::new();
+        let result = chain.instantiate(42, "creator", INIT_MSG, &[], None);
+        match result.unwrap_err() {
+            ChainError::NoSuchCode { code_id } => assert_eq!(code_id, 42),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn execute_dispatches_messages() {
+        let (chain, address) = setup(&coins(100, "earth"));
+
+        let response = chain
+            .execute("verifies", address.as_str(), br#"{"release":{}}"#, &[])
+            .unwrap();
+        assert_eq!(response.data, Some(Binary::from([0xF0, 0x0B, 0xAA])));
+        let types: Vec<&str> = response.events.iter().map(|e| e.ty.as_str()).collect();
+        assert_eq!(types, ["execute", "wasm", "wasm-hackatom", "transfer"]);
+
+        assert_eq!(chain.balance("benefits"), coins(100, "earth"));
+        assert_eq!(chain.balance(address.as_str()), vec![]);
+    }
+
+    #[test]
+    fn failed_execute_is_rolled_back() {
+        let (chain, address) = setup(&coins(100, "earth"));
+
+        let result = chain.execute(
+            "creator",
+            address.as_str(),
+            br#"{"release":{}}"#,
+            &coins(50, "earth"),
+        );
+        match result.unwrap_err() {
+            ChainError::Contract { msg } => assert!(msg.contains("Unauthorized")),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // funds sent along with the failed message are back
+        assert_eq!(chain.balance("creator"), coins(900, "earth"));
+        assert_eq!(chain.balance(address.as_str()), coins(100, "earth"));
+    }
+
+    #[test]
+    fn execute_fails_for_insufficient_funds() {
+        let (chain, address) = setup(&[]);
+        let result = chain.execute(
+            "verifies",
+            address.as_str(),
+            br#"{"release":{}}"#,
+            &coins(1, "earth"),
+        );
+        match result.unwrap_err() {
+            ChainError::InsufficientFunds { address, amount } => {
+                assert_eq!(address, "verifies");
+                assert_eq!(amount, Coin::new(1, "earth"));
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn migrate_requires_admin() {
+        let (chain, address) = setup(&[]);
+        let msg = br#"{"verifier":"someone else"}"#;
+
+        let result = chain.migrate("creator", address.as_str(), 1, msg);
+        assert!(matches!(result, Err(ChainError::Unauthorized { .. })));
+
+        chain.migrate("admin", address.as_str(), 1, msg).unwrap();
+        let response = chain
+            .query(address.as_str(), br#"{"verifier":{}}"#)
+            .unwrap();
+        assert_eq!(response.as_slice(), br#"{"verifier":"someone else"}"#);
+    }
+
+    #[test]
+    fn contracts_can_query_bank() {
+        let (chain, address) = setup(&[]);
+        chain.set_balance("someone", &coins(7, "moon"));
+
+        let response = chain
+            .query(
+                address.as_str(),
+                br#"{"other_balance":{"address":"someone"}}"#,
+            )
+            .unwrap();
+        let response: AllBalanceResponse = from_binary(&response).unwrap();
+        assert_eq!(response.amount, coins(7, "moon"));
+    }
+
+    #[test]
+    fn contracts_can_query_contracts() {
+        let (chain, address) = setup(&[]);
+
+        // hackatom queries itself recursively
+        let response = chain
+            .query(address.as_str(), br#"{"recurse":{"depth":3,"work":0}}"#)
+            .unwrap();
+        assert_eq!(response.as_slice(), br#"{"hashed":"Y29udHJhY3Qw"}"#);
+    }
+
+    #[test]
+    fn querier_handles_raw_and_contract_info_queries() {
+        let (chain, address) = setup(&[]);
+        let querier = ChainQuerier {
+            chain: chain.clone(),
+        };
+
+        let request: QueryRequest<Empty> = QueryRequest::Wasm(WasmQuery::Raw {
+            contract_addr: address.to_string(),
+            key: Binary::from(b"config"),
+        });
+        let (result, _) = querier.query_raw(&to_vec(&request).unwrap(), 1_000_000);
+        let data = result.unwrap().unwrap().unwrap();
+        assert!(data.as_slice().starts_with(br#"{"verifier":"verifies""#));
+
+        let request: QueryRequest<Empty> = QueryRequest::Wasm(WasmQuery::ContractInfo {
+            contract_addr: address.to_string(),
+        });
+        let (result, _) = querier.query_raw(&to_vec(&request).unwrap(), 1_000_000);
+        let info: ContractInfoResponse = from_binary(&result.unwrap().unwrap().unwrap()).unwrap();
+        assert_eq!(info.code_id, 1);
+        assert_eq!(info.creator, "creator");
+        assert_eq!(info.admin, Some("admin".to_string()));
+
+        let request: QueryRequest<Empty> = QueryRequest::Wasm(WasmQuery::ContractInfo {
+            contract_addr: "nope".to_string(),
+        });
+        let (result, _) = querier.query_raw(&to_vec(&request).unwrap(), 1_000_000);
+        match result.unwrap() {
+            SystemResult::Err(SystemError::NoSuchContract { addr }) => assert_eq!(addr, "nope"),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn encode_instantiate_response_works() {
+        let encoded = encode_instantiate_response("contract7", None);
+        assert_eq!(encoded.as_slice(), b"\x0a\x09contract7");
+
+        let data = Binary::from(vec![0xAA; 200]);
+        let encoded = encode_instantiate_response("c", Some(&data));
+        assert_eq!(&encoded.as_slice()[..6], b"\x0a\x01c\x12\xc8\x01");
+        assert_eq!(encoded.len(), 6 + 200);
+    }
+}
@@ -6,6 +6,7 @@ use std::collections::HashSet;
 use wasmer::{Exports, Function, ImportObject, Instance as WasmerInstance, Module, Val};
 
 use crate::compatibility::check_wasm;
+use crate::environment::GasConfig;
 use crate::features::features_from_csv;
 use crate::instance::{Instance, InstanceOptions};
 use crate::size::Size;
@@ -89,6 +90,7 @@ pub struct MockInstanceOptions<'a> {
     pub supported_features: HashSet<String>,
     pub gas_limit: u64,
     pub print_debug: bool,
+    pub gas_config: GasConfig,
     /// Memory limit in bytes. Use a value that is divisible by the Wasm page size 65536, e.g. full MiBs.
     pub memory_limit: Option<Size>,
 }
@@ -96,9 +98,23 @@ pub struct MockInstanceOptions<'a> {
 impl MockInstanceOptions<'_> {
     fn default_features() -> HashSet<String> {
         #[allow(unused_mut)]
-        let mut out = features_from_csv("iterator,staking");
+        let mut out = features_from_csv("iterator,staking,abort");
         #[cfg(feature = "stargate")]
         out.insert("stargate".to_string());
+        #[cfg(feature = "extended_storage")]
+        out.insert("extended_storage".to_string());
+        #[cfg(feature = "secp256r1")]
+        out.insert("secp256r1".to_string());
+        #[cfg(feature = "hashes")]
+        out.insert("hashes".to_string());
+        #[cfg(feature = "bls12_381")]
+        out.insert("bls12_381".to_string());
+        #[cfg(feature = "sr25519")]
+        out.insert("sr25519".to_string());
+        #[cfg(feature = "secp256k1_schnorr")]
+        out.insert("secp256k1_schnorr".to_string());
+        #[cfg(feature = "secp256k1_batch")]
+        out.insert("secp256k1_batch".to_string());
         out
     }
 }
@@ -115,6 +131,7 @@ impl Default for MockInstanceOptions<'_> {
             supported_features: Self::default_features(),
             gas_limit: DEFAULT_GAS_LIMIT,
             print_debug: DEFAULT_PRINT_DEBUG,
+            gas_config: GasConfig::default(),
             memory_limit: DEFAULT_MEMORY_LIMIT,
         }
     }
@@ -152,6 +169,8 @@ pub fn mock_instance_with_options(
     let options = InstanceOptions {
         gas_limit: options.gas_limit,
         print_debug: options.print_debug,
+        gas_config: Some(options.gas_config),
+        max_stack_depth: None,
     };
     Instance::from_code(wasm, backend, options, memory_limit).unwrap()
 }
@@ -162,6 +181,8 @@ pub fn mock_instance_options() -> (InstanceOptions, Option<Size>) {
         InstanceOptions {
             gas_limit: DEFAULT_GAS_LIMIT,
             print_debug: DEFAULT_PRINT_DEBUG,
+            gas_config: None,
+            max_stack_depth: None,
         },
         DEFAULT_MEMORY_LIMIT,
     )
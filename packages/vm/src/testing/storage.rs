@@ -20,13 +20,13 @@ const GAS_COST_LAST_ITERATION: u64 = 37;
 const GAS_COST_RANGE: u64 = 11;
 
 #[cfg(feature = "iterator")]
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 struct Iter {
     data: Vec<Record>,
     position: usize,
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct MockStorage {
     data: BTreeMap<Vec<u8>, Vec<u8>>,
     #[cfg(feature = "iterator")]
@@ -58,6 +58,37 @@ impl MockStorage {
         }
         (Ok(out), total)
     }
+
+    /// Advances the iterator like `next` but only returns and charges
+    /// the part of the element selected by `part`.
+    #[cfg(feature = "iterator")]
+    fn next_part(
+        &mut self,
+        iterator_id: u32,
+        part: impl FnOnce(&Record) -> &Vec<u8>,
+    ) -> BackendResult<Option<Vec<u8>>> {
+        let iterator = match self.iterators.get_mut(&iterator_id) {
+            Some(i) => i,
+            None => {
+                return (
+                    Err(BackendError::iterator_does_not_exist(iterator_id)),
+                    GasInfo::free(),
+                )
+            }
+        };
+
+        let (value, gas_info): (Option<Vec<u8>>, GasInfo) =
+            if iterator.data.len() > iterator.position {
+                let item = part(&iterator.data[iterator.position]).clone();
+                iterator.position += 1;
+                let gas_cost = item.len() as u64;
+                (Some(item), GasInfo::with_cost(gas_cost))
+            } else {
+                (None, GasInfo::with_externally_used(GAS_COST_LAST_ITERATION))
+            };
+
+        (Ok(value), gas_info)
+    }
 }
 
 impl Storage for MockStorage {
@@ -126,6 +157,16 @@ impl Storage for MockStorage {
         (Ok(value), gas_info)
     }
 
+    #[cfg(feature = "iterator")]
+    fn next_key(&mut self, iterator_id: u32) -> BackendResult<Option<Vec<u8>>> {
+        self.next_part(iterator_id, |(key, _)| key)
+    }
+
+    #[cfg(feature = "iterator")]
+    fn next_value(&mut self, iterator_id: u32) -> BackendResult<Option<Vec<u8>>> {
+        self.next_part(iterator_id, |(_, value)| value)
+    }
+
     fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()> {
         self.data.insert(key.to_vec(), value.to_vec());
         let gas_info = GasInfo::with_externally_used((key.len() + value.len()) as u64);
@@ -341,4 +382,37 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn next_key_and_next_value_work() {
+        let mut store = MockStorage::new();
+        store.set(b"ant", b"hill").0.unwrap();
+        store.set(b"foo", b"bar").0.unwrap();
+        store.set(b"ze", b"bra").0.unwrap();
+
+        let iter_id = store.scan(None, None, Order::Ascending).0.unwrap();
+        let (key, gas_info) = store.next_key(iter_id);
+        assert_eq!(key.unwrap(), Some(b"ant".to_vec()));
+        assert_eq!(gas_info, GasInfo::with_cost(3));
+        let (value, gas_info) = store.next_value(iter_id);
+        assert_eq!(value.unwrap(), Some(b"bar".to_vec()));
+        assert_eq!(gas_info, GasInfo::with_cost(3));
+        let (record, _) = store.next(iter_id);
+        assert_eq!(record.unwrap(), Some((b"ze".to_vec(), b"bra".to_vec())));
+        let (key, gas_info) = store.next_key(iter_id);
+        assert_eq!(key.unwrap(), None);
+        assert_eq!(
+            gas_info,
+            GasInfo::with_externally_used(GAS_COST_LAST_ITERATION)
+        );
+        let (value, _) = store.next_value(iter_id);
+        assert_eq!(value.unwrap(), None);
+
+        // unknown iterator
+        match store.next_key(42).0.unwrap_err() {
+            BackendError::IteratorDoesNotExist { id, .. } => assert_eq!(id, 42),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
 }
@@ -0,0 +1,228 @@
+use serde::{Serialize, Serializer};
+use std::io::Write;
+use std::sync::Mutex;
+
+/// A value passed from the contract to an import
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceValue {
+    /// The content of a region in the contract's memory. This is hex encoded in JSON.
+    Bytes(#[serde(serialize_with = "serialize_hex")] Vec<u8>),
+    /// A plain number like an iterator ID or an order
+    Number(i64),
+    /// An optional region that was not set (null pointer)
+    None,
+}
+
+/// One call of an import by the contract
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TraceEvent {
+    /// The name of the import, e.g. `db_read`
+    pub import: &'static str,
+    pub args: Vec<TraceValue>,
+    /// The number of bytes written into the contract's memory as a result of the call.
+    /// This is 0 for imports that only return a status code.
+    pub result_size: u64,
+    /// Gas used by the VM during the call (metered internally)
+    pub gas_used_internally: u64,
+    /// Gas reported as used by the backend during the call (metered externally)
+    pub gas_used_externally: u64,
+    /// Set if the import failed with a VM error, which aborts the contract execution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A sink for import calls that can be attached to an instance using
+/// [`Instance::set_tracer`](crate::Instance::set_tracer).
+///
+/// Tracers are called synchronously during contract execution and should be fast.
+pub trait Tracer: Send + Sync {
+    /// Called after every import call
+    fn trace(&self, event: &TraceEvent);
+
+    /// Returns false if this tracer ignores all events, such that the VM can skip collecting them.
+    fn enabled(&self) -> bool {
+        true
+    }
+}
+
+/// The default tracer of every instance, ignoring all events
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NoopTracer;
+
+impl Tracer for NoopTracer {
+    fn trace(&self, _event: &TraceEvent) {}
+
+    fn enabled(&self) -> bool {
+        false
+    }
+}
+
+/// A tracer that writes one JSON object per import call to the given writer,
+/// separated by newlines ([JSON Lines](https://jsonlines.org/)).
+///
+/// Write errors are ignored, such that tracing never affects contract execution.
+pub struct JsonLinesTracer<T: Write + Send> {
+    writer: Mutex<T>,
+}
+
+impl<T: Write + Send> JsonLinesTracer<T> {
+    pub fn new(writer: T) -> Self {
+        JsonLinesTracer {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Returns the writer, e.g. to inspect a buffer the events were written into
+    pub fn into_inner(self) -> T {
+        self.writer.into_inner().unwrap()
+    }
+}
+
+impl<T: Write + Send> Tracer for JsonLinesTracer<T> {
+    fn trace(&self, event: &TraceEvent) {
+        let mut writer = self.writer.lock().unwrap();
+        if serde_json::to_writer(&mut *writer, event).is_ok() {
+            let _ = writer.write_all(b"\n");
+        }
+    }
+}
+
+/// Collects the arguments and the result size of an import call while it is executed.
+/// Nothing is collected unless tracing is enabled.
+pub(crate) struct ImportTrace {
+    enabled: bool,
+    args: Vec<TraceValue>,
+    result_size: usize,
+}
+
+impl ImportTrace {
+    pub fn new(enabled: bool) -> Self {
+        ImportTrace {
+            enabled,
+            args: Vec::new(),
+            result_size: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn bytes(&mut self, data: &[u8]) {
+        if self.enabled {
+            self.args.push(TraceValue::Bytes(data.to_vec()));
+        }
+    }
+
+    pub fn maybe_bytes(&mut self, data: Option<&[u8]>) {
+        if self.enabled {
+            self.args.push(match data {
+                Some(data) => TraceValue::Bytes(data.to_vec()),
+                None => TraceValue::None,
+            });
+        }
+    }
+
+    pub fn number(&mut self, value: impl Into<i64>) {
+        if self.enabled {
+            self.args.push(TraceValue::Number(value.into()));
+        }
+    }
+
+    /// Records the data that is written into the contract's memory
+    pub fn result(&mut self, data: &[u8]) {
+        self.result_size = data.len();
+    }
+
+    pub fn into_parts(self) -> (Vec<TraceValue>, u64) {
+        (self.args, self.result_size as u64)
+    }
+}
+
+fn serialize_hex<S: Serializer, T: AsRef<[u8]>>(
+    data: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_event() -> TraceEvent {
+        TraceEvent {
+            import: "db_scan",
+            args: vec![
+                TraceValue::Bytes(b"foo".to_vec()),
+                TraceValue::None,
+                TraceValue::Number(1),
+            ],
+            result_size: 0,
+            gas_used_internally: 100,
+            gas_used_externally: 5,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn noop_tracer_is_disabled() {
+        let tracer = NoopTracer;
+        assert!(!tracer.enabled());
+        tracer.trace(&make_event());
+    }
+
+    #[test]
+    fn json_lines_tracer_works() {
+        let tracer = JsonLinesTracer::new(Vec::<u8>::new());
+        assert!(tracer.enabled());
+        tracer.trace(&make_event());
+        tracer.trace(&TraceEvent {
+            import: "db_read",
+            args: vec![TraceValue::Bytes(vec![0xAA, 0x01])],
+            result_size: 3,
+            gas_used_internally: 7,
+            gas_used_externally: 0,
+            error: Some("Ran out of gas during contract execution".to_string()),
+        });
+
+        let output = String::from_utf8(tracer.into_inner()).unwrap();
+        assert_eq!(
+            output,
+            concat!(
+                r#"{"import":"db_scan","args":[{"bytes":"666f6f"},"none",{"number":1}],"result_size":0,"gas_used_internally":100,"gas_used_externally":5}"#,
+                "\n",
+                r#"{"import":"db_read","args":[{"bytes":"aa01"}],"result_size":3,"gas_used_internally":7,"gas_used_externally":0,"error":"Ran out of gas during contract execution"}"#,
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn import_trace_only_collects_when_enabled() {
+        let mut trace = ImportTrace::new(false);
+        trace.bytes(b"foo");
+        trace.number(3);
+        trace.result(b"bar");
+        let (args, result_size) = trace.into_parts();
+        assert_eq!(args, vec![]);
+        assert_eq!(result_size, 3);
+
+        let mut trace = ImportTrace::new(true);
+        trace.bytes(b"foo");
+        trace.maybe_bytes(None);
+        trace.number(3);
+        trace.result(b"bar");
+        let (args, result_size) = trace.into_parts();
+        assert_eq!(
+            args,
+            vec![
+                TraceValue::Bytes(b"foo".to_vec()),
+                TraceValue::None,
+                TraceValue::Number(3)
+            ]
+        );
+        assert_eq!(result_size, 3);
+    }
+}
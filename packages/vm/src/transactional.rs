@@ -0,0 +1,651 @@
+//! A write cache on top of another [`Storage`].
+//!
+//! [`TransactionalStorage`] buffers all writes in a sorted overlay, such that the changes of
+//! a failed submessage or an aborted execution can be discarded without touching the wrapped
+//! store. Nested transactions are supported via checkpoints.
+
+#[cfg(feature = "iterator")]
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+#[cfg(feature = "iterator")]
+use std::collections::HashMap;
+#[cfg(feature = "iterator")]
+use std::convert::TryInto;
+#[cfg(feature = "iterator")]
+use std::ops::{Bound, RangeBounds};
+
+#[cfg(feature = "iterator")]
+use cosmwasm_std::{Order, Record};
+
+#[cfg(feature = "iterator")]
+use crate::backend::BackendError;
+use crate::backend::{BackendResult, GasInfo, Storage};
+use crate::environment::LinearGasCost;
+
+/// The changes of one transaction. A value of `None` marks a removed key.
+type Changes = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// Gas prices of a [`TransactionalStorage`] for the operations it serves from its overlay.
+///
+/// Those should be the prices of the wrapped store, such that buffering does not change the
+/// gas usage of a contract. All of them are reported as externally used gas.
+/// The defaults are the prices of the [`MockStorage`](crate::testing::MockStorage).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TransactionalGasConfig {
+    /// Reading a buffered value, per byte of the key
+    pub read_cost: LinearGasCost,
+    /// Writing a value, per byte of the key and the value
+    pub write_cost: LinearGasCost,
+    /// Removing a value, per byte of the key
+    pub remove_cost: LinearGasCost,
+    /// Iterating over a buffered value, per byte of the key and the value
+    pub next_cost: LinearGasCost,
+}
+
+impl Default for TransactionalGasConfig {
+    fn default() -> Self {
+        let per_byte = LinearGasCost {
+            base: 0,
+            per_item: 1,
+        };
+        Self {
+            read_cost: per_byte,
+            write_cost: per_byte,
+            remove_cost: per_byte,
+            next_cost: per_byte,
+        }
+    }
+}
+
+/// A [`Storage`] that buffers `set` and `remove` in an overlay instead of writing them
+/// to the wrapped store. Reads see the buffered changes on top of the wrapped store.
+///
+/// The overlay is organized in transactions. [`TransactionalStorage::checkpoint`] starts
+/// a nested transaction, [`TransactionalStorage::commit`] ends the innermost transaction
+/// keeping its changes and [`TransactionalStorage::rollback`] ends it discarding them.
+/// Committing the outermost transaction writes the changes into the wrapped store.
+///
+/// Gas: every operation is charged when it is called. Reads from the wrapped store report
+/// the gas of the wrapped store (summed up if one operation requires multiple calls).
+/// Writes and reads served from the overlay are charged according to the
+/// [`TransactionalGasConfig`]. Committing is free since the writes were charged already.
+///
+/// Iterators: IDs are assigned by the wrapper in the same deterministic way as by the
+/// [`MockStorage`](crate::testing::MockStorage), independent of the IDs of the wrapped store.
+/// An iterator sees the changes made before it was created, but no later ones.
+pub struct TransactionalStorage<S: Storage> {
+    inner: S,
+    gas_config: TransactionalGasConfig,
+    /// The changes of the open transactions, the innermost last. This is never empty.
+    transactions: Vec<Changes>,
+    #[cfg(feature = "iterator")]
+    iterators: HashMap<u32, MergedIter>,
+}
+
+impl<S: Storage> TransactionalStorage<S> {
+    /// Wraps the given store using the default gas prices.
+    /// The wrapper starts with one open transaction.
+    pub fn new(inner: S) -> Self {
+        Self::with_gas_config(inner, TransactionalGasConfig::default())
+    }
+
+    /// Wraps the given store using the given gas prices for the overlay.
+    /// The wrapper starts with one open transaction.
+    pub fn with_gas_config(inner: S, gas_config: TransactionalGasConfig) -> Self {
+        TransactionalStorage {
+            inner,
+            gas_config,
+            transactions: vec![Changes::new()],
+            #[cfg(feature = "iterator")]
+            iterators: HashMap::new(),
+        }
+    }
+
+    /// Starts a nested transaction. Its changes can be discarded by
+    /// [`TransactionalStorage::rollback`] without affecting the changes made before.
+    pub fn checkpoint(&mut self) {
+        self.transactions.push(Changes::new());
+    }
+
+    /// The number of checkpoints that were not committed or rolled back yet
+    pub fn checkpoints(&self) -> usize {
+        self.transactions.len() - 1
+    }
+
+    /// Ends the innermost transaction keeping its changes.
+    ///
+    /// For a nested transaction, the changes become part of the enclosing transaction.
+    /// For the outermost transaction, the changes are written into the wrapped store.
+    /// If a write fails, the changes that were not written yet remain in the overlay.
+    ///
+    /// This is free since every change was charged when it was made. The gas the wrapped
+    /// store reports for the writes is not returned in order to not charge them twice.
+    pub fn commit(&mut self) -> BackendResult<()> {
+        if self.transactions.len() > 1 {
+            let changes = self.transactions.pop().unwrap();
+            self.innermost().extend(changes);
+            return (Ok(()), GasInfo::free());
+        }
+
+        let mut changes = std::mem::take(self.innermost()).into_iter();
+        let mut failed = None;
+        for (key, value) in changes.by_ref() {
+            let (result, _) = match &value {
+                Some(value) => self.inner.set(&key, value),
+                None => self.inner.remove(&key),
+            };
+            if let Err(err) = result {
+                failed = Some((key, value, err));
+                break;
+            }
+        }
+
+        match failed {
+            Some((key, value, err)) => {
+                let remaining = self.innermost();
+                remaining.insert(key, value);
+                remaining.extend(changes);
+                (Err(err), GasInfo::free())
+            }
+            None => (Ok(()), GasInfo::free()),
+        }
+    }
+
+    /// Ends the innermost transaction discarding its changes
+    pub fn rollback(&mut self) {
+        if self.transactions.len() > 1 {
+            self.transactions.pop();
+        } else {
+            self.innermost().clear();
+        }
+    }
+
+    /// Returns the wrapped store
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns the wrapped store. Changes that were not committed are dropped.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn innermost(&mut self) -> &mut Changes {
+        self.transactions
+            .last_mut()
+            .expect("There is always at least one transaction")
+    }
+
+    /// Returns the latest change of the given key, if any
+    fn change(&self, key: &[u8]) -> Option<&Option<Vec<u8>>> {
+        self.transactions
+            .iter()
+            .rev()
+            .find_map(|changes| changes.get(key))
+    }
+
+    /// Returns the latest changes within the given range, ordered by key
+    #[cfg(feature = "iterator")]
+    fn changes_in_range(&self, start: Option<&[u8]>, end: Option<&[u8]>) -> Changes {
+        let bounds = (
+            start.map_or(Bound::Unbounded, |x| Bound::Included(x.to_vec())),
+            end.map_or(Bound::Unbounded, |x| Bound::Excluded(x.to_vec())),
+        );
+        let mut merged = Changes::new();
+        if let (Bound::Included(start), Bound::Excluded(end)) =
+            (bounds.start_bound(), bounds.end_bound())
+        {
+            // BTreeMap.range panics if start > end. This is just an empty range.
+            if start > end {
+                return merged;
+            }
+        }
+        for changes in &self.transactions {
+            for (key, value) in changes.range(bounds.clone()) {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+        merged
+    }
+}
+
+impl<S: Storage> Storage for TransactionalStorage<S> {
+    fn get(&self, key: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        match self.change(key) {
+            Some(value) => {
+                let gas_cost = self.gas_config.read_cost.total_cost(key.len() as u64);
+                (Ok(value.clone()), GasInfo::with_externally_used(gas_cost))
+            }
+            None => self.inner.get(key),
+        }
+    }
+
+    #[cfg(feature = "iterator")]
+    fn scan(
+        &mut self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> BackendResult<u32> {
+        let (result, gas_info) = self.inner.scan(start, end, order);
+        let inner_id = match result {
+            Ok(id) => id,
+            Err(err) => return (Err(err), gas_info),
+        };
+
+        let changes = self.changes_in_range(start, end);
+        let changes: Vec<(Vec<u8>, Option<Vec<u8>>)> = match order {
+            Order::Ascending => changes.into_iter().collect(),
+            Order::Descending => changes.into_iter().rev().collect(),
+        };
+
+        let last_id: u32 = self
+            .iterators
+            .len()
+            .try_into()
+            .expect("Found more iterator IDs than supported");
+        let new_id = last_id + 1;
+        self.iterators.insert(
+            new_id,
+            MergedIter {
+                inner_id,
+                inner_peeked: None,
+                changes,
+                position: 0,
+                order,
+            },
+        );
+        (Ok(new_id), gas_info)
+    }
+
+    #[cfg(feature = "iterator")]
+    fn next(&mut self, iterator_id: u32) -> BackendResult<Option<Record>> {
+        let iter = match self.iterators.get_mut(&iterator_id) {
+            Some(iter) => iter,
+            None => {
+                return (
+                    Err(BackendError::iterator_does_not_exist(iterator_id)),
+                    GasInfo::free(),
+                )
+            }
+        };
+
+        let next_cost = self.gas_config.next_cost;
+        let mut gas_info = GasInfo::free();
+        loop {
+            if iter.inner_peeked.is_none() {
+                let (result, info) = self.inner.next(iter.inner_id);
+                gas_info += info;
+                match result {
+                    Ok(record) => iter.inner_peeked = Some(record),
+                    Err(err) => return (Err(err), gas_info),
+                }
+            }
+
+            // Less or Equal takes the next change, Greater the next record of the wrapped store
+            let ordering = match (
+                iter.inner_peeked.as_ref().unwrap(),
+                iter.changes.get(iter.position),
+            ) {
+                (None, None) => return (Ok(None), gas_info),
+                (Some(_), None) => Ordering::Greater,
+                (None, Some(_)) => Ordering::Less,
+                (Some((inner_key, _)), Some((changed_key, _))) => match iter.order {
+                    Order::Ascending => changed_key.cmp(inner_key),
+                    Order::Descending => inner_key.cmp(changed_key),
+                },
+            };
+            if ordering == Ordering::Equal {
+                // The change shadows the record of the wrapped store
+                iter.inner_peeked = None;
+            }
+
+            if ordering != Ordering::Greater {
+                let (key, value) = iter.changes[iter.position].clone();
+                iter.position += 1;
+                match value {
+                    Some(value) => {
+                        let gas_cost = next_cost.total_cost((key.len() + value.len()) as u64);
+                        gas_info += GasInfo::with_externally_used(gas_cost);
+                        return (Ok(Some((key, value))), gas_info);
+                    }
+                    // removed keys are skipped
+                    None => continue,
+                }
+            } else {
+                let record = iter.inner_peeked.take().unwrap();
+                return (Ok(record), gas_info);
+            }
+        }
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()> {
+        self.innermost().insert(key.to_vec(), Some(value.to_vec()));
+        let gas_cost = self
+            .gas_config
+            .write_cost
+            .total_cost((key.len() + value.len()) as u64);
+        (Ok(()), GasInfo::with_externally_used(gas_cost))
+    }
+
+    fn remove(&mut self, key: &[u8]) -> BackendResult<()> {
+        self.innermost().insert(key.to_vec(), None);
+        let gas_cost = self.gas_config.remove_cost.total_cost(key.len() as u64);
+        (Ok(()), GasInfo::with_externally_used(gas_cost))
+    }
+}
+
+/// An iterator over the wrapped store merged with the changes at the time of its creation
+#[cfg(feature = "iterator")]
+struct MergedIter {
+    inner_id: u32,
+    /// The next record of the wrapped store. `None` if it was not read yet and
+    /// `Some(None)` if the wrapped iterator is exhausted.
+    inner_peeked: Option<Option<Record>>,
+    /// The changes in iteration order
+    changes: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+    position: usize,
+    order: Order,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockStorage;
+
+    fn make_storage() -> TransactionalStorage<MockStorage> {
+        let mut inner = MockStorage::new();
+        inner.set(b"ant", b"hill").0.unwrap();
+        inner.set(b"foo", b"bar").0.unwrap();
+        inner.set(b"ze", b"bra").0.unwrap();
+        TransactionalStorage::new(inner)
+    }
+
+    #[test]
+    fn get_sees_changes() {
+        let mut store = make_storage();
+        assert_eq!(store.get(b"foo").0.unwrap(), Some(b"bar".to_vec()));
+
+        store.set(b"foo", b"baz").0.unwrap();
+        store.set(b"new", b"value").0.unwrap();
+        store.remove(b"ant").0.unwrap();
+        assert_eq!(store.get(b"foo").0.unwrap(), Some(b"baz".to_vec()));
+        assert_eq!(store.get(b"new").0.unwrap(), Some(b"value".to_vec()));
+        assert_eq!(store.get(b"ant").0.unwrap(), None);
+        assert_eq!(store.get(b"ze").0.unwrap(), Some(b"bra".to_vec()));
+
+        // wrapped store is unchanged
+        assert_eq!(store.inner().get(b"foo").0.unwrap(), Some(b"bar".to_vec()));
+        assert_eq!(store.inner().get(b"new").0.unwrap(), None);
+        assert_eq!(store.inner().get(b"ant").0.unwrap(), Some(b"hill".to_vec()));
+    }
+
+    #[test]
+    fn commit_writes_changes() {
+        let mut store = make_storage();
+        store.set(b"foo", b"baz").0.unwrap();
+        store.remove(b"ant").0.unwrap();
+
+        let (result, gas_info) = store.commit();
+        result.unwrap();
+        // the set and the remove were charged already
+        assert_eq!(gas_info, GasInfo::free());
+
+        let inner = store.into_inner();
+        assert_eq!(inner.get(b"foo").0.unwrap(), Some(b"baz".to_vec()));
+        assert_eq!(inner.get(b"ant").0.unwrap(), None);
+    }
+
+    #[test]
+    fn rollback_discards_changes() {
+        let mut store = make_storage();
+        store.set(b"foo", b"baz").0.unwrap();
+        store.rollback();
+        assert_eq!(store.get(b"foo").0.unwrap(), Some(b"bar".to_vec()));
+
+        let (result, gas_info) = store.commit();
+        result.unwrap();
+        assert_eq!(gas_info, GasInfo::free());
+        assert_eq!(store.inner().get(b"foo").0.unwrap(), Some(b"bar".to_vec()));
+    }
+
+    #[test]
+    fn checkpoints_work() {
+        let mut store = make_storage();
+        store.set(b"a", b"1").0.unwrap();
+        assert_eq!(store.checkpoints(), 0);
+
+        store.checkpoint();
+        store.set(b"b", b"2").0.unwrap();
+        store.remove(b"a").0.unwrap();
+        store.checkpoint();
+        store.set(b"c", b"3").0.unwrap();
+        assert_eq!(store.checkpoints(), 2);
+
+        // discard c
+        store.rollback();
+        assert_eq!(store.checkpoints(), 1);
+        assert_eq!(store.get(b"c").0.unwrap(), None);
+        assert_eq!(store.get(b"b").0.unwrap(), Some(b"2".to_vec()));
+        assert_eq!(store.get(b"a").0.unwrap(), None);
+
+        // keep b and the removal of a, nothing is written yet
+        store.commit().0.unwrap();
+        assert_eq!(store.checkpoints(), 0);
+        assert_eq!(store.get(b"b").0.unwrap(), Some(b"2".to_vec()));
+        assert_eq!(store.get(b"a").0.unwrap(), None);
+        assert_eq!(store.inner().get(b"b").0.unwrap(), None);
+
+        store.commit().0.unwrap();
+        let inner = store.into_inner();
+        assert_eq!(inner.get(b"a").0.unwrap(), None);
+        assert_eq!(inner.get(b"b").0.unwrap(), Some(b"2".to_vec()));
+        assert_eq!(inner.get(b"c").0.unwrap(), None);
+    }
+
+    #[test]
+    fn gas_is_charged_at_call_time() {
+        let mut store = make_storage();
+        // read through, charged by MockStorage
+        assert_eq!(store.get(b"foo").1, GasInfo::with_externally_used(3));
+        // buffered, charged like MockStorage by default
+        assert_eq!(
+            store.set(b"foo", b"baz").1,
+            GasInfo::with_externally_used(6)
+        );
+        assert_eq!(store.get(b"foo").1, GasInfo::with_externally_used(3));
+        assert_eq!(store.remove(b"ze").1, GasInfo::with_externally_used(2));
+        assert_eq!(store.get(b"ze").1, GasInfo::with_externally_used(2));
+    }
+
+    #[test]
+    fn buffering_does_not_change_gas_usage() {
+        let mut buffered = make_storage();
+        let mut unbuffered = make_storage().into_inner();
+        let mut buffered_gas = GasInfo::free();
+        let mut unbuffered_gas = GasInfo::free();
+        buffered_gas += buffered.set(b"foo", b"baz").1;
+        unbuffered_gas += unbuffered.set(b"foo", b"baz").1;
+        buffered_gas += buffered.get(b"foo").1;
+        unbuffered_gas += unbuffered.get(b"foo").1;
+        buffered_gas += buffered.remove(b"ant").1;
+        unbuffered_gas += unbuffered.remove(b"ant").1;
+        buffered_gas += buffered.commit().1;
+        assert_eq!(buffered_gas, unbuffered_gas);
+    }
+
+    #[test]
+    fn with_gas_config_works() {
+        let gas_config = TransactionalGasConfig {
+            read_cost: LinearGasCost {
+                base: 1000,
+                per_item: 3,
+            },
+            write_cost: LinearGasCost {
+                base: 2000,
+                per_item: 30,
+            },
+            remove_cost: LinearGasCost {
+                base: 1000,
+                per_item: 0,
+            },
+            next_cost: LinearGasCost {
+                base: 30,
+                per_item: 3,
+            },
+        };
+        let mut store = TransactionalStorage::with_gas_config(MockStorage::new(), gas_config);
+        assert_eq!(
+            store.set(b"foo", b"bar").1,
+            GasInfo::with_externally_used(2000 + 6 * 30)
+        );
+        assert_eq!(
+            store.get(b"foo").1,
+            GasInfo::with_externally_used(1000 + 3 * 3)
+        );
+        assert_eq!(store.remove(b"foo").1, GasInfo::with_externally_used(1000));
+    }
+
+    #[cfg(feature = "iterator")]
+    fn all(store: &mut TransactionalStorage<MockStorage>, iterator_id: u32) -> Vec<Record> {
+        let mut out = Vec::new();
+        while let Some(record) = store.next(iterator_id).0.unwrap() {
+            out.push(record);
+        }
+        out
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn iterator_merges_changes() {
+        let mut store = make_storage();
+        store.set(b"foo", b"baz").0.unwrap();
+        store.set(b"bee", b"hive").0.unwrap();
+        store.set(b"zz", b"top").0.unwrap();
+        store.remove(b"ant").0.unwrap();
+        store.remove(b"missing").0.unwrap();
+        store.checkpoint();
+        store.set(b"ant", b"colony").0.unwrap();
+        store.remove(b"ze").0.unwrap();
+
+        let id = store.scan(None, None, Order::Ascending).0.unwrap();
+        assert_eq!(
+            all(&mut store, id),
+            vec![
+                (b"ant".to_vec(), b"colony".to_vec()),
+                (b"bee".to_vec(), b"hive".to_vec()),
+                (b"foo".to_vec(), b"baz".to_vec()),
+                (b"zz".to_vec(), b"top".to_vec()),
+            ]
+        );
+
+        let id = store.scan(None, None, Order::Descending).0.unwrap();
+        assert_eq!(
+            all(&mut store, id),
+            vec![
+                (b"zz".to_vec(), b"top".to_vec()),
+                (b"foo".to_vec(), b"baz".to_vec()),
+                (b"bee".to_vec(), b"hive".to_vec()),
+                (b"ant".to_vec(), b"colony".to_vec()),
+            ]
+        );
+
+        let id = store
+            .scan(Some(b"b"), Some(b"zz"), Order::Ascending)
+            .0
+            .unwrap();
+        assert_eq!(
+            all(&mut store, id),
+            vec![
+                (b"bee".to_vec(), b"hive".to_vec()),
+                (b"foo".to_vec(), b"baz".to_vec()),
+            ]
+        );
+
+        let id = store
+            .scan(Some(b"z"), Some(b"a"), Order::Ascending)
+            .0
+            .unwrap();
+        assert_eq!(all(&mut store, id), vec![]);
+
+        // the merged result is the same as after committing
+        store.commit().0.unwrap();
+        store.commit().0.unwrap();
+        let mut inner = store.into_inner();
+        let id = inner.scan(None, None, Order::Ascending).0.unwrap();
+        assert_eq!(
+            inner.all(id).0.unwrap(),
+            vec![
+                (b"ant".to_vec(), b"colony".to_vec()),
+                (b"bee".to_vec(), b"hive".to_vec()),
+                (b"foo".to_vec(), b"baz".to_vec()),
+                (b"zz".to_vec(), b"top".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn iterator_ids_are_deterministic() {
+        let mut store = make_storage();
+        // the wrapped store already has an iterator
+        store.inner.scan(None, None, Order::Ascending).0.unwrap();
+
+        assert_eq!(store.scan(None, None, Order::Ascending).0.unwrap(), 1);
+        assert_eq!(store.scan(None, None, Order::Descending).0.unwrap(), 2);
+        assert_eq!(
+            store.next(3).0.unwrap_err(),
+            BackendError::iterator_does_not_exist(3)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn iterator_ignores_later_changes() {
+        let mut store = make_storage();
+        let id = store.scan(None, None, Order::Ascending).0.unwrap();
+        assert_eq!(
+            store.next(id).0.unwrap(),
+            Some((b"ant".to_vec(), b"hill".to_vec()))
+        );
+        store.set(b"bee", b"hive").0.unwrap();
+        store.remove(b"foo").0.unwrap();
+        assert_eq!(
+            store.next(id).0.unwrap(),
+            Some((b"foo".to_vec(), b"bar".to_vec()))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn iterator_charges_gas() {
+        let mut store = make_storage();
+        store.set(b"foo", b"baz").0.unwrap();
+        store.set(b"bee", b"hive").0.unwrap();
+
+        let (result, gas_info) = store.scan(None, None, Order::Ascending);
+        let id = result.unwrap();
+        // range cost of MockStorage
+        assert_eq!(gas_info, GasInfo::with_externally_used(11));
+
+        // ant from the wrapped store
+        let (record, gas_info) = store.next(id);
+        assert_eq!(record.unwrap(), Some((b"ant".to_vec(), b"hill".to_vec())));
+        assert_eq!(gas_info, GasInfo::with_cost(7));
+        // bee from the overlay, but foo was already read from the wrapped store
+        let (record, gas_info) = store.next(id);
+        assert_eq!(record.unwrap(), Some((b"bee".to_vec(), b"hive".to_vec())));
+        assert_eq!(gas_info, GasInfo::new(6, 7));
+        // foo from the overlay shadows the record of the wrapped store
+        let (record, gas_info) = store.next(id);
+        assert_eq!(record.unwrap(), Some((b"foo".to_vec(), b"baz".to_vec())));
+        assert_eq!(gas_info, GasInfo::with_externally_used(6));
+        let (record, gas_info) = store.next(id);
+        assert_eq!(record.unwrap(), Some((b"ze".to_vec(), b"bra".to_vec())));
+        assert_eq!(gas_info, GasInfo::with_cost(5));
+        // last iteration
+        let (record, gas_info) = store.next(id);
+        assert_eq!(record.unwrap(), None);
+        assert_eq!(gas_info, GasInfo::with_externally_used(37));
+    }
+}
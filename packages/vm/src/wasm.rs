@@ -3,14 +3,19 @@ use crate::{
     environment::Environment,
     memory::{validate_region, Region},
     static_analysis::ExportInfo,
+    wasm_backend::{
+        exported_global_index, get_max_stack_depth, get_stack_depth, set_max_stack_depth,
+        set_stack_depth, stack_depth_exceeded,
+    },
     BackendApi, CommunicationError, CommunicationResult, Querier, Storage, VmError, VmResult,
 };
 use parity_wasm::elements::FunctionType;
 use wasmer::{Array, HostFunction, Instance as WasmerInstance, Memory as WasmerMemory, WasmPtr};
-use wasmer::{Function, Val};
+use wasmer::{Function, Global, Val};
 use wasmer_middlewares::metering::{get_remaining_points, set_remaining_points, MeteringPoints};
 
-/// Abstracts over different wasm backends, allowing for both Wasmer as well as the Substrate runtime to be used as the actual VM backend.
+/// Abstracts over different wasm backends, allowing for both Wasmer as well as the wasmi interpreter
+/// (see the `interpreter` feature) to be used as the actual VM backend.
 pub trait WasmVM {
     type ExportInfo: ExportInfo;
     type Memory: Memory;
@@ -20,6 +25,15 @@ pub trait WasmVM {
     fn get_gas_left(&self) -> u64;
     fn set_gas_left(&self, new: u64);
     fn call_function(&self, name: &str, args: &[Val]) -> VmResult<Box<[Val]>>;
+    /// Returns the values of all mutable globals defined by the contract, ordered by index.
+    /// Those are exported by the VM when the contract is compiled.
+    fn globals(&self) -> VmResult<Vec<Val>>;
+    /// Sets all mutable globals to the given values as returned by `globals`.
+    fn set_globals(&self, values: &[Val]) -> VmResult<()>;
+    /// Returns the maximum stack depth of the contract, see `DEFAULT_MAX_STACK_DEPTH`.
+    fn max_stack_depth(&self) -> u32;
+    /// Sets the maximum stack depth. Calls exceeding it fail with `VmError::StackDepthExceeded`.
+    fn set_max_stack_depth(&self, max_depth: u32);
 }
 
 impl WasmVM for WasmerInstance {
@@ -60,15 +74,64 @@ impl WasmVM for WasmerInstance {
     fn call_function(&self, name: &str, args: &[Val]) -> VmResult<Box<[Val]>> {
         // Clone function before calling it to avoid dead locks
         let func = self.exports.get_function(name)?.clone();
+        // This is not 0 if the contract calls an import that calls back into the contract
+        let stack_depth = get_stack_depth(self);
 
         func.call(args).map_err(|runtime_err| -> VmError {
             let err: VmError = match get_remaining_points(self) {
+                MeteringPoints::Remaining(_) if stack_depth_exceeded(self) => {
+                    VmError::stack_depth_exceeded(get_max_stack_depth(self))
+                }
                 MeteringPoints::Remaining(_) => VmError::from(runtime_err),
                 MeteringPoints::Exhausted => VmError::gas_depletion(),
             };
+            // A trap skips the decrements of the stack depth
+            set_stack_depth(self, stack_depth);
             err
         })
     }
+
+    fn globals(&self) -> VmResult<Vec<Val>> {
+        Ok(mutable_globals(self)
+            .iter()
+            .map(|global| global.get())
+            .collect())
+    }
+
+    fn set_globals(&self, values: &[Val]) -> VmResult<()> {
+        let globals = mutable_globals(self);
+        if globals.len() != values.len() {
+            return Err(VmError::generic_err(format!(
+                "Expected {} global values but got {}",
+                globals.len(),
+                values.len()
+            )));
+        }
+        for (global, value) in globals.iter().zip(values) {
+            global.set(value.clone())?;
+        }
+        Ok(())
+    }
+
+    fn max_stack_depth(&self) -> u32 {
+        get_max_stack_depth(self)
+    }
+
+    fn set_max_stack_depth(&self, max_depth: u32) {
+        set_max_stack_depth(self, max_depth);
+    }
+}
+
+/// The mutable globals of the contract ordered by index, see `export_mutable_globals`
+fn mutable_globals(instance: &WasmerInstance) -> Vec<Global> {
+    let mut globals: Vec<(u32, Global)> = instance
+        .exports
+        .iter()
+        .globals()
+        .filter_map(|(name, global)| Some((exported_global_index(name)?, global.clone())))
+        .collect();
+    globals.sort_by_key(|(index, _)| *index);
+    globals.into_iter().map(|(_, global)| global).collect()
 }
 
 pub trait Memory {
@@ -80,6 +143,10 @@ pub trait Memory {
     fn set_region(&self, ptr: u32, data: Region) -> CommunicationResult<()>;
     fn read_region(&self, ptr: u32, max_length: usize) -> VmResult<Vec<u8>>;
     fn maybe_read_region(&self, ptr: u32, max_length: usize) -> VmResult<Option<Vec<u8>>>;
+    /// Copies the entire memory
+    fn read_all(&self) -> Vec<u8>;
+    /// Overwrites the entire memory. The data must have the current size of the memory.
+    fn write_all(&self, data: &[u8]) -> VmResult<()>;
 }
 
 pub trait Pages {
@@ -175,6 +242,25 @@ impl Memory for WasmerMemory {
         }
     }
 
+    fn read_all(&self) -> Vec<u8> {
+        // Safe because the memory is not accessed concurrently while the copy is made
+        unsafe { self.data_unchecked() }.to_vec()
+    }
+
+    fn write_all(&self, data: &[u8]) -> VmResult<()> {
+        let size = self.data_size() as usize;
+        if data.len() != size {
+            return Err(VmError::generic_err(format!(
+                "Cannot write {} bytes into a memory of {} bytes",
+                data.len(),
+                size
+            )));
+        }
+        // Safe because the memory is not accessed concurrently while the data is written
+        unsafe { self.data_unchecked_mut() }.copy_from_slice(data);
+        Ok(())
+    }
+
     fn set_region(&self, ptr: u32, data: Region) -> CommunicationResult<()> {
         let wptr = WasmPtr::<Region>::new(ptr);
 
@@ -5,18 +5,28 @@ use wasmer::{Module, ModuleMiddleware};
 use crate::errors::VmResult;
 use crate::size::Size;
 
+use super::globals::with_exported_globals;
+use super::operator_costs::OperatorCostTable;
+use super::stack_limiter::FrameWeights;
 use super::store::make_compile_time_store;
 
 /// Compiles a given Wasm bytecode into a module.
 /// The given memory limit (in bytes) is used when memories are created.
 /// If no memory limit is passed, the resulting compiled module should
 /// not be used for execution.
+/// Gas for each operator is charged according to `cost_table`.
+/// All mutable globals are exported, such that the state of instances can be restored.
+/// Calls are weighted by the frame sizes of their callees to limit the stack depth.
 pub fn compile(
     code: &[u8],
     memory_limit: Option<Size>,
+    cost_table: &OperatorCostTable,
     middlewares: &[Arc<dyn ModuleMiddleware>],
 ) -> VmResult<Module> {
-    let store = make_compile_time_store(memory_limit, middlewares);
+    let code = with_exported_globals(code)?;
+    // Invalid code is rejected by the compiler below
+    let frame_weights = FrameWeights::from_code(&code).unwrap_or_default();
+    let store = make_compile_time_store(memory_limit, cost_table, frame_weights, middlewares);
     let module = Module::new(&store, code)?;
     Ok(module)
 }
@@ -29,7 +39,7 @@ mod tests {
 
     #[test]
     fn contract_with_floats_fails_check() {
-        let err = compile(CONTRACT, None, &[]).unwrap_err();
+        let err = compile(CONTRACT, None, &OperatorCostTable::default(), &[]).unwrap_err();
         assert!(err.to_string().contains("Float operator detected:"));
     }
 }
@@ -68,6 +68,28 @@ impl Default for Gatekeeper {
     }
 }
 
+#[cfg(feature = "interpreter")]
+impl Gatekeeper {
+    /// Applies the same checks as the middleware to all function bodies of the given Wasm
+    /// bytecode. This is used by backends that do not compile with Wasmer.
+    pub fn check_code(&self, code: &[u8]) -> Result<(), String> {
+        use wasmer::wasmparser::{Parser, Payload};
+
+        for payload in Parser::new(0).parse_all(code) {
+            if let Payload::CodeSectionEntry(body) = payload.map_err(|e| e.to_string())? {
+                let mut reader = body.get_operators_reader().map_err(|e| e.to_string())?;
+                while !reader.eof() {
+                    let operator = reader.read().map_err(|e| e.to_string())?;
+                    self.config
+                        .check_operator(&operator)
+                        .map_err(|msg| format!("{}: {}", MIDDLEWARE_NAME, msg))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl ModuleMiddleware for Gatekeeper {
     /// Generates a `FunctionMiddleware` for a given function.
     fn generate_function_middleware(&self, _: LocalFunctionIndex) -> Box<dyn FunctionMiddleware> {
@@ -96,6 +118,17 @@ impl FunctionMiddleware for FunctionGatekeeper {
         operator: Operator<'a>,
         state: &mut MiddlewareReaderState<'a>,
     ) -> Result<(), MiddlewareError> {
+        self.config
+            .check_operator(&operator)
+            .map_err(|msg| MiddlewareError::new(MIDDLEWARE_NAME, msg))?;
+        state.push_operator(operator);
+        Ok(())
+    }
+}
+
+impl GatekeeperConfig {
+    /// Returns an error message if the given operator is not allowed
+    fn check_operator(&self, operator: &Operator) -> Result<(), String> {
         match operator {
             Operator::Unreachable
             | Operator::Nop
@@ -205,10 +238,7 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::I64Extend16S
             | Operator::I64ExtendI32S
             | Operator::I64Extend32S
-            | Operator::I64ExtendI32U => {
-                state.push_operator(operator);
-                Ok(())
-            }
+            | Operator::I64ExtendI32U => Ok(()),
             Operator::RefNull { .. }
             | Operator::RefIsNull
             | Operator::RefFunc { .. }
@@ -219,12 +249,11 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::TableSet { .. }
             | Operator::TableGrow { .. }
             | Operator::TableSize { .. } => {
-                if self.config.allow_feature_reference_types {
-                    state.push_operator(operator);
+                if self.allow_feature_reference_types {
                     Ok(())
                 } else {
                     let msg = format!("Reference type operation detected: {:?}. Reference types are not supported.", operator);
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
             Operator::MemoryAtomicNotify { .. }
@@ -294,12 +323,11 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::I64AtomicRmw8CmpxchgU { .. }
             | Operator::I64AtomicRmw16CmpxchgU { .. }
             | Operator::I64AtomicRmw32CmpxchgU { .. } => {
-                if self.config.allow_feature_threads {
-                    state.push_operator(operator);
+                if self.allow_feature_threads {
                     Ok(())
                 } else {
                     let msg = format!("Threads operator detected: {:?}. The Wasm Threads extension is not supported.", operator);
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
             Operator::V128Load { .. }
@@ -486,15 +514,14 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::F64x2ConvertLowI32x4U
             | Operator::F32x4DemoteF64x2Zero
             | Operator::F64x2PromoteLowF32x4 => {
-                if self.config.allow_feature_simd {
-                    state.push_operator(operator);
+                if self.allow_feature_simd {
                     Ok(())
                 } else {
                     let msg = format!(
                         "SIMD operator detected: {:?}. The Wasm SIMD extension is not supported.",
                         operator
                     );
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
             Operator::F32Load { .. }
@@ -625,15 +652,14 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::I32x4TruncSatF32x4U
             | Operator::F32x4ConvertI32x4S
             | Operator::F32x4ConvertI32x4U => {
-                if self.config.allow_floats {
-                    state.push_operator(operator);
+                if self.allow_floats {
                     Ok(())
                 } else {
                     let msg = format!(
                         "Float operator detected: {:?}. The use of floats is not supported.",
                         operator
                     );
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
             Operator::MemoryInit { .. }
@@ -644,12 +670,11 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::ElemDrop { .. }
             | Operator::TableCopy { .. }
             | Operator::TableFill { .. } => {
-                if self.config.allow_feature_bulk_memory_operations {
-                    state.push_operator(operator);
+                if self.allow_feature_bulk_memory_operations {
                     Ok(())
                 } else {
                     let msg = format!("Bulk memory operation detected: {:?}. Bulk memory operations are not supported.", operator);
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
             Operator::Try { .. }
@@ -659,12 +684,11 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::Unwind { .. }
             | Operator::Delegate { .. }
             | Operator::CatchAll => {
-                if self.config.allow_feature_exception_handling {
-                    state.push_operator(operator);
+                if self.allow_feature_exception_handling {
                     Ok(())
                 } else {
                     let msg = format!("Exception handling operation detected: {:?}. Exception handling is not supported.", operator);
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
         }
@@ -749,4 +773,34 @@ mod tests {
             .to_string()
             .contains("Bulk memory operation"));
     }
+
+    #[test]
+    #[cfg(feature = "interpreter")]
+    fn check_code_works() {
+        let valid = wat::parse_str(
+            r#"
+            (module
+                (func (export "sum") (param i32 i32) (result i32)
+                    get_local 0
+                    get_local 1
+                    i32.add
+                ))
+            "#,
+        )
+        .unwrap();
+        Gatekeeper::default().check_code(&valid).unwrap();
+
+        let floaty = wat::parse_str(
+            r#"
+            (module
+                (func $to_float (param i32) (result f32)
+                    get_local 0
+                    f32.convert_u/i32
+                ))
+            "#,
+        )
+        .unwrap();
+        let err = Gatekeeper::default().check_code(&floaty).unwrap_err();
+        assert!(err.starts_with("Gatekeeper: Float operator detected:"));
+    }
 }
@@ -0,0 +1,138 @@
+use parity_wasm::elements::{ExportEntry, ImportCountType, Internal, Module as ParityModule};
+
+use crate::errors::{VmError, VmResult};
+use crate::static_analysis::deserialize_wasm;
+
+/// The prefix of the exports that are added for the mutable globals of a contract,
+/// followed by the index of the global. Those allow the VM to save and restore
+/// globals that the contract does not export itself, like the stack pointer.
+pub const GLOBAL_EXPORT_PREFIX: &str = "cosmwasm_global_";
+
+/// Returns the indices of all mutable globals defined in the module
+fn mutable_global_indices(module: &ParityModule) -> Vec<usize> {
+    let imported = module.import_count(ImportCountType::Global);
+    module
+        .global_section()
+        .map(|section| {
+            section
+                .entries()
+                .iter()
+                .enumerate()
+                .filter(|(_, global)| global.global_type().is_mutable())
+                .map(|(position, _)| imported + position)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Adds an export named [`GLOBAL_EXPORT_PREFIX`] followed by the global index
+/// for every mutable global defined in the module.
+pub fn export_mutable_globals(module: &mut ParityModule) {
+    let indices = mutable_global_indices(module);
+
+    // Contracts without an export section are rejected by `check_wasm` anyways
+    if let Some(section) = module.export_section_mut() {
+        for index in indices {
+            section.entries_mut().push(ExportEntry::new(
+                format!("{}{}", GLOBAL_EXPORT_PREFIX, index),
+                Internal::Global(index as u32),
+            ));
+        }
+    }
+}
+
+/// Returns the given Wasm code with the exports of `export_mutable_globals` added
+pub fn with_exported_globals(code: &[u8]) -> VmResult<Vec<u8>> {
+    let mut module = deserialize_wasm(code)?;
+    export_mutable_globals(&mut module);
+    parity_wasm::serialize(module).map_err(|err| {
+        VmError::static_validation_err(format!(
+            "Wasm bytecode could not be serialized. Serialization error: \"{}\"",
+            err
+        ))
+    })
+}
+
+/// Returns the number of mutable globals defined in the given Wasm code.
+/// Instances of this code are expected to export that many globals, see `export_mutable_globals`.
+pub fn count_mutable_globals(code: &[u8]) -> VmResult<usize> {
+    let module = deserialize_wasm(code)?;
+    Ok(mutable_global_indices(&module).len())
+}
+
+/// Returns the global index of an export added by `export_mutable_globals`
+pub fn exported_global_index(name: &str) -> Option<u32> {
+    name.strip_prefix(GLOBAL_EXPORT_PREFIX)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_mutable_globals_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+            (import "env" "g" (global i32))
+            (global $sp (mut i32) (i32.const 1024))
+            (global $base i32 (i32.const 2048))
+            (global $counter (mut i64) (i64.const 0))
+            (memory (export "memory") 1)
+            )"#,
+        )
+        .unwrap();
+        let code = with_exported_globals(&wasm).unwrap();
+        let module = deserialize_wasm(&code).unwrap();
+
+        let exports: Vec<(String, Internal)> = module
+            .export_section()
+            .unwrap()
+            .entries()
+            .iter()
+            .map(|entry| (entry.field().to_string(), *entry.internal()))
+            .collect();
+        assert_eq!(
+            exports,
+            vec![
+                ("memory".to_string(), Internal::Memory(0)),
+                ("cosmwasm_global_1".to_string(), Internal::Global(1)),
+                ("cosmwasm_global_3".to_string(), Internal::Global(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn with_exported_globals_fails_for_invalid_code() {
+        match with_exported_globals(b"not wasm").unwrap_err() {
+            VmError::StaticValidationErr { msg, .. } => {
+                assert!(msg.starts_with("Wasm bytecode could not be deserialized."))
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn count_mutable_globals_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+            (import "env" "g" (global (mut i32)))
+            (global $sp (mut i32) (i32.const 1024))
+            (global $base i32 (i32.const 2048))
+            (global $counter (mut i64) (i64.const 0))
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(count_mutable_globals(&wasm).unwrap(), 2);
+
+        let wasm = wat::parse_str(r#"(module)"#).unwrap();
+        assert_eq!(count_mutable_globals(&wasm).unwrap(), 0);
+    }
+
+    #[test]
+    fn exported_global_index_works() {
+        assert_eq!(exported_global_index("cosmwasm_global_0"), Some(0));
+        assert_eq!(exported_global_index("cosmwasm_global_17"), Some(17));
+        assert_eq!(exported_global_index("cosmwasm_global_"), None);
+        assert_eq!(exported_global_index("memory"), None);
+    }
+}
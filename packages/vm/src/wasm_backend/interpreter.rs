@@ -0,0 +1,935 @@
+//! A backend that executes contracts with the wasmi interpreter instead of compiling them
+//! with Wasmer. This can be used on platforms where JIT compilation is not allowed and as
+//! a second implementation to cross-check the behaviour of the compiled backend.
+//!
+//! Metering is injected into the bytecode as calls of an imported `gas` function at the
+//! beginning of every block, using the same [`OperatorCostTable`] as the Wasmer backend.
+//! Prices are the same but the block boundaries of both metering implementations differ
+//! slightly, such that gas usage is not exactly the same for both backends.
+//!
+//! The stack depth is limited by the stack limits of the interpreter. The value stack holds
+//! the maximum stack depth in values and the number of nested calls is limited to the maximum
+//! stack depth divided by [`BASE_FRAME_WEIGHT`], which approximates the frame weights of the
+//! `StackLimiter` of the Wasmer backend.
+
+use std::cell::Cell;
+use std::convert::{TryFrom, TryInto};
+use std::mem;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use parity_wasm::elements::{Instruction, Internal, MemoryType, Module as ParityModule};
+use wasm_instrument::gas_metering::{self, MemoryGrowCost, Rules};
+use wasmer::Val;
+use wasmi::memory_units::Pages as WasmiPages;
+use wasmi::{
+    Externals, FuncInstance, FuncRef, GlobalRef, ImportsBuilder, MemoryRef, ModuleImportResolver,
+    ModuleInstance, ModuleRef, RuntimeArgs, RuntimeValue, Signature, StackRecycler, Trap, TrapKind,
+    ValueType,
+};
+
+use crate::backend::{BackendApi, Querier, Storage};
+use crate::conversion::to_u32;
+use crate::environment::Environment;
+use crate::errors::{CommunicationError, CommunicationResult, VmError, VmResult};
+#[cfg(feature = "extended_storage")]
+use crate::imports::do_db_read_many;
+#[cfg(feature = "secp256k1_batch")]
+use crate::imports::do_secp256k1_batch_verify;
+#[cfg(feature = "secp256k1_schnorr")]
+use crate::imports::do_secp256k1_schnorr_verify;
+#[cfg(feature = "sr25519")]
+use crate::imports::do_sr25519_verify;
+use crate::imports::{
+    do_abort, do_addr_canonicalize, do_addr_humanize, do_addr_validate, do_db_read, do_db_remove,
+    do_db_write, do_debug, do_ed25519_batch_verify, do_ed25519_verify, do_query_chain,
+    do_secp256k1_recover_pubkey, do_secp256k1_verify,
+};
+#[cfg(feature = "hashes")]
+use crate::imports::{do_blake2b, do_keccak256, do_ripemd160, do_sha256};
+#[cfg(feature = "bls12_381")]
+use crate::imports::{
+    do_bls12_381_aggregate_g1, do_bls12_381_aggregate_g2, do_bls12_381_aggregate_verify,
+    do_bls12_381_hash_to_g1, do_bls12_381_hash_to_g2, do_bls12_381_pairing_equality,
+};
+#[cfg(feature = "iterator")]
+use crate::imports::{do_db_next, do_db_scan};
+#[cfg(all(feature = "iterator", feature = "extended_storage"))]
+use crate::imports::{do_db_next_key, do_db_next_value};
+#[cfg(feature = "secp256r1")]
+use crate::imports::{do_secp256r1_recover_pubkey, do_secp256r1_verify};
+use crate::memory::{validate_region, Region};
+use crate::size::Size;
+use crate::static_analysis::deserialize_wasm;
+use crate::wasm::{Memory, Pages, WasmVM};
+
+use super::gatekeeper::Gatekeeper;
+use super::globals::{export_mutable_globals, exported_global_index};
+use super::operator_costs::OperatorCostTable;
+use super::stack_limiter::{BASE_FRAME_WEIGHT, DEFAULT_MAX_STACK_DEPTH};
+use super::store::limit_to_pages;
+
+/// The name of the import module containing the `gas` function called by the injected metering code
+const METERING_MODULE: &str = "metering";
+
+/// A module that was validated and instrumented for execution in the interpreter.
+///
+/// This stores the instrumented code in parity-wasm format, which can be shared across threads.
+/// Modules of wasmi itself cannot, so they are loaded from this for every instance.
+pub struct WasmiModule {
+    code: ParityModule,
+}
+
+/// Validates the given Wasm bytecode and prepares it for execution in the interpreter.
+///
+/// This applies the same operator restrictions as the Gatekeeper middleware of the Wasmer
+/// backend and injects metering using `cost_table`. The memory limit (in bytes) is set as
+/// the maximum size of the contract's memory.
+pub fn compile_interpreted(
+    code: &[u8],
+    memory_limit: Option<Size>,
+    cost_table: &OperatorCostTable,
+) -> VmResult<WasmiModule> {
+    Gatekeeper::default()
+        .check_code(code)
+        .map_err(|msg| VmError::compile_err(format!("Could not compile: {}", msg)))?;
+
+    let mut module = deserialize_wasm(code)?;
+    export_mutable_globals(&mut module);
+    if let Some(limit) = memory_limit {
+        limit_memories(&mut module, limit_to_pages(limit).0)?;
+    }
+    if u32::try_from(cost_table.memory_grow_per_page).is_err() {
+        return Err(VmError::compile_err(
+            "Could not inject metering. Operator prices must not exceed u32::MAX.",
+        ));
+    }
+    let module = gas_metering::inject(module, &MeteringRules(*cost_table), METERING_MODULE)
+        .map_err(|_| {
+            VmError::compile_err(
+                "Could not inject metering. Operator prices must not exceed u32::MAX.",
+            )
+        })?;
+
+    // Validates the instrumented module
+    wasmi::Module::from_parity_wasm_module(module.clone())
+        .map_err(|e| VmError::compile_err(format!("Could not compile: {}", e)))?;
+    Ok(WasmiModule { code: module })
+}
+
+/// Sets the given limit (in pages) as the maximum of all memories that do not have a lower
+/// maximum. This is equivalent to `LimitingTunables` for the Wasmer backend.
+fn limit_memories(module: &mut ParityModule, limit: u32) -> VmResult<()> {
+    if let Some(section) = module.memory_section_mut() {
+        for memory in section.entries_mut() {
+            let limits = memory.limits();
+            if limits.initial() > limit {
+                return Err(VmError::compile_err(
+                    "Minimum exceeds the allowed memory limit",
+                ));
+            }
+            let maximum = match limits.maximum() {
+                Some(maximum) if maximum > limit => {
+                    return Err(VmError::compile_err(
+                        "Maximum exceeds the allowed memory limit",
+                    ))
+                }
+                Some(maximum) => maximum,
+                None => limit,
+            };
+            *memory = MemoryType::new(limits.initial(), Some(maximum));
+        }
+    }
+    Ok(())
+}
+
+struct MeteringRules(OperatorCostTable);
+
+impl Rules for MeteringRules {
+    fn instruction_cost(&self, instruction: &Instruction) -> Option<u32> {
+        self.0.instruction_cost(instruction).try_into().ok()
+    }
+
+    fn memory_grow_cost(&self) -> MemoryGrowCost {
+        // memory.grow is priced as an operator like in the Wasmer backend, plus a fee per page
+        // if set. Prices exceeding u32::MAX are rejected by `compile_interpreted`.
+        match self
+            .0
+            .memory_grow_per_page
+            .try_into()
+            .ok()
+            .and_then(NonZeroU32::new)
+        {
+            Some(price) => MemoryGrowCost::Linear(price),
+            None => MemoryGrowCost::Free,
+        }
+    }
+}
+
+/// An instance of a contract in the interpreter.
+///
+/// In contrast to Wasmer instances this is not `Send`, i.e. it must be used on the thread
+/// that created it.
+pub struct WasmiInstance {
+    module: Arc<WasmiModule>,
+    instance: ModuleRef,
+    memory: MemoryRef,
+    imports: Arc<dyn HostFunctions>,
+    gas_left: Cell<u64>,
+    /// True iff execution was aborted by the metering because it ran out of gas
+    gas_exhausted: Cell<bool>,
+    max_stack_depth: Cell<u32>,
+}
+
+impl WasmiInstance {
+    /// Instantiates the module with the imports of the given environment.
+    ///
+    /// The instance still needs to be linked to the environment via `Environment::set_wasm_instance`.
+    pub(crate) fn new<A, S, Q>(
+        module: Arc<WasmiModule>,
+        env: Environment<A, S, Q, WasmiInstance>,
+    ) -> VmResult<Self>
+    where
+        A: BackendApi + 'static,
+        S: Storage + 'static,
+        Q: Querier + 'static,
+    {
+        let imports = Arc::new(WasmiImports { env });
+        let resolver = ImportsBuilder::new()
+            .with_resolver("env", imports.as_ref())
+            .with_resolver(METERING_MODULE, &MeteringResolver);
+        let loaded = wasmi::Module::from_parity_wasm_module(module.code.clone())
+            .map_err(|e| VmError::instantiation_err(format!("Error loading module: {}", e)))?;
+        let instance = ModuleInstance::new(&loaded, &resolver)
+            .and_then(|not_started| {
+                not_started
+                    .run_start(&mut HostExternals(imports.as_ref()))
+                    .map_err(wasmi::Error::Trap)
+            })
+            .map_err(|original| {
+                VmError::instantiation_err(format!("Error instantiating module: {:?}", original))
+            })?;
+
+        // Every contract in CosmWasm must have exactly one exported memory.
+        // This is ensured by `check_wasm`/`check_wasm_memories`.
+        let memory = module
+            .code
+            .export_section()
+            .and_then(|section| {
+                section
+                    .entries()
+                    .iter()
+                    .find(|entry| matches!(entry.internal(), Internal::Memory(_)))
+            })
+            .and_then(|entry| instance.export_by_name(entry.field()))
+            .and_then(|export| export.as_memory().cloned())
+            .ok_or_else(|| {
+                VmError::instantiation_err("A contract must have exactly one exported memory.")
+            })?;
+
+        Ok(WasmiInstance {
+            module,
+            instance,
+            memory,
+            imports,
+            gas_left: Cell::new(0),
+            gas_exhausted: Cell::new(false),
+            max_stack_depth: Cell::new(DEFAULT_MAX_STACK_DEPTH),
+        })
+    }
+
+    /// The mutable globals of the contract ordered by index, see `export_mutable_globals`
+    fn mutable_globals(&self) -> Vec<GlobalRef> {
+        let mut globals: Vec<(u32, GlobalRef)> = self
+            .module
+            .code
+            .export_section()
+            .map(|section| {
+                section
+                    .entries()
+                    .iter()
+                    .filter_map(|entry| {
+                        let index = exported_global_index(entry.field())?;
+                        let global = self.instance.export_by_name(entry.field())?;
+                        Some((index, global.as_global()?.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        globals.sort_by_key(|(index, _)| *index);
+        globals.into_iter().map(|(_, global)| global).collect()
+    }
+
+    /// Called by the injected metering code at the beginning of every block
+    fn charge_gas(&self, amount: u64) -> VmResult<()> {
+        let gas_left = self.gas_left.get();
+        if amount > gas_left {
+            self.gas_left.set(0);
+            self.gas_exhausted.set(true);
+            Err(VmError::gas_depletion())
+        } else {
+            self.gas_left.set(gas_left - amount);
+            Ok(())
+        }
+    }
+}
+
+impl WasmVM for WasmiInstance {
+    type ExportInfo = ParityModule;
+    type Memory = MemoryRef;
+
+    fn module(&self) -> &Self::ExportInfo {
+        &self.module.code
+    }
+
+    fn memory(&self) -> Self::Memory {
+        self.memory.clone()
+    }
+
+    fn get_gas_left(&self) -> u64 {
+        if self.gas_exhausted.get() {
+            0
+        } else {
+            self.gas_left.get()
+        }
+    }
+
+    fn set_gas_left(&self, new: u64) {
+        self.gas_left.set(new);
+        self.gas_exhausted.set(false);
+    }
+
+    fn call_function(&self, name: &str, args: &[Val]) -> VmResult<Box<[Val]>> {
+        let args = args
+            .iter()
+            .map(|arg| match arg {
+                Val::I32(value) => Ok(RuntimeValue::I32(*value)),
+                Val::I64(value) => Ok(RuntimeValue::I64(*value)),
+                _ => Err(VmError::generic_err(format!(
+                    "Unsupported argument type for function {}",
+                    name
+                ))),
+            })
+            .collect::<VmResult<Vec<_>>>()?;
+
+        let mut externals = HostExternals(self.imports.as_ref());
+        let max_stack_depth = self.max_stack_depth.get();
+        // Every value on the stack of the interpreter takes 8 bytes. Like in the `StackLimiter`,
+        // the frame of the called export is not counted.
+        let mut stack = StackRecycler::with_limits(
+            max_stack_depth as usize * mem::size_of::<u64>(),
+            (max_stack_depth / BASE_FRAME_WEIGHT) as usize + 1,
+        );
+        match self
+            .instance
+            .invoke_export_with_stack(name, &args, &mut externals, &mut stack)
+        {
+            Ok(result) => Ok(result
+                .into_iter()
+                .map(|value| match value {
+                    RuntimeValue::I32(value) => Ok(Val::I32(value)),
+                    RuntimeValue::I64(value) => Ok(Val::I64(value)),
+                    _ => Err(VmError::generic_err(format!(
+                        "Unsupported return type of function {}",
+                        name
+                    ))),
+                })
+                .collect::<VmResult<Vec<_>>>()?
+                .into_boxed_slice()),
+            Err(_) if self.gas_exhausted.get() => Err(VmError::gas_depletion()),
+            Err(wasmi::Error::Trap(trap)) if matches!(trap.kind(), TrapKind::StackOverflow) => {
+                Err(VmError::stack_depth_exceeded(max_stack_depth))
+            }
+            Err(original) => Err(VmError::from(original)),
+        }
+    }
+
+    fn globals(&self) -> VmResult<Vec<Val>> {
+        self.mutable_globals()
+            .iter()
+            .map(|global| match global.get() {
+                RuntimeValue::I32(value) => Ok(Val::I32(value)),
+                RuntimeValue::I64(value) => Ok(Val::I64(value)),
+                _ => Err(VmError::generic_err("Unsupported global type")),
+            })
+            .collect()
+    }
+
+    fn set_globals(&self, values: &[Val]) -> VmResult<()> {
+        let globals = self.mutable_globals();
+        if globals.len() != values.len() {
+            return Err(VmError::generic_err(format!(
+                "Expected {} global values but got {}",
+                globals.len(),
+                values.len()
+            )));
+        }
+        for (global, value) in globals.iter().zip(values) {
+            let value = match value {
+                Val::I32(value) => RuntimeValue::I32(*value),
+                Val::I64(value) => RuntimeValue::I64(*value),
+                _ => return Err(VmError::generic_err("Unsupported global type")),
+            };
+            global.set(value)?;
+        }
+        Ok(())
+    }
+
+    fn max_stack_depth(&self) -> u32 {
+        self.max_stack_depth.get()
+    }
+
+    fn set_max_stack_depth(&self, max_depth: u32) {
+        self.max_stack_depth.set(max_depth);
+    }
+}
+
+impl Memory for MemoryRef {
+    type Pages = WasmiPages;
+
+    fn size(&self) -> Self::Pages {
+        self.current_size()
+    }
+
+    /// maybe_read_region is like read_region, but gracefully handles null pointer (0) by returning None
+    /// meant to be used where the argument is optional (like scan)
+    fn maybe_read_region(&self, ptr: u32, max_length: usize) -> VmResult<Option<Vec<u8>>> {
+        if ptr == 0 {
+            Ok(None)
+        } else {
+            self.read_region(ptr, max_length).map(Some)
+        }
+    }
+
+    fn read_region(&self, ptr: u32, max_length: usize) -> VmResult<Vec<u8>> {
+        let region = self.get_region(ptr)?;
+
+        if region.length > to_u32(max_length)? {
+            return Err(CommunicationError::region_length_too_big(
+                region.length as usize,
+                max_length,
+            )
+            .into());
+        }
+
+        self.get(region.offset, region.length as usize)
+            .map_err(|_| region_out_of_bounds(self, &region).into())
+    }
+
+    fn get_region(&self, ptr: u32) -> CommunicationResult<Region> {
+        let mut data = [0u8; mem::size_of::<Region>()];
+        self.get_into(ptr, &mut data).map_err(|_| {
+            CommunicationError::deref_err(ptr, "Could not dereference this pointer to a Region")
+        })?;
+        // Regions are stored as three little endian u32 values (see `Region`)
+        let field =
+            |index: usize| u32::from_le_bytes(data[index * 4..(index + 1) * 4].try_into().unwrap());
+        let region = Region {
+            offset: field(0),
+            capacity: field(1),
+            length: field(2),
+        };
+        validate_region(&region)?;
+        Ok(region)
+    }
+
+    fn write_region(&self, ptr: u32, data: &[u8]) -> VmResult<()> {
+        let mut region = self.get_region(ptr)?;
+
+        let region_capacity = region.capacity as usize;
+        if data.len() > region_capacity {
+            return Err(CommunicationError::region_too_small(region_capacity, data.len()).into());
+        }
+        self.set(region.offset, data)
+            .map_err(|_| region_out_of_bounds(self, &region))?;
+        region.length = data.len() as u32;
+        self.set_region(ptr, region)?;
+        Ok(())
+    }
+
+    fn read_all(&self) -> Vec<u8> {
+        let size = self.current_size().0 * WasmiPages::BYTE_SIZE.0;
+        self.get(0, size)
+            .expect("Reading the current memory size is in bounds")
+    }
+
+    fn write_all(&self, data: &[u8]) -> VmResult<()> {
+        let size = self.current_size().0 * WasmiPages::BYTE_SIZE.0;
+        if data.len() != size {
+            return Err(VmError::generic_err(format!(
+                "Cannot write {} bytes into a memory of {} bytes",
+                data.len(),
+                size
+            )));
+        }
+        self.set(0, data)?;
+        Ok(())
+    }
+
+    fn set_region(&self, ptr: u32, data: Region) -> CommunicationResult<()> {
+        let mut bytes = Vec::with_capacity(mem::size_of::<Region>());
+        for field in [data.offset, data.capacity, data.length] {
+            bytes.extend_from_slice(&field.to_le_bytes());
+        }
+        self.set(ptr, &bytes).map_err(|_| {
+            CommunicationError::deref_err(ptr, "Could not dereference this pointer to a Region")
+        })
+    }
+}
+
+fn region_out_of_bounds(memory: &MemoryRef, region: &Region) -> CommunicationError {
+    CommunicationError::deref_err(region.offset, format!(
+        "Tried to access memory of region {:?} in wasm memory of size {} bytes. This typically happens when the given Region pointer does not point to a proper Region struct.",
+        region,
+        memory.current_size().0 * wasmi::memory_units::Pages::BYTE_SIZE.0
+    ))
+}
+
+impl Pages for WasmiPages {
+    fn inner(&self) -> u32 {
+        self.0 as u32
+    }
+}
+
+// Indices of the host functions. Those are used to dispatch calls from the interpreter.
+const GAS: usize = 0;
+const DB_READ: usize = 1;
+const DB_WRITE: usize = 2;
+const DB_REMOVE: usize = 3;
+const ADDR_VALIDATE: usize = 4;
+const ADDR_CANONICALIZE: usize = 5;
+const ADDR_HUMANIZE: usize = 6;
+const SECP256K1_VERIFY: usize = 7;
+const SECP256K1_RECOVER_PUBKEY: usize = 8;
+const ED25519_VERIFY: usize = 9;
+const ED25519_BATCH_VERIFY: usize = 10;
+const DEBUG: usize = 11;
+const QUERY_CHAIN: usize = 12;
+#[cfg(feature = "iterator")]
+const DB_SCAN: usize = 13;
+#[cfg(feature = "iterator")]
+const DB_NEXT: usize = 14;
+#[cfg(feature = "extended_storage")]
+const DB_READ_MANY: usize = 15;
+#[cfg(all(feature = "iterator", feature = "extended_storage"))]
+const DB_NEXT_KEY: usize = 16;
+#[cfg(all(feature = "iterator", feature = "extended_storage"))]
+const DB_NEXT_VALUE: usize = 17;
+#[cfg(feature = "hashes")]
+const SHA256: usize = 18;
+#[cfg(feature = "hashes")]
+const KECCAK256: usize = 19;
+#[cfg(feature = "hashes")]
+const RIPEMD160: usize = 20;
+#[cfg(feature = "hashes")]
+const BLAKE2B: usize = 21;
+#[cfg(feature = "secp256r1")]
+const SECP256R1_VERIFY: usize = 22;
+#[cfg(feature = "secp256r1")]
+const SECP256R1_RECOVER_PUBKEY: usize = 23;
+#[cfg(feature = "bls12_381")]
+const BLS12_381_AGGREGATE_G1: usize = 24;
+#[cfg(feature = "bls12_381")]
+const BLS12_381_AGGREGATE_G2: usize = 25;
+#[cfg(feature = "bls12_381")]
+const BLS12_381_PAIRING_EQUALITY: usize = 26;
+#[cfg(feature = "bls12_381")]
+const BLS12_381_AGGREGATE_VERIFY: usize = 27;
+#[cfg(feature = "bls12_381")]
+const BLS12_381_HASH_TO_G1: usize = 28;
+#[cfg(feature = "bls12_381")]
+const BLS12_381_HASH_TO_G2: usize = 29;
+#[cfg(feature = "sr25519")]
+const SR25519_VERIFY: usize = 30;
+#[cfg(feature = "secp256k1_schnorr")]
+const SECP256K1_SCHNORR_VERIFY: usize = 31;
+#[cfg(feature = "secp256k1_batch")]
+const SECP256K1_BATCH_VERIFY: usize = 32;
+const ABORT: usize = 33;
+
+/// Calls of imported functions, independent of the environment's type parameters
+trait HostFunctions {
+    fn invoke(&self, index: usize, args: RuntimeArgs) -> Result<Option<RuntimeValue>, Trap>;
+}
+
+/// Adapter to pass [`HostFunctions`] into the interpreter
+struct HostExternals<'a>(&'a dyn HostFunctions);
+
+impl Externals for HostExternals<'_> {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        self.0.invoke(index, args)
+    }
+}
+
+/// The `env` imports. Those are the same as for the Wasmer backend (see `Instance::from_module`).
+struct WasmiImports<A: BackendApi, S: Storage, Q: Querier> {
+    env: Environment<A, S, Q, WasmiInstance>,
+}
+
+impl<A, S, Q> ModuleImportResolver for WasmiImports<A, S, Q>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+{
+    fn resolve_func(
+        &self,
+        field_name: &str,
+        signature: &Signature,
+    ) -> Result<FuncRef, wasmi::Error> {
+        use ValueType::{I32, I64};
+
+        let (index, params, result): (usize, &[ValueType], Option<ValueType>) = match field_name {
+            "db_read" => (DB_READ, &[I32], Some(I32)),
+            "db_write" => (DB_WRITE, &[I32, I32], None),
+            "db_remove" => (DB_REMOVE, &[I32], None),
+            "addr_validate" => (ADDR_VALIDATE, &[I32], Some(I32)),
+            "addr_canonicalize" => (ADDR_CANONICALIZE, &[I32, I32], Some(I32)),
+            "addr_humanize" => (ADDR_HUMANIZE, &[I32, I32], Some(I32)),
+            "secp256k1_verify" => (SECP256K1_VERIFY, &[I32, I32, I32], Some(I32)),
+            "secp256k1_recover_pubkey" => (SECP256K1_RECOVER_PUBKEY, &[I32, I32, I32], Some(I64)),
+            "ed25519_verify" => (ED25519_VERIFY, &[I32, I32, I32], Some(I32)),
+            "ed25519_batch_verify" => (ED25519_BATCH_VERIFY, &[I32, I32, I32], Some(I32)),
+            "debug" => (DEBUG, &[I32], None),
+            "abort" => (ABORT, &[I32], None),
+            "query_chain" => (QUERY_CHAIN, &[I32], Some(I32)),
+            #[cfg(feature = "iterator")]
+            "db_scan" => (DB_SCAN, &[I32, I32, I32], Some(I32)),
+            #[cfg(feature = "iterator")]
+            "db_next" => (DB_NEXT, &[I32], Some(I32)),
+            #[cfg(feature = "extended_storage")]
+            "db_read_many" => (DB_READ_MANY, &[I32], Some(I32)),
+            #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+            "db_next_key" => (DB_NEXT_KEY, &[I32], Some(I32)),
+            #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+            "db_next_value" => (DB_NEXT_VALUE, &[I32], Some(I32)),
+            #[cfg(feature = "secp256r1")]
+            "secp256r1_verify" => (SECP256R1_VERIFY, &[I32, I32, I32], Some(I32)),
+            #[cfg(feature = "secp256r1")]
+            "secp256r1_recover_pubkey" => (SECP256R1_RECOVER_PUBKEY, &[I32, I32, I32], Some(I64)),
+            #[cfg(feature = "hashes")]
+            "sha256" => (SHA256, &[I32], Some(I32)),
+            #[cfg(feature = "hashes")]
+            "keccak256" => (KECCAK256, &[I32], Some(I32)),
+            #[cfg(feature = "hashes")]
+            "ripemd160" => (RIPEMD160, &[I32], Some(I32)),
+            #[cfg(feature = "hashes")]
+            "blake2b" => (BLAKE2B, &[I32], Some(I32)),
+            #[cfg(feature = "bls12_381")]
+            "bls12_381_aggregate_g1" => (BLS12_381_AGGREGATE_G1, &[I32], Some(I64)),
+            #[cfg(feature = "bls12_381")]
+            "bls12_381_aggregate_g2" => (BLS12_381_AGGREGATE_G2, &[I32], Some(I64)),
+            #[cfg(feature = "bls12_381")]
+            "bls12_381_pairing_equality" => {
+                (BLS12_381_PAIRING_EQUALITY, &[I32, I32, I32, I32], Some(I32))
+            }
+            #[cfg(feature = "bls12_381")]
+            "bls12_381_aggregate_verify" => {
+                (BLS12_381_AGGREGATE_VERIFY, &[I32, I32, I32, I32], Some(I32))
+            }
+            #[cfg(feature = "bls12_381")]
+            "bls12_381_hash_to_g1" => (BLS12_381_HASH_TO_G1, &[I32, I32], Some(I32)),
+            #[cfg(feature = "bls12_381")]
+            "bls12_381_hash_to_g2" => (BLS12_381_HASH_TO_G2, &[I32, I32], Some(I32)),
+            #[cfg(feature = "sr25519")]
+            "sr25519_verify" => (SR25519_VERIFY, &[I32, I32, I32], Some(I32)),
+            #[cfg(feature = "secp256k1_schnorr")]
+            "secp256k1_schnorr_verify" => (SECP256K1_SCHNORR_VERIFY, &[I32, I32, I32], Some(I32)),
+            #[cfg(feature = "secp256k1_batch")]
+            "secp256k1_batch_verify" => (SECP256K1_BATCH_VERIFY, &[I32, I32, I32], Some(I32)),
+            _ => {
+                return Err(wasmi::Error::Instantiation(format!(
+                    "Unknown import env.{}",
+                    field_name
+                )))
+            }
+        };
+        resolve_host_function(field_name, signature, index, params, result)
+    }
+}
+
+impl<A, S, Q> HostFunctions for WasmiImports<A, S, Q>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+{
+    fn invoke(&self, index: usize, args: RuntimeArgs) -> Result<Option<RuntimeValue>, Trap> {
+        let env = &self.env;
+        let result = match index {
+            GAS => {
+                let amount: u32 = args.nth_checked(0)?;
+                env.with_wasm_instance(|instance| instance.charge_gas(amount.into()))
+                    .map(|_| None)
+            }
+            DB_READ => do_db_read(env, args.nth_checked(0)?).map(i32_result),
+            DB_WRITE => do_db_write(env, args.nth_checked(0)?, args.nth_checked(1)?).map(|_| None),
+            DB_REMOVE => do_db_remove(env, args.nth_checked(0)?).map(|_| None),
+            ADDR_VALIDATE => do_addr_validate(env, args.nth_checked(0)?).map(i32_result),
+            ADDR_CANONICALIZE => {
+                do_addr_canonicalize(env, args.nth_checked(0)?, args.nth_checked(1)?)
+                    .map(i32_result)
+            }
+            ADDR_HUMANIZE => {
+                do_addr_humanize(env, args.nth_checked(0)?, args.nth_checked(1)?).map(i32_result)
+            }
+            SECP256K1_VERIFY => do_secp256k1_verify(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+            )
+            .map(i32_result),
+            SECP256K1_RECOVER_PUBKEY => do_secp256k1_recover_pubkey(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+            )
+            .map(i64_result),
+            ED25519_VERIFY => do_ed25519_verify(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+            )
+            .map(i32_result),
+            ED25519_BATCH_VERIFY => do_ed25519_batch_verify(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+            )
+            .map(i32_result),
+            DEBUG => do_debug(env, args.nth_checked(0)?).map(|_| None),
+            ABORT => do_abort(env, args.nth_checked(0)?).map(|_| None),
+            QUERY_CHAIN => do_query_chain(env, args.nth_checked(0)?).map(i32_result),
+            #[cfg(feature = "iterator")]
+            DB_SCAN => do_db_scan(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+            )
+            .map(i32_result),
+            #[cfg(feature = "iterator")]
+            DB_NEXT => do_db_next(env, args.nth_checked(0)?).map(i32_result),
+            #[cfg(feature = "extended_storage")]
+            DB_READ_MANY => do_db_read_many(env, args.nth_checked(0)?).map(i32_result),
+            #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+            DB_NEXT_KEY => do_db_next_key(env, args.nth_checked(0)?).map(i32_result),
+            #[cfg(all(feature = "iterator", feature = "extended_storage"))]
+            DB_NEXT_VALUE => do_db_next_value(env, args.nth_checked(0)?).map(i32_result),
+            #[cfg(feature = "secp256r1")]
+            SECP256R1_VERIFY => do_secp256r1_verify(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+            )
+            .map(i32_result),
+            #[cfg(feature = "secp256r1")]
+            SECP256R1_RECOVER_PUBKEY => do_secp256r1_recover_pubkey(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+            )
+            .map(i64_result),
+            #[cfg(feature = "hashes")]
+            SHA256 => do_sha256(env, args.nth_checked(0)?).map(i32_result),
+            #[cfg(feature = "hashes")]
+            KECCAK256 => do_keccak256(env, args.nth_checked(0)?).map(i32_result),
+            #[cfg(feature = "hashes")]
+            RIPEMD160 => do_ripemd160(env, args.nth_checked(0)?).map(i32_result),
+            #[cfg(feature = "hashes")]
+            BLAKE2B => do_blake2b(env, args.nth_checked(0)?).map(i32_result),
+            #[cfg(feature = "bls12_381")]
+            BLS12_381_AGGREGATE_G1 => {
+                do_bls12_381_aggregate_g1(env, args.nth_checked(0)?).map(i64_result)
+            }
+            #[cfg(feature = "bls12_381")]
+            BLS12_381_AGGREGATE_G2 => {
+                do_bls12_381_aggregate_g2(env, args.nth_checked(0)?).map(i64_result)
+            }
+            #[cfg(feature = "bls12_381")]
+            BLS12_381_PAIRING_EQUALITY => do_bls12_381_pairing_equality(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+                args.nth_checked(3)?,
+            )
+            .map(i32_result),
+            #[cfg(feature = "bls12_381")]
+            BLS12_381_AGGREGATE_VERIFY => do_bls12_381_aggregate_verify(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+                args.nth_checked(3)?,
+            )
+            .map(i32_result),
+            #[cfg(feature = "bls12_381")]
+            BLS12_381_HASH_TO_G1 => {
+                do_bls12_381_hash_to_g1(env, args.nth_checked(0)?, args.nth_checked(1)?)
+                    .map(i32_result)
+            }
+            #[cfg(feature = "bls12_381")]
+            BLS12_381_HASH_TO_G2 => {
+                do_bls12_381_hash_to_g2(env, args.nth_checked(0)?, args.nth_checked(1)?)
+                    .map(i32_result)
+            }
+            #[cfg(feature = "sr25519")]
+            SR25519_VERIFY => do_sr25519_verify(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+            )
+            .map(i32_result),
+            #[cfg(feature = "secp256k1_schnorr")]
+            SECP256K1_SCHNORR_VERIFY => do_secp256k1_schnorr_verify(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+            )
+            .map(i32_result),
+            #[cfg(feature = "secp256k1_batch")]
+            SECP256K1_BATCH_VERIFY => do_secp256k1_batch_verify(
+                env,
+                args.nth_checked(0)?,
+                args.nth_checked(1)?,
+                args.nth_checked(2)?,
+            )
+            .map(i32_result),
+            _ => return Err(Trap::new(TrapKind::UnexpectedSignature)),
+        };
+        result.map_err(Trap::from)
+    }
+}
+
+/// Resolves the `gas` function called by the injected metering code
+struct MeteringResolver;
+
+impl ModuleImportResolver for MeteringResolver {
+    fn resolve_func(
+        &self,
+        field_name: &str,
+        signature: &Signature,
+    ) -> Result<FuncRef, wasmi::Error> {
+        match field_name {
+            "gas" => resolve_host_function(field_name, signature, GAS, &[ValueType::I32], None),
+            _ => Err(wasmi::Error::Instantiation(format!(
+                "Unknown import {}.{}",
+                METERING_MODULE, field_name
+            ))),
+        }
+    }
+}
+
+fn resolve_host_function(
+    field_name: &str,
+    signature: &Signature,
+    index: usize,
+    params: &[ValueType],
+    result: Option<ValueType>,
+) -> Result<FuncRef, wasmi::Error> {
+    if signature.params() != params || signature.return_type() != result {
+        return Err(wasmi::Error::Instantiation(format!(
+            "Incompatible signature of import {}: {:?}",
+            field_name, signature
+        )));
+    }
+    Ok(FuncInstance::alloc_host(signature.clone(), index))
+}
+
+fn i32_result(value: u32) -> Option<RuntimeValue> {
+    Some(RuntimeValue::I32(value as i32))
+}
+
+fn i64_result(value: u64) -> Option<RuntimeValue> {
+    Some(RuntimeValue::I64(value as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static CONTRACT: &[u8] = include_bytes!("../../testdata/hackatom.wasm");
+    static FLOATY: &[u8] = include_bytes!("../../testdata/floaty.wasm");
+
+    #[test]
+    fn compile_interpreted_works() {
+        let module = compile_interpreted(CONTRACT, None, &OperatorCostTable::default()).unwrap();
+        // metering is imported
+        let imports = module.code.import_section().unwrap();
+        assert!(imports
+            .entries()
+            .iter()
+            .any(|entry| entry.module() == METERING_MODULE && entry.field() == "gas"));
+    }
+
+    #[test]
+    fn compile_interpreted_applies_gatekeeper() {
+        let err = compile_interpreted(FLOATY, None, &OperatorCostTable::default()).unwrap_err();
+        assert!(err.to_string().contains("Float operator detected:"));
+    }
+
+    #[test]
+    fn compile_interpreted_limits_memory() {
+        let wasm = wat::parse_str(r#"(module (memory (export "memory") 3))"#).unwrap();
+        let module =
+            compile_interpreted(&wasm, Some(Size::kibi(640)), &OperatorCostTable::default())
+                .unwrap();
+        let memories = module.code.memory_section().unwrap().entries();
+        assert_eq!(memories[0].limits().initial(), 3);
+        assert_eq!(memories[0].limits().maximum(), Some(10));
+
+        let wasm = wat::parse_str(r#"(module (memory (export "memory") 11))"#).unwrap();
+        let err = compile_interpreted(&wasm, Some(Size::kibi(640)), &OperatorCostTable::default())
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Minimum exceeds the allowed memory limit"));
+
+        let wasm = wat::parse_str(r#"(module (memory (export "memory") 3 12))"#).unwrap();
+        let err = compile_interpreted(&wasm, Some(Size::kibi(640)), &OperatorCostTable::default())
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Maximum exceeds the allowed memory limit"));
+    }
+
+    #[test]
+    fn compile_interpreted_checks_memory_grow_price() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "grow") (param i32) (result i32)
+                    local.get 0
+                    memory.grow)
+            )"#,
+        )
+        .unwrap();
+        let cost_table = OperatorCostTable {
+            memory_grow_per_page: 1000,
+            ..OperatorCostTable::default()
+        };
+        compile_interpreted(&wasm, None, &cost_table).unwrap();
+
+        let cost_table = OperatorCostTable {
+            memory_grow_per_page: u32::MAX as u64 + 1,
+            ..OperatorCostTable::default()
+        };
+        let err = compile_interpreted(&wasm, None, &cost_table).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Operator prices must not exceed u32::MAX"));
+    }
+}
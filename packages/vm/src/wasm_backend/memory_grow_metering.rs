@@ -0,0 +1,264 @@
+use std::sync::Mutex;
+
+use loupe::{MemoryUsage, MemoryUsageTracker};
+use wasmer::wasmparser::{Operator, Type as WpType, TypeOrFuncType as WpTypeOrFuncType};
+use wasmer::{
+    ExportIndex, FunctionMiddleware, GlobalInit, GlobalType, LocalFunctionIndex, MiddlewareError,
+    MiddlewareReaderState, ModuleMiddleware, Mutability, Type,
+};
+use wasmer_types::{GlobalIndex, ModuleInfo};
+
+/// The globals exported by Wasmer's `Metering` middleware
+const REMAINING_POINTS_EXPORT: &str = "wasmer_metering_remaining_points";
+const POINTS_EXHAUSTED_EXPORT: &str = "wasmer_metering_points_exhausted";
+
+#[derive(Debug, Clone, Copy)]
+struct MemoryGrowMeteringGlobalIndexes {
+    remaining_points: GlobalIndex,
+    points_exhausted: GlobalIndex,
+    /// Holds the number of requested pages while the price is calculated
+    delta: GlobalIndex,
+}
+
+/// A middleware that charges a fee per requested page before every `memory.grow`.
+///
+/// The fee is subtracted from the remaining points of Wasmer's `Metering` middleware,
+/// which must come before this one. If the remaining points do not cover the fee, execution
+/// traps and the points are marked as exhausted, just like for any other operator. The fee
+/// is charged for the requested pages, no matter if growing succeeds or not.
+///
+/// With a price of 0 the code is not changed at all.
+///
+/// Like Wasmer's `Metering`, an instance of the middleware can only be used for one module.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct MemoryGrowMetering {
+    price_per_page: u64,
+    global_indexes: Mutex<Option<MemoryGrowMeteringGlobalIndexes>>,
+}
+
+impl MemoryGrowMetering {
+    pub fn new(price_per_page: u64) -> Self {
+        Self {
+            price_per_page,
+            global_indexes: Mutex::new(None),
+        }
+    }
+}
+
+impl MemoryUsage for MemoryGrowMetering {
+    fn size_of_val(&self, _: &mut dyn MemoryUsageTracker) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+impl ModuleMiddleware for MemoryGrowMetering {
+    /// Generates a `FunctionMiddleware` for a given function.
+    fn generate_function_middleware(&self, _: LocalFunctionIndex) -> Box<dyn FunctionMiddleware> {
+        Box::new(FunctionMemoryGrowMetering {
+            price_per_page: self.price_per_page,
+            global_indexes: *self.global_indexes.lock().unwrap(),
+        })
+    }
+
+    /// Looks up the globals of the metering and adds a global for the requested pages.
+    fn transform_module_info(&self, module_info: &mut ModuleInfo) {
+        let mut global_indexes = self.global_indexes.lock().unwrap();
+        if global_indexes.is_some() {
+            panic!("MemoryGrowMetering::transform_module_info: Attempting to use a `MemoryGrowMetering` middleware from multiple modules.");
+        }
+        if self.price_per_page == 0 {
+            return;
+        }
+
+        let remaining_points = metering_global(module_info, REMAINING_POINTS_EXPORT);
+        let points_exhausted = metering_global(module_info, POINTS_EXHAUSTED_EXPORT);
+
+        let delta = module_info
+            .globals
+            .push(GlobalType::new(Type::I32, Mutability::Var));
+        module_info
+            .global_initializers
+            .push(GlobalInit::I32Const(0));
+
+        *global_indexes = Some(MemoryGrowMeteringGlobalIndexes {
+            remaining_points,
+            points_exhausted,
+            delta,
+        });
+    }
+}
+
+/// Returns the index of a global exported by the `Metering` middleware
+fn metering_global(module_info: &ModuleInfo, name: &str) -> GlobalIndex {
+    match module_info.exports.get(name) {
+        Some(ExportIndex::Global(index)) => *index,
+        _ => panic!(
+            "Global {} not found. The `Metering` middleware must come before `MemoryGrowMetering`.",
+            name
+        ),
+    }
+}
+
+#[derive(Debug)]
+struct FunctionMemoryGrowMetering {
+    price_per_page: u64,
+    /// `None` iff the price is 0
+    global_indexes: Option<MemoryGrowMeteringGlobalIndexes>,
+}
+
+impl FunctionMiddleware for FunctionMemoryGrowMetering {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        let global_indexes = match (&operator, self.global_indexes) {
+            (Operator::MemoryGrow { .. }, Some(global_indexes)) => global_indexes,
+            _ => {
+                state.push_operator(operator);
+                return Ok(());
+            }
+        };
+        let remaining_points = global_indexes.remaining_points.as_u32();
+        let points_exhausted = global_indexes.points_exhausted.as_u32();
+        let delta = global_indexes.delta.as_u32();
+        // The bits are interpreted as unsigned by the operators below
+        let price = self.price_per_page as i64;
+
+        state.extend(&[
+            // delta = the requested pages on top of the stack
+            Operator::GlobalSet {
+                global_index: delta,
+            },
+            // if remaining / price < delta { trap }
+            // This is remaining < delta * price without overflowing the multiplication.
+            Operator::GlobalGet {
+                global_index: remaining_points,
+            },
+            Operator::I64Const { value: price },
+            Operator::I64DivU,
+            Operator::GlobalGet {
+                global_index: delta,
+            },
+            Operator::I64ExtendI32U,
+            Operator::I64LtU,
+            Operator::If {
+                ty: WpTypeOrFuncType::Type(WpType::EmptyBlockType),
+            },
+            Operator::I32Const { value: 1 },
+            Operator::GlobalSet {
+                global_index: points_exhausted,
+            },
+            Operator::Unreachable,
+            Operator::End,
+            // remaining -= delta * price
+            Operator::GlobalGet {
+                global_index: remaining_points,
+            },
+            Operator::GlobalGet {
+                global_index: delta,
+            },
+            Operator::I64ExtendI32U,
+            Operator::I64Const { value: price },
+            Operator::I64Mul,
+            Operator::I64Sub,
+            Operator::GlobalSet {
+                global_index: remaining_points,
+            },
+            // restore the argument of memory.grow
+            Operator::GlobalGet {
+                global_index: delta,
+            },
+        ]);
+        state.push_operator(operator);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use wasmer::{CompilerConfig, Cranelift, ImportObject, Instance, Module, Store, Universal};
+    use wasmer_middlewares::metering::{
+        get_remaining_points, set_remaining_points, MeteringPoints,
+    };
+    use wasmer_middlewares::Metering;
+
+    /// Grows memory by the given number of pages and returns the previous size
+    const GROW_WAT: &str = r#"(module
+        (memory 1)
+        (func (export "grow") (param $pages i32) (result i32)
+            local.get $pages
+            memory.grow
+        )
+    )"#;
+
+    fn make_instance(price_per_page: u64) -> Instance {
+        let metering = Arc::new(Metering::new(0, |_: &Operator| 1));
+        let memory_metering = Arc::new(MemoryGrowMetering::new(price_per_page));
+        let mut compiler_config = Cranelift::default();
+        compiler_config.push_middleware(metering);
+        compiler_config.push_middleware(memory_metering);
+        let store = Store::new(&Universal::new(compiler_config).engine());
+        let wasm = wat::parse_str(GROW_WAT).unwrap();
+        let module = Module::new(&store, &wasm).unwrap();
+        Instance::new(&module, &ImportObject::new()).unwrap()
+    }
+
+    fn grow(instance: &Instance, pages: i32) -> Result<i32, wasmer::RuntimeError> {
+        let grow = instance.exports.get_function("grow").unwrap();
+        grow.call(&[pages.into()])
+            .map(|results| results[0].i32().unwrap())
+    }
+
+    #[test]
+    fn memory_grow_metering_charges_per_page() {
+        let instance = make_instance(1000);
+        set_remaining_points(&instance, 10_000);
+        assert_eq!(grow(&instance, 3).unwrap(), 1);
+        // 3 operators (local.get, memory.grow, end) + 3 pages
+        assert_eq!(
+            get_remaining_points(&instance),
+            MeteringPoints::Remaining(10_000 - 3 - 3000)
+        );
+
+        set_remaining_points(&instance, 10_000);
+        assert_eq!(grow(&instance, 0).unwrap(), 4);
+        assert_eq!(
+            get_remaining_points(&instance),
+            MeteringPoints::Remaining(10_000 - 3)
+        );
+    }
+
+    #[test]
+    fn memory_grow_metering_traps_when_out_of_points() {
+        let instance = make_instance(1000);
+        set_remaining_points(&instance, 2999);
+        grow(&instance, 3).unwrap_err();
+        assert_eq!(get_remaining_points(&instance), MeteringPoints::Exhausted);
+        // memory did not grow
+        set_remaining_points(&instance, 10_000);
+        assert_eq!(grow(&instance, 0).unwrap(), 1);
+
+        // the fee for huge requests does not overflow
+        set_remaining_points(&instance, u64::MAX);
+        grow(&instance, -1).unwrap();
+        assert_eq!(
+            get_remaining_points(&instance),
+            MeteringPoints::Remaining(u64::MAX - 3 - 1000 * u32::MAX as u64)
+        );
+    }
+
+    #[test]
+    fn memory_grow_metering_is_noop_for_price_zero() {
+        let instance = make_instance(0);
+        set_remaining_points(&instance, 10_000);
+        assert_eq!(grow(&instance, 3).unwrap(), 1);
+        assert_eq!(
+            get_remaining_points(&instance),
+            MeteringPoints::Remaining(10_000 - 3)
+        );
+    }
+}
@@ -1,8 +1,22 @@
 mod compile;
 mod gatekeeper;
+mod globals;
+#[cfg(feature = "interpreter")]
+mod interpreter;
 mod limiting_tunables;
+mod memory_grow_metering;
+mod operator_costs;
+mod stack_limiter;
 mod store;
 
 pub use compile::compile;
+pub use globals::{count_mutable_globals, exported_global_index, GLOBAL_EXPORT_PREFIX};
+#[cfg(feature = "interpreter")]
+pub use interpreter::{compile_interpreted, WasmiInstance, WasmiModule};
 pub use limiting_tunables::LimitingTunables;
-pub use store::make_runtime_store;
+pub use operator_costs::OperatorCostTable;
+pub use stack_limiter::{
+    get_max_stack_depth, get_stack_depth, set_max_stack_depth, set_stack_depth,
+    stack_depth_exceeded, BASE_FRAME_WEIGHT, DEFAULT_MAX_STACK_DEPTH,
+};
+pub use store::{compiler_name, make_runtime_store};
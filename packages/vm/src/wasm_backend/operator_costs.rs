@@ -0,0 +1,518 @@
+#[cfg(feature = "interpreter")]
+use parity_wasm::elements::Instruction;
+use sha2::{Digest, Sha256};
+use wasmer::wasmparser::Operator;
+
+/// The flat fee per operation that was used before operator classes existed.
+/// The target is 1 Teragas per millisecond (see GAS.md).
+const DEFAULT_OPERATOR_COST: u64 = 150_000;
+
+/// The default fee per page of `memory.grow`. This is the price of initializing all bytes
+/// of a page (64 KiB) with 8 byte stores, such that growing memory is not cheaper than
+/// writing to it.
+const DEFAULT_MEMORY_GROW_PER_PAGE_COST: u64 = 65536 / 8 * DEFAULT_OPERATOR_COST;
+
+/// Gas prices for Wasm operators, grouped by operator class.
+///
+/// The table is baked into the compiled module by the metering middleware. Modules
+/// compiled with different tables must not be mixed, which is why the file system
+/// cache stores them in a directory derived from [`OperatorCostTable::version`].
+///
+/// The default prices every class at the same flat fee of 150_000 like the flat pricing
+/// used before. In addition, every page requested by `memory.grow` costs 1_228_800_000.
+///
+/// In https://github.com/CosmWasm/cosmwasm/pull/1042 a profiler is developed to
+/// identify runtime differences between different Wasm operation, which can be
+/// used to derive class specific prices.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OperatorCostTable {
+    /// Constants, locals, globals, `nop`, `drop`, `select` and `unreachable`
+    pub trivial: u64,
+    /// Blocks, branches, `return` and the like
+    pub control_flow: u64,
+    /// Integer comparisons, additions, subtractions, bit operations, shifts and rotations
+    pub integer_arithmetic: u64,
+    /// `i32.mul` and `i64.mul`
+    pub integer_multiplication: u64,
+    /// Integer divisions and remainders
+    pub integer_division: u64,
+    /// Integer conversions such as `i32.wrap_i64`, `i64.extend_i32_u` and sign extensions
+    pub integer_conversion: u64,
+    /// Loads and stores from/to linear memory
+    pub memory_access: u64,
+    /// `memory.size`
+    pub memory_size: u64,
+    /// `memory.grow`
+    pub memory_grow: u64,
+    /// An additional fee per requested page of `memory.grow`, charged before the memory grows.
+    /// Requests that fail because of the memory limit are charged as well.
+    pub memory_grow_per_page: u64,
+    /// `call`
+    pub call: u64,
+    /// `call_indirect`
+    pub call_indirect: u64,
+    /// Everything else. Most of those operators are rejected by the gatekeeper anyways.
+    pub other: u64,
+}
+
+impl Default for OperatorCostTable {
+    fn default() -> Self {
+        Self {
+            memory_grow_per_page: DEFAULT_MEMORY_GROW_PER_PAGE_COST,
+            ..Self::flat(DEFAULT_OPERATOR_COST)
+        }
+    }
+}
+
+impl OperatorCostTable {
+    /// Creates a table that charges the same price for every operator
+    /// and no additional fee per page of `memory.grow`.
+    pub const fn flat(cost: u64) -> Self {
+        Self {
+            trivial: cost,
+            control_flow: cost,
+            integer_arithmetic: cost,
+            integer_multiplication: cost,
+            integer_division: cost,
+            integer_conversion: cost,
+            memory_access: cost,
+            memory_size: cost,
+            memory_grow: cost,
+            memory_grow_per_page: 0,
+            call: cost,
+            call_indirect: cost,
+            other: cost,
+        }
+    }
+
+    /// Returns the price of the given operator.
+    pub fn cost(&self, operator: &Operator) -> u64 {
+        match operator {
+            Operator::Unreachable
+            | Operator::Nop
+            | Operator::Drop
+            | Operator::Select
+            | Operator::TypedSelect { .. }
+            | Operator::LocalGet { .. }
+            | Operator::LocalSet { .. }
+            | Operator::LocalTee { .. }
+            | Operator::GlobalGet { .. }
+            | Operator::GlobalSet { .. }
+            | Operator::I32Const { .. }
+            | Operator::I64Const { .. } => self.trivial,
+
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Else
+            | Operator::End
+            | Operator::Br { .. }
+            | Operator::BrIf { .. }
+            | Operator::BrTable { .. }
+            | Operator::Return => self.control_flow,
+
+            Operator::Call { .. } => self.call,
+            Operator::CallIndirect { .. } => self.call_indirect,
+
+            Operator::I32Load { .. }
+            | Operator::I64Load { .. }
+            | Operator::I32Load8S { .. }
+            | Operator::I32Load8U { .. }
+            | Operator::I32Load16S { .. }
+            | Operator::I32Load16U { .. }
+            | Operator::I64Load8S { .. }
+            | Operator::I64Load8U { .. }
+            | Operator::I64Load16S { .. }
+            | Operator::I64Load16U { .. }
+            | Operator::I64Load32S { .. }
+            | Operator::I64Load32U { .. }
+            | Operator::I32Store { .. }
+            | Operator::I64Store { .. }
+            | Operator::I32Store8 { .. }
+            | Operator::I32Store16 { .. }
+            | Operator::I64Store8 { .. }
+            | Operator::I64Store16 { .. }
+            | Operator::I64Store32 { .. } => self.memory_access,
+
+            Operator::MemorySize { .. } => self.memory_size,
+            Operator::MemoryGrow { .. } => self.memory_grow,
+
+            Operator::I32Eqz
+            | Operator::I32Eq
+            | Operator::I32Ne
+            | Operator::I32LtS
+            | Operator::I32LtU
+            | Operator::I32GtS
+            | Operator::I32GtU
+            | Operator::I32LeS
+            | Operator::I32LeU
+            | Operator::I32GeS
+            | Operator::I32GeU
+            | Operator::I64Eqz
+            | Operator::I64Eq
+            | Operator::I64Ne
+            | Operator::I64LtS
+            | Operator::I64LtU
+            | Operator::I64GtS
+            | Operator::I64GtU
+            | Operator::I64LeS
+            | Operator::I64LeU
+            | Operator::I64GeS
+            | Operator::I64GeU
+            | Operator::I32Clz
+            | Operator::I32Ctz
+            | Operator::I32Popcnt
+            | Operator::I32Add
+            | Operator::I32Sub
+            | Operator::I32And
+            | Operator::I32Or
+            | Operator::I32Xor
+            | Operator::I32Shl
+            | Operator::I32ShrS
+            | Operator::I32ShrU
+            | Operator::I32Rotl
+            | Operator::I32Rotr
+            | Operator::I64Clz
+            | Operator::I64Ctz
+            | Operator::I64Popcnt
+            | Operator::I64Add
+            | Operator::I64Sub
+            | Operator::I64And
+            | Operator::I64Or
+            | Operator::I64Xor
+            | Operator::I64Shl
+            | Operator::I64ShrS
+            | Operator::I64ShrU
+            | Operator::I64Rotl
+            | Operator::I64Rotr => self.integer_arithmetic,
+
+            Operator::I32Mul | Operator::I64Mul => self.integer_multiplication,
+
+            Operator::I32DivS
+            | Operator::I32DivU
+            | Operator::I32RemS
+            | Operator::I32RemU
+            | Operator::I64DivS
+            | Operator::I64DivU
+            | Operator::I64RemS
+            | Operator::I64RemU => self.integer_division,
+
+            Operator::I32WrapI64
+            | Operator::I64ExtendI32S
+            | Operator::I64ExtendI32U
+            | Operator::I32Extend8S
+            | Operator::I32Extend16S
+            | Operator::I64Extend8S
+            | Operator::I64Extend16S
+            | Operator::I64Extend32S => self.integer_conversion,
+
+            _ => self.other,
+        }
+    }
+
+    /// Returns the price of the given instruction in parity-wasm representation.
+    /// This is the equivalent of [`OperatorCostTable::cost`] for the interpreter backend.
+    #[cfg(feature = "interpreter")]
+    pub(crate) fn instruction_cost(&self, instruction: &Instruction) -> u64 {
+        match instruction {
+            Instruction::Unreachable
+            | Instruction::Nop
+            | Instruction::Drop
+            | Instruction::Select
+            | Instruction::GetLocal(_)
+            | Instruction::SetLocal(_)
+            | Instruction::TeeLocal(_)
+            | Instruction::GetGlobal(_)
+            | Instruction::SetGlobal(_)
+            | Instruction::I32Const(_)
+            | Instruction::I64Const(_) => self.trivial,
+
+            Instruction::Block(_)
+            | Instruction::Loop(_)
+            | Instruction::If(_)
+            | Instruction::Else
+            | Instruction::End
+            | Instruction::Br(_)
+            | Instruction::BrIf(_)
+            | Instruction::BrTable(_)
+            | Instruction::Return => self.control_flow,
+
+            Instruction::Call(_) => self.call,
+            Instruction::CallIndirect(_, _) => self.call_indirect,
+
+            Instruction::I32Load(_, _)
+            | Instruction::I64Load(_, _)
+            | Instruction::I32Load8S(_, _)
+            | Instruction::I32Load8U(_, _)
+            | Instruction::I32Load16S(_, _)
+            | Instruction::I32Load16U(_, _)
+            | Instruction::I64Load8S(_, _)
+            | Instruction::I64Load8U(_, _)
+            | Instruction::I64Load16S(_, _)
+            | Instruction::I64Load16U(_, _)
+            | Instruction::I64Load32S(_, _)
+            | Instruction::I64Load32U(_, _)
+            | Instruction::I32Store(_, _)
+            | Instruction::I64Store(_, _)
+            | Instruction::I32Store8(_, _)
+            | Instruction::I32Store16(_, _)
+            | Instruction::I64Store8(_, _)
+            | Instruction::I64Store16(_, _)
+            | Instruction::I64Store32(_, _) => self.memory_access,
+
+            Instruction::CurrentMemory(_) => self.memory_size,
+            Instruction::GrowMemory(_) => self.memory_grow,
+
+            Instruction::I32Eqz
+            | Instruction::I32Eq
+            | Instruction::I32Ne
+            | Instruction::I32LtS
+            | Instruction::I32LtU
+            | Instruction::I32GtS
+            | Instruction::I32GtU
+            | Instruction::I32LeS
+            | Instruction::I32LeU
+            | Instruction::I32GeS
+            | Instruction::I32GeU
+            | Instruction::I64Eqz
+            | Instruction::I64Eq
+            | Instruction::I64Ne
+            | Instruction::I64LtS
+            | Instruction::I64LtU
+            | Instruction::I64GtS
+            | Instruction::I64GtU
+            | Instruction::I64LeS
+            | Instruction::I64LeU
+            | Instruction::I64GeS
+            | Instruction::I64GeU
+            | Instruction::I32Clz
+            | Instruction::I32Ctz
+            | Instruction::I32Popcnt
+            | Instruction::I32Add
+            | Instruction::I32Sub
+            | Instruction::I32And
+            | Instruction::I32Or
+            | Instruction::I32Xor
+            | Instruction::I32Shl
+            | Instruction::I32ShrS
+            | Instruction::I32ShrU
+            | Instruction::I32Rotl
+            | Instruction::I32Rotr
+            | Instruction::I64Clz
+            | Instruction::I64Ctz
+            | Instruction::I64Popcnt
+            | Instruction::I64Add
+            | Instruction::I64Sub
+            | Instruction::I64And
+            | Instruction::I64Or
+            | Instruction::I64Xor
+            | Instruction::I64Shl
+            | Instruction::I64ShrS
+            | Instruction::I64ShrU
+            | Instruction::I64Rotl
+            | Instruction::I64Rotr => self.integer_arithmetic,
+
+            Instruction::I32Mul | Instruction::I64Mul => self.integer_multiplication,
+
+            Instruction::I32DivS
+            | Instruction::I32DivU
+            | Instruction::I32RemS
+            | Instruction::I32RemU
+            | Instruction::I64DivS
+            | Instruction::I64DivU
+            | Instruction::I64RemS
+            | Instruction::I64RemU => self.integer_division,
+
+            Instruction::I32WrapI64 | Instruction::I64ExtendSI32 | Instruction::I64ExtendUI32 => {
+                self.integer_conversion
+            }
+
+            _ => self.other,
+        }
+    }
+
+    /// A short identifier of the prices in this table. Two tables have the same version
+    /// if and only if (up to hash collisions) they assign the same price to every operator.
+    ///
+    /// This is used as part of the file system cache path.
+    pub fn version(&self) -> String {
+        let mut hasher = Sha256::new();
+        for price in [
+            self.trivial,
+            self.control_flow,
+            self.integer_arithmetic,
+            self.integer_multiplication,
+            self.integer_division,
+            self.integer_conversion,
+            self.memory_access,
+            self.memory_size,
+            self.memory_grow,
+            self.call,
+            self.call_indirect,
+            self.other,
+        ] {
+            hasher.update(price.to_be_bytes());
+        }
+        // Only hashed when set, such that tables without a per page fee keep the
+        // version (and thus the cached modules) they had before the fee existed
+        if self.memory_grow_per_page != 0 {
+            hasher.update(self.memory_grow_per_page.to_be_bytes());
+        }
+        hex::encode(&hasher.finalize()[0..4])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_works() {
+        let table = OperatorCostTable::default();
+        assert_eq!(
+            table,
+            OperatorCostTable {
+                memory_grow_per_page: 1_228_800_000,
+                ..OperatorCostTable::flat(150_000)
+            }
+        );
+        assert_eq!(table.cost(&Operator::Nop), 150_000);
+        assert_eq!(table.cost(&Operator::I64DivU), 150_000);
+        assert_eq!(
+            table.cost(&Operator::MemoryGrow {
+                mem: 0,
+                mem_byte: 0
+            }),
+            150_000
+        );
+    }
+
+    #[test]
+    fn cost_uses_operator_class() {
+        let table = OperatorCostTable {
+            trivial: 1,
+            control_flow: 2,
+            integer_arithmetic: 3,
+            integer_multiplication: 4,
+            integer_division: 5,
+            integer_conversion: 6,
+            memory_access: 7,
+            memory_size: 8,
+            memory_grow: 9,
+            memory_grow_per_page: 13,
+            call: 10,
+            call_indirect: 11,
+            other: 12,
+        };
+        assert_eq!(table.cost(&Operator::LocalGet { local_index: 0 }), 1);
+        assert_eq!(table.cost(&Operator::I64Const { value: 42 }), 1);
+        assert_eq!(table.cost(&Operator::Br { relative_depth: 0 }), 2);
+        assert_eq!(table.cost(&Operator::End), 2);
+        assert_eq!(table.cost(&Operator::I32Add), 3);
+        assert_eq!(table.cost(&Operator::I64Mul), 4);
+        assert_eq!(table.cost(&Operator::I64DivU), 5);
+        assert_eq!(table.cost(&Operator::I32RemS), 5);
+        assert_eq!(table.cost(&Operator::I32WrapI64), 6);
+        assert_eq!(
+            table.cost(&Operator::I32Load {
+                memarg: wasmer::wasmparser::MemoryImmediate {
+                    align: 2,
+                    offset: 0,
+                    memory: 0,
+                }
+            }),
+            7
+        );
+        assert_eq!(
+            table.cost(&Operator::MemorySize {
+                mem: 0,
+                mem_byte: 0
+            }),
+            8
+        );
+        assert_eq!(
+            table.cost(&Operator::MemoryGrow {
+                mem: 0,
+                mem_byte: 0
+            }),
+            9
+        );
+        assert_eq!(table.cost(&Operator::Call { function_index: 3 }), 10);
+        assert_eq!(
+            table.cost(&Operator::CallIndirect {
+                index: 0,
+                table_index: 0
+            }),
+            11
+        );
+        assert_eq!(table.cost(&Operator::I32TruncSatF32S), 12);
+    }
+
+    #[test]
+    #[cfg(feature = "interpreter")]
+    fn instruction_cost_matches_cost() {
+        let table = OperatorCostTable {
+            trivial: 1,
+            control_flow: 2,
+            integer_arithmetic: 3,
+            integer_multiplication: 4,
+            integer_division: 5,
+            integer_conversion: 6,
+            memory_access: 7,
+            memory_size: 8,
+            memory_grow: 9,
+            memory_grow_per_page: 13,
+            call: 10,
+            call_indirect: 11,
+            other: 12,
+        };
+        assert_eq!(table.instruction_cost(&Instruction::GetLocal(0)), 1);
+        assert_eq!(table.instruction_cost(&Instruction::I64Const(42)), 1);
+        assert_eq!(table.instruction_cost(&Instruction::Br(0)), 2);
+        assert_eq!(table.instruction_cost(&Instruction::End), 2);
+        assert_eq!(table.instruction_cost(&Instruction::I32Add), 3);
+        assert_eq!(table.instruction_cost(&Instruction::I64Mul), 4);
+        assert_eq!(table.instruction_cost(&Instruction::I64DivU), 5);
+        assert_eq!(table.instruction_cost(&Instruction::I32RemS), 5);
+        assert_eq!(table.instruction_cost(&Instruction::I32WrapI64), 6);
+        assert_eq!(table.instruction_cost(&Instruction::I32Load(2, 0)), 7);
+        assert_eq!(table.instruction_cost(&Instruction::CurrentMemory(0)), 8);
+        assert_eq!(table.instruction_cost(&Instruction::GrowMemory(0)), 9);
+        assert_eq!(table.instruction_cost(&Instruction::Call(3)), 10);
+        assert_eq!(table.instruction_cost(&Instruction::CallIndirect(0, 0)), 11);
+        assert_eq!(table.instruction_cost(&Instruction::F32Add), 12);
+    }
+
+    #[test]
+    fn version_works() {
+        let default = OperatorCostTable::default();
+        assert_eq!(default.version(), "02c26bde");
+        // stable
+        assert_eq!(default.version(), OperatorCostTable::default().version());
+        // tables without a fee per page keep the version they had before the fee existed
+        assert_eq!(OperatorCostTable::flat(150_000).version(), "d82c9559");
+
+        // changes when any price changes
+        let cheap_locals = OperatorCostTable {
+            trivial: 100_000,
+            ..OperatorCostTable::default()
+        };
+        assert_ne!(cheap_locals.version(), default.version());
+        let expensive_grow = OperatorCostTable {
+            memory_grow: 1_000_000,
+            ..OperatorCostTable::default()
+        };
+        assert_ne!(expensive_grow.version(), default.version());
+        assert_ne!(expensive_grow.version(), cheap_locals.version());
+        let priced_pages = OperatorCostTable {
+            memory_grow_per_page: 1_000_000,
+            ..OperatorCostTable::default()
+        };
+        assert_ne!(priced_pages.version(), default.version());
+        assert_ne!(
+            priced_pages.version(),
+            OperatorCostTable::flat(150_000).version()
+        );
+        assert_ne!(priced_pages.version(), expensive_grow.version());
+    }
+}
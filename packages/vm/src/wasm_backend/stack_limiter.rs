@@ -0,0 +1,701 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use loupe::{MemoryUsage, MemoryUsageTracker};
+use wasmer::wasmparser::{
+    FuncType, FunctionBody, ImportSectionEntryType, Operator, Parser, Payload, Type as WpType,
+    TypeDef, TypeOrFuncType as WpTypeOrFuncType,
+};
+use wasmer::{
+    ExportIndex, FunctionMiddleware, GlobalInit, GlobalType, Instance, LocalFunctionIndex,
+    MiddlewareError, MiddlewareReaderState, ModuleMiddleware, Mutability, Type,
+};
+use wasmer_types::{GlobalIndex, ModuleInfo};
+
+/// The maximum stack depth of a contract unless set otherwise via [`set_max_stack_depth`].
+///
+/// The depth is the sum of the frame weights of all nested calls (see [`FrameWeights`]),
+/// i.e. it is measured in Wasm values. This is low enough to never exhaust the native stack
+/// of the host, such that execution fails in the same way on all machines. Since every frame
+/// weighs at least [`BASE_FRAME_WEIGHT`], there are never more than 1024 nested calls.
+pub const DEFAULT_MAX_STACK_DEPTH: u32 = 64 * 1024;
+
+/// The weight of a frame without parameters, locals and operands. This accounts for the
+/// native stack a call uses independent of the called function, like the return address
+/// and saved registers.
+pub const BASE_FRAME_WEIGHT: u32 = 64;
+
+/// The name of the exported global counting the active calls
+const STACK_DEPTH_EXPORT: &str = "cosmwasm_stack_depth";
+/// The name of the exported global holding the maximum depth
+const MAX_STACK_DEPTH_EXPORT: &str = "cosmwasm_max_stack_depth";
+
+/// The weights of the call frames of a module's functions.
+///
+/// The weight of a function is [`BASE_FRAME_WEIGHT`] plus the number of its parameters and
+/// locals plus the maximum height of its operand stack, like the stack cost in the stack
+/// height limiter of wasm-instrument. Imported functions have the base weight.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FrameWeights {
+    /// By function index, including imported functions
+    functions: Vec<u32>,
+    /// By type index, the maximum weight of all functions with an equal signature.
+    /// This is used for `call_indirect`, whose callee is not known statically.
+    signatures: Vec<u32>,
+}
+
+impl FrameWeights {
+    /// Computes the frame weights of all functions in the given Wasm code
+    pub fn from_code(code: &[u8]) -> Result<Self, String> {
+        let mut types: Vec<Option<FuncType>> = vec![];
+        let mut function_types: Vec<u32> = vec![];
+        let mut functions: Vec<u32> = vec![];
+        for payload in Parser::new(0).parse_all(code) {
+            match payload.map_err(|e| e.to_string())? {
+                Payload::TypeSection(reader) => {
+                    for type_def in reader {
+                        types.push(match type_def.map_err(|e| e.to_string())? {
+                            TypeDef::Func(func_type) => Some(func_type),
+                            _ => None,
+                        });
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        let import = import.map_err(|e| e.to_string())?;
+                        if let ImportSectionEntryType::Function(type_index) = import.ty {
+                            function_types.push(type_index);
+                            functions.push(BASE_FRAME_WEIGHT);
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    for type_index in reader {
+                        function_types.push(type_index.map_err(|e| e.to_string())?);
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let func_type = function_types
+                        .get(functions.len())
+                        .and_then(|type_index| func_type(&types, *type_index))
+                        .ok_or_else(|| "Function without a signature".to_string())?;
+                    let weight = frame_weight(&body, func_type, &types, &function_types)
+                        .map_err(|e| e.to_string())?;
+                    functions.push(weight);
+                }
+                _ => {}
+            }
+        }
+
+        let mut by_signature: HashMap<&FuncType, u32> = HashMap::new();
+        for (weight, type_index) in functions.iter().zip(function_types.iter()) {
+            if let Some(func_type) = func_type(&types, *type_index) {
+                let max = by_signature.entry(func_type).or_default();
+                *max = (*max).max(*weight);
+            }
+        }
+        let signatures = types
+            .iter()
+            .map(|func_type| {
+                func_type
+                    .as_ref()
+                    .and_then(|func_type| by_signature.get(func_type).copied())
+                    .unwrap_or(BASE_FRAME_WEIGHT)
+            })
+            .collect();
+        Ok(Self {
+            functions,
+            signatures,
+        })
+    }
+
+    /// The weight of a call of the given function
+    pub fn call(&self, function_index: u32) -> u32 {
+        self.functions
+            .get(function_index as usize)
+            .copied()
+            .unwrap_or(BASE_FRAME_WEIGHT)
+    }
+
+    /// The weight of an indirect call of a function of the given type
+    pub fn call_indirect(&self, type_index: u32) -> u32 {
+        self.signatures
+            .get(type_index as usize)
+            .copied()
+            .unwrap_or(BASE_FRAME_WEIGHT)
+    }
+}
+
+fn func_type(types: &[Option<FuncType>], type_index: u32) -> Option<&FuncType> {
+    types.get(type_index as usize)?.as_ref()
+}
+
+fn frame_weight(
+    body: &FunctionBody,
+    func_type: &FuncType,
+    types: &[Option<FuncType>],
+    function_types: &[u32],
+) -> wasmer::wasmparser::Result<u32> {
+    let mut locals = func_type.params.len() as u32;
+    let mut reader = body.get_locals_reader()?;
+    for _ in 0..reader.get_count() {
+        let (count, _) = reader.read()?;
+        locals = locals.saturating_add(count);
+    }
+    let height = max_operand_height(body, func_type, types, function_types)?;
+    Ok(BASE_FRAME_WEIGHT
+        .saturating_add(locals)
+        .saturating_add(height))
+}
+
+/// A block of a function during the computation of the operand stack height
+struct BlockFrame {
+    /// The height of the operand stack when the block was entered, without its parameters
+    height: u32,
+    params: u32,
+    results: u32,
+}
+
+/// Computes the maximum height of the operand stack of a function.
+///
+/// Code after an unconditional branch is unreachable, such that the stack is reset to the
+/// height of the enclosing block. Operators that are not supported by CosmWasm are treated
+/// as pushing one value. They are rejected by the `Gatekeeper` anyways.
+fn max_operand_height(
+    body: &FunctionBody,
+    func_type: &FuncType,
+    types: &[Option<FuncType>],
+    function_types: &[u32],
+) -> wasmer::wasmparser::Result<u32> {
+    let signature = |type_index: u32| {
+        func_type(types, type_index)
+            .map(|func_type| {
+                (
+                    func_type.params.len() as u32,
+                    func_type.returns.len() as u32,
+                )
+            })
+            .unwrap_or_default()
+    };
+    let block_signature = |ty: WpTypeOrFuncType| match ty {
+        WpTypeOrFuncType::Type(WpType::EmptyBlockType) => (0, 0),
+        WpTypeOrFuncType::Type(_) => (0, 1),
+        WpTypeOrFuncType::FuncType(type_index) => signature(type_index),
+    };
+
+    let mut blocks = vec![BlockFrame {
+        height: 0,
+        params: 0,
+        results: func_type.returns.len() as u32,
+    }];
+    let mut height: u32 = 0;
+    let mut max_height: u32 = 0;
+    let mut reader = body.get_operators_reader()?;
+    while !reader.eof() {
+        let operator = reader.read()?;
+        // The bottom of the stack in the current block
+        let floor = blocks.last().map(|block| block.height).unwrap_or_default();
+        let (pop, push) = match operator {
+            Operator::Block { ty } | Operator::Loop { ty } | Operator::If { ty } => {
+                let (params, results) = block_signature(ty);
+                let condition = if matches!(operator, Operator::If { .. }) {
+                    1
+                } else {
+                    0
+                };
+                height = floor.max(height.saturating_sub(condition + params));
+                blocks.push(BlockFrame {
+                    height,
+                    params,
+                    results,
+                });
+                (0, params)
+            }
+            Operator::Else => {
+                height = floor;
+                let params = blocks.last().map(|block| block.params).unwrap_or_default();
+                (0, params)
+            }
+            Operator::End => {
+                height = floor;
+                let results = blocks.pop().map(|block| block.results).unwrap_or_default();
+                (0, results)
+            }
+            Operator::Unreachable
+            | Operator::Br { .. }
+            | Operator::BrTable { .. }
+            | Operator::Return => {
+                height = floor;
+                (0, 0)
+            }
+            Operator::Call { function_index } => {
+                let type_index = function_types
+                    .get(function_index as usize)
+                    .copied()
+                    .unwrap_or(u32::MAX);
+                signature(type_index)
+            }
+            Operator::CallIndirect { index, .. } => {
+                let (params, results) = signature(index);
+                (params + 1, results)
+            }
+            Operator::Nop => (0, 0),
+            Operator::BrIf { .. }
+            | Operator::Drop
+            | Operator::LocalSet { .. }
+            | Operator::GlobalSet { .. } => (1, 0),
+            Operator::Select | Operator::TypedSelect { .. } => (3, 1),
+            Operator::I32Store { .. }
+            | Operator::I64Store { .. }
+            | Operator::I32Store8 { .. }
+            | Operator::I32Store16 { .. }
+            | Operator::I64Store8 { .. }
+            | Operator::I64Store16 { .. }
+            | Operator::I64Store32 { .. } => (2, 0),
+            Operator::LocalTee { .. }
+            | Operator::I32Load { .. }
+            | Operator::I64Load { .. }
+            | Operator::I32Load8S { .. }
+            | Operator::I32Load8U { .. }
+            | Operator::I32Load16S { .. }
+            | Operator::I32Load16U { .. }
+            | Operator::I64Load8S { .. }
+            | Operator::I64Load8U { .. }
+            | Operator::I64Load16S { .. }
+            | Operator::I64Load16U { .. }
+            | Operator::I64Load32S { .. }
+            | Operator::I64Load32U { .. }
+            | Operator::MemoryGrow { .. }
+            | Operator::I32Eqz
+            | Operator::I64Eqz
+            | Operator::I32Clz
+            | Operator::I32Ctz
+            | Operator::I32Popcnt
+            | Operator::I64Clz
+            | Operator::I64Ctz
+            | Operator::I64Popcnt
+            | Operator::I32WrapI64
+            | Operator::I32Extend8S
+            | Operator::I32Extend16S
+            | Operator::I64Extend8S
+            | Operator::I64Extend16S
+            | Operator::I64Extend32S
+            | Operator::I64ExtendI32S
+            | Operator::I64ExtendI32U => (1, 1),
+            Operator::I32Eq
+            | Operator::I32Ne
+            | Operator::I32LtS
+            | Operator::I32LtU
+            | Operator::I32GtS
+            | Operator::I32GtU
+            | Operator::I32LeS
+            | Operator::I32LeU
+            | Operator::I32GeS
+            | Operator::I32GeU
+            | Operator::I64Eq
+            | Operator::I64Ne
+            | Operator::I64LtS
+            | Operator::I64LtU
+            | Operator::I64GtS
+            | Operator::I64GtU
+            | Operator::I64LeS
+            | Operator::I64LeU
+            | Operator::I64GeS
+            | Operator::I64GeU
+            | Operator::I32Add
+            | Operator::I32Sub
+            | Operator::I32Mul
+            | Operator::I32DivS
+            | Operator::I32DivU
+            | Operator::I32RemS
+            | Operator::I32RemU
+            | Operator::I32And
+            | Operator::I32Or
+            | Operator::I32Xor
+            | Operator::I32Shl
+            | Operator::I32ShrS
+            | Operator::I32ShrU
+            | Operator::I32Rotl
+            | Operator::I32Rotr
+            | Operator::I64Add
+            | Operator::I64Sub
+            | Operator::I64Mul
+            | Operator::I64DivS
+            | Operator::I64DivU
+            | Operator::I64RemS
+            | Operator::I64RemU
+            | Operator::I64And
+            | Operator::I64Or
+            | Operator::I64Xor
+            | Operator::I64Shl
+            | Operator::I64ShrS
+            | Operator::I64ShrU
+            | Operator::I64Rotl
+            | Operator::I64Rotr => (2, 1),
+            // LocalGet, GlobalGet, constants, MemorySize and unsupported operators
+            _ => (0, 1),
+        };
+        let floor = blocks.last().map(|block| block.height).unwrap_or_default();
+        height = floor.max(height.saturating_sub(pop)).saturating_add(push);
+        max_height = max_height.max(height);
+    }
+    Ok(max_height)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StackLimiterGlobalIndexes {
+    depth: GlobalIndex,
+    max_depth: GlobalIndex,
+}
+
+/// A middleware that limits the stack depth of a contract.
+///
+/// Every `call` and `call_indirect` increments a counter by the frame weight of the callee
+/// before and decrements it after the call. When the counter exceeds the maximum depth,
+/// execution traps. In contrast to the native stack, this does not depend on the frame sizes
+/// of the compiler or the stack size of the host. Use [`stack_depth_exceeded`] to find out
+/// if a trap was caused by the limit.
+///
+/// Like Wasmer's `Metering`, an instance of the middleware can only be used for one module,
+/// whose frame weights are passed to [`StackLimiter::new`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct StackLimiter {
+    frame_weights: Arc<FrameWeights>,
+    global_indexes: Mutex<Option<StackLimiterGlobalIndexes>>,
+}
+
+impl StackLimiter {
+    pub fn new(frame_weights: FrameWeights) -> Self {
+        Self {
+            frame_weights: Arc::new(frame_weights),
+            global_indexes: Mutex::new(None),
+        }
+    }
+}
+
+impl Default for StackLimiter {
+    /// Creates a limiter that assigns the base weight to every call
+    fn default() -> Self {
+        Self::new(FrameWeights::default())
+    }
+}
+
+impl MemoryUsage for StackLimiter {
+    fn size_of_val(&self, _: &mut dyn MemoryUsageTracker) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+impl ModuleMiddleware for StackLimiter {
+    /// Generates a `FunctionMiddleware` for a given function.
+    fn generate_function_middleware(&self, _: LocalFunctionIndex) -> Box<dyn FunctionMiddleware> {
+        let global_indexes =
+            self.global_indexes.lock().unwrap().expect(
+                "StackLimiter::generate_function_middleware: globals not set. This is a bug.",
+            );
+        Box::new(FunctionStackLimiter {
+            frame_weights: self.frame_weights.clone(),
+            global_indexes,
+        })
+    }
+
+    /// Adds and exports the globals for the current and the maximum depth.
+    fn transform_module_info(&self, module_info: &mut ModuleInfo) {
+        let mut global_indexes = self.global_indexes.lock().unwrap();
+        if global_indexes.is_some() {
+            panic!("StackLimiter::transform_module_info: Attempting to use a `StackLimiter` middleware from multiple modules.");
+        }
+
+        let depth = module_info
+            .globals
+            .push(GlobalType::new(Type::I32, Mutability::Var));
+        module_info
+            .global_initializers
+            .push(GlobalInit::I32Const(0));
+        module_info
+            .exports
+            .insert(STACK_DEPTH_EXPORT.to_string(), ExportIndex::Global(depth));
+
+        let max_depth = module_info
+            .globals
+            .push(GlobalType::new(Type::I32, Mutability::Var));
+        module_info
+            .global_initializers
+            .push(GlobalInit::I32Const(DEFAULT_MAX_STACK_DEPTH as i32));
+        module_info.exports.insert(
+            MAX_STACK_DEPTH_EXPORT.to_string(),
+            ExportIndex::Global(max_depth),
+        );
+
+        *global_indexes = Some(StackLimiterGlobalIndexes { depth, max_depth });
+    }
+}
+
+#[derive(Debug)]
+struct FunctionStackLimiter {
+    frame_weights: Arc<FrameWeights>,
+    global_indexes: StackLimiterGlobalIndexes,
+}
+
+impl FunctionMiddleware for FunctionStackLimiter {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        let depth = self.global_indexes.depth.as_u32();
+        let max_depth = self.global_indexes.max_depth.as_u32();
+        let weight = match operator {
+            Operator::Call { function_index } => Some(self.frame_weights.call(function_index)),
+            Operator::CallIndirect { index, .. } => Some(self.frame_weights.call_indirect(index)),
+            _ => None,
+        };
+        match weight {
+            Some(weight) => {
+                let weight = weight as i32;
+                state.extend(&[
+                    // depth += weight
+                    Operator::GlobalGet {
+                        global_index: depth,
+                    },
+                    Operator::I32Const { value: weight },
+                    Operator::I32Add,
+                    Operator::GlobalSet {
+                        global_index: depth,
+                    },
+                    // if depth > max_depth { trap }
+                    Operator::GlobalGet {
+                        global_index: depth,
+                    },
+                    Operator::GlobalGet {
+                        global_index: max_depth,
+                    },
+                    Operator::I32GtU,
+                    Operator::If {
+                        ty: WpTypeOrFuncType::Type(WpType::EmptyBlockType),
+                    },
+                    Operator::Unreachable,
+                    Operator::End,
+                ]);
+                state.push_operator(operator);
+                state.extend(&[
+                    // depth -= weight
+                    Operator::GlobalGet {
+                        global_index: depth,
+                    },
+                    Operator::I32Const { value: weight },
+                    Operator::I32Sub,
+                    Operator::GlobalSet {
+                        global_index: depth,
+                    },
+                ]);
+            }
+            None => state.push_operator(operator),
+        }
+        Ok(())
+    }
+}
+
+fn stack_global(instance: &Instance, name: &str) -> Option<u32> {
+    let global = instance.exports.get_global(name).ok()?;
+    global.get().i32().map(|value| value as u32)
+}
+
+fn set_stack_global(instance: &Instance, name: &str, value: u32) {
+    // Instances of modules that were not compiled with the middleware have no such global
+    if let Ok(global) = instance.exports.get_global(name) {
+        global
+            .set((value as i32).into())
+            .expect("Can't set stack limiter global. This is a bug.");
+    }
+}
+
+/// Returns the current stack depth of the instance, i.e. the sum of the weights of all
+/// active calls
+pub fn get_stack_depth(instance: &Instance) -> u32 {
+    stack_global(instance, STACK_DEPTH_EXPORT).unwrap_or_default()
+}
+
+/// Sets the current stack depth. This is used to reset the counter after a trap, which
+/// skips the decrements.
+pub fn set_stack_depth(instance: &Instance, depth: u32) {
+    set_stack_global(instance, STACK_DEPTH_EXPORT, depth);
+}
+
+pub fn get_max_stack_depth(instance: &Instance) -> u32 {
+    stack_global(instance, MAX_STACK_DEPTH_EXPORT).unwrap_or(DEFAULT_MAX_STACK_DEPTH)
+}
+
+/// Sets the maximum stack depth, see [`DEFAULT_MAX_STACK_DEPTH`]
+pub fn set_max_stack_depth(instance: &Instance, max_depth: u32) {
+    set_stack_global(instance, MAX_STACK_DEPTH_EXPORT, max_depth);
+}
+
+/// Returns true if the last trap of the instance was caused by exceeding the maximum depth.
+/// Must be called before the depth is reset.
+pub fn stack_depth_exceeded(instance: &Instance) -> bool {
+    get_stack_depth(instance) > get_max_stack_depth(instance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmer::{CompilerConfig, Cranelift, ImportObject, Module, Store, Universal};
+
+    /// A function that calls itself `n` times
+    fn recursive_wat() -> Vec<u8> {
+        wat::parse_str(
+            r#"(module
+                (func $recurse (export "recurse") (param $n i32)
+                    local.get $n
+                    if
+                        local.get $n
+                        i32.const 1
+                        i32.sub
+                        call $recurse
+                    end
+                )
+            )"#,
+        )
+        .unwrap()
+    }
+
+    /// The frame weight of `$recurse`: one parameter and at most two operands
+    const RECURSE_WEIGHT: u32 = BASE_FRAME_WEIGHT + 1 + 2;
+
+    fn make_instance(wasm: &[u8]) -> Instance {
+        let limiter = Arc::new(StackLimiter::new(FrameWeights::from_code(wasm).unwrap()));
+        let mut compiler_config = Cranelift::default();
+        compiler_config.push_middleware(limiter);
+        let store = Store::new(&Universal::new(compiler_config).engine());
+        let module = Module::new(&store, wasm).unwrap();
+        Instance::new(&module, &ImportObject::new()).unwrap()
+    }
+
+    #[test]
+    fn frame_weights_from_code_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (type $t (func (param i32) (result i32)))
+                (import "env" "imported" (func $imported (param i32)))
+                (func $small (type $t)
+                    local.get 0
+                )
+                (func $large (type $t) (local i64 i64)
+                    i32.const 1
+                    block (result i32)
+                        i32.const 2
+                        i32.const 3
+                        i32.add
+                        br 0
+                        i32.const 4
+                        i32.const 5
+                        drop
+                        drop
+                    end
+                    i32.add
+                )
+                (func $other (param i64) (result i32)
+                    i32.const 0
+                )
+            )"#,
+        )
+        .unwrap();
+        let weights = FrameWeights::from_code(&wasm).unwrap();
+        assert_eq!(
+            weights,
+            FrameWeights {
+                functions: vec![
+                    BASE_FRAME_WEIGHT,
+                    BASE_FRAME_WEIGHT + 1 + 1,
+                    BASE_FRAME_WEIGHT + 3 + 3,
+                    BASE_FRAME_WEIGHT + 1 + 1,
+                ],
+                // $imported has the only type of its own
+                signatures: vec![
+                    BASE_FRAME_WEIGHT + 3 + 3,
+                    BASE_FRAME_WEIGHT,
+                    BASE_FRAME_WEIGHT + 1 + 1,
+                ],
+            }
+        );
+        assert_eq!(weights.call(2), BASE_FRAME_WEIGHT + 3 + 3);
+        assert_eq!(weights.call(4), BASE_FRAME_WEIGHT);
+        assert_eq!(weights.call_indirect(0), BASE_FRAME_WEIGHT + 3 + 3);
+        assert_eq!(weights.call_indirect(3), BASE_FRAME_WEIGHT);
+
+        assert_eq!(
+            FrameWeights::from_code(&recursive_wat()).unwrap().call(0),
+            RECURSE_WEIGHT
+        );
+    }
+
+    #[test]
+    fn stack_limiter_counts_nested_calls() {
+        let instance = make_instance(&recursive_wat());
+        assert_eq!(get_stack_depth(&instance), 0);
+        assert_eq!(get_max_stack_depth(&instance), DEFAULT_MAX_STACK_DEPTH);
+
+        let recurse = instance.exports.get_function("recurse").unwrap();
+        recurse.call(&[10.into()]).unwrap();
+        // all calls returned
+        assert_eq!(get_stack_depth(&instance), 0);
+        assert!(!stack_depth_exceeded(&instance));
+
+        // the top level call is not counted
+        let max_calls = DEFAULT_MAX_STACK_DEPTH / RECURSE_WEIGHT;
+        recurse.call(&[(max_calls as i32).into()]).unwrap();
+        assert_eq!(get_stack_depth(&instance), 0);
+        recurse.call(&[(max_calls as i32 + 1).into()]).unwrap_err();
+        assert!(stack_depth_exceeded(&instance));
+    }
+
+    #[test]
+    fn stack_limiter_traps_when_limit_is_exceeded() {
+        let instance = make_instance(&recursive_wat());
+        set_max_stack_depth(&instance, 5 * RECURSE_WEIGHT);
+        assert_eq!(get_max_stack_depth(&instance), 5 * RECURSE_WEIGHT);
+
+        let recurse = instance.exports.get_function("recurse").unwrap();
+        recurse.call(&[5.into()]).unwrap();
+        assert!(!stack_depth_exceeded(&instance));
+
+        recurse.call(&[6.into()]).unwrap_err();
+        assert!(stack_depth_exceeded(&instance));
+        assert_eq!(get_stack_depth(&instance), 6 * RECURSE_WEIGHT);
+
+        set_stack_depth(&instance, 0);
+        assert!(!stack_depth_exceeded(&instance));
+        recurse.call(&[5.into()]).unwrap();
+    }
+
+    #[test]
+    fn stack_limiter_counts_indirect_calls() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (type $t (func (param i32)))
+                (table 1 funcref)
+                (elem (i32.const 0) $recurse)
+                (func $recurse (export "recurse") (param $n i32)
+                    local.get $n
+                    if
+                        local.get $n
+                        i32.const 1
+                        i32.sub
+                        i32.const 0
+                        call_indirect (type $t)
+                    end
+                )
+            )"#,
+        )
+        .unwrap();
+        let instance = make_instance(&wasm);
+        set_max_stack_depth(&instance, 3 * RECURSE_WEIGHT);
+
+        let recurse = instance.exports.get_function("recurse").unwrap();
+        recurse.call(&[3.into()]).unwrap();
+        recurse.call(&[4.into()]).unwrap_err();
+        assert!(stack_depth_exceeded(&instance));
+    }
+}
@@ -14,6 +14,9 @@ use crate::size::Size;
 
 use super::gatekeeper::Gatekeeper;
 use super::limiting_tunables::LimitingTunables;
+use super::memory_grow_metering::MemoryGrowMetering;
+use super::operator_costs::OperatorCostTable;
+use super::stack_limiter::{FrameWeights, StackLimiter};
 
 /// WebAssembly linear memory objects have sizes measured in pages. Each page
 /// is 65536 (2^16) bytes. In WebAssembly version 1, a linear memory can have at
@@ -21,25 +24,26 @@ use super::limiting_tunables::LimitingTunables;
 /// https://github.com/WebAssembly/memory64/blob/master/proposals/memory64/Overview.md
 const MAX_WASM_MEMORY: usize = 4 * 1024 * 1024 * 1024;
 
-fn cost(_operator: &Operator) -> u64 {
-    // A flat fee for each operation
-    // The target is 1 Teragas per millisecond (see GAS.md).
-    //
-    // In https://github.com/CosmWasm/cosmwasm/pull/1042 a profiler is developed to
-    // identify runtime differences between different Wasm operation, but this is not yet
-    // precise enough to derive insights from it.
-    150_000
-}
-
 /// Created a store with the default compiler and the given memory limit (in bytes).
 /// If memory_limit is None, no limit is applied.
+/// Operators are metered using the prices in `cost_table`, which also contains the fee
+/// per page of `memory.grow` charged by the `MemoryGrowMetering`.
+/// The stack depth is limited by the `StackLimiter` using the given `frame_weights` of the
+/// module. It comes after the metering such that its instructions are not charged.
 pub fn make_compile_time_store(
     memory_limit: Option<Size>,
+    cost_table: &OperatorCostTable,
+    frame_weights: FrameWeights,
     middlewares: &[Arc<dyn ModuleMiddleware>],
 ) -> Store {
     let gas_limit = 0;
     let deterministic = Arc::new(Gatekeeper::default());
-    let metering = Arc::new(Metering::new(gas_limit, cost));
+    let cost_table = *cost_table;
+    let metering = Arc::new(Metering::new(gas_limit, move |operator: &Operator| {
+        cost_table.cost(operator)
+    }));
+    let memory_grow_metering = Arc::new(MemoryGrowMetering::new(cost_table.memory_grow_per_page));
+    let stack_limiter = Arc::new(StackLimiter::new(frame_weights));
 
     #[cfg(feature = "cranelift")]
     {
@@ -49,6 +53,8 @@ pub fn make_compile_time_store(
         }
         config.push_middleware(deterministic);
         config.push_middleware(metering);
+        config.push_middleware(memory_grow_metering);
+        config.push_middleware(stack_limiter);
         let engine = Universal::new(config).engine();
         make_store_with_engine(&engine, memory_limit)
     }
@@ -61,11 +67,22 @@ pub fn make_compile_time_store(
         }
         config.push_middleware(deterministic);
         config.push_middleware(metering);
+        config.push_middleware(memory_grow_metering);
+        config.push_middleware(stack_limiter);
         let engine = Universal::new(config).engine();
         make_store_with_engine(&engine, memory_limit)
     }
 }
 
+/// The name of the compiler used by `make_compile_time_store`
+pub fn compiler_name() -> &'static str {
+    if cfg!(feature = "cranelift") {
+        "cranelift"
+    } else {
+        "singlepass"
+    }
+}
+
 /// Created a store with no compiler and the given memory limit (in bytes)
 /// If memory_limit is None, no limit is applied.
 pub fn make_runtime_store(memory_limit: Option<Size>) -> Store {
@@ -86,7 +103,7 @@ fn make_store_with_engine(engine: &dyn Engine, memory_limit: Option<Size>) -> St
     }
 }
 
-fn limit_to_pages(limit: Size) -> Pages {
+pub(super) fn limit_to_pages(limit: Size) -> Pages {
     let capped = std::cmp::min(limit.0, MAX_WASM_MEMORY);
     // round down to ensure the limit is less than or equal to the config
     let pages: u32 = (capped / WASM_PAGE_SIZE)
@@ -99,6 +116,9 @@ fn limit_to_pages(limit: Size) -> Pages {
 mod tests {
     use super::*;
     use wasmer::{ImportObject, Instance, Memory, Module};
+    use wasmer_middlewares::metering::{
+        get_remaining_points, set_remaining_points, MeteringPoints,
+    };
 
     /// A Wasm module with an exported memory (min: 4 pages, max: none)
     const EXPORTED_MEMORY_WAT: &str = r#"(module
@@ -125,7 +145,12 @@ mod tests {
         let wasm = wat::parse_str(EXPORTED_MEMORY_WAT).unwrap();
 
         // No limit
-        let store = make_compile_time_store(None, &[]);
+        let store = make_compile_time_store(
+            None,
+            &OperatorCostTable::default(),
+            FrameWeights::default(),
+            &[],
+        );
         let module = Module::new(&store, &wasm).unwrap();
         let module_memory = module.info().memories.last().unwrap();
         assert_eq!(module_memory.minimum, Pages(4));
@@ -142,7 +167,12 @@ mod tests {
         assert_eq!(instance_memory.ty().maximum, None);
 
         // Set limit
-        let store = make_compile_time_store(Some(Size::kibi(23 * 64)), &[]);
+        let store = make_compile_time_store(
+            Some(Size::kibi(23 * 64)),
+            &OperatorCostTable::default(),
+            FrameWeights::default(),
+            &[],
+        );
         let module = Module::new(&store, &wasm).unwrap();
         let module_memory = module.info().memories.last().unwrap();
         assert_eq!(module_memory.minimum, Pages(4));
@@ -159,12 +189,50 @@ mod tests {
         assert_eq!(instance_memory.ty().maximum, Some(Pages(23)));
     }
 
+    #[test]
+    fn make_compile_time_store_applies_cost_table() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (func (export "divide") (result i64)
+                    i64.const 10
+                    i64.const 3
+                    i64.div_u
+                )
+            )"#,
+        )
+        .unwrap();
+        let cost_table = OperatorCostTable {
+            trivial: 1,
+            control_flow: 10,
+            integer_division: 100,
+            ..OperatorCostTable::default()
+        };
+
+        let store = make_compile_time_store(None, &cost_table, FrameWeights::default(), &[]);
+        let module = Module::new(&store, &wasm).unwrap();
+        let instance = Instance::new(&module, &ImportObject::new()).unwrap();
+        set_remaining_points(&instance, 1000);
+        let divide = instance.exports.get_function("divide").unwrap();
+        divide.call(&[]).unwrap();
+
+        // 2x i64.const, 1x i64.div_u, 1x end
+        assert_eq!(
+            get_remaining_points(&instance),
+            MeteringPoints::Remaining(1000 - 1 - 1 - 100 - 10)
+        );
+    }
+
     #[test]
     fn make_runtime_store_applies_memory_limit() {
         // Compile
         let serialized = {
             let wasm = wat::parse_str(EXPORTED_MEMORY_WAT).unwrap();
-            let store = make_compile_time_store(None, &[]);
+            let store = make_compile_time_store(
+                None,
+                &OperatorCostTable::default(),
+                FrameWeights::default(),
+                &[],
+            );
             let module = Module::new(&store, &wasm).unwrap();
             module.serialize().unwrap()
         };